@@ -0,0 +1,50 @@
+//! 最大流算法基准：在稠密随机图上比较 Edmonds-Karp 与 Dinic
+//!
+//! 运行：`cargo bench --bench max_flow`
+
+use chaingraph::algorithm::{Dinic, EdmondsKarp};
+use chaingraph::graph::{Graph, VertexId};
+use chaingraph::types::{TokenAmount, VertexLabel};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{Rng, SeedableRng};
+use std::sync::Arc;
+
+/// 生成 vertices 个顶点、每个顶点约 degree 条随机出边的转账图
+fn dense_graph(vertices: usize, degree: usize) -> (Arc<Graph>, VertexId, VertexId) {
+    let graph = Graph::in_memory().unwrap();
+    let ids: Vec<VertexId> = (0..vertices)
+        .map(|_| graph.add_vertex(VertexLabel::Account).unwrap())
+        .collect();
+    let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+    for &src in &ids {
+        for _ in 0..degree {
+            let dst = ids[rng.gen_range(0..vertices)];
+            if dst != src {
+                let amount = TokenAmount::from_u64(rng.gen_range(1..1_000));
+                graph.add_transfer(src, dst, amount, 1).unwrap();
+            }
+        }
+    }
+    (graph, ids[0], ids[vertices - 1])
+}
+
+fn bench_max_flow(c: &mut Criterion) {
+    let mut group = c.benchmark_group("max_flow");
+    group.sample_size(10);
+    for (vertices, degree) in [(100, 20), (300, 40)] {
+        let (graph, source, sink) = dense_graph(vertices, degree);
+        let size = format!("{}x{}", vertices, degree);
+        group.bench_with_input(BenchmarkId::new("edmonds_karp", &size), &(), |b, _| {
+            let algo = EdmondsKarp::new(graph.clone());
+            b.iter(|| algo.max_flow(source, sink).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("dinic", &size), &(), |b, _| {
+            let algo = Dinic::new(graph.clone());
+            b.iter(|| algo.max_flow(source, sink).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_max_flow);
+criterion_main!(benches);
@@ -2,7 +2,7 @@
 //!
 //! 导入样例数据并执行查询
 
-use chaingraph::graph::Graph;
+use chaingraph::graph::GraphCatalog;
 use chaingraph::import::BatchImporter;
 use chaingraph::query::{GqlParser, QueryExecutor};
 
@@ -10,8 +10,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("ChainGraph 演示");
     println!("================\n");
 
-    // 创建内存图（避免持久化问题）
-    let graph = Graph::in_memory()?;
+    // 在临时目录中创建图目录（避免持久化问题）
+    let data_dir = std::env::temp_dir().join(format!("chaingraph_demo_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&data_dir);
+    let catalog = GraphCatalog::open(&data_dir, Some(1024))?;
+    let graph = catalog.current_graph();
 
     // 导入样例数据
     println!("1. 导入样例数据...");
@@ -27,7 +30,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     // 创建查询执行器
-    let executor = QueryExecutor::new(graph.clone());
+    let executor = QueryExecutor::new(catalog.clone());
 
     // 执行一些 GQL 查询
     println!("2. 执行 GQL 查询...\n");
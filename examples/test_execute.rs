@@ -1,7 +1,7 @@
 //! ChainGraph GQL 执行测试
 //! 测试 ISO GQL 39075 标准语句的执行
 
-use chaingraph::graph::Graph;
+use chaingraph::graph::GraphCatalog;
 use chaingraph::query::{GqlParser, QueryExecutor};
 
 fn main() {
@@ -9,9 +9,12 @@ fn main() {
     println!("║         ChainGraph GQL 执行测试 (ISO GQL 39075)               ║");
     println!("╚═══════════════════════════════════════════════════════════════╝\n");
 
-    // 创建内存图 (in_memory() 返回 Arc<Graph>)
-    let graph = Graph::in_memory().expect("Failed to create graph");
-    let executor = QueryExecutor::new(graph);
+    // 在临时目录中创建图目录
+    let data_dir =
+        std::env::temp_dir().join(format!("chaingraph_execute_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&data_dir);
+    let catalog = GraphCatalog::open(&data_dir, Some(1024)).expect("Failed to open catalog");
+    let executor = QueryExecutor::new(catalog);
 
     // 首先插入一些测试数据
     println!("── 准备测试数据 ──");
@@ -0,0 +1,119 @@
+//! 净余额计算
+//!
+//! 不依赖存储的 `balance` 属性，直接由 Transfer 边汇总地址的转入与转出
+
+use crate::error::{Error, Result};
+use crate::graph::{Graph, VertexId};
+use crate::types::{EdgeLabel, TokenAmount};
+
+/// 地址在区块区间内的转入/转出汇总
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetBalance {
+    /// 转入总额
+    pub inflow: TokenAmount,
+    /// 转出总额
+    pub outflow: TokenAmount,
+    /// 转入笔数
+    pub inflow_count: usize,
+    /// 转出笔数
+    pub outflow_count: usize,
+}
+
+impl NetBalance {
+    /// 汇总顶点的 Transfer 边；指定区块区间（闭区间）时缺少区块号的边被排除
+    pub fn compute(
+        graph: &Graph,
+        vertex: VertexId,
+        from_block: Option<u64>,
+        to_block: Option<u64>,
+    ) -> Result<Self> {
+        if !graph.vertex_exists(vertex) {
+            return Err(Error::VertexNotFound(format!("{:?}", vertex)));
+        }
+
+        let in_window = |block: Option<u64>| match (from_block, to_block, block) {
+            (None, None, _) => true,
+            (_, _, None) => false,
+            (lo, hi, Some(b)) => lo.is_none_or(|lo| b >= lo) && hi.is_none_or(|hi| b <= hi),
+        };
+
+        let mut balance = NetBalance {
+            inflow: TokenAmount::from_u64(0),
+            outflow: TokenAmount::from_u64(0),
+            inflow_count: 0,
+            outflow_count: 0,
+        };
+        let directions = [
+            (true, graph.get_incoming_edges(vertex)),
+            (false, graph.get_outgoing_edges(vertex)),
+        ];
+        for (inbound, edges) in directions {
+            for edge in edges {
+                if edge.label() != &EdgeLabel::Transfer || !in_window(edge.block_number()) {
+                    continue;
+                }
+                let amount = edge.amount().map(|a| a.0).unwrap_or_default();
+                if inbound {
+                    balance.inflow.0 = balance.inflow.0.saturating_add(amount);
+                    balance.inflow_count += 1;
+                } else {
+                    balance.outflow.0 = balance.outflow.0.saturating_add(amount);
+                    balance.outflow_count += 1;
+                }
+            }
+        }
+        Ok(balance)
+    }
+
+    /// 转入减转出的十进制字符串，净流出时带负号
+    pub fn net(&self) -> String {
+        let (inflow, outflow) = (self.inflow.0, self.outflow.0);
+        if inflow >= outflow {
+            (inflow - outflow).to_string()
+        } else {
+            format!("-{}", outflow - inflow)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_net_balance() {
+        let graph = Graph::in_memory().unwrap();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        let c = graph.add_account("0xc".to_string()).unwrap();
+        graph
+            .add_transfer(a, b, TokenAmount::from_u64(100), 10)
+            .unwrap();
+        graph
+            .add_transfer(c, b, TokenAmount::from_u64(50), 20)
+            .unwrap();
+        graph
+            .add_transfer(b, a, TokenAmount::from_u64(30), 30)
+            .unwrap();
+
+        let balance = NetBalance::compute(&graph, b, None, None).unwrap();
+        assert_eq!(balance.inflow, TokenAmount::from_u64(150));
+        assert_eq!(balance.outflow, TokenAmount::from_u64(30));
+        assert_eq!((balance.inflow_count, balance.outflow_count), (2, 1));
+        assert_eq!(balance.net(), "120");
+
+        // 只统计区块 15..=30 的转账
+        let windowed = NetBalance::compute(&graph, b, Some(15), Some(30)).unwrap();
+        assert_eq!(windowed.net(), "20");
+        let sender = NetBalance::compute(&graph, a, None, None).unwrap();
+        assert_eq!(sender.net(), "-70");
+
+        // 没有边的顶点净额为 0
+        let d = graph.add_account("0xd".to_string()).unwrap();
+        let empty = NetBalance::compute(&graph, d, None, None).unwrap();
+        assert_eq!(empty.net(), "0");
+        assert_eq!(empty.inflow_count + empty.outflow_count, 0);
+
+        assert!(NetBalance::compute(&graph, VertexId::new(999), None, None).is_err());
+    }
+}
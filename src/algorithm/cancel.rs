@@ -0,0 +1,75 @@
+//! 取消令牌
+//!
+//! 用于中断长时间运行的图算法（例如客户端断开连接后的路径搜索）
+
+use crate::error::{Error, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// 取消令牌，克隆后共享同一取消状态
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// 创建未取消的令牌
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 请求取消
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// 是否已请求取消
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// 在循环边界调用：已取消时返回 `Error::Cancelled`
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 返回一个守卫，守卫被丢弃时自动取消令牌
+    pub fn drop_guard(&self) -> CancelGuard {
+        CancelGuard {
+            token: self.clone(),
+        }
+    }
+}
+
+/// 丢弃时取消令牌的守卫
+#[derive(Debug)]
+pub struct CancelGuard {
+    token: CancellationToken,
+}
+
+impl Drop for CancelGuard {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop_guard_cancels_clones() {
+        let token = CancellationToken::new();
+        let shared = token.clone();
+        assert!(shared.check().is_ok());
+
+        drop(token.drop_guard());
+
+        assert!(shared.is_cancelled());
+        assert!(matches!(shared.check(), Err(Error::Cancelled)));
+    }
+}
@@ -0,0 +1,242 @@
+//! 地址聚类
+//!
+//! 把 UTXO 模型的"共同输入"启发式改写到账户模型：同一实体控制的多个地址
+//! 往往在相近的区块内向相同的收款地址（交易所充值地址、归集地址等）转账。
+//!
+//! 假设与局限：
+//! - 只看转出方向的 Transfer 边，共同的资金来源不作为证据
+//! - 两个地址之间的直接转账不作为证据，实体间的普通往来同样会产生直接转账
+//! - 对手数超过 `max_hub_degree` 的收款地址（交易所热钱包、热门合约）被视为公共设施，
+//!   既不作为证据，也不继续扩展候选范围
+//! - 结果只是"可能属于同一实体"的线索：批量代发、空投领取等行为会产生误合并，
+//!   各自使用独立收款地址的同一实体则无法识别
+
+use crate::error::{Error, Result};
+use crate::graph::{Graph, VertexId};
+use crate::types::EdgeLabel;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// 地址聚类参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClusterParams {
+    /// 两个地址至少共同转账给多少个收款地址才归为一类
+    pub min_shared: usize,
+    /// 两笔转账的区块差不超过该值才算"同时"转出
+    pub block_window: u64,
+    /// 对手数超过该值的地址视为公共设施
+    pub max_hub_degree: usize,
+}
+
+impl Default for ClusterParams {
+    fn default() -> Self {
+        Self {
+            min_shared: 2,
+            block_window: 100,
+            max_hub_degree: 50,
+        }
+    }
+}
+
+/// 一个候选地址的聚类结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressCluster {
+    /// 地址顶点
+    pub vertex: VertexId,
+    /// 所属类的编号，取类内最小的顶点 ID
+    pub cluster: VertexId,
+}
+
+impl AddressCluster {
+    /// 对种子地址 hops 跳（忽略方向）以内的地址做聚类，
+    /// 结果按类编号、再按顶点 ID 升序排列；没有同伴的地址自成一类
+    pub fn compute(
+        graph: &Graph,
+        seed: VertexId,
+        hops: usize,
+        params: ClusterParams,
+    ) -> Result<Vec<Self>> {
+        if !graph.vertex_exists(seed) {
+            return Err(Error::VertexNotFound(format!("{:?}", seed)));
+        }
+        let is_hub = |vertex: VertexId| {
+            graph.out_degree(vertex) + graph.in_degree(vertex) > params.max_hub_degree
+        };
+
+        // 沿 Transfer 边双向扩展候选地址，不穿过公共设施
+        let mut candidates = vec![seed];
+        let mut visited = HashSet::from([seed]);
+        let mut queue = VecDeque::from([(seed, 0)]);
+        while let Some((vertex, depth)) = queue.pop_front() {
+            if depth == hops || (vertex != seed && is_hub(vertex)) {
+                continue;
+            }
+            let edges = graph
+                .get_outgoing_edges(vertex)
+                .into_iter()
+                .chain(graph.get_incoming_edges(vertex));
+            for edge in edges {
+                if edge.label() != &EdgeLabel::Transfer {
+                    continue;
+                }
+                let other = if edge.src() == vertex {
+                    edge.dst()
+                } else {
+                    edge.src()
+                };
+                if visited.insert(other) {
+                    candidates.push(other);
+                    queue.push_back((other, depth + 1));
+                }
+            }
+        }
+
+        // 收款地址 -> 向它转账的候选地址及区块
+        let mut senders: HashMap<VertexId, Vec<(VertexId, u64)>> = HashMap::new();
+        for &vertex in &candidates {
+            for edge in graph.get_outgoing_edges(vertex) {
+                if edge.label() != &EdgeLabel::Transfer || edge.dst() == vertex {
+                    continue;
+                }
+                senders
+                    .entry(edge.dst())
+                    .or_default()
+                    .push((vertex, edge.block_number().unwrap_or_default()));
+            }
+        }
+
+        // 统计每对地址在时间窗口内共同转账的收款地址
+        let mut shared: HashMap<(VertexId, VertexId), HashSet<VertexId>> = HashMap::new();
+        for (recipient, transfers) in &senders {
+            if is_hub(*recipient) {
+                continue;
+            }
+            for (i, &(a, block_a)) in transfers.iter().enumerate() {
+                for &(b, block_b) in &transfers[i + 1..] {
+                    if a == b || a == *recipient || b == *recipient {
+                        continue;
+                    }
+                    if block_a.abs_diff(block_b) > params.block_window {
+                        continue;
+                    }
+                    let pair = if a.as_u64() < b.as_u64() {
+                        (a, b)
+                    } else {
+                        (b, a)
+                    };
+                    shared.entry(pair).or_default().insert(*recipient);
+                }
+            }
+        }
+
+        // 并查集合并满足条件的地址对
+        let mut parent: HashMap<VertexId, VertexId> = candidates.iter().map(|&v| (v, v)).collect();
+        fn find(parent: &mut HashMap<VertexId, VertexId>, vertex: VertexId) -> VertexId {
+            let mut root = vertex;
+            while parent[&root] != root {
+                root = parent[&root];
+            }
+            let mut current = vertex;
+            while current != root {
+                let next = parent[&current];
+                parent.insert(current, root);
+                current = next;
+            }
+            root
+        }
+        for ((a, b), recipients) in shared {
+            if recipients.len() < params.min_shared.max(1) {
+                continue;
+            }
+            let (root_a, root_b) = (find(&mut parent, a), find(&mut parent, b));
+            if root_a == root_b {
+                continue;
+            }
+            // 以较小的顶点 ID 作为根，类编号即类内最小 ID
+            if root_a.as_u64() < root_b.as_u64() {
+                parent.insert(root_b, root_a);
+            } else {
+                parent.insert(root_a, root_b);
+            }
+        }
+
+        let mut clusters: Vec<AddressCluster> = candidates
+            .iter()
+            .map(|&vertex| AddressCluster {
+                vertex,
+                cluster: find(&mut parent, vertex),
+            })
+            .collect();
+        clusters.sort_by_key(|c| (c.cluster.as_u64(), c.vertex.as_u64()));
+        Ok(clusters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TokenAmount;
+
+    #[test]
+    fn test_cluster_addresses_separates_entities() {
+        let graph = Graph::in_memory().unwrap();
+        let account = |addr: &str| graph.add_account(addr.to_string()).unwrap();
+        let (a1, a2) = (account("0xa1"), account("0xa2"));
+        let (b1, b2) = (account("0xb1"), account("0xb2"));
+        let (da, da2, db, db2) = (
+            account("0xda"),
+            account("0xda2"),
+            account("0xdb"),
+            account("0xdb2"),
+        );
+        let amount = TokenAmount::from_u64(1);
+        // 实体 A 的两个地址在相近区块向同两个地址转账，实体 B 同理
+        for (src, dst, block) in [
+            (a1, da, 10),
+            (a2, da, 12),
+            (a1, da2, 20),
+            (a2, da2, 25),
+            (b1, db, 10),
+            (b2, db, 11),
+            (b1, db2, 30),
+            (b2, db2, 31),
+            // 两个实体之间的往来不作为证据
+            (a1, b1, 40),
+            (a2, b2, 41),
+            // 区块相距太远的共同收款不计
+            (a1, db, 5_000),
+        ] {
+            graph.add_transfer(src, dst, amount, block).unwrap();
+        }
+
+        let clusters = AddressCluster::compute(&graph, a1, 3, ClusterParams::default()).unwrap();
+        let cluster_of = |vertex| {
+            clusters
+                .iter()
+                .find(|c| c.vertex == vertex)
+                .map(|c| c.cluster)
+                .unwrap()
+        };
+        assert_eq!(clusters.len(), 8);
+        assert_eq!(cluster_of(a1), a1);
+        assert_eq!(cluster_of(a2), a1);
+        assert_eq!(cluster_of(b1), b1);
+        assert_eq!(cluster_of(b2), b1);
+        assert_ne!(cluster_of(a1), cluster_of(b1));
+        // 只接收转账的地址各自成类
+        assert_eq!(cluster_of(da), da);
+        assert_eq!(cluster_of(da2), da2);
+
+        // 要求三个共同收款地址时不再合并；一跳以内只看到种子的直接对手
+        let strict = ClusterParams {
+            min_shared: 3,
+            ..Default::default()
+        };
+        let clusters = AddressCluster::compute(&graph, a1, 3, strict).unwrap();
+        assert!(clusters.iter().all(|c| c.vertex == c.cluster));
+        let nearby = AddressCluster::compute(&graph, a1, 1, ClusterParams::default()).unwrap();
+        assert_eq!(nearby.len(), 5);
+        assert!(
+            AddressCluster::compute(&graph, VertexId::new(999), 1, Default::default()).is_err()
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! 连通分量
+//!
+//! 基于 `GraphView` 计算，配合 `AsUndirected` 可按无向图解释
+
+use crate::graph::{GraphView, VertexId};
+use std::collections::HashSet;
+
+/// 弱连通分量：忽略边的方向，经后继或前驱相连的顶点属于同一分量
+///
+/// 每个分量内按顶点 ID 升序，分量按最小顶点 ID 排序。
+pub fn weakly_connected_components<G: GraphView + ?Sized>(view: &G) -> Vec<Vec<VertexId>> {
+    let mut visited = HashSet::new();
+    let mut components = Vec::new();
+    for start in view.vertex_ids() {
+        if !visited.insert(start) {
+            continue;
+        }
+        let mut component = vec![start];
+        let mut stack = vec![start];
+        while let Some(current) = stack.pop() {
+            let mut adjacent = view.neighbors(current);
+            adjacent.extend(view.predecessors(current));
+            for next in adjacent {
+                if visited.insert(next) {
+                    component.push(next);
+                    stack.push(next);
+                }
+            }
+        }
+        component.sort_by_key(|id| id.as_u64());
+        components.push(component);
+    }
+    components
+}
+
+/// 强连通分量（Kosaraju）：分量内任意两个顶点沿后继方向互相可达
+///
+/// 排序规则与 `weakly_connected_components` 相同。
+pub fn strongly_connected_components<G: GraphView + ?Sized>(view: &G) -> Vec<Vec<VertexId>> {
+    // 第一遍：沿后继做迭代 DFS，记录完成顺序
+    let mut visited = HashSet::new();
+    let mut finished = Vec::new();
+    for start in view.vertex_ids() {
+        if !visited.insert(start) {
+            continue;
+        }
+        let mut stack = vec![(start, view.neighbors(start))];
+        while let Some((vertex, pending)) = stack.last_mut() {
+            match pending.pop() {
+                Some(next) => {
+                    if visited.insert(next) {
+                        let successors = view.neighbors(next);
+                        stack.push((next, successors));
+                    }
+                }
+                None => {
+                    finished.push(*vertex);
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    // 第二遍：按完成顺序倒序沿前驱收集分量
+    let mut assigned = HashSet::new();
+    let mut components = Vec::new();
+    for &root in finished.iter().rev() {
+        if !assigned.insert(root) {
+            continue;
+        }
+        let mut component = vec![root];
+        let mut stack = vec![root];
+        while let Some(current) = stack.pop() {
+            for prev in view.predecessors(current) {
+                if assigned.insert(prev) {
+                    component.push(prev);
+                    stack.push(prev);
+                }
+            }
+        }
+        component.sort_by_key(|id| id.as_u64());
+        components.push(component);
+    }
+    components.sort_by_key(|component| component[0].as_u64());
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{AsUndirected, Graph};
+    use crate::types::TokenAmount;
+
+    #[test]
+    fn test_undirected_view_merges_components() {
+        let graph = Graph::in_memory().unwrap();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        let c = graph.add_account("0xc".to_string()).unwrap();
+        let d = graph.add_account("0xd".to_string()).unwrap();
+        let e = graph.add_account("0xe".to_string()).unwrap();
+        let amount = TokenAmount::from_u64(1);
+        // a <-> b 成环，b -> c 单向，d -> e 单向
+        graph.add_transfer(a, b, amount, 1).unwrap();
+        graph.add_transfer(b, a, amount, 2).unwrap();
+        graph.add_transfer(b, c, amount, 3).unwrap();
+        graph.add_transfer(d, e, amount, 4).unwrap();
+
+        // 有向 SCC 把单向边两端分开
+        let scc = strongly_connected_components(graph.as_ref());
+        assert_eq!(scc, vec![vec![a, b], vec![c], vec![d], vec![e]]);
+
+        // 无向视图下 SCC 与 WCC 一致，单向边两端合并
+        let undirected = AsUndirected(&graph);
+        let wcc = weakly_connected_components(&undirected);
+        assert_eq!(wcc, vec![vec![a, b, c], vec![d, e]]);
+        assert_eq!(strongly_connected_components(&undirected), wcc);
+        assert_eq!(weakly_connected_components(graph.as_ref()), wcc);
+    }
+}
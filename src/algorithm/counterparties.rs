@@ -0,0 +1,165 @@
+//! 交易对手排名
+//!
+//! 由 Transfer 边统计与某地址往来最多的对手地址
+
+use super::TraceDirection;
+use crate::error::{Error, Result};
+use crate::graph::{Graph, VertexId};
+use crate::types::{EdgeLabel, TokenAmount};
+use std::collections::HashMap;
+
+/// 交易对手排序依据
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CounterpartyRank {
+    /// 按转账笔数（默认）
+    #[default]
+    Count,
+    /// 按转账总额
+    Volume,
+}
+
+impl std::str::FromStr for CounterpartyRank {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "count" => Ok(CounterpartyRank::Count),
+            "volume" | "amount" => Ok(CounterpartyRank::Volume),
+            other => Err(Error::QueryError(format!(
+                "未知的排序依据 '{}'，可选: count, volume",
+                other
+            ))),
+        }
+    }
+}
+
+/// 一个交易对手及其与目标地址之间的转账汇总
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counterparty {
+    /// 对手顶点
+    pub vertex: VertexId,
+    /// 转账笔数
+    pub transfer_count: usize,
+    /// 转账总额
+    pub volume: TokenAmount,
+}
+
+impl Counterparty {
+    /// 统计顶点在指定方向上的 Transfer 对手，按 rank 降序返回前 k 个；
+    /// 排序依据相同时按另一项指标、再按顶点 ID 排序，结果稳定
+    pub fn top(
+        graph: &Graph,
+        vertex: VertexId,
+        k: usize,
+        direction: TraceDirection,
+        rank: CounterpartyRank,
+    ) -> Result<Vec<Self>> {
+        if !graph.vertex_exists(vertex) {
+            return Err(Error::VertexNotFound(format!("{:?}", vertex)));
+        }
+
+        let mut edges = Vec::new();
+        if direction != TraceDirection::Backward {
+            edges.extend(graph.get_outgoing_edges(vertex));
+        }
+        if direction != TraceDirection::Forward {
+            edges.extend(graph.get_incoming_edges(vertex));
+        }
+
+        let mut totals: HashMap<VertexId, Counterparty> = HashMap::new();
+        for edge in edges {
+            if edge.label() != &EdgeLabel::Transfer {
+                continue;
+            }
+            let other = if edge.src() == vertex {
+                edge.dst()
+            } else {
+                edge.src()
+            };
+            if other == vertex {
+                continue;
+            }
+            let entry = totals.entry(other).or_insert(Counterparty {
+                vertex: other,
+                transfer_count: 0,
+                volume: TokenAmount::default(),
+            });
+            entry.transfer_count += 1;
+            let amount = edge.amount().map(|a| a.0).unwrap_or_default();
+            entry.volume.0 = entry.volume.0.saturating_add(amount);
+        }
+
+        let mut ranked: Vec<Counterparty> = totals.into_values().collect();
+        ranked.sort_by(|a, b| {
+            let by_count = b.transfer_count.cmp(&a.transfer_count);
+            let by_volume = b.volume.0.cmp(&a.volume.0);
+            match rank {
+                CounterpartyRank::Count => by_count.then(by_volume),
+                CounterpartyRank::Volume => by_volume.then(by_count),
+            }
+            .then(a.vertex.as_u64().cmp(&b.vertex.as_u64()))
+        });
+        ranked.truncate(k);
+        Ok(ranked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_counterparties() {
+        let graph = Graph::in_memory().unwrap();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        let c = graph.add_account("0xc".to_string()).unwrap();
+        let d = graph.add_account("0xd".to_string()).unwrap();
+        // a -> b 三笔小额，a -> c 一笔大额，d -> a 两笔
+        for block in 1..=3 {
+            graph
+                .add_transfer(a, b, TokenAmount::from_u64(10), block)
+                .unwrap();
+        }
+        graph
+            .add_transfer(a, c, TokenAmount::from_u64(500), 4)
+            .unwrap();
+        for block in 5..=6 {
+            graph
+                .add_transfer(d, a, TokenAmount::from_u64(40), block)
+                .unwrap();
+        }
+        graph.add_edge(EdgeLabel::Call, a, d).unwrap();
+
+        let top = |k, direction, rank| -> Vec<(VertexId, usize)> {
+            Counterparty::top(&graph, a, k, direction, rank)
+                .unwrap()
+                .into_iter()
+                .map(|c| (c.vertex, c.transfer_count))
+                .collect()
+        };
+
+        let both = TraceDirection::Both;
+        assert_eq!(
+            top(3, both, CounterpartyRank::Count),
+            [(b, 3), (d, 2), (c, 1)]
+        );
+        assert_eq!(top(2, both, CounterpartyRank::Volume), [(c, 1), (d, 2)]);
+        // 只看转出：d 是转入方，Call 边不计
+        assert_eq!(
+            top(5, TraceDirection::Forward, CounterpartyRank::Count),
+            [(b, 3), (c, 1)]
+        );
+        assert_eq!(
+            top(5, TraceDirection::Backward, CounterpartyRank::Count),
+            [(d, 2)]
+        );
+
+        let first = Counterparty::top(&graph, a, 1, both, CounterpartyRank::Count).unwrap();
+        assert_eq!(first[0].volume, TokenAmount::from_u64(30));
+        assert!(
+            Counterparty::top(&graph, VertexId::new(999), 3, both, CounterpartyRank::Count)
+                .is_err()
+        );
+    }
+}
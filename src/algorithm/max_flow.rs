@@ -1,8 +1,10 @@
 //! 最大流算法
 //!
-//! 实现 Edmonds-Karp 算法（基于 BFS 的 Ford-Fulkerson）
+//! 实现 Edmonds-Karp 算法（基于 BFS 的 Ford-Fulkerson）与 Dinic 算法，
 //! 用于分析区块链资金流动的最大通量
 
+use super::CancellationToken;
+use crate::error::{Error, Result};
 use crate::graph::{Graph, VertexId};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet, VecDeque};
@@ -19,52 +21,143 @@ pub struct MaxFlow {
     pub source_side: HashSet<VertexId>,
 }
 
+/// 结果中默认最多列出的流量边数（REST 接口与 `CALL max_flow` 共用）
+pub const DEFAULT_MAX_FLOW_EDGES: usize = 100;
+
+/// 一条边上的流量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlowEdge {
+    pub source: VertexId,
+    pub target: VertexId,
+    pub flow: f64,
+}
+
+impl MaxFlow {
+    /// 按流量从大到小取前 `limit` 条边，流量相同时按顶点 ID 排序以保证结果稳定；
+    /// 第二个返回值表示是否被截断
+    pub fn top_flows(&self, limit: usize) -> (Vec<FlowEdge>, bool) {
+        let mut edges: Vec<FlowEdge> = self
+            .flow
+            .iter()
+            .map(|(&(source, target), &flow)| FlowEdge {
+                source,
+                target,
+                flow,
+            })
+            .collect();
+        edges.sort_by(|a, b| {
+            b.flow
+                .total_cmp(&a.flow)
+                .then(a.source.as_u64().cmp(&b.source.as_u64()))
+                .then(a.target.as_u64().cmp(&b.target.as_u64()))
+        });
+        let truncated = edges.len() > limit;
+        edges.truncate(limit);
+        (edges, truncated)
+    }
+}
+
+/// 最大流算法选择；序列化为 `edmonds_karp` / `dinic`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaxFlowAlgorithm {
+    /// Edmonds-Karp（默认）
+    #[default]
+    EdmondsKarp,
+    /// Dinic，适合边数多的稠密图
+    Dinic,
+}
+
+impl std::str::FromStr for MaxFlowAlgorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "edmonds_karp" | "edmonds-karp" | "ek" => Ok(MaxFlowAlgorithm::EdmondsKarp),
+            "dinic" => Ok(MaxFlowAlgorithm::Dinic),
+            other => Err(Error::QueryError(format!(
+                "未知的最大流算法 '{}'，可选: edmonds_karp, dinic",
+                other
+            ))),
+        }
+    }
+}
+
+impl MaxFlowAlgorithm {
+    /// 用所选算法计算从 source 到 sink 的最大流
+    pub fn max_flow(
+        self,
+        graph: Arc<Graph>,
+        cancel: CancellationToken,
+        source: VertexId,
+        sink: VertexId,
+    ) -> Result<MaxFlow> {
+        match self {
+            MaxFlowAlgorithm::EdmondsKarp => EdmondsKarp::new(graph)
+                .with_cancellation(cancel)
+                .max_flow(source, sink),
+            MaxFlowAlgorithm::Dinic => Dinic::new(graph)
+                .with_cancellation(cancel)
+                .max_flow(source, sink),
+        }
+    }
+}
+
+/// 收集边容量：同一对顶点之间的 Transfer 边容量累加；
+/// 图中没有 Transfer 边时退而使用所有边
+fn collect_capacity(graph: &Graph) -> HashMap<(VertexId, VertexId), f64> {
+    let mut capacity: HashMap<(VertexId, VertexId), f64> = HashMap::new();
+    for edge_id in graph
+        .edge_index()
+        .get_by_label(&crate::types::EdgeLabel::Transfer)
+    {
+        if let Some(edge) = graph.get_edge(edge_id) {
+            *capacity.entry((edge.src(), edge.dst())).or_insert(0.0) += edge.weight();
+        }
+    }
+
+    if capacity.is_empty() {
+        for label in &[
+            crate::types::VertexLabel::Account,
+            crate::types::VertexLabel::Contract,
+            crate::types::VertexLabel::Token,
+        ] {
+            for vertex_id in graph.vertex_index().get_by_label(label) {
+                for edge in graph.get_outgoing_edges(vertex_id) {
+                    *capacity.entry((edge.src(), edge.dst())).or_insert(0.0) += edge.weight();
+                }
+            }
+        }
+    }
+    capacity
+}
+
 /// Edmonds-Karp 最大流算法
 pub struct EdmondsKarp {
     graph: Arc<Graph>,
+    /// 取消令牌，每轮增广前检查
+    cancel: CancellationToken,
 }
 
 impl EdmondsKarp {
     /// 创建算法实例
     pub fn new(graph: Arc<Graph>) -> Self {
-        Self { graph }
+        Self {
+            graph,
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// 设置取消令牌：令牌被取消后 max_flow 尽快返回 `Error::Cancelled`
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
     }
 
     /// 计算从 source 到 sink 的最大流
-    pub fn max_flow(&self, source: VertexId, sink: VertexId) -> MaxFlow {
+    pub fn max_flow(&self, source: VertexId, sink: VertexId) -> Result<MaxFlow> {
         // 构建容量矩阵
-        let mut capacity: HashMap<(VertexId, VertexId), f64> = HashMap::new();
-        let mut vertices = HashSet::new();
-
-        // 收集所有边和顶点
-        for edge_id in self
-            .graph
-            .edge_index()
-            .get_by_label(&crate::types::EdgeLabel::Transfer)
-        {
-            if let Some(edge) = self.graph.get_edge(edge_id) {
-                let src = edge.src();
-                let dst = edge.dst();
-                vertices.insert(src);
-                vertices.insert(dst);
-
-                // 累加同一边的容量
-                *capacity.entry((src, dst)).or_insert(0.0) += edge.weight();
-            }
-        }
-
-        // 如果没有转账边，尝试使用所有边
-        if capacity.is_empty() {
-            for vertex_id in self.get_all_vertices() {
-                for edge in self.graph.get_outgoing_edges(vertex_id) {
-                    let src = edge.src();
-                    let dst = edge.dst();
-                    vertices.insert(src);
-                    vertices.insert(dst);
-                    *capacity.entry((src, dst)).or_insert(0.0) += edge.weight();
-                }
-            }
-        }
+        let capacity = collect_capacity(&self.graph);
 
         // 流量矩阵
         let mut flow: HashMap<(VertexId, VertexId), f64> = HashMap::new();
@@ -72,14 +165,16 @@ impl EdmondsKarp {
         // 构建邻接表
         let mut adj: HashMap<VertexId, Vec<VertexId>> = HashMap::new();
         for &(src, dst) in capacity.keys() {
-            adj.entry(src).or_insert_with(Vec::new).push(dst);
-            adj.entry(dst).or_insert_with(Vec::new).push(src); // 反向边
+            adj.entry(src).or_default().push(dst);
+            adj.entry(dst).or_default().push(src); // 反向边
         }
 
         let mut max_flow_value = 0.0;
 
         // Edmonds-Karp: 重复 BFS 找增广路径
         loop {
+            self.cancel.check()?;
+
             // BFS 找增广路径
             let path = self.bfs_find_path(source, sink, &capacity, &flow, &adj);
 
@@ -107,11 +202,11 @@ impl EdmondsKarp {
         let positive_flow: HashMap<(VertexId, VertexId), f64> =
             flow.into_iter().filter(|(_, v)| *v > 0.0).collect();
 
-        MaxFlow {
+        Ok(MaxFlow {
             value: max_flow_value,
             flow: positive_flow,
             source_side,
-        }
+        })
     }
 
     /// BFS 找增广路径
@@ -211,33 +306,24 @@ impl EdmondsKarp {
         visited
     }
 
-    /// 获取所有顶点（辅助方法）
-    fn get_all_vertices(&self) -> Vec<VertexId> {
-        let mut vertices = Vec::new();
-        for label in &[
-            crate::types::VertexLabel::Account,
-            crate::types::VertexLabel::Contract,
-            crate::types::VertexLabel::Token,
-        ] {
-            vertices.extend(self.graph.vertex_index().get_by_label(label));
-        }
-        vertices
-    }
-
     /// 计算多源多汇最大流
     /// 通过添加超级源点和超级汇点实现
-    pub fn multi_source_sink_max_flow(&self, sources: &[VertexId], sinks: &[VertexId]) -> f64 {
+    pub fn multi_source_sink_max_flow(
+        &self,
+        sources: &[VertexId],
+        sinks: &[VertexId],
+    ) -> Result<f64> {
         // 简化实现：计算所有源-汇对的最大流之和
         let mut total_flow = 0.0;
         for &source in sources {
             for &sink in sinks {
                 if source != sink {
-                    let result = self.max_flow(source, sink);
+                    let result = self.max_flow(source, sink)?;
                     total_flow += result.value;
                 }
             }
         }
-        total_flow
+        Ok(total_flow)
     }
 
     /// 分析资金流动瓶颈
@@ -246,8 +332,8 @@ impl EdmondsKarp {
         &self,
         source: VertexId,
         sink: VertexId,
-    ) -> Vec<(VertexId, VertexId, f64)> {
-        let result = self.max_flow(source, sink);
+    ) -> Result<Vec<(VertexId, VertexId, f64)>> {
+        let result = self.max_flow(source, sink)?;
 
         // 瓶颈边是那些流量等于容量的边
         let mut bottlenecks = Vec::new();
@@ -271,7 +357,200 @@ impl EdmondsKarp {
             }
         }
 
-        bottlenecks
+        Ok(bottlenecks)
+    }
+}
+
+/// Dinic 最大流算法
+///
+/// 每轮先用 BFS 给残余网络分层，再在层次图上推送阻塞流，轮数不超过顶点数；
+/// 在稠密的容量图上比每次只增广一条最短路径的 Edmonds-Karp 快得多。
+/// 最大流值与 `EdmondsKarp` 相同，流量在等价路径间的分配可能不同
+pub struct Dinic {
+    graph: Arc<Graph>,
+    /// 取消令牌，每轮分层前检查
+    cancel: CancellationToken,
+}
+
+impl Dinic {
+    /// 创建算法实例
+    pub fn new(graph: Arc<Graph>) -> Self {
+        Self {
+            graph,
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// 设置取消令牌：令牌被取消后 max_flow 尽快返回 `Error::Cancelled`
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// 计算从 source 到 sink 的最大流
+    pub fn max_flow(&self, source: VertexId, sink: VertexId) -> Result<MaxFlow> {
+        let capacity = collect_capacity(&self.graph);
+        let mut network = ResidualNetwork::new(&capacity);
+        let (s, t) = match (network.index.get(&source), network.index.get(&sink)) {
+            (Some(&s), Some(&t)) if s != t => (s, t),
+            _ => {
+                return Ok(MaxFlow {
+                    value: 0.0,
+                    flow: HashMap::new(),
+                    source_side: HashSet::from([source]),
+                })
+            }
+        };
+
+        let mut max_flow_value = 0.0;
+        loop {
+            self.cancel.check()?;
+            let level = network.levels(s);
+            if level[t] == usize::MAX {
+                break;
+            }
+            // 当前弧指针：已确认走不通的弧在本轮不再尝试
+            let mut next_arc = vec![0; network.adj.len()];
+            while let Some(pushed) = network.augment(s, t, &level, &mut next_arc) {
+                max_flow_value += pushed;
+            }
+        }
+
+        // 正向弧上的流量为容量减残余容量，互为反向的两条边只保留净流量
+        let mut flow: HashMap<(VertexId, VertexId), f64> = HashMap::new();
+        for arc in (0..network.to.len()).step_by(2) {
+            let used = network.capacity[arc] - network.residual[arc];
+            if used == 0.0 {
+                continue;
+            }
+            let u = network.vertices[network.to[arc ^ 1]];
+            let v = network.vertices[network.to[arc]];
+            *flow.entry((u, v)).or_insert(0.0) += used;
+            *flow.entry((v, u)).or_insert(0.0) -= used;
+        }
+        let positive_flow: HashMap<(VertexId, VertexId), f64> =
+            flow.into_iter().filter(|(_, v)| *v > 0.0).collect();
+
+        let level = network.levels(s);
+        let source_side = (0..network.vertices.len())
+            .filter(|&i| level[i] != usize::MAX)
+            .map(|i| network.vertices[i])
+            .collect();
+
+        Ok(MaxFlow {
+            value: max_flow_value,
+            flow: positive_flow,
+            source_side,
+        })
+    }
+}
+
+/// 以下标表示的残余网络；弧 `i ^ 1` 是弧 `i` 的反向弧
+struct ResidualNetwork {
+    vertices: Vec<VertexId>,
+    index: HashMap<VertexId, usize>,
+    /// 每个顶点的出弧
+    adj: Vec<Vec<usize>>,
+    /// 弧的终点
+    to: Vec<usize>,
+    /// 弧的原始容量（反向弧为 0）
+    capacity: Vec<f64>,
+    /// 弧的残余容量
+    residual: Vec<f64>,
+}
+
+impl ResidualNetwork {
+    fn new(capacity: &HashMap<(VertexId, VertexId), f64>) -> Self {
+        let mut network = Self {
+            vertices: Vec::new(),
+            index: HashMap::new(),
+            adj: Vec::new(),
+            to: Vec::new(),
+            capacity: Vec::new(),
+            residual: Vec::new(),
+        };
+        for (&(src, dst), &cap) in capacity {
+            let (u, v) = (network.vertex(src), network.vertex(dst));
+            for (from, to, cap) in [(u, v, cap), (v, u, 0.0)] {
+                network.adj[from].push(network.to.len());
+                network.to.push(to);
+                network.capacity.push(cap);
+                network.residual.push(cap);
+            }
+        }
+        network
+    }
+
+    fn vertex(&mut self, id: VertexId) -> usize {
+        if let Some(&i) = self.index.get(&id) {
+            return i;
+        }
+        self.index.insert(id, self.vertices.len());
+        self.vertices.push(id);
+        self.adj.push(Vec::new());
+        self.vertices.len() - 1
+    }
+
+    /// 沿残余容量为正的弧 BFS 分层，不可达的顶点层数为 `usize::MAX`
+    fn levels(&self, s: usize) -> Vec<usize> {
+        let mut level = vec![usize::MAX; self.adj.len()];
+        let mut queue = VecDeque::from([s]);
+        level[s] = 0;
+        while let Some(u) = queue.pop_front() {
+            for &arc in &self.adj[u] {
+                let v = self.to[arc];
+                if level[v] == usize::MAX && self.residual[arc] > 0.0 {
+                    level[v] = level[u] + 1;
+                    queue.push_back(v);
+                }
+            }
+        }
+        level
+    }
+
+    /// 在层次图上找一条增广路径并推送瓶颈流量，找不到时返回 None。
+    /// 用显式栈代替递归，长路径不会耗尽调用栈
+    fn augment(
+        &mut self,
+        s: usize,
+        t: usize,
+        level: &[usize],
+        next_arc: &mut [usize],
+    ) -> Option<f64> {
+        let mut path: Vec<usize> = Vec::new();
+        let mut u = s;
+        loop {
+            if u == t {
+                let bottleneck = path
+                    .iter()
+                    .map(|&arc| self.residual[arc])
+                    .fold(f64::INFINITY, f64::min);
+                for &arc in &path {
+                    self.residual[arc] -= bottleneck;
+                    self.residual[arc ^ 1] += bottleneck;
+                }
+                return Some(bottleneck);
+            }
+
+            let mut advanced = false;
+            while next_arc[u] < self.adj[u].len() {
+                let arc = self.adj[u][next_arc[u]];
+                let v = self.to[arc];
+                if self.residual[arc] > 0.0 && level[v] == level[u] + 1 {
+                    path.push(arc);
+                    u = v;
+                    advanced = true;
+                    break;
+                }
+                next_arc[u] += 1;
+            }
+            if !advanced {
+                // 死路：退回上一个顶点并跳过通往这里的弧
+                let arc = path.pop()?;
+                u = self.to[arc ^ 1];
+                next_arc[u] += 1;
+            }
+        }
     }
 }
 
@@ -331,7 +610,7 @@ mod tests {
         let graph = create_flow_graph();
         let algo = EdmondsKarp::new(graph);
 
-        let result = algo.max_flow(VertexId::new(1), VertexId::new(5));
+        let result = algo.max_flow(VertexId::new(1), VertexId::new(5)).unwrap();
 
         // 最大流应该是 15 (10 through A + 5 through B-C)
         assert!(
@@ -358,7 +637,7 @@ mod tests {
             .unwrap();
 
         let algo = EdmondsKarp::new(graph);
-        let result = algo.max_flow(v1, v3);
+        let result = algo.max_flow(v1, v3).unwrap();
 
         // 瓶颈在 v2 -> v3，最大流是 5
         assert!((result.value - 5.0).abs() < 0.01);
@@ -390,9 +669,152 @@ mod tests {
             .unwrap();
 
         let algo = EdmondsKarp::new(graph);
-        let result = algo.max_flow(s, t);
+        let result = algo.max_flow(s, t).unwrap();
 
         // 总流量应该是 15
         assert!((result.value - 15.0).abs() < 0.01);
     }
+
+    #[test]
+    fn test_top_flows() {
+        let graph = create_flow_graph();
+        let algo = EdmondsKarp::new(graph);
+        let result = algo.max_flow(VertexId::new(1), VertexId::new(5)).unwrap();
+
+        let (all, truncated) = result.top_flows(usize::MAX);
+        assert!(!truncated);
+        assert_eq!(all.len(), result.flow.len());
+        assert!(all.windows(2).all(|w| w[0].flow >= w[1].flow));
+
+        // 截断后保留流量最大的边
+        let (top, truncated) = result.top_flows(2);
+        assert!(truncated);
+        assert_eq!(top, all[..2]);
+    }
+
+    #[test]
+    fn test_max_flow_cancelled() {
+        let graph = create_flow_graph();
+        let cancel = CancellationToken::new();
+        let algo = EdmondsKarp::new(graph).with_cancellation(cancel.clone());
+
+        cancel.cancel();
+        let result = algo.max_flow(VertexId::new(1), VertexId::new(5));
+        assert!(matches!(result, Err(crate::error::Error::Cancelled)));
+    }
+
+    #[test]
+    fn test_dinic_matches_edmonds_karp() {
+        let check = |graph: Arc<Graph>, source: VertexId, sink: VertexId| {
+            let expected = EdmondsKarp::new(graph.clone())
+                .max_flow(source, sink)
+                .unwrap();
+            let result = Dinic::new(graph).max_flow(source, sink).unwrap();
+            assert!(
+                (result.value - expected.value).abs() < 1e-6,
+                "Dinic {} != Edmonds-Karp {}",
+                result.value,
+                expected.value
+            );
+            // 最小割的源侧不含汇点，流量守恒
+            assert!(result.source_side.contains(&source));
+            assert!(!result.source_side.contains(&sink) || result.value == 0.0);
+            let mut balance: HashMap<VertexId, f64> = HashMap::new();
+            for (&(u, v), &f) in &result.flow {
+                *balance.entry(u).or_default() -= f;
+                *balance.entry(v).or_default() += f;
+            }
+            for (vertex, net) in balance {
+                if vertex != source && vertex != sink {
+                    assert!(net.abs() < 1e-6, "{:?} 流量不守恒: {}", vertex, net);
+                }
+            }
+            result.value
+        };
+
+        assert_eq!(
+            check(create_flow_graph(), VertexId::new(1), VertexId::new(5)),
+            15.0
+        );
+
+        // 互为反向的边、孤立的汇点、源点等于汇点
+        let graph = Graph::in_memory().unwrap();
+        let v: Vec<VertexId> = (0..5)
+            .map(|_| graph.add_vertex(VertexLabel::Account).unwrap())
+            .collect();
+        for (src, dst, amount) in [(0, 1, 7), (1, 0, 3), (1, 2, 4), (0, 2, 2), (2, 3, 9)] {
+            graph
+                .add_transfer(v[src], v[dst], TokenAmount::from_u64(amount), 1)
+                .unwrap();
+        }
+        assert_eq!(check(graph.clone(), v[0], v[3]), 6.0);
+        assert_eq!(check(graph.clone(), v[0], v[4]), 0.0);
+        assert_eq!(check(graph, v[2], v[2]), 0.0);
+
+        // 随机稠密图
+        use rand::{Rng, SeedableRng};
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        for _ in 0..5 {
+            let graph = Graph::in_memory().unwrap();
+            let v: Vec<VertexId> = (0..12)
+                .map(|_| graph.add_vertex(VertexLabel::Account).unwrap())
+                .collect();
+            for _ in 0..60 {
+                let (src, dst) = (rng.gen_range(0..12), rng.gen_range(0..12));
+                if src != dst {
+                    let amount = TokenAmount::from_u64(rng.gen_range(1..100));
+                    graph.add_transfer(v[src], v[dst], amount, 1).unwrap();
+                }
+            }
+            check(graph, v[0], v[11]);
+        }
+    }
+
+    #[test]
+    fn test_select_max_flow_algorithm() {
+        assert_eq!(
+            "Dinic".parse::<MaxFlowAlgorithm>().unwrap(),
+            MaxFlowAlgorithm::Dinic
+        );
+        assert_eq!(
+            "ek".parse::<MaxFlowAlgorithm>().unwrap(),
+            MaxFlowAlgorithm::EdmondsKarp
+        );
+        assert!("push_relabel".parse::<MaxFlowAlgorithm>().is_err());
+        assert_eq!(MaxFlowAlgorithm::default(), MaxFlowAlgorithm::EdmondsKarp);
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let result = MaxFlowAlgorithm::Dinic.max_flow(
+            create_flow_graph(),
+            cancel,
+            VertexId::new(1),
+            VertexId::new(5),
+        );
+        assert!(matches!(result, Err(crate::error::Error::Cancelled)));
+    }
+
+    #[test]
+    fn test_capacity_from_typed_amount_after_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let graph = Graph::open(dir.path(), Some(64)).unwrap();
+            let s = graph.add_vertex(VertexLabel::Account).unwrap();
+            let t = graph.add_vertex(VertexLabel::Account).unwrap();
+            graph
+                .add_transfer(s, t, TokenAmount::from_u64(42), 1)
+                .unwrap();
+            graph.flush().unwrap();
+        }
+
+        // 从磁盘加载的边重建类型化金额，容量直接取自该字段
+        let graph = Graph::open(dir.path(), Some(64)).unwrap();
+        let edge = graph.get_outgoing_edges(VertexId::new(1)).remove(0);
+        assert_eq!(edge.amount(), Some(&TokenAmount::from_u64(42)));
+
+        let result = EdmondsKarp::new(graph)
+            .max_flow(VertexId::new(1), VertexId::new(2))
+            .unwrap();
+        assert_eq!(result.value, 42.0);
+    }
 }
@@ -1,9 +1,27 @@
 //! 图算法模块
 //!
-//! 包含路径追踪和最大流算法
+//! 包含路径追踪、最大流（Edmonds-Karp 与 Dinic）、连通分量、净余额计算、交易对手排名和地址聚类，
+//! 以及并行计算使用的线程池
 
+mod balance;
+mod cancel;
+mod clustering;
+mod components;
+mod counterparties;
 mod max_flow;
 mod path_tracing;
+mod thread_pool;
 
-pub use max_flow::{EdmondsKarp, MaxFlow};
-pub use path_tracing::{PathFinder, PathResult, TraceDirection};
+pub use balance::NetBalance;
+pub use cancel::{CancelGuard, CancellationToken};
+pub use clustering::{AddressCluster, ClusterParams};
+pub use components::{strongly_connected_components, weakly_connected_components};
+pub use counterparties::{Counterparty, CounterpartyRank};
+pub use max_flow::{
+    Dinic, EdmondsKarp, FlowEdge, MaxFlow, MaxFlowAlgorithm, DEFAULT_MAX_FLOW_EDGES,
+};
+pub use path_tracing::{
+    MultiSourcePaths, PathFinder, PathResult, Reachability, TraceDirection, ValueFlowPath,
+    MAX_ALL_PATHS, MAX_REACHABILITY_SEEDS,
+};
+pub use thread_pool::build_thread_pool;
@@ -2,14 +2,19 @@
 //!
 //! 用于区块链链路追踪场景
 
-use crate::graph::{EdgeId, Graph, VertexId};
-use crate::types::EdgeLabel;
+use super::CancellationToken;
+use crate::error::{Error, Result};
+use crate::graph::{Edge, EdgeId, Graph, VertexId};
+use crate::types::{EdgeLabel, TokenAmount};
+use rayon::prelude::*;
+use rayon::ThreadPool;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 
-/// 追踪方向
+/// 追踪方向；序列化为小写（`forward` / `backward` / `both`）
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TraceDirection {
     /// 正向追踪（沿出边方向）
     Forward,
@@ -30,6 +35,18 @@ pub struct PathResult {
     pub length: usize,
     /// 路径总权重（如总金额）
     pub total_weight: f64,
+    /// 终点的分支数超过追踪的扇出上限，未继续展开
+    #[serde(default)]
+    pub truncated: bool,
+    /// 路径上各边转账金额之和
+    #[serde(default)]
+    pub total_amount: TokenAmount,
+    /// 路径上最早的区块号，没有边带区块号时为空
+    #[serde(default)]
+    pub min_block: Option<u64>,
+    /// 路径上最晚的区块号
+    #[serde(default)]
+    pub max_block: Option<u64>,
 }
 
 impl PathResult {
@@ -39,6 +56,10 @@ impl PathResult {
             edges: Vec::new(),
             length: 0,
             total_weight: 0.0,
+            truncated: false,
+            total_amount: TokenAmount::default(),
+            min_block: None,
+            max_block: None,
         }
     }
 
@@ -48,19 +69,201 @@ impl PathResult {
             edges: Vec::new(),
             length: 0,
             total_weight: 0.0,
+            truncated: false,
+            total_amount: TokenAmount::default(),
+            min_block: None,
+            max_block: None,
         }
     }
+
+    /// 由路径上的边汇总转账总额与区块范围
+    fn summarize(&mut self, graph: &Graph) {
+        self.total_amount = TokenAmount::default();
+        self.min_block = None;
+        self.max_block = None;
+        for edge in self.edges.iter().filter_map(|&id| graph.get_edge(id)) {
+            if let Some(amount) = edge.amount() {
+                self.total_amount.0 = self.total_amount.0.saturating_add(amount.0);
+            }
+            if let Some(block) = edge.block_number() {
+                self.min_block = Some(self.min_block.map_or(block, |min| min.min(block)));
+                self.max_block = Some(self.max_block.map_or(block, |max| max.max(block)));
+            }
+        }
+    }
+}
+
+/// 按比例传递的资金路径
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValueFlowPath {
+    /// 路径本身
+    pub path: PathResult,
+    /// 起点资金沿该路径到达终点的比例（0.0 ~ 1.0）
+    pub fraction: f64,
+}
+
+/// 多起点到同一终点的最短路径
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiSourcePaths {
+    /// 与输入起点一一对应，不可达的起点为 None
+    pub paths: Vec<Option<PathResult>>,
+    /// 反向 BFS 访问的顶点数
+    pub visited: usize,
 }
 
+/// `reachability` 一次最多接受的种子顶点数
+pub const MAX_REACHABILITY_SEEDS: usize = 64;
+
+/// 可达矩阵中的一项：from 能否在限定跳数内到达 to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reachability {
+    pub from: VertexId,
+    pub to: VertexId,
+    pub reachable: bool,
+}
+
+/// `edge` 是否早于 `previous`：两条边都有 timestamp 时按时间比较，
+/// 否则按 block_number；缺少比较依据时不算更早
+fn happens_before(edge: &Edge, previous: &Edge) -> bool {
+    match (edge.timestamp(), previous.timestamp()) {
+        (Some(time), Some(prev)) => time < prev,
+        _ => matches!(
+            (edge.block_number(), previous.block_number()),
+            (Some(block), Some(prev)) if block < prev
+        ),
+    }
+}
+
+/// 服务端 all_paths 单次请求最多返回的路径数
+pub const MAX_ALL_PATHS: usize = 10_000;
+
+/// 路径搜索可经过的边的判定条件
+pub type EdgePredicate = Arc<dyn Fn(&Edge) -> bool + Send + Sync>;
+
 /// 路径查找器
 pub struct PathFinder {
     graph: Arc<Graph>,
+    /// all_paths 是否要求沿路径的 block_number 单调不减
+    monotonic_blocks: bool,
+    /// 取消令牌，all_paths 在每次扩展前检查
+    cancel: CancellationToken,
+    /// trace 经过的顶点分支数上限，超过时不再经由该顶点展开
+    max_fanout: Option<usize>,
+    /// shortest_path / all_paths 沿边的方向
+    direction: TraceDirection,
+    /// shortest_path / all_paths 只经过满足条件的边
+    edge_filter: Option<EdgePredicate>,
+    /// all_paths 是否按起点的各条出边并行搜索
+    parallel: bool,
+    /// all_paths 最多返回的路径数
+    max_paths: Option<usize>,
+    /// 并行搜索使用的线程池，未设置时使用 rayon 全局线程池
+    thread_pool: Option<Arc<ThreadPool>>,
 }
 
 impl PathFinder {
     /// 创建路径查找器
     pub fn new(graph: Arc<Graph>) -> Self {
-        Self { graph }
+        Self {
+            graph,
+            monotonic_blocks: false,
+            cancel: CancellationToken::new(),
+            max_fanout: None,
+            direction: TraceDirection::Forward,
+            edge_filter: None,
+            parallel: false,
+            max_paths: None,
+            thread_pool: None,
+        }
+    }
+
+    /// 设置时间因果约束：开启后 all_paths 只返回后继边不早于前驱边的路径。
+    /// 相邻两条边都有 timestamp 时按时间比较，否则按 block_number；
+    /// 缺少比较依据的边不受约束
+    pub fn with_monotonic_blocks(mut self, monotonic_blocks: bool) -> Self {
+        self.monotonic_blocks = monotonic_blocks;
+        self
+    }
+
+    /// 设置追踪的扇出上限：trace 途经的顶点（不含起点）符合过滤条件的边数
+    /// 超过上限时不再展开，到达该顶点的路径标记为 `truncated`。
+    /// 用于避免经过交易所热钱包等枢纽地址时路径数爆炸
+    pub fn with_max_fanout(mut self, max_fanout: Option<usize>) -> Self {
+        self.max_fanout = max_fanout;
+        self
+    }
+
+    /// 设置 shortest_path、all_paths 与 k_shortest_paths 的搜索方向，默认沿出边
+    pub fn with_direction(mut self, direction: TraceDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// 限制 shortest_path、all_paths 与 k_shortest_paths 只经过满足条件的边，
+    /// 如只走 Transfer 边
+    pub fn with_edge_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&Edge) -> bool + Send + Sync + 'static,
+    {
+        self.edge_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// 按搜索方向和边过滤条件列出当前顶点可走的边及其另一端
+    fn next_hops(&self, current: VertexId) -> Vec<(Edge, VertexId)> {
+        self.hops(current, self.direction)
+    }
+
+    fn hops(&self, current: VertexId, direction: TraceDirection) -> Vec<(Edge, VertexId)> {
+        let mut edges = match direction {
+            TraceDirection::Forward => self.graph.get_outgoing_edges(current),
+            TraceDirection::Backward => self.graph.get_incoming_edges(current),
+            TraceDirection::Both => {
+                let mut all = self.graph.get_outgoing_edges(current);
+                all.extend(self.graph.get_incoming_edges(current));
+                all
+            }
+        };
+        if let Some(filter) = &self.edge_filter {
+            edges.retain(|edge| filter(edge));
+        }
+        edges
+            .into_iter()
+            .map(|edge| {
+                let neighbor = if edge.src() == current {
+                    edge.dst()
+                } else {
+                    edge.src()
+                };
+                (edge, neighbor)
+            })
+            .collect()
+    }
+
+    /// 开启后 all_paths 把起点的每条出边交给 rayon 线程池分别搜索，
+    /// 结果按出边顺序合并，与串行搜索的结果和顺序一致
+    pub fn with_parallelism(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// 设置并行搜索使用的线程池，限制 all_paths 占用的线程数
+    pub fn with_thread_pool(mut self, pool: Arc<ThreadPool>) -> Self {
+        self.thread_pool = Some(pool);
+        self
+    }
+
+    /// 设置 all_paths 返回的路径数上限，达到上限后停止搜索。
+    /// 并行搜索时每个分支最多保留上限条路径，合并后截断
+    pub fn with_max_paths(mut self, max_paths: Option<usize>) -> Self {
+        self.max_paths = max_paths;
+        self
+    }
+
+    /// 设置取消令牌：令牌被取消后 all_paths 尽快返回 `Error::Cancelled`
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
     }
 
     /// BFS 最短路径查找
@@ -77,8 +280,7 @@ impl PathFinder {
         queue.push_back(start);
 
         while let Some(current) = queue.pop_front() {
-            for edge in self.graph.get_outgoing_edges(current) {
-                let neighbor = edge.dst();
+            for (edge, neighbor) in self.next_hops(current) {
                 if !visited.contains(&neighbor) {
                     visited.insert(neighbor);
                     parent.insert(neighbor, (current, edge.id()));
@@ -95,6 +297,68 @@ impl PathFinder {
         None
     }
 
+    /// 多个起点到同一终点的最短路径：从终点沿反方向做一次 BFS，
+    /// 所有起点都已到达时提前结束，代替逐个起点调用 `shortest_path`
+    pub fn multi_source_shortest(
+        &self,
+        sources: &[VertexId],
+        target: VertexId,
+    ) -> Result<MultiSourcePaths> {
+        let reverse = match self.direction {
+            TraceDirection::Forward => TraceDirection::Backward,
+            TraceDirection::Backward => TraceDirection::Forward,
+            TraceDirection::Both => TraceDirection::Both,
+        };
+
+        // 顶点 -> (朝终点方向的下一个顶点, 经过的边)
+        let mut toward: HashMap<VertexId, (VertexId, EdgeId)> = HashMap::new();
+        let mut visited = HashSet::from([target]);
+        let mut queue = VecDeque::from([target]);
+        let mut remaining: HashSet<VertexId> =
+            sources.iter().copied().filter(|&s| s != target).collect();
+
+        while let Some(current) = queue.pop_front() {
+            if remaining.is_empty() {
+                break;
+            }
+            self.cancel.check()?;
+            for (edge, neighbor) in self.hops(current, reverse) {
+                if visited.insert(neighbor) {
+                    toward.insert(neighbor, (current, edge.id()));
+                    remaining.remove(&neighbor);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        let paths = sources
+            .iter()
+            .map(|&source| {
+                if source != target && !toward.contains_key(&source) {
+                    return None;
+                }
+                let mut path = PathResult::with_start(source);
+                let mut current = source;
+                while let Some(&(next, edge_id)) = toward.get(&current) {
+                    path.vertices.push(next);
+                    path.edges.push(edge_id);
+                    if let Some(edge) = self.graph.get_edge(edge_id) {
+                        path.total_weight += edge.weight();
+                    }
+                    current = next;
+                }
+                path.length = path.edges.len();
+                path.summarize(&self.graph);
+                Some(path)
+            })
+            .collect();
+
+        Ok(MultiSourcePaths {
+            paths,
+            visited: visited.len(),
+        })
+    }
+
     /// 重构路径
     fn reconstruct_path(
         &self,
@@ -128,20 +392,80 @@ impl PathFinder {
         path.edges = edges;
         path.length = path.edges.len();
         path.total_weight = total_weight;
+        path.summarize(&self.graph);
 
         path
     }
 
     /// 查找所有路径（限制深度）
-    pub fn all_paths(&self, start: VertexId, end: VertexId, max_depth: usize) -> Vec<PathResult> {
+    pub fn all_paths(
+        &self,
+        start: VertexId,
+        end: VertexId,
+        max_depth: usize,
+    ) -> Result<Vec<PathResult>> {
+        if self.parallel && start != end && max_depth > 0 {
+            return self.par_all_paths(start, end, max_depth);
+        }
+
         let mut results = Vec::new();
         let mut path = PathResult::with_start(start);
         let mut visited = HashSet::new();
         visited.insert(start);
 
-        self.dfs_all_paths(start, end, max_depth, &mut visited, &mut path, &mut results);
+        self.dfs_all_paths(start, end, max_depth, &mut visited, &mut path, &mut results)?;
 
-        results
+        Ok(results)
+    }
+
+    /// 起点的每条出边作为一个分支并行搜索，按出边顺序合并即得到串行 DFS 的结果顺序
+    fn par_all_paths(
+        &self,
+        start: VertexId,
+        end: VertexId,
+        max_depth: usize,
+    ) -> Result<Vec<PathResult>> {
+        match &self.thread_pool {
+            Some(pool) => pool.install(|| self.search_branches(start, end, max_depth)),
+            None => self.search_branches(start, end, max_depth),
+        }
+    }
+
+    fn search_branches(
+        &self,
+        start: VertexId,
+        end: VertexId,
+        max_depth: usize,
+    ) -> Result<Vec<PathResult>> {
+        let branches: Vec<Vec<PathResult>> = self
+            .next_hops(start)
+            .into_par_iter()
+            .filter(|(_, neighbor)| *neighbor != start)
+            .map(|(edge, neighbor)| {
+                let mut results = Vec::new();
+                let mut path = PathResult::with_start(start);
+                path.vertices.push(neighbor);
+                path.edges.push(edge.id());
+                path.total_weight += edge.weight();
+                let mut visited = HashSet::from([start, neighbor]);
+
+                self.dfs_all_paths(
+                    neighbor,
+                    end,
+                    max_depth - 1,
+                    &mut visited,
+                    &mut path,
+                    &mut results,
+                )?;
+                Ok(results)
+            })
+            .collect::<Result<_>>()?;
+
+        let mut results: Vec<PathResult> = branches.into_iter().flatten().collect();
+        if let Some(limit) = self.max_paths {
+            results.truncate(limit);
+        }
+        Ok(results)
     }
 
     fn dfs_all_paths(
@@ -152,25 +476,44 @@ impl PathFinder {
         visited: &mut HashSet<VertexId>,
         path: &mut PathResult,
         results: &mut Vec<PathResult>,
-    ) {
+    ) -> Result<()> {
+        self.cancel.check()?;
+        if self.max_paths.is_some_and(|limit| results.len() >= limit) {
+            return Ok(());
+        }
+
         if current == end {
+            path.summarize(&self.graph);
             results.push(path.clone());
-            return;
+            return Ok(());
         }
 
         if remaining_depth == 0 {
-            return;
+            return Ok(());
         }
 
-        for edge in self.graph.get_outgoing_edges(current) {
-            let neighbor = edge.dst();
+        // 时间因果约束：下一条边不能早于当前路径的最后一条边
+        let previous = if self.monotonic_blocks {
+            path.edges.last().and_then(|&id| self.graph.get_edge(id))
+        } else {
+            None
+        };
+
+        for (edge, neighbor) in self.next_hops(current) {
+            if previous
+                .as_ref()
+                .is_some_and(|prev| happens_before(&edge, prev))
+            {
+                continue;
+            }
+
             if !visited.contains(&neighbor) {
                 visited.insert(neighbor);
                 path.vertices.push(neighbor);
                 path.edges.push(edge.id());
                 path.total_weight += edge.weight();
 
-                self.dfs_all_paths(neighbor, end, remaining_depth - 1, visited, path, results);
+                self.dfs_all_paths(neighbor, end, remaining_depth - 1, visited, path, results)?;
 
                 path.total_weight -= edge.weight();
                 path.edges.pop();
@@ -178,25 +521,32 @@ impl PathFinder {
                 visited.remove(&neighbor);
             }
         }
+
+        Ok(())
     }
 
     /// K 最短路径（Yen's 算法简化版）
-    pub fn k_shortest_paths(&self, start: VertexId, end: VertexId, k: usize) -> Vec<PathResult> {
+    pub fn k_shortest_paths(
+        &self,
+        start: VertexId,
+        end: VertexId,
+        k: usize,
+    ) -> Result<Vec<PathResult>> {
         let mut results = Vec::new();
 
         // 先找最短路径
         if let Some(shortest) = self.shortest_path(start, end) {
             results.push(shortest);
         } else {
-            return results;
+            return Ok(results);
         }
 
         // 使用 all_paths 找更多路径并排序
         let max_depth = 10; // 限制搜索深度
-        let all = self.all_paths(start, end, max_depth);
+        let all = self.all_paths(start, end, max_depth)?;
 
         let mut sorted_paths = all;
-        sorted_paths.sort_by(|a, b| a.length.cmp(&b.length));
+        sorted_paths.sort_by_key(|p| p.length);
 
         for path in sorted_paths {
             if results.len() >= k {
@@ -209,7 +559,7 @@ impl PathFinder {
             }
         }
 
-        results
+        Ok(results)
     }
 
     /// 链路追踪（从起点向外扩展）
@@ -221,6 +571,25 @@ impl PathFinder {
         edge_filter: Option<&[EdgeLabel]>,
     ) -> Vec<PathResult> {
         let mut results = Vec::new();
+        self.trace_each(start, direction, max_depth, edge_filter, |path| {
+            results.push(path.clone());
+            true
+        });
+        results
+    }
+
+    /// 链路追踪，每发现一条路径立即回调（顺序与 `trace` 的结果一致）；
+    /// 回调返回 false 时停止追踪，用于流式输出和客户端断开后提前结束
+    pub fn trace_each<F>(
+        &self,
+        start: VertexId,
+        direction: TraceDirection,
+        max_depth: usize,
+        edge_filter: Option<&[EdgeLabel]>,
+        mut on_path: F,
+    ) where
+        F: FnMut(&PathResult) -> bool,
+    {
         let mut visited = HashSet::new();
         let mut path = PathResult::with_start(start);
         visited.insert(start);
@@ -232,12 +601,11 @@ impl PathFinder {
             edge_filter,
             &mut visited,
             &mut path,
-            &mut results,
+            &mut on_path,
         );
-
-        results
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn dfs_trace(
         &self,
         current: VertexId,
@@ -246,36 +614,42 @@ impl PathFinder {
         edge_filter: Option<&[EdgeLabel]>,
         visited: &mut HashSet<VertexId>,
         path: &mut PathResult,
-        results: &mut Vec<PathResult>,
-    ) {
-        // 记录当前路径（如果不是起点）
-        if path.vertices.len() > 1 {
-            results.push(path.clone());
-        }
-
-        if remaining_depth == 0 {
-            return;
-        }
-
+        on_path: &mut dyn FnMut(&PathResult) -> bool,
+    ) -> bool {
         // 获取边
-        let edges = match direction {
-            TraceDirection::Forward => self.graph.get_outgoing_edges(current),
-            TraceDirection::Backward => self.graph.get_incoming_edges(current),
-            TraceDirection::Both => {
-                let mut all = self.graph.get_outgoing_edges(current);
-                all.extend(self.graph.get_incoming_edges(current));
-                all
+        let mut edges = if remaining_depth == 0 {
+            Vec::new()
+        } else {
+            match direction {
+                TraceDirection::Forward => self.graph.get_outgoing_edges(current),
+                TraceDirection::Backward => self.graph.get_incoming_edges(current),
+                TraceDirection::Both => {
+                    let mut all = self.graph.get_outgoing_edges(current);
+                    all.extend(self.graph.get_incoming_edges(current));
+                    all
+                }
             }
         };
+        // 边类型过滤
+        if let Some(filter) = edge_filter {
+            edges.retain(|edge| filter.contains(edge.label()));
+        }
 
-        for edge in edges {
-            // 边类型过滤
-            if let Some(filter) = edge_filter {
-                if !filter.contains(edge.label()) {
-                    continue;
-                }
+        // 记录当前路径（如果不是起点）；枢纽顶点只记录、不展开
+        if path.vertices.len() > 1 {
+            path.truncated = self.max_fanout.is_some_and(|limit| edges.len() > limit);
+            path.summarize(&self.graph);
+            let keep_going = on_path(path);
+            let truncated = std::mem::take(&mut path.truncated);
+            if !keep_going {
+                return false;
+            }
+            if truncated {
+                return true;
             }
+        }
 
+        for edge in edges {
             let neighbor = match direction {
                 TraceDirection::Forward => edge.dst(),
                 TraceDirection::Backward => edge.src(),
@@ -294,22 +668,26 @@ impl PathFinder {
                 path.edges.push(edge.id());
                 path.total_weight += edge.weight();
 
-                self.dfs_trace(
+                let keep_going = self.dfs_trace(
                     neighbor,
                     direction,
                     remaining_depth - 1,
                     edge_filter,
                     visited,
                     path,
-                    results,
+                    on_path,
                 );
 
                 path.total_weight -= edge.weight();
                 path.edges.pop();
                 path.vertices.pop();
                 visited.remove(&neighbor);
+                if !keep_going {
+                    return false;
+                }
             }
         }
+        true
     }
 
     /// 查找资金流向（转账链路）
@@ -332,6 +710,191 @@ impl PathFinder {
         )
     }
 
+    /// 按比例追踪资金流：每个顶点收到的资金按各条转出边的金额占比继续拆分，
+    /// 返回从 start 到 target、送达比例不低于 tolerance 的所有路径
+    ///
+    /// 路径比例只会沿途递减，低于 tolerance 的分支直接剪枝。
+    pub fn trace_value(
+        &self,
+        start: VertexId,
+        target: VertexId,
+        tolerance: f64,
+        max_depth: usize,
+    ) -> Result<Vec<ValueFlowPath>> {
+        let mut results = Vec::new();
+        let mut path = PathResult::with_start(start);
+        let mut visited = HashSet::new();
+        visited.insert(start);
+
+        self.dfs_trace_value(
+            start,
+            target,
+            tolerance,
+            max_depth,
+            1.0,
+            &mut visited,
+            &mut path,
+            &mut results,
+        )?;
+
+        Ok(results)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn dfs_trace_value(
+        &self,
+        current: VertexId,
+        target: VertexId,
+        tolerance: f64,
+        remaining_depth: usize,
+        fraction: f64,
+        visited: &mut HashSet<VertexId>,
+        path: &mut PathResult,
+        results: &mut Vec<ValueFlowPath>,
+    ) -> Result<()> {
+        self.cancel.check()?;
+
+        if current == target && path.length > 0 {
+            path.summarize(&self.graph);
+            results.push(ValueFlowPath {
+                path: path.clone(),
+                fraction,
+            });
+            return Ok(());
+        }
+
+        if remaining_depth == 0 {
+            return Ok(());
+        }
+
+        let transfers: Vec<_> = self
+            .graph
+            .get_outgoing_edges(current)
+            .into_iter()
+            .filter(|e| *e.label() == EdgeLabel::Transfer)
+            .collect();
+        let total: f64 = transfers.iter().map(|e| e.weight()).sum();
+        if total <= 0.0 {
+            return Ok(());
+        }
+
+        for edge in transfers {
+            let share = fraction * edge.weight() / total;
+            let neighbor = edge.dst();
+            if share < tolerance || visited.contains(&neighbor) {
+                continue;
+            }
+
+            visited.insert(neighbor);
+            path.vertices.push(neighbor);
+            path.edges.push(edge.id());
+            path.length += 1;
+            path.total_weight += edge.weight();
+
+            self.dfs_trace_value(
+                neighbor,
+                target,
+                tolerance,
+                remaining_depth - 1,
+                share,
+                visited,
+                path,
+                results,
+            )?;
+
+            path.total_weight -= edge.weight();
+            path.length -= 1;
+            path.edges.pop();
+            path.vertices.pop();
+            visited.remove(&neighbor);
+        }
+
+        Ok(())
+    }
+
+    /// 计算一组种子顶点之间的可达矩阵：从每个种子做有界 BFS，
+    /// 返回所有有序种子对（不含自身）在 max_depth 跳内是否可达
+    pub fn reachability(
+        &self,
+        seeds: &[VertexId],
+        max_depth: usize,
+        direction: TraceDirection,
+    ) -> Result<Vec<Reachability>> {
+        let mut unique = Vec::new();
+        for &seed in seeds {
+            if !unique.contains(&seed) {
+                unique.push(seed);
+            }
+        }
+        if unique.len() > MAX_REACHABILITY_SEEDS {
+            return Err(Error::AlgorithmError(format!(
+                "种子顶点数 {} 超过上限 {}",
+                unique.len(),
+                MAX_REACHABILITY_SEEDS
+            )));
+        }
+
+        let mut matrix = Vec::with_capacity(unique.len() * unique.len().saturating_sub(1));
+        for &from in &unique {
+            let targets: HashSet<VertexId> =
+                unique.iter().copied().filter(|&v| v != from).collect();
+            let reached = self.bounded_bfs(from, max_depth, direction, &targets)?;
+            for &to in &unique {
+                if to != from {
+                    matrix.push(Reachability {
+                        from,
+                        to,
+                        reachable: reached.contains(&to),
+                    });
+                }
+            }
+        }
+        Ok(matrix)
+    }
+
+    /// 有界 BFS，返回 max_depth 跳内访问到的 targets；全部找到后提前结束
+    fn bounded_bfs(
+        &self,
+        start: VertexId,
+        max_depth: usize,
+        direction: TraceDirection,
+        targets: &HashSet<VertexId>,
+    ) -> Result<HashSet<VertexId>> {
+        let mut reached = HashSet::new();
+        let mut visited = HashSet::new();
+        visited.insert(start);
+        let mut frontier = vec![start];
+
+        for _ in 0..max_depth {
+            if frontier.is_empty() || reached.len() == targets.len() {
+                break;
+            }
+            self.cancel.check()?;
+            let mut next = Vec::new();
+            for vertex in frontier {
+                let neighbors = match direction {
+                    TraceDirection::Forward => self.graph.neighbors(vertex),
+                    TraceDirection::Backward => self.graph.predecessors(vertex),
+                    TraceDirection::Both => {
+                        let mut all = self.graph.neighbors(vertex);
+                        all.extend(self.graph.predecessors(vertex));
+                        all
+                    }
+                };
+                for neighbor in neighbors {
+                    if visited.insert(neighbor) {
+                        if targets.contains(&neighbor) {
+                            reached.insert(neighbor);
+                        }
+                        next.push(neighbor);
+                    }
+                }
+            }
+            frontier = next;
+        }
+        Ok(reached)
+    }
+
     /// 判断两点是否连通
     pub fn is_reachable(&self, start: VertexId, end: VertexId) -> bool {
         self.shortest_path(start, end).is_some()
@@ -365,7 +928,7 @@ impl PathFinder {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{TokenAmount, VertexLabel};
+    use crate::types::VertexLabel;
 
     fn create_test_graph() -> Arc<Graph> {
         let graph = Graph::in_memory().unwrap();
@@ -379,11 +942,11 @@ mod tests {
         let v5 = graph.add_vertex(VertexLabel::Account).unwrap();
 
         let amount = TokenAmount::from_u64(100);
-        graph.add_transfer(v1, v2, amount.clone(), 1).unwrap();
-        graph.add_transfer(v2, v3, amount.clone(), 2).unwrap();
-        graph.add_transfer(v3, v4, amount.clone(), 3).unwrap();
-        graph.add_transfer(v1, v5, amount.clone(), 4).unwrap();
-        graph.add_transfer(v5, v4, amount.clone(), 5).unwrap();
+        graph.add_transfer(v1, v2, amount, 1).unwrap();
+        graph.add_transfer(v2, v3, amount, 2).unwrap();
+        graph.add_transfer(v3, v4, amount, 3).unwrap();
+        graph.add_transfer(v1, v5, amount, 4).unwrap();
+        graph.add_transfer(v5, v4, amount, 5).unwrap();
 
         graph
     }
@@ -408,10 +971,302 @@ mod tests {
         let graph = create_test_graph();
         let finder = PathFinder::new(graph);
 
-        let paths = finder.all_paths(VertexId::new(1), VertexId::new(4), 5);
+        let paths = finder
+            .all_paths(VertexId::new(1), VertexId::new(4), 5)
+            .unwrap();
         assert_eq!(paths.len(), 2); // 两条路径
     }
 
+    #[test]
+    fn test_path_amount_and_block_span() {
+        let graph = Graph::in_memory().unwrap();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        let c = graph.add_account("0xc".to_string()).unwrap();
+        graph
+            .add_transfer(a, b, TokenAmount::from_u64(70), 12)
+            .unwrap();
+        graph
+            .add_transfer(b, c, TokenAmount::from_u64(30), 9)
+            .unwrap();
+        let finder = PathFinder::new(graph);
+
+        let path = finder.shortest_path(a, c).unwrap();
+        assert_eq!(path.total_amount, TokenAmount::from_u64(100));
+        assert_eq!((path.min_block, path.max_block), (Some(9), Some(12)));
+
+        // all_paths 与 trace 的结果同样带有汇总字段
+        let all = finder.all_paths(a, c, 3).unwrap();
+        assert_eq!(all[0].total_amount, TokenAmount::from_u64(100));
+        let traced = finder.trace(a, TraceDirection::Forward, 3, None);
+        let first_hop = traced.iter().find(|p| p.vertices == [a, b]).unwrap();
+        assert_eq!(first_hop.total_amount, TokenAmount::from_u64(70));
+        assert_eq!(first_hop.min_block, Some(12));
+        assert_eq!(first_hop.max_block, Some(12));
+
+        let empty = finder.shortest_path(a, a).unwrap();
+        assert_eq!(empty.total_amount, TokenAmount::default());
+        assert_eq!(empty.min_block, None);
+    }
+
+    #[test]
+    fn test_parallel_all_paths_matches_sequential() {
+        // 起点连到 4 个顶点，每个再连到 3 个顶点，全部汇入终点，并带有层内横向边
+        let graph = Graph::in_memory().unwrap();
+        let start = graph.add_vertex(VertexLabel::Account).unwrap();
+        let end = graph.add_vertex(VertexLabel::Account).unwrap();
+        let first: Vec<_> = (0..4)
+            .map(|_| graph.add_vertex(VertexLabel::Account).unwrap())
+            .collect();
+        let second: Vec<_> = (0..3)
+            .map(|_| graph.add_vertex(VertexLabel::Account).unwrap())
+            .collect();
+        let amount = TokenAmount::from_u64(1);
+        for (i, &u) in first.iter().enumerate() {
+            graph.add_transfer(start, u, amount, 1).unwrap();
+            let next = first[(i + 1) % 4];
+            graph.add_transfer(u, next, amount, 2).unwrap();
+            for &v in &second {
+                graph.add_transfer(u, v, amount, 3).unwrap();
+            }
+        }
+        for &v in &second {
+            graph.add_transfer(v, end, amount, 4).unwrap();
+        }
+
+        let sequential = PathFinder::new(graph.clone())
+            .all_paths(start, end, 6)
+            .unwrap();
+        let parallel = PathFinder::new(graph.clone())
+            .with_parallelism(true)
+            .all_paths(start, end, 6)
+            .unwrap();
+        assert!(sequential.len() > 20);
+        let sorted = |paths: &[PathResult]| {
+            let mut vertices: Vec<Vec<u64>> = paths
+                .iter()
+                .map(|p| p.vertices.iter().map(|v| v.as_u64()).collect())
+                .collect();
+            vertices.sort();
+            vertices
+        };
+        assert_eq!(sorted(&parallel), sorted(&sequential));
+        // 按分支顺序合并后顺序也一致
+        let order =
+            |paths: &[PathResult]| paths.iter().map(|p| p.edges.clone()).collect::<Vec<_>>();
+        assert_eq!(order(&parallel), order(&sequential));
+
+        // 上限截断后两种方式返回相同的前 N 条
+        let capped = |parallel| {
+            PathFinder::new(graph.clone())
+                .with_parallelism(parallel)
+                .with_max_paths(Some(5))
+                .all_paths(start, end, 6)
+                .unwrap()
+        };
+        assert_eq!(capped(true).len(), 5);
+        assert_eq!(order(&capped(true)), order(&capped(false)));
+        assert_eq!(order(&capped(false)), order(&sequential[..5]));
+    }
+
+    #[test]
+    fn test_parallel_all_paths_honors_thread_pool() {
+        let graph = Graph::in_memory().unwrap();
+        let start = graph.add_vertex(VertexLabel::Account).unwrap();
+        let end = graph.add_vertex(VertexLabel::Account).unwrap();
+        let amount = TokenAmount::from_u64(1);
+        for _ in 0..16 {
+            let middle = graph.add_vertex(VertexLabel::Account).unwrap();
+            graph.add_transfer(start, middle, amount, 1).unwrap();
+            graph.add_transfer(middle, end, amount, 2).unwrap();
+        }
+
+        // 通过边过滤记录执行搜索的线程
+        let threads = Arc::new(parking_lot::Mutex::new(HashSet::new()));
+        let seen = threads.clone();
+        let paths = PathFinder::new(graph)
+            .with_parallelism(true)
+            .with_thread_pool(crate::algorithm::build_thread_pool(2).unwrap())
+            .with_edge_filter(move |_| {
+                let name = std::thread::current().name().map(str::to_string);
+                seen.lock().insert(name);
+                true
+            })
+            .all_paths(start, end, 3)
+            .unwrap();
+        assert_eq!(paths.len(), 16);
+
+        let threads = threads.lock();
+        assert!(!threads.is_empty() && threads.len() <= 2);
+        assert!(threads.iter().all(|name| name
+            .as_deref()
+            .is_some_and(|name| name.starts_with("chaingraph-worker-"))));
+    }
+
+    #[test]
+    fn test_multi_source_shortest_matches_individual_searches() {
+        // 三条长度不同的链汇入终点 t，另有一个无法到达 t 的顶点
+        let graph = Graph::in_memory().unwrap();
+        let t = graph.add_vertex(VertexLabel::Account).unwrap();
+        let amount = TokenAmount::from_u64(1);
+        let mut sources = Vec::new();
+        for chain_length in [1, 3, 5] {
+            let mut next = t;
+            for _ in 0..chain_length {
+                let v = graph.add_vertex(VertexLabel::Account).unwrap();
+                graph.add_transfer(v, next, amount, 1).unwrap();
+                next = v;
+            }
+            sources.push(next);
+        }
+        let isolated = graph.add_vertex(VertexLabel::Account).unwrap();
+        sources.push(isolated);
+        sources.push(t);
+
+        let finder = PathFinder::new(graph);
+        let multi = finder.multi_source_shortest(&sources, t).unwrap();
+        assert_eq!(multi.paths.len(), sources.len());
+        let mut individual_visits = 0;
+        for (source, path) in sources.iter().zip(&multi.paths) {
+            let single = finder.shortest_path(*source, t);
+            assert_eq!(
+                path.as_ref().map(|p| &p.vertices),
+                single.as_ref().map(|p| &p.vertices)
+            );
+            if let Some(single) = single {
+                // 单独搜索至少要访问路径上的全部顶点
+                individual_visits += single.vertices.len();
+                assert_eq!(path.as_ref().unwrap().length, single.length);
+            }
+        }
+        assert_eq!(multi.paths[0].as_ref().unwrap().length, 1);
+        assert_eq!(multi.paths[2].as_ref().unwrap().length, 5);
+        assert!(multi.paths[3].is_none());
+        assert!(multi.visited < individual_visits);
+    }
+
+    #[test]
+    fn test_reachability_matrix() {
+        let graph = create_test_graph();
+        let finder = PathFinder::new(graph);
+        let (v1, v3, v4) = (VertexId::new(1), VertexId::new(3), VertexId::new(4));
+
+        let matrix = finder
+            .reachability(&[v1, v3, v4], 2, TraceDirection::Forward)
+            .unwrap();
+        let reachable = |from, to| {
+            matrix
+                .iter()
+                .find(|r| r.from == from && r.to == to)
+                .unwrap()
+                .reachable
+        };
+        assert_eq!(matrix.len(), 6);
+        // 1 -> 5 -> 4 两跳可达，1 -> 2 -> 3 两跳可达
+        assert!(reachable(v1, v4));
+        assert!(reachable(v1, v3));
+        assert!(reachable(v3, v4));
+        assert!(!reachable(v4, v1));
+        assert!(!reachable(v3, v1));
+
+        // 限制为 1 跳后 1 无法到达 3、4
+        let one_hop = finder
+            .reachability(&[v1, v3, v4], 1, TraceDirection::Forward)
+            .unwrap();
+        assert!(one_hop
+            .iter()
+            .filter(|r| r.from == v1)
+            .all(|r| !r.reachable));
+
+        // 反向追踪：4 可以回溯到 1
+        let backward = finder
+            .reachability(&[v1, v4], 2, TraceDirection::Backward)
+            .unwrap();
+        assert!(backward
+            .iter()
+            .any(|r| r.from == v4 && r.to == v1 && r.reachable));
+
+        let too_many: Vec<VertexId> = (0..=MAX_REACHABILITY_SEEDS as u64)
+            .map(VertexId::new)
+            .collect();
+        assert!(finder
+            .reachability(&too_many, 2, TraceDirection::Forward)
+            .is_err());
+    }
+
+    #[test]
+    fn test_all_paths_cancelled() {
+        let graph = create_test_graph();
+        let cancel = CancellationToken::new();
+        let finder = PathFinder::new(graph).with_cancellation(cancel.clone());
+
+        cancel.cancel();
+        let result = finder.all_paths(VertexId::new(1), VertexId::new(4), 5);
+        assert!(matches!(result, Err(crate::error::Error::Cancelled)));
+    }
+
+    #[test]
+    fn test_all_paths_monotonic_blocks() {
+        let graph = Graph::in_memory().unwrap();
+
+        // 1 -(10)-> 2 -(20)-> 4   时间有序
+        // 1 -(100)-> 3 -(50)-> 4  违反时间因果
+        let v1 = graph.add_vertex(VertexLabel::Account).unwrap();
+        let v2 = graph.add_vertex(VertexLabel::Account).unwrap();
+        let v3 = graph.add_vertex(VertexLabel::Account).unwrap();
+        let v4 = graph.add_vertex(VertexLabel::Account).unwrap();
+
+        let amount = TokenAmount::from_u64(100);
+        graph.add_transfer(v1, v2, amount, 10).unwrap();
+        graph.add_transfer(v2, v4, amount, 20).unwrap();
+        graph.add_transfer(v1, v3, amount, 100).unwrap();
+        graph.add_transfer(v3, v4, amount, 50).unwrap();
+
+        let unordered = PathFinder::new(graph.clone()).all_paths(v1, v4, 5).unwrap();
+        assert_eq!(unordered.len(), 2);
+
+        let ordered = PathFinder::new(graph)
+            .with_monotonic_blocks(true)
+            .all_paths(v1, v4, 5)
+            .unwrap();
+        assert_eq!(ordered.len(), 1);
+        assert_eq!(ordered[0].vertices, vec![v1, v2, v4]);
+    }
+
+    #[test]
+    fn test_all_paths_monotonic_timestamps() {
+        let graph = Graph::in_memory().unwrap();
+        let v1 = graph.add_vertex(VertexLabel::Account).unwrap();
+        let v2 = graph.add_vertex(VertexLabel::Account).unwrap();
+        let v3 = graph.add_vertex(VertexLabel::Account).unwrap();
+        let at = |secs| {
+            vec![(
+                "timestamp".to_string(),
+                crate::types::PropertyValue::Timestamp(secs),
+            )]
+        };
+
+        // 同一区块内按时间戳排序：v2 -> v3 早于 v1 -> v2
+        let amount = TokenAmount::from_u64(1);
+        graph
+            .add_transfer_with_properties(v1, v2, amount, 7, at(200))
+            .unwrap();
+        graph
+            .add_transfer_with_properties(v2, v3, amount, 7, at(100))
+            .unwrap();
+
+        let finder = PathFinder::new(graph.clone()).with_monotonic_blocks(true);
+        assert!(finder.all_paths(v1, v3, 3).unwrap().is_empty());
+
+        // 更早的 v1 -> v2 使路径满足时间顺序
+        let earlier = graph
+            .add_transfer_with_properties(v1, v2, amount, 7, at(50))
+            .unwrap();
+        let paths = finder.all_paths(v1, v3, 3).unwrap();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].edges[0], earlier);
+    }
+
     #[test]
     fn test_trace() {
         let graph = create_test_graph();
@@ -421,6 +1276,65 @@ mod tests {
         assert!(!traces.is_empty());
     }
 
+    #[test]
+    fn test_trace_each_stops_early() {
+        let graph = create_test_graph();
+        let finder = PathFinder::new(graph);
+
+        let all = finder.trace(VertexId::new(1), TraceDirection::Forward, 3, None);
+        assert!(all.len() > 1);
+
+        // 回调返回 false 后不再产出路径
+        let mut seen = Vec::new();
+        finder.trace_each(VertexId::new(1), TraceDirection::Forward, 3, None, |path| {
+            seen.push(path.vertices.clone());
+            false
+        });
+        assert_eq!(seen, vec![all[0].vertices.clone()]);
+    }
+
+    #[test]
+    fn test_trace_max_fanout() {
+        // a -> hub -> 50 个地址，a -> b -> c
+        let graph = Graph::in_memory().unwrap();
+        let amount = TokenAmount::from_u64(1);
+        let a = graph.add_vertex(VertexLabel::Account).unwrap();
+        let hub = graph.add_vertex(VertexLabel::Account).unwrap();
+        let b = graph.add_vertex(VertexLabel::Account).unwrap();
+        let c = graph.add_vertex(VertexLabel::Account).unwrap();
+        graph.add_transfer(a, hub, amount, 1).unwrap();
+        graph.add_transfer(a, b, amount, 1).unwrap();
+        graph.add_transfer(b, c, amount, 2).unwrap();
+        for _ in 0..50 {
+            let leaf = graph.add_vertex(VertexLabel::Account).unwrap();
+            graph.add_transfer(hub, leaf, amount, 2).unwrap();
+        }
+
+        let unbounded = PathFinder::new(graph.clone());
+        let all = unbounded.trace(a, TraceDirection::Forward, 3, None);
+        assert_eq!(all.len(), 53);
+        assert!(all.iter().all(|path| !path.truncated));
+
+        let bounded = PathFinder::new(graph).with_max_fanout(Some(10));
+        let traces = bounded.trace(a, TraceDirection::Forward, 3, None);
+        let ends: Vec<(Vec<VertexId>, bool)> = traces
+            .iter()
+            .map(|path| (path.vertices.clone(), path.truncated))
+            .collect();
+        assert_eq!(
+            ends,
+            vec![
+                (vec![a, hub], true),
+                (vec![a, b], false),
+                (vec![a, b, c], false),
+            ]
+        );
+
+        // 上限只作用于途经顶点，起点本身照常展开
+        let from_hub = bounded.trace(hub, TraceDirection::Forward, 2, None);
+        assert_eq!(from_hub.len(), 50);
+    }
+
     #[test]
     fn test_n_hop_neighbors() {
         let graph = create_test_graph();
@@ -432,4 +1346,46 @@ mod tests {
         let two_hop = finder.n_hop_neighbors(VertexId::new(1), 2);
         assert_eq!(two_hop.len(), 2); // v3 和 v4
     }
+
+    #[test]
+    fn test_trace_value_split_and_merge() {
+        let graph = Graph::in_memory().unwrap();
+
+        // s 拆分为 a(60) 和 b(40)，a 全部转给 t，b 一半转给 t、一半转给 c
+        let s = graph.add_vertex(VertexLabel::Account).unwrap();
+        let a = graph.add_vertex(VertexLabel::Account).unwrap();
+        let b = graph.add_vertex(VertexLabel::Account).unwrap();
+        let c = graph.add_vertex(VertexLabel::Account).unwrap();
+        let t = graph.add_vertex(VertexLabel::Account).unwrap();
+
+        graph
+            .add_transfer(s, a, TokenAmount::from_u64(60), 1)
+            .unwrap();
+        graph
+            .add_transfer(s, b, TokenAmount::from_u64(40), 1)
+            .unwrap();
+        graph
+            .add_transfer(a, t, TokenAmount::from_u64(60), 2)
+            .unwrap();
+        graph
+            .add_transfer(b, t, TokenAmount::from_u64(20), 2)
+            .unwrap();
+        graph
+            .add_transfer(b, c, TokenAmount::from_u64(20), 2)
+            .unwrap();
+
+        let finder = PathFinder::new(graph);
+        let mut paths = finder.trace_value(s, t, 0.1, 10).unwrap();
+        paths.sort_by(|x, y| y.fraction.total_cmp(&x.fraction));
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].path.vertices, vec![s, a, t]);
+        assert!((paths[0].fraction - 0.6).abs() < 1e-9);
+        assert_eq!(paths[1].path.vertices, vec![s, b, t]);
+        assert!((paths[1].fraction - 0.2).abs() < 1e-9);
+
+        // 提高阈值后只保留主路径
+        let paths = finder.trace_value(s, t, 0.5, 10).unwrap();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].path.length, 2);
+    }
 }
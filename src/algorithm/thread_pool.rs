@@ -0,0 +1,34 @@
+//! 线程池
+//!
+//! 并行导入与并行算法默认使用 rayon 全局线程池（占满所有核心），
+//! 受限部署可构建固定大小的线程池交给导入器和路径查找器使用
+
+use crate::error::{Error, Result};
+use rayon::ThreadPool;
+use std::sync::Arc;
+
+/// 构建指定线程数的 rayon 线程池；线程数为 0 时报错
+pub fn build_thread_pool(threads: usize) -> Result<Arc<ThreadPool>> {
+    if threads == 0 {
+        return Err(Error::AlgorithmError("线程数必须大于 0".to_string()));
+    }
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|index| format!("chaingraph-worker-{}", index))
+        .build()
+        .map(Arc::new)
+        .map_err(|e| Error::AlgorithmError(format!("创建线程池失败: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_thread_pool() {
+        let pool = build_thread_pool(3).unwrap();
+        assert_eq!(pool.current_num_threads(), 3);
+        assert_eq!(pool.install(rayon::current_num_threads), 3);
+        assert!(build_thread_pool(0).is_err());
+    }
+}
@@ -6,21 +6,25 @@
 //! - 控制台命令 (:help, :tee, :pager 等)
 //! - 表格和垂直格式输出
 //! - 脚本文件执行
+//! - 子命令（`stats`、`bench`、`index`）离线查看存储、测量性能和管理索引
 
 use chaingraph::algorithm::{EdmondsKarp, PathFinder, TraceDirection};
 use chaingraph::cli::commands::{execute_console_command, is_console_command, CommandResult, ConsoleState};
 use chaingraph::cli::completer::GqlCompleter;
 use chaingraph::cli::printer::{check_vertical_display, PrintMode, Printer};
-use chaingraph::graph::{GraphCatalog, VertexId};
+use chaingraph::graph::{AdjacencyOrder, Graph, GraphCatalog, VertexId};
+use chaingraph::metrics;
 use chaingraph::query::{GqlParser, QueryExecutor};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use colored::Colorize;
 use rustyline::error::ReadlineError;
 use rustyline::{Config, Editor};
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Instant;
 
 #[derive(Parser, Debug)]
 #[command(name = "chaingraph-cli")]
@@ -28,13 +32,17 @@ use std::sync::Arc;
 #[command(version)]
 struct Args {
     /// 数据目录
-    #[arg(short, long, default_value = "./data")]
+    #[arg(short, long, default_value = "./data", global = true)]
     data_dir: String,
 
     /// 缓冲池大小（页面数）
-    #[arg(short, long, default_value = "512")]
+    #[arg(short, long, default_value = "512", global = true)]
     buffer_size: usize,
 
+    /// 目标图名称（默认为 catalog 中记录的当前图）
+    #[arg(short, long, global = true)]
+    graph: Option<String>,
+
     /// 执行单个查询后退出
     #[arg(short = 'e', long)]
     execute: Option<String>,
@@ -46,19 +54,109 @@ struct Args {
     /// 查询超时（秒）
     #[arg(long)]
     timeout: Option<u64>,
+
+    /// 邻接边返回顺序: insertion（默认）、edge-id 或 block-number
+    #[arg(long, default_value = "insertion")]
+    adjacency_order: AdjacencyOrder,
+
+    /// 子命令（不指定时进入交互/查询模式）
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// 打印图统计信息后退出，无需启动服务
+    Stats,
+
+    /// 重复执行查询文件中的查询，报告吞吐量和延迟分位数
+    Bench {
+        /// 查询文件（每行一条，跳过空行和注释）
+        #[arg(short, long)]
+        queries: PathBuf,
+
+        /// 计时轮数（每轮执行全部查询）
+        #[arg(long, default_value = "10")]
+        iterations: usize,
+
+        /// 预热轮数（不计入统计）
+        #[arg(long, default_value = "1")]
+        warmup: usize,
+    },
+
+    /// 管理顶点属性索引
+    #[command(subcommand)]
+    Index(IndexCommand),
+
+    /// 把多个数据目录的当前图合并到输出目录，按地址和交易哈希去重顶点
+    Merge {
+        /// 输入数据目录（按顺序合并）
+        #[arg(required = true, num_args = 2..)]
+        inputs: Vec<PathBuf>,
+
+        /// 输出数据目录（不存在时创建，合并到其当前图）
+        #[arg(long)]
+        out: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum IndexCommand {
+    /// 创建属性索引
+    Create {
+        /// 顶点标签
+        #[arg(long)]
+        label: String,
+        /// 属性名
+        #[arg(long)]
+        property: String,
+    },
+    /// 列出属性索引
+    List,
+    /// 删除属性索引
+    Drop {
+        /// 顶点标签
+        #[arg(long)]
+        label: String,
+        /// 属性名
+        #[arg(long)]
+        property: String,
+    },
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
 
-    // 打印欢迎信息
-    println!("{}", "ChainGraph CLI - Web3 区块链链路追踪图数据库".green().bold());
-    println!("{}", "=".repeat(50).dimmed());
+    // 合并只读写命令行指定的目录，不打开 --data-dir
+    if let Some(Command::Merge { inputs, out }) = &args.command {
+        return run_merge(inputs, out, args.buffer_size);
+    }
 
     // 打开图目录（多图）
     let catalog = GraphCatalog::open(&args.data_dir, Some(args.buffer_size))?;
+    catalog.set_adjacency_order(args.adjacency_order);
     let catalog = Arc::new(catalog);
-    let graph = catalog.current_graph();
+    let graph = match &args.graph {
+        Some(name) => catalog.select_graph(name)?,
+        None => catalog.current_graph(),
+    };
+
+    if let Some(command) = &args.command {
+        return match command {
+            Command::Stats => print_stats(&catalog, &graph),
+            Command::Bench {
+                queries,
+                iterations,
+                warmup,
+            } => run_bench(&catalog, queries, *iterations, *warmup),
+            Command::Index(command) => run_index_command(&graph, command),
+            Command::Merge { .. } => unreachable!("merge 在打开数据目录前处理"),
+        };
+    }
+
+    // 打印欢迎信息
+    println!("{}", "ChainGraph CLI - Web3 区块链链路追踪图数据库".green().bold());
+    println!("{}", "=".repeat(50).dimmed());
 
     println!("数据库已连接: {}", args.data_dir.cyan());
     println!("  当前图: {}", catalog.current_graph_name().yellow());
@@ -87,6 +185,172 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     run_interactive(&catalog, &mut console_state)
 }
 
+/// 打印图统计信息（`stats` 子命令）
+fn print_stats(catalog: &GraphCatalog, graph: &Graph) -> Result<(), Box<dyn std::error::Error>> {
+    let vertices_by_label: BTreeMap<String, usize> = graph
+        .vertex_index()
+        .label_counts()
+        .into_iter()
+        .map(|(label, count)| (label.to_string(), count))
+        .collect();
+    let edges_by_type: BTreeMap<String, usize> = graph
+        .edge_index()
+        .label_counts()
+        .into_iter()
+        .map(|(label, count)| (label.to_string(), count))
+        .collect();
+
+    println!("图: {}", catalog.current_graph_name());
+    println!("  顶点数: {}", graph.vertex_count());
+    for (label, count) in &vertices_by_label {
+        println!("    {}: {}", label, count);
+    }
+    println!("  边数: {}", graph.edge_count());
+    for (label, count) in &edges_by_type {
+        println!("    {}: {}", label, count);
+    }
+
+    let watermark = graph.buffer_pool_watermark();
+    let snapshot = metrics::global_metrics().snapshot();
+    println!("缓冲池:");
+    println!("  大小: {} 页", watermark.total_pages);
+    println!(
+        "  缓存页面数: {} ({:.1}%, {:?})",
+        watermark.cached_pages, watermark.usage_percent, watermark.status
+    );
+    println!(
+        "  命中/未命中: {} / {}",
+        snapshot.buffer_pool_hits, snapshot.buffer_pool_misses
+    );
+    println!("数据文件大小: {} 字节", graph.buffer_pool().file_size()?);
+    Ok(())
+}
+
+/// 运行基准测试（`bench` 子命令）
+fn run_bench(
+    catalog: &Arc<GraphCatalog>,
+    file_path: &PathBuf,
+    iterations: usize,
+    warmup: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let reader = BufReader::new(File::open(file_path)?);
+    let mut statements = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("--") || line.starts_with("//") {
+            continue;
+        }
+        statements.push(GqlParser::new(line).parse()?);
+    }
+    if statements.is_empty() {
+        return Err(format!("查询文件中没有查询: {}", file_path.display()).into());
+    }
+
+    let executor = QueryExecutor::new(catalog.clone());
+    for _ in 0..warmup {
+        for stmt in &statements {
+            executor.execute(stmt)?;
+        }
+    }
+
+    let histogram = metrics::LatencyHistogram::new();
+    let started = Instant::now();
+    for _ in 0..iterations {
+        for stmt in &statements {
+            let query_started = Instant::now();
+            executor.execute(stmt)?;
+            histogram.record(query_started.elapsed());
+        }
+    }
+    let elapsed = started.elapsed();
+
+    let ms = |quantile: f64| histogram.percentile(quantile).as_secs_f64() * 1000.0;
+    println!(
+        "基准测试: {} 条查询 × {} 轮（预热 {} 轮）",
+        statements.len(),
+        iterations,
+        warmup
+    );
+    println!("  执行次数: {}", histogram.count());
+    println!("  总耗时: {:.3} ms", elapsed.as_secs_f64() * 1000.0);
+    println!(
+        "  吞吐量: {:.1} qps",
+        histogram.count() as f64 / elapsed.as_secs_f64().max(f64::EPSILON)
+    );
+    println!("  延迟 p50: {:.3} ms", ms(0.50));
+    println!("  延迟 p95: {:.3} ms", ms(0.95));
+    println!("  延迟 p99: {:.3} ms", ms(0.99));
+    Ok(())
+}
+
+/// 管理属性索引（`index` 子命令）
+fn run_index_command(
+    graph: &Graph,
+    command: &IndexCommand,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        IndexCommand::Create { label, property } => {
+            graph.create_index(label, property)?;
+            println!("索引已创建: {}({})", label, property);
+        }
+        IndexCommand::List => {
+            let indexes = graph.list_indexes();
+            if indexes.is_empty() {
+                println!("没有属性索引");
+            }
+            for index in indexes {
+                println!(
+                    "{}({}): {} 个顶点",
+                    index.label, index.property, index.entries
+                );
+            }
+        }
+        IndexCommand::Drop { label, property } => {
+            graph.drop_index(label, property)?;
+            println!("索引已删除: {}({})", label, property);
+        }
+    }
+    Ok(())
+}
+
+/// 合并多个数据目录（`merge` 子命令）
+fn run_merge(
+    inputs: &[PathBuf],
+    out: &Path,
+    buffer_size: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for input in inputs {
+        if !input.is_dir() {
+            return Err(format!("数据目录不存在: {}", input.display()).into());
+        }
+    }
+
+    let target = GraphCatalog::open(out, Some(buffer_size))?;
+    let graph = target.current_graph();
+    for input in inputs {
+        let source = GraphCatalog::open(input, Some(buffer_size))?;
+        let stats = graph.merge_from(&source.current_graph())?;
+        println!(
+            "{}: 新增顶点 {}，合并重复顶点 {}，追加边 {}，跳过重复转账 {}",
+            input.display(),
+            stats.vertices_added,
+            stats.vertices_deduplicated,
+            stats.edges_added,
+            stats.edges_deduplicated
+        );
+    }
+    graph.flush()?;
+
+    println!(
+        "已合并到 {}: 顶点数 {}，边数 {}",
+        out.display(),
+        graph.vertex_count(),
+        graph.edge_count()
+    );
+    Ok(())
+}
+
 /// 运行交互模式
 fn run_interactive(
     catalog: &Arc<GraphCatalog>,
@@ -336,7 +600,7 @@ fn handle_command(
                 println!("用法: maxflow <源点 ID> <汇点 ID>");
             } else if let (Ok(src), Ok(sink)) = (ids[0].parse::<u64>(), ids[1].parse::<u64>()) {
                 let algo = EdmondsKarp::new(graph.clone());
-                let result = algo.max_flow(VertexId::new(src), VertexId::new(sink));
+                let result = algo.max_flow(VertexId::new(src), VertexId::new(sink))?;
                 println!("最大流: {}", result.value.to_string().green());
                 println!("流量分配:");
                 for ((u, v), flow) in result.flow.iter().take(10) {
@@ -364,6 +628,7 @@ fn handle_command(
                 || upper.starts_with("DROP")
                 || upper.starts_with("SHOW")
                 || upper.starts_with("DESCRIBE")
+                || upper.starts_with("EXPLAIN")
                 || upper.starts_with("DESC")
                 || upper.starts_with("LET")
                 || upper.starts_with("FOR")
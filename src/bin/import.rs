@@ -2,8 +2,9 @@
 //!
 //! 从 CSV 或 JSON 文件批量导入区块链数据
 
-use chaingraph::graph::Graph;
-use chaingraph::import::BatchImporter;
+use chaingraph::algorithm::build_thread_pool;
+use chaingraph::graph::{Graph, GraphCatalog};
+use chaingraph::import::{BatchImporter, DedupKey};
 use clap::Parser;
 use std::path::PathBuf;
 
@@ -19,6 +20,10 @@ struct Args {
     #[arg(short, long, default_value = "./data")]
     data_dir: String,
 
+    /// 目标图名称（默认为 catalog 中记录的当前图）
+    #[arg(short, long)]
+    graph: Option<String>,
+
     /// 输入格式: csv, jsonl
     #[arg(short, long, default_value = "csv")]
     format: String,
@@ -34,6 +39,22 @@ struct Args {
     /// 缓冲池大小（页面数）
     #[arg(long, default_value = "2048")]
     buffer_size: usize,
+
+    /// 仅校验输入文件，不写入数据库
+    #[arg(long)]
+    dry_run: bool,
+
+    /// 跳过重复转账: tx-hash（按交易哈希）, transfer（按完整转账）
+    #[arg(long)]
+    dedup: Option<String>,
+
+    /// 按地址顺序分配顶点 ID，顺序与并行导入结果一致
+    #[arg(long)]
+    deterministic_ids: bool,
+
+    /// 并行导入使用的线程数（默认使用全部核心）
+    #[arg(long)]
+    threads: Option<usize>,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -46,13 +67,39 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("格式: {}", args.format);
     println!("批次大小: {}", args.batch_size);
     println!("并行模式: {}", args.parallel);
-
-    // 打开图数据库
-    let graph = Graph::open(&args.data_dir, Some(args.buffer_size))?;
+    if let Some(threads) = args.threads {
+        println!("线程数: {}", threads);
+    }
+
+    if args.dry_run {
+        return validate(&args);
+    }
+
+    // 打开图目录并定位目标图
+    let catalog = GraphCatalog::open(&args.data_dir, Some(args.buffer_size))?;
+    let graph = match &args.graph {
+        Some(name) => catalog.select_graph(name)?,
+        None => catalog.current_graph(),
+    };
+    println!("目标图: {}", catalog.current_graph_name());
 
     println!("\n开始导入...");
 
-    let importer = BatchImporter::new(graph.clone()).with_batch_size(args.batch_size);
+    let mut importer = BatchImporter::new(graph.clone())
+        .with_batch_size(args.batch_size)
+        .with_deterministic_ids(args.deterministic_ids);
+    if let Some(threads) = args.threads {
+        importer = importer.with_thread_pool(build_thread_pool(threads)?);
+    }
+    match args.dedup.as_deref() {
+        None => {}
+        Some("tx-hash") => importer = importer.with_dedup(DedupKey::TxHash),
+        Some("transfer") => importer = importer.with_dedup(DedupKey::Transfer),
+        Some(other) => {
+            eprintln!("不支持的去重方式: {}", other);
+            std::process::exit(1);
+        }
+    }
 
     let stats = match args.format.as_str() {
         "csv" => {
@@ -76,6 +123,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("  顶点导入: {}", stats.vertices_imported);
     println!("  边导入: {}", stats.edges_imported);
     println!("  错误数: {}", stats.errors);
+    println!("  跳过重复: {}", stats.duplicates);
+    println!("  提前刷盘: {}", stats.flushes);
     println!("  耗时: {} ms", stats.duration_ms);
     println!("\n当前图大小:");
     println!("  顶点数: {}", graph.vertex_count());
@@ -83,3 +132,29 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// 试运行：只解析输入文件并报告错误，不打开数据目录
+fn validate(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    println!("\n试运行校验...");
+
+    let importer = BatchImporter::new(Graph::in_memory()?);
+    let stats = match args.format.as_str() {
+        "csv" => importer.validate_transfers_csv(&args.input)?,
+        "jsonl" | "json" => importer.validate_jsonl(&args.input)?,
+        _ => {
+            eprintln!("不支持的格式: {}", args.format);
+            std::process::exit(1);
+        }
+    };
+
+    println!("\n校验完成（未写入数据）:");
+    println!("  可导入顶点: {}", stats.vertices_imported);
+    println!("  可导入边: {}", stats.edges_imported);
+    println!("  错误数: {}", stats.errors);
+    for (line, error) in &stats.line_errors {
+        println!("    第 {} 行: {}", line, error);
+    }
+    println!("  耗时: {} ms", stats.duration_ms);
+
+    Ok(())
+}
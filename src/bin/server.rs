@@ -2,8 +2,8 @@
 //!
 //! 启动 HTTP API 服务器
 
-use chaingraph::graph::GraphCatalog;
-use chaingraph::server::{start_server, ServerConfig};
+use chaingraph::graph::{AdjacencyOrder, GraphCatalog};
+use chaingraph::server::{start_server, JsonFieldCase, ServerConfig};
 use clap::Parser;
 
 #[derive(Parser, Debug)]
@@ -25,6 +25,58 @@ struct Args {
     /// 缓冲池大小（页面数）
     #[arg(short, long, default_value = "1024")]
     buffer_size: usize,
+
+    /// 单条查询最大长度（字节）
+    #[arg(long, default_value = "1048576")]
+    max_query_length: usize,
+
+    /// 请求体大小上限（字节）
+    #[arg(long, default_value = "4194304")]
+    max_body_size: usize,
+
+    /// 单个 MATCH 的中间绑定行数上限，超出时查询中止
+    #[arg(long, default_value = "1000000")]
+    max_bindings: usize,
+
+    /// 无标签节点模式在没有 LIMIT 时允许全量扫描的最大顶点数
+    #[arg(long, default_value = "100000")]
+    max_unlabeled_scan: usize,
+
+    /// 最大流结果中列出的流量边数上限
+    #[arg(long, default_value = "100")]
+    max_flow_edges: usize,
+
+    /// 没有 LIMIT 的 MATCH / SELECT 最多返回的行数
+    #[arg(long, default_value = "100000")]
+    max_rows: usize,
+
+    /// 单个 MATCH 中可变长度边段合计的展开步数上限
+    #[arg(long, default_value = "1000000")]
+    max_expansion_steps: usize,
+
+    /// 算法 CALL 结果缓存的条目数，0 表示不缓存；图有写入后旧结果自动失效
+    #[arg(long, default_value = "0")]
+    result_cache_size: usize,
+
+    /// 邻接边返回顺序: insertion（默认）、edge-id 或 block-number
+    #[arg(long, default_value = "insertion")]
+    adjacency_order: AdjacencyOrder,
+
+    /// JSON 响应字段命名风格: snake（默认）或 camel
+    #[arg(long, default_value = "snake")]
+    json_field_case: JsonFieldCase,
+
+    /// 并行算法使用的线程数（默认使用全部核心）
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// 管理接口（/admin/*）的 API key，未设置时管理接口禁用
+    #[arg(long)]
+    admin_api_key: Option<String>,
+
+    /// 生产模式：错误响应只返回通用提示与错误码，详细信息仅写入服务端日志
+    #[arg(long)]
+    hide_error_details: bool,
 }
 
 #[tokio::main]
@@ -38,6 +90,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // 打开图目录（多图）
     let catalog = GraphCatalog::open(&args.data_dir, Some(args.buffer_size))?;
+    catalog.set_adjacency_order(args.adjacency_order);
     let current = catalog.current_graph();
 
     println!("图数据库已加载");
@@ -49,6 +102,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let config = ServerConfig {
         host: args.host,
         port: args.port,
+        max_query_length: args.max_query_length,
+        max_body_size: args.max_body_size,
+        max_bindings: args.max_bindings,
+        max_unlabeled_scan: args.max_unlabeled_scan,
+        max_flow_edges: args.max_flow_edges,
+        max_rows: args.max_rows,
+        max_expansion_steps: args.max_expansion_steps,
+        result_cache_size: args.result_cache_size,
+        json_field_case: args.json_field_case,
+        threads: args.threads,
+        admin_api_key: args.admin_api_key,
+        verbose_errors: !args.hide_error_details,
     };
 
     start_server(config, catalog).await?;
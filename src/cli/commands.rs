@@ -85,11 +85,7 @@ pub fn execute_console_command(input: &str, state: &mut ConsoleState, graph: &Ar
     let input = input.trim();
     
     // 移除开头的冒号
-    let cmd_line = if input.starts_with(':') {
-        &input[1..]
-    } else {
-        input
-    };
+    let cmd_line = input.strip_prefix(':').unwrap_or(input);
 
     let parts: Vec<&str> = cmd_line.splitn(2, ' ').collect();
     let cmd = parts[0].to_lowercase();
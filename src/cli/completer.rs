@@ -20,7 +20,8 @@ const GQL_KEYWORDS: &[&str] = &[
     "GRAPH", "NODE", "EDGE", "TYPE", "LABEL", "INDEX", "CONSTRAINT",
     // 元数据
     "SHOW", "DESCRIBE", "DESC", "GRAPHS", "GRAPH", "TYPE", "TYPES", "LABELS",
-    "INDEXES", "CONSTRAINTS", "PROCEDURES", "FUNCTIONS",
+    "INDEXES", "CONSTRAINTS", "PROCEDURES", "FUNCTIONS", "TRANSACTIONS",
+    "EXPLAIN", "ANALYZE",
     // 过程调用
     "CALL", "YIELD",
     // 会话和事务
@@ -43,7 +44,7 @@ fn get_sub_commands(keyword: &str) -> Option<&'static [&'static str]> {
     match keyword {
         "SHOW" => Some(&[
             "GRAPHS", "GRAPH", "TYPES", "LABELS", "EDGE", "TYPES", "INDEXES",
-            "CONSTRAINTS", "PROCEDURES", "FUNCTIONS",
+            "CONSTRAINTS", "PROCEDURES", "FUNCTIONS", "TRANSACTIONS",
         ]),
         "DESCRIBE" | "DESC" => Some(&["GRAPH", "LABEL", "EDGE", "TYPE", "INDEX"]),
         "CREATE" => Some(&["GRAPH", "INDEX", "CONSTRAINT"]),
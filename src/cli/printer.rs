@@ -167,7 +167,7 @@ DDL 语句:
 元数据查询:
   SHOW GRAPHS                  -- 列出所有图
   SHOW LABELS                  -- 列出所有顶点标签
-  SHOW EDGE TYPES              -- 列出所有边类型
+  SHOW EDGE TYPES              -- 列出图中存在的边类型
   SHOW PROCEDURES              -- 列出所有过程
   SHOW FUNCTIONS               -- 列出所有函数
   SHOW INDEXES                 -- 列出所有索引
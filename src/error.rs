@@ -48,6 +48,9 @@ pub enum Error {
     #[error("无效的交易哈希: {0}")]
     InvalidTxHash(String),
 
+    #[error("属性类型不匹配: {0}")]
+    TypeMismatch(String),
+
     #[error("导入错误: {0}")]
     ImportError(String),
 
@@ -57,6 +60,9 @@ pub enum Error {
     #[error("算法错误: {0}")]
     AlgorithmError(String),
 
+    #[error("操作已取消")]
+    Cancelled,
+
     #[error("IO 错误: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -66,3 +72,31 @@ pub enum Error {
     #[error("内部错误: {0}")]
     InternalError(String),
 }
+
+impl Error {
+    /// 稳定的错误码，在不返回详细信息时供客户端区分错误类别
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::VertexNotFound(_) => "VERTEX_NOT_FOUND",
+            Error::EdgeNotFound(_) => "EDGE_NOT_FOUND",
+            Error::VertexAlreadyExists(_) => "VERTEX_EXISTS",
+            Error::PageNotFound(_)
+            | Error::BufferPoolFull
+            | Error::StorageError(_)
+            | Error::ChecksumMismatch { .. }
+            | Error::IoError(_) => "STORAGE_ERROR",
+            Error::ParseError(_) | Error::QueryParseError(_) => "PARSE_ERROR",
+            Error::QueryError(_) | Error::QueryExecutionError(_) => "QUERY_ERROR",
+            Error::NotFound(_) => "NOT_FOUND",
+            Error::InvalidAddress(_) | Error::InvalidTxHash(_) | Error::TypeMismatch(_) => {
+                "INVALID_INPUT"
+            }
+            Error::ImportError(_) => "IMPORT_ERROR",
+            Error::AlgorithmError(_) => "ALGORITHM_ERROR",
+            Error::Cancelled => "CANCELLED",
+            Error::ServerError(_) | Error::SerializationError(_) | Error::InternalError(_) => {
+                "INTERNAL_ERROR"
+            }
+        }
+    }
+}
@@ -1,18 +1,28 @@
 //! Graph catalog for multi-graph management
 //!
 //! Responsible for loading, creating, dropping and switching graphs on disk.
+//! Each graph lives in its own data file `<base_dir>/<name>.cg`, with side
+//! files named `<name>.<file>` next to it, and `catalog.json` only indexes the
+//! graph names, so a damaged graph does not affect the others. Graphs stored
+//! in the older `<base_dir>/<name>/` directory layout are moved into this
+//! layout when the catalog opens them.
 
 use crate::error::{Error, Result};
-use crate::graph::Graph;
+use crate::graph::{AdjacencyOrder, Graph, TransactionManager};
+use crate::storage::DiskStorage;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 const DEFAULT_GRAPH_NAME: &str = "default";
 const CATALOG_FILE: &str = "catalog.json";
+/// Extension of a graph's data file
+const GRAPH_FILE_EXT: &str = "cg";
+/// Side files a graph keeps next to its data file
+const GRAPH_SIDE_FILES: [&str; 2] = ["indexes.json", "address_tags.json"];
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 struct CatalogMeta {
@@ -26,6 +36,11 @@ pub struct GraphCatalog {
     buffer_pool_size: Option<usize>,
     current_graph: RwLock<String>,
     graphs: RwLock<HashMap<String, Arc<Graph>>>,
+    /// Registered graphs that failed to open; kept in the catalog so they
+    /// load again once repaired
+    unavailable: RwLock<HashSet<String>>,
+    transactions: TransactionManager,
+    adjacency_order: RwLock<AdjacencyOrder>,
 }
 
 impl GraphCatalog {
@@ -40,18 +55,34 @@ impl GraphCatalog {
             buffer_pool_size,
             current_graph: RwLock::new(String::new()),
             graphs: RwLock::new(HashMap::new()),
+            unavailable: RwLock::new(HashSet::new()),
+            transactions: TransactionManager::new(),
+            adjacency_order: RwLock::new(AdjacencyOrder::default()),
         };
 
         // Load meta if exists; otherwise bootstrap default graph
         if let Some(meta) = catalog.load_meta()? {
             for name in &meta.graphs {
-                let g = catalog.open_graph_dir(name)?;
-                catalog.graphs.write().insert(name.clone(), g);
+                // A graph that fails to open is skipped so the others stay usable
+                match catalog.open_graph_file(name) {
+                    Ok(g) => {
+                        catalog.graphs.write().insert(name.clone(), g);
+                    }
+                    Err(e) => {
+                        eprintln!("警告: 打开图 '{}' 失败，已跳过: {}", name, e);
+                        catalog.unavailable.write().insert(name.clone());
+                    }
+                }
             }
-            if meta.graphs.is_empty() {
+            let loaded = catalog.graphs.read().contains_key(&meta.current_graph);
+            if catalog.graphs.read().is_empty() {
                 catalog.bootstrap_default()?;
-            } else {
+            } else if loaded {
                 *catalog.current_graph.write() = meta.current_graph;
+            } else {
+                let mut names = catalog.list_graphs();
+                names.sort();
+                *catalog.current_graph.write() = names[0].clone();
             }
         } else {
             catalog.bootstrap_default()?;
@@ -61,7 +92,7 @@ impl GraphCatalog {
     }
 
     fn bootstrap_default(&mut self) -> Result<()> {
-        let g = self.open_graph_dir(DEFAULT_GRAPH_NAME)?;
+        let g = self.open_graph_file(DEFAULT_GRAPH_NAME)?;
         self.graphs
             .write()
             .insert(DEFAULT_GRAPH_NAME.to_string(), g);
@@ -69,9 +100,68 @@ impl GraphCatalog {
         self.save_meta()
     }
 
-    fn open_graph_dir(&self, name: &str) -> Result<Arc<Graph>> {
-        let dir = self.base_dir.join(name);
-        Graph::open(dir, self.buffer_pool_size)
+    fn open_graph_file(&self, name: &str) -> Result<Arc<Graph>> {
+        self.migrate_graph_dir(name)?;
+        let graph = Graph::open_file(self.graph_file(name), self.buffer_pool_size)?;
+        graph.set_adjacency_order(*self.adjacency_order.read());
+        Ok(graph)
+    }
+
+    /// Data file of a graph: `<base_dir>/<name>.cg`.
+    pub fn graph_file(&self, name: &str) -> PathBuf {
+        self.base_dir.join(format!("{}.{}", name, GRAPH_FILE_EXT))
+    }
+
+    /// A graph's data file and the side files that exist next to it.
+    fn graph_files(&self, name: &str) -> Vec<PathBuf> {
+        let mut files = vec![self.graph_file(name)];
+        files.extend(
+            GRAPH_SIDE_FILES
+                .iter()
+                .map(|side| self.base_dir.join(format!("{}.{}", name, side))),
+        );
+        files.retain(|path| path.exists());
+        files
+    }
+
+    /// Whether a graph has data on disk in either layout.
+    fn graph_exists_on_disk(&self, name: &str) -> bool {
+        self.graph_file(name).exists()
+            || DiskStorage::data_file_in(self.legacy_graph_dir(name)).exists()
+    }
+
+    /// Directory of a graph in the older one-directory-per-graph layout.
+    fn legacy_graph_dir(&self, name: &str) -> PathBuf {
+        self.base_dir.join(name)
+    }
+
+    /// Move a graph from `<base_dir>/<name>/` into `<base_dir>/<name>.cg`
+    /// and its side files. The directory is removed once empty; a graph
+    /// already in the file layout is left alone.
+    fn migrate_graph_dir(&self, name: &str) -> Result<()> {
+        let dir = self.legacy_graph_dir(name);
+        let data = DiskStorage::data_file_in(&dir);
+        if !data.exists() || self.graph_file(name).exists() {
+            return Ok(());
+        }
+        let rename = |from: &Path, to: PathBuf| {
+            fs::rename(from, &to).map_err(|e| {
+                Error::StorageError(format!(
+                    "迁移图 '{}' 失败 ({:?} -> {:?}): {}",
+                    name, from, to, e
+                ))
+            })
+        };
+        for side in GRAPH_SIDE_FILES {
+            let path = dir.join(side);
+            if path.exists() {
+                rename(&path, self.base_dir.join(format!("{}.{}", name, side)))?;
+            }
+        }
+        // The data file moves last: until it does, the next open retries
+        rename(&data, self.graph_file(name))?;
+        let _ = fs::remove_dir(&dir);
+        Ok(())
     }
 
     fn meta_path(&self) -> PathBuf {
@@ -91,7 +181,8 @@ impl GraphCatalog {
     }
 
     fn save_meta(&self) -> Result<()> {
-        let graphs: Vec<String> = self.graphs.read().keys().cloned().collect();
+        let mut graphs: Vec<String> = self.graphs.read().keys().cloned().collect();
+        graphs.extend(self.unavailable.read().iter().cloned());
         let meta = CatalogMeta {
             current_graph: self.current_graph.read().clone(),
             graphs,
@@ -106,13 +197,12 @@ impl GraphCatalog {
 
     /// Create a new graph and register it. Fails if name exists.
     pub fn create_graph(&self, name: &str) -> Result<Arc<Graph>> {
-        if self.graphs.read().contains_key(name) {
+        let exists =
+            self.graphs.read().contains_key(name) || self.unavailable.read().contains(name);
+        if exists {
             return Err(Error::QueryError(format!("Graph '{}' already exists", name)));
         }
-        let dir = self.base_dir.join(name);
-        fs::create_dir_all(&dir)
-            .map_err(|e| Error::StorageError(format!("创建图目录失败: {}", e)))?;
-        let graph = Graph::open(dir, self.buffer_pool_size)?;
+        let graph = self.open_graph_file(name)?;
         self.graphs.write().insert(name.to_string(), graph.clone());
         if self.current_graph.read().is_empty() {
             *self.current_graph.write() = name.to_string();
@@ -121,14 +211,19 @@ impl GraphCatalog {
         Ok(graph)
     }
 
-    /// Drop a graph and remove its directory.
+    /// Drop a graph and remove its data and side files.
     pub fn drop_graph(&self, name: &str) -> Result<()> {
         let mut graphs = self.graphs.write();
-        if graphs.remove(name).is_none() {
+        if graphs.remove(name).is_none() && !self.unavailable.write().remove(name) {
             return Err(Error::QueryError(format!("Graph '{}' not found", name)));
         }
-        let dir = self.base_dir.join(name);
-        if dir.exists() {
+        for path in self.graph_files(name) {
+            fs::remove_file(&path)
+                .map_err(|e| Error::StorageError(format!("删除图文件失败: {}", e)))?;
+        }
+        // A graph that failed to migrate may still sit in the old layout
+        let dir = self.legacy_graph_dir(name);
+        if DiskStorage::data_file_in(&dir).exists() {
             fs::remove_dir_all(&dir)
                 .map_err(|e| Error::StorageError(format!("删除图目录失败: {}", e)))?;
         }
@@ -140,6 +235,8 @@ impl GraphCatalog {
                 *self.current_graph.write() = String::new();
             }
         }
+        // save_meta re-acquires the graphs lock
+        drop(graphs);
         self.save_meta()
     }
 
@@ -150,10 +247,10 @@ impl GraphCatalog {
             self.save_meta()?;
             return Ok(g.clone());
         }
-        // Try open lazy if directory exists
-        let dir = self.base_dir.join(name);
-        if dir.exists() {
-            let g = Graph::open(dir, self.buffer_pool_size)?;
+        // Try open lazy if the graph exists on disk
+        if self.graph_exists_on_disk(name) {
+            let g = self.open_graph_file(name)?;
+            self.unavailable.write().remove(name);
             self.graphs.write().insert(name.to_string(), g.clone());
             *self.current_graph.write() = name.to_string();
             self.save_meta()?;
@@ -162,6 +259,14 @@ impl GraphCatalog {
         Err(Error::QueryError(format!("Graph '{}' not found", name)))
     }
 
+    /// Switch the current graph for this process only, without rewriting
+    /// the catalog's persisted default (used by the `--graph` CLI flag).
+    pub fn select_graph(&self, name: &str) -> Result<Arc<Graph>> {
+        let graph = self.ensure_graph(name)?;
+        *self.current_graph.write() = name.to_string();
+        Ok(graph)
+    }
+
     /// Get current graph instance.
     pub fn current_graph(&self) -> Arc<Graph> {
         let name = self.current_graph.read().clone();
@@ -169,14 +274,13 @@ impl GraphCatalog {
             return g.clone();
         }
         // Fallback: bootstrap default
-        self.open_graph_dir(DEFAULT_GRAPH_NAME)
-            .map(|g| {
+        self.open_graph_file(DEFAULT_GRAPH_NAME)
+            .inspect(|g| {
                 self.graphs
                     .write()
                     .insert(DEFAULT_GRAPH_NAME.to_string(), g.clone());
                 *self.current_graph.write() = DEFAULT_GRAPH_NAME.to_string();
                 let _ = self.save_meta();
-                g
             })
             .expect("Graph catalog missing default graph")
     }
@@ -185,11 +289,36 @@ impl GraphCatalog {
         self.current_graph.read().clone()
     }
 
+    /// Open transactions tracked across all graphs of this catalog.
+    pub fn transactions(&self) -> &TransactionManager {
+        &self.transactions
+    }
+
+    /// Adjacency order applied to every graph of this catalog.
+    pub fn adjacency_order(&self) -> AdjacencyOrder {
+        *self.adjacency_order.read()
+    }
+
+    /// Set the adjacency order for all loaded graphs and any opened later.
+    pub fn set_adjacency_order(&self, order: AdjacencyOrder) {
+        *self.adjacency_order.write() = order;
+        for graph in self.graphs.read().values() {
+            graph.set_adjacency_order(order);
+        }
+    }
+
     /// List graph names.
     pub fn list_graphs(&self) -> Vec<String> {
         self.graphs.read().keys().cloned().collect()
     }
 
+    /// Registered graphs that could not be opened when the catalog loaded.
+    pub fn unavailable_graphs(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.unavailable.read().iter().cloned().collect();
+        names.sort();
+        names
+    }
+
     /// Get a specific graph by name without switching context.
     pub fn get_graph(&self, name: &str) -> Option<Arc<Graph>> {
         self.graphs.read().get(name).cloned()
@@ -200,9 +329,9 @@ impl GraphCatalog {
         if let Some(g) = self.get_graph(name) {
             return Ok(g);
         }
-        let dir = self.base_dir.join(name);
-        if dir.exists() {
-            let g = Graph::open(dir, self.buffer_pool_size)?;
+        if self.graph_exists_on_disk(name) {
+            let g = self.open_graph_file(name)?;
+            self.unavailable.write().remove(name);
             self.graphs.write().insert(name.to_string(), g.clone());
             self.save_meta()?;
             return Ok(g);
@@ -210,3 +339,93 @@ impl GraphCatalog {
         Err(Error::QueryError(format!("Graph '{}' not found", name)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TokenAmount;
+
+    #[test]
+    fn test_graphs_use_separate_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        {
+            let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+            for name in ["alpha", "beta"] {
+                let graph = catalog.create_graph(name).unwrap();
+                let a = graph.add_account(format!("0x{}a", name)).unwrap();
+                let b = graph.add_account(format!("0x{}b", name)).unwrap();
+                graph
+                    .add_transfer(a, b, TokenAmount::from_u64(1), 1)
+                    .unwrap();
+                graph.flush().unwrap();
+            }
+            // 每个图有独立的数据文件，catalog.json 只记录索引
+            assert!(dir.path().join(CATALOG_FILE).exists());
+            for name in ["alpha", "beta"] {
+                assert!(dir.path().join(format!("{}.cg", name)).exists());
+            }
+        }
+
+        // 损坏 alpha 的数据文件头，beta 仍可独立打开
+        let alpha_file = dir.path().join("alpha.cg");
+        let mut bytes = fs::read(&alpha_file).unwrap();
+        bytes[..8].fill(0);
+        fs::write(&alpha_file, bytes).unwrap();
+
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        assert_eq!(catalog.unavailable_graphs(), vec!["alpha".to_string()]);
+        let beta = catalog.use_graph("beta").unwrap();
+        assert_eq!(beta.vertex_count(), 2);
+        assert!(catalog.get_graph("alpha").is_none());
+        assert!(catalog.use_graph("alpha").is_err());
+
+        // 损坏的图仍登记在 catalog 中，可以删除
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        assert_eq!(catalog.unavailable_graphs(), vec!["alpha".to_string()]);
+        catalog.drop_graph("alpha").unwrap();
+        assert!(!alpha_file.exists());
+        assert!(dir.path().join("beta.cg").exists());
+        assert!(catalog.unavailable_graphs().is_empty());
+    }
+
+    #[test]
+    fn test_legacy_graph_directories_are_migrated() {
+        let dir = tempfile::TempDir::new().unwrap();
+        // 旧布局：每个图一个目录 <base>/<name>/data.cgd
+        {
+            let graph = Graph::open(dir.path().join("legacy"), Some(64)).unwrap();
+            let a = graph.add_account("0xlegacya".to_string()).unwrap();
+            let b = graph.add_account("0xlegacyb".to_string()).unwrap();
+            graph
+                .add_transfer(a, b, TokenAmount::from_u64(1), 1)
+                .unwrap();
+            graph.set_address_tag("0xlegacya", "exchange").unwrap();
+            graph.flush().unwrap();
+        }
+        let meta = CatalogMeta {
+            current_graph: "legacy".to_string(),
+            graphs: vec!["legacy".to_string()],
+        };
+        fs::write(
+            dir.path().join(CATALOG_FILE),
+            serde_json::to_vec_pretty(&meta).unwrap(),
+        )
+        .unwrap();
+
+        // 打开 catalog 时迁移为 <base>/legacy.cg，数据与标签保持不变
+        for _ in 0..2 {
+            let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+            assert!(catalog.unavailable_graphs().is_empty());
+            let graph = catalog.use_graph("legacy").unwrap();
+            assert_eq!(graph.vertex_count(), 2);
+            assert_eq!(graph.edge_count(), 1);
+            assert_eq!(
+                graph.get_address_tag("0xlegacya").as_deref(),
+                Some("exchange")
+            );
+            assert!(dir.path().join("legacy.cg").exists());
+            assert!(dir.path().join("legacy.address_tags.json").exists());
+            assert!(!dir.path().join("legacy").exists());
+        }
+    }
+}
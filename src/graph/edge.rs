@@ -3,12 +3,13 @@
 //! Web3 场景的边类型：转账、调用、创建、授权
 
 use crate::graph::vertex::VertexId;
-use crate::types::{EdgeLabel, PropertyValue, TokenAmount};
+use crate::types::{EdgeLabel, PropertyValue, TokenAmount, TxHash};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-/// 边 ID（全局唯一）
+/// 边 ID（全局唯一），序列化为裸 u64
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct EdgeId(pub u64);
 
 impl EdgeId {
@@ -40,6 +41,10 @@ pub struct Edge {
     dst: VertexId,
     /// 属性
     properties: HashMap<String, PropertyValue>,
+    /// 转账类边的金额（`amount` 属性的类型化副本，供算法直接读取；
+    /// 不单独序列化，反序列化后由属性重建）
+    #[serde(skip)]
+    amount: Option<TokenAmount>,
     /// 所在页面 ID
     page_id: Option<u64>,
     /// 页面内偏移
@@ -55,6 +60,7 @@ impl Edge {
             src,
             dst,
             properties: HashMap::new(),
+            amount: None,
             page_id: None,
             page_offset: None,
         }
@@ -69,8 +75,7 @@ impl Edge {
         block_number: u64,
     ) -> Self {
         let mut e = Self::new(id, EdgeLabel::Transfer, src, dst);
-        e.properties
-            .insert("amount".to_string(), PropertyValue::TokenAmount(amount));
+        e.set_amount(amount);
         e.properties.insert(
             "block_number".to_string(),
             PropertyValue::Integer(block_number as i64),
@@ -115,8 +120,7 @@ impl Edge {
         block_number: u64,
     ) -> Self {
         let mut e = Self::new(id, EdgeLabel::Approve, src, dst);
-        e.properties
-            .insert("amount".to_string(), PropertyValue::TokenAmount(amount));
+        e.set_amount(amount);
         e.properties.insert(
             "block_number".to_string(),
             PropertyValue::Integer(block_number as i64),
@@ -149,13 +153,19 @@ impl Edge {
         self.properties.get(key)
     }
 
-    /// 设置属性
+    /// 设置属性（设置 `amount` 时同步类型化金额）
     pub fn set_property(&mut self, key: String, value: PropertyValue) {
+        if key == "amount" {
+            self.amount = Self::typed_amount(&value);
+        }
         self.properties.insert(key, value);
     }
 
     /// 移除属性
     pub fn remove_property(&mut self, key: &str) -> Option<PropertyValue> {
+        if key == "amount" {
+            self.amount = None;
+        }
         self.properties.remove(key)
     }
 
@@ -166,10 +176,18 @@ impl Edge {
 
     /// 获取转账金额
     pub fn amount(&self) -> Option<&TokenAmount> {
-        if let Some(PropertyValue::TokenAmount(amt)) = self.properties.get("amount") {
-            Some(amt)
-        } else {
-            None
+        self.amount.as_ref()
+    }
+
+    /// 设置转账金额（同时写入 `amount` 属性）
+    pub fn set_amount(&mut self, amount: TokenAmount) {
+        self.set_property("amount".to_string(), PropertyValue::TokenAmount(amount));
+    }
+
+    fn typed_amount(value: &PropertyValue) -> Option<TokenAmount> {
+        match value {
+            PropertyValue::TokenAmount(amt) | PropertyValue::Amount(amt) => Some(*amt),
+            _ => None,
         }
     }
 
@@ -182,6 +200,33 @@ impl Edge {
         }
     }
 
+    /// 获取转账时间（UTC 秒，`timestamp` 属性），未记录时为 None
+    pub fn timestamp(&self) -> Option<i64> {
+        if let Some(PropertyValue::Timestamp(secs)) = self.properties.get("timestamp") {
+            Some(*secs)
+        } else {
+            None
+        }
+    }
+
+    /// 获取事件日志序号（`log_index` 属性），未记录时为 None
+    pub fn log_index(&self) -> Option<u64> {
+        if let Some(PropertyValue::Integer(n)) = self.properties.get("log_index") {
+            Some(*n as u64)
+        } else {
+            None
+        }
+    }
+
+    /// 转账的唯一键 (tx_hash, log_index)：同一交易内的多笔转账按日志序号区分，
+    /// 两个属性都存在时才有
+    pub fn transfer_key(&self) -> Option<(TxHash, u64)> {
+        match self.properties.get("tx_hash") {
+            Some(PropertyValue::TxHash(hash)) => Some((*hash, self.log_index()?)),
+            _ => None,
+        }
+    }
+
     /// 设置页面位置
     pub fn set_page_location(&mut self, page_id: u64, offset: u32) {
         self.page_id = Some(page_id);
@@ -203,7 +248,9 @@ impl Edge {
 
     /// 从字节反序列化
     pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
-        bincode::deserialize(bytes).ok()
+        let mut edge: Self = bincode::deserialize(bytes).ok()?;
+        edge.amount = edge.properties.get("amount").and_then(Self::typed_amount);
+        Some(edge)
     }
 
     /// 估算字节大小
@@ -222,8 +269,8 @@ impl Edge {
             .sum::<usize>()
     }
 
-    /// 获取边的权重（用于最大流算法）
-    /// 对于转账边，返回金额；对于其他边返回 1
+    /// 获取边的权重 / 容量（用于最大流和按金额的路径算法）
+    /// 对于转账类边，直接读取类型化金额；对于其他边返回 1
     pub fn weight(&self) -> f64 {
         if let Some(amt) = self.amount() {
             // 将 U256 转换为 f64（可能会损失精度，但用于最大流算法足够）
@@ -290,4 +337,60 @@ mod tests {
 
         assert_eq!(e.weight(), 1000.0);
     }
+
+    #[test]
+    fn test_edge_ids_json_are_plain_integers() {
+        assert_eq!(serde_json::to_string(&EdgeId::new(42)).unwrap(), "42");
+
+        let e = Edge::new_transfer(
+            EdgeId::new(7),
+            VertexId::new(100),
+            VertexId::new(200),
+            TokenAmount::from_u64(1000),
+            12345678,
+        );
+        let json = serde_json::to_value(&e).unwrap();
+        assert_eq!(json["id"], 7);
+        assert_eq!(json["src"], 100);
+        assert_eq!(json["dst"], 200);
+    }
+
+    #[test]
+    fn test_typed_amount() {
+        let amount = TokenAmount::from_u64(1000);
+        let mut e = Edge::new_transfer(
+            EdgeId::new(1),
+            VertexId::new(100),
+            VertexId::new(200),
+            amount,
+            1,
+        );
+
+        // 算法读取类型化字段，不依赖属性表
+        e.properties.clear();
+        assert_eq!(e.amount(), Some(&amount));
+        assert_eq!(e.weight(), 1000.0);
+
+        // 序列化往返后由属性重建
+        let e = Edge::new_transfer(
+            EdgeId::new(2),
+            VertexId::new(1),
+            VertexId::new(2),
+            amount,
+            1,
+        );
+        let back = Edge::from_bytes(&e.to_bytes()).unwrap();
+        assert_eq!(back.amount(), Some(&amount));
+
+        // 通过属性修改金额同样同步
+        let mut back = back;
+        back.set_property(
+            "amount".to_string(),
+            PropertyValue::TokenAmount(TokenAmount::from_u64(7)),
+        );
+        assert_eq!(back.weight(), 7.0);
+        back.remove_property("amount");
+        assert_eq!(back.amount(), None);
+        assert_eq!(back.weight(), 1.0);
+    }
 }
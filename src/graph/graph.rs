@@ -3,24 +3,34 @@
 //! 基于 SSD 存储的图数据库核心，支持数据持久化
 
 use super::edge::{Edge, EdgeId};
-use super::index::{EdgeIndex, VertexIndex};
+use super::index::{EdgeIndex, IndexDefinition, IndexInfo, PropertyIndex, VertexIndex};
+use super::snapshot::{PendingIds, ReadSnapshot};
 use super::vertex::{Vertex, VertexId};
 use crate::error::{Error, Result};
-use crate::storage::{BufferPool, PageType};
-use crate::types::{EdgeLabel, VertexLabel};
-use parking_lot::RwLock;
+use crate::storage::{BufferPool, DiskStorage, PageType};
+use crate::types::{EdgeLabel, PropertyValue, TxHash, VertexLabel};
+use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
-/// Meta 页面 ID（动态分配，存储在图结构中）
-/// 也可以存储在 GraphMeta 中
+/// 属性索引定义文件（与数据文件同目录的附属文件，JSON 格式）
+const INDEX_FILE: &str = "indexes.json";
+
+/// 地址标记文件（与数据文件同目录的附属文件，JSON 格式）
+const ADDRESS_TAG_FILE: &str = "address_tags.json";
+
+// Meta 页面 ID（动态分配，存储在图结构中）
+// 也可以存储在 GraphMeta 中
 
 /// 页面数据区可用大小（约 4060 字节）
 const PAGE_DATA_SIZE: usize = 4060;
 
+/// 记录长度前缀的最高位：置位表示删除标记，数据为 8 字节的顶点或边 ID
+const TOMBSTONE_FLAG: u32 = 1 << 31;
+
 /// 内部存储的 schema 表示（不依赖 query/ast）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoredPropertySpec {
@@ -29,6 +39,38 @@ pub struct StoredPropertySpec {
     pub is_primary_key: bool,
 }
 
+impl StoredPropertySpec {
+    /// 值是否符合声明的类型。字符串类型也接受地址、哈希和金额等以字符串形式
+    /// 表示的值；未识别的类型名不做限制，Null 总是允许
+    pub fn accepts(&self, value: &PropertyValue) -> bool {
+        use PropertyValue as V;
+        if matches!(value, V::Null) {
+            return true;
+        }
+        match self.data_type.to_uppercase().as_str() {
+            "STRING" | "VARCHAR" | "TEXT" => matches!(
+                value,
+                V::String(_) | V::Address(_) | V::TxHash(_) | V::Amount(_) | V::TokenAmount(_)
+            ),
+            "INT" | "INT32" | "INT64" | "INTEGER" | "BIGINT" | "UINT" | "UINT64" => matches!(
+                value,
+                V::Int(_) | V::Integer(_) | V::UInt(_) | V::BlockNumber(_) | V::Timestamp(_)
+            ),
+            "FLOAT" | "FLOAT64" | "DOUBLE" => {
+                matches!(value, V::Float(_) | V::Int(_) | V::Integer(_) | V::UInt(_))
+            }
+            "BOOL" | "BOOLEAN" => matches!(value, V::Bool(_) | V::Boolean(_)),
+            "TIMESTAMP" | "DATETIME" => matches!(value, V::Timestamp(_) | V::Int(_) | V::Integer(_)),
+            "AMOUNT" | "UINT256" => matches!(value, V::Amount(_) | V::TokenAmount(_) | V::UInt(_)),
+            "ADDRESS" => matches!(value, V::Address(_) | V::String(_)),
+            "BYTES" => matches!(value, V::Bytes(_)),
+            "MAP" => matches!(value, V::Map(_)),
+            t if t.starts_with("LIST") || t.starts_with("SET<") => matches!(value, V::List(_)),
+            _ => true,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct StoredGraphSchema {
     /// node label -> properties
@@ -37,6 +79,50 @@ pub struct StoredGraphSchema {
     pub edge_types: HashMap<String, Vec<StoredPropertySpec>>,
 }
 
+impl StoredGraphSchema {
+    /// 按节点类型声明校验属性；未声明的标签或属性不做限制
+    pub fn check_vertex_properties(
+        &self,
+        label: &str,
+        properties: &HashMap<String, PropertyValue>,
+    ) -> Result<()> {
+        Self::check_properties(self.node_types.get(label), label, properties)
+    }
+
+    /// 按边类型声明校验属性；未声明的标签或属性不做限制
+    pub fn check_edge_properties(
+        &self,
+        label: &str,
+        properties: &HashMap<String, PropertyValue>,
+    ) -> Result<()> {
+        Self::check_properties(self.edge_types.get(label), label, properties)
+    }
+
+    fn check_properties(
+        specs: Option<&Vec<StoredPropertySpec>>,
+        label: &str,
+        properties: &HashMap<String, PropertyValue>,
+    ) -> Result<()> {
+        let Some(specs) = specs else {
+            return Ok(());
+        };
+        for spec in specs {
+            if let Some(value) = properties.get(&spec.name) {
+                if !spec.accepts(value) {
+                    return Err(Error::TypeMismatch(format!(
+                        "{}.{} 声明为 {}，写入的值为 {}",
+                        label,
+                        spec.name,
+                        spec.data_type,
+                        value.type_name()
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 /// 图元数据（存储在 Meta 页面中）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct GraphMeta {
@@ -67,6 +153,88 @@ impl Default for GraphMeta {
     }
 }
 
+/// 一致性检查报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConsistencyReport {
+    /// 检查的顶点记录数
+    pub vertices_checked: usize,
+    /// 检查的边记录数
+    pub edges_checked: usize,
+    /// 发现的不一致项
+    pub issues: Vec<String>,
+}
+
+impl ConsistencyReport {
+    /// 是否未发现任何不一致
+    pub fn is_consistent(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// `Graph::merge_from` 的合并统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeStats {
+    /// 新复制的顶点数
+    pub vertices_added: usize,
+    /// 按地址或交易哈希与已有顶点合并的顶点数
+    pub vertices_deduplicated: usize,
+    /// 追加的边数
+    pub edges_added: usize,
+    /// 与已有转账 (tx_hash, log_index) 相同而跳过的边数
+    pub edges_deduplicated: usize,
+}
+
+/// `Graph::checkpoint` 的刷盘统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointStats {
+    /// 写回磁盘的脏页数
+    pub pages_flushed: usize,
+    /// 耗时（毫秒）
+    pub duration_ms: u64,
+}
+
+/// 邻接边的返回顺序
+///
+/// 默认 `Insertion` 按写入（或从页面加载）的顺序返回，开销最小；
+/// 需要可复现的遍历结果（如 `all_paths`、链路追踪）时可切换为稳定排序。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AdjacencyOrder {
+    /// 插入顺序（默认）
+    #[default]
+    Insertion,
+    /// 按边 ID 升序
+    EdgeId,
+    /// 按区块号升序，无区块号的边排在最后，同区块按边 ID
+    BlockNumber,
+}
+
+impl AdjacencyOrder {
+    /// 名称（与 `FromStr` 对应）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdjacencyOrder::Insertion => "insertion",
+            AdjacencyOrder::EdgeId => "edge-id",
+            AdjacencyOrder::BlockNumber => "block-number",
+        }
+    }
+}
+
+impl std::str::FromStr for AdjacencyOrder {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().replace('_', "-").as_str() {
+            "insertion" => Ok(AdjacencyOrder::Insertion),
+            "edge-id" => Ok(AdjacencyOrder::EdgeId),
+            "block-number" => Ok(AdjacencyOrder::BlockNumber),
+            other => Err(Error::ParseError(format!(
+                "未知的邻接顺序 '{}'，可选: insertion, edge-id, block-number",
+                other
+            ))),
+        }
+    }
+}
+
 /// 图数据库
 pub struct Graph {
     /// 缓冲池
@@ -75,6 +243,10 @@ pub struct Graph {
     vertex_index: VertexIndex,
     /// 边索引
     edge_index: EdgeIndex,
+    /// 顶点属性索引
+    property_index: PropertyIndex,
+    /// 地址标记（交易所、混币器等），与顶点数据分开保存
+    address_tags: RwLock<BTreeMap<String, String>>,
     /// 下一个顶点 ID
     next_vertex_id: AtomicU64,
     /// 下一个边 ID
@@ -97,13 +269,35 @@ pub struct Graph {
     dirty: RwLock<bool>,
     /// Meta 页面 ID
     meta_page_id: RwLock<u64>,
+    /// 邻接边返回顺序
+    adjacency_order: RwLock<AdjacencyOrder>,
+    /// 写入中的顶点 ID（读快照水位）
+    pending_vertices: PendingIds,
+    /// 写入中的边 ID（读快照水位）
+    pending_edges: PendingIds,
+    /// 串行化 `add_transfer_unique` 的查重与写入
+    unique_transfer_lock: Mutex<()>,
+    /// 数据版本，每次写入后加一
+    version: AtomicU64,
+    /// 本次打开的实例编号，每次打开随机生成，进程重启后也不会重复
+    instance: u64,
 }
 
 impl Graph {
     /// 打开或创建图数据库
     pub fn open<P: AsRef<Path>>(data_dir: P, buffer_pool_size: Option<usize>) -> Result<Arc<Self>> {
-        let buffer_pool = BufferPool::new(data_dir, buffer_pool_size)?;
+        Self::open_with(BufferPool::new(data_dir, buffer_pool_size)?)
+    }
 
+    /// 打开存放在单个数据文件中的图，附属文件与之同目录并以图文件名为前缀
+    pub fn open_file<P: AsRef<Path>>(
+        data_path: P,
+        buffer_pool_size: Option<usize>,
+    ) -> Result<Arc<Self>> {
+        Self::open_with(BufferPool::open_file(data_path, buffer_pool_size)?)
+    }
+
+    fn open_with(buffer_pool: Arc<BufferPool>) -> Result<Arc<Self>> {
         // 尝试加载已有的元数据
         let meta = Self::load_meta_from_pool(&buffer_pool)?;
 
@@ -111,6 +305,8 @@ impl Graph {
             buffer_pool,
             vertex_index: VertexIndex::new(),
             edge_index: EdgeIndex::new(),
+            property_index: PropertyIndex::new(),
+            address_tags: RwLock::new(BTreeMap::new()),
             next_vertex_id: AtomicU64::new(meta.next_vertex_id),
             next_edge_id: AtomicU64::new(meta.next_edge_id),
             vertex_cache: RwLock::new(HashMap::new()),
@@ -122,14 +318,67 @@ impl Graph {
             current_edge_page_space: RwLock::new(0),
             dirty: RwLock::new(false),
             meta_page_id: RwLock::new(meta.meta_page_id),
+            adjacency_order: RwLock::new(AdjacencyOrder::default()),
+            pending_vertices: PendingIds::default(),
+            pending_edges: PendingIds::default(),
+            unique_transfer_lock: Mutex::new(()),
+            version: AtomicU64::new(0),
+            instance: uuid::Uuid::new_v4().as_u64_pair().0,
         });
 
         // 加载所有顶点和边
         graph.load_all_data()?;
+        graph.load_property_indexes()?;
+        graph.load_address_tags()?;
 
         Ok(graph)
     }
 
+    /// 加载属性索引定义并由已加载的顶点构建索引
+    fn load_property_indexes(&self) -> Result<()> {
+        let path = self.buffer_pool.side_file(INDEX_FILE);
+        if !path.exists() {
+            return Ok(());
+        }
+        let data = std::fs::read(&path)?;
+        let definitions: Vec<IndexDefinition> = serde_json::from_slice(&data)
+            .map_err(|e| Error::StorageError(format!("解析索引定义失败: {}", e)))?;
+        let vertices = self.vertex_cache.read();
+        for definition in definitions {
+            self.property_index.create(definition, vertices.values());
+        }
+        Ok(())
+    }
+
+    /// 保存属性索引定义
+    fn save_property_indexes(&self) -> Result<()> {
+        let data = serde_json::to_vec_pretty(&self.property_index.definitions())
+            .map_err(|e| Error::SerializationError(e.to_string()))?;
+        std::fs::write(self.buffer_pool.side_file(INDEX_FILE), data)?;
+        Ok(())
+    }
+
+    /// 加载地址标记
+    fn load_address_tags(&self) -> Result<()> {
+        let path = self.buffer_pool.side_file(ADDRESS_TAG_FILE);
+        if !path.exists() {
+            return Ok(());
+        }
+        let data = std::fs::read(&path)?;
+        let tags = serde_json::from_slice(&data)
+            .map_err(|e| Error::StorageError(format!("解析地址标记失败: {}", e)))?;
+        *self.address_tags.write() = tags;
+        Ok(())
+    }
+
+    /// 保存地址标记
+    fn save_address_tags(&self, tags: &BTreeMap<String, String>) -> Result<()> {
+        let data = serde_json::to_vec_pretty(tags)
+            .map_err(|e| Error::SerializationError(e.to_string()))?;
+        std::fs::write(self.buffer_pool.side_file(ADDRESS_TAG_FILE), data)?;
+        Ok(())
+    }
+
     /// 从缓冲池加载元数据
     fn load_meta_from_pool(buffer_pool: &Arc<BufferPool>) -> Result<GraphMeta> {
         // 扫描前几个页面寻找 Meta 页面
@@ -173,6 +422,9 @@ impl Graph {
             self.load_edges_from_page(page_id)?;
         }
 
+        // 全部记录读完后再由最终版本建立索引
+        self.rebuild_indexes();
+
         // 更新当前页面的剩余空间
         if let Some(&last_vertex_page) = vertex_pages.last() {
             if let Ok(handle) = self.buffer_pool.fetch_page(last_vertex_page) {
@@ -206,26 +458,21 @@ impl Graph {
             let mut offset = 0;
             while offset + 4 <= page.free_offset as usize {
                 // 读取条目长度
-                let entry_len =
-                    u32::from_le_bytes(page.data[offset..offset + 4].try_into().unwrap()) as usize;
+                let header = u32::from_le_bytes(page.data[offset..offset + 4].try_into().unwrap());
+                let entry_len = (header & !TOMBSTONE_FLAG) as usize;
 
                 if entry_len == 0 || offset + 4 + entry_len > page.free_offset as usize {
                     break;
                 }
+                let entry = &page.data[offset + 4..offset + 4 + entry_len];
 
-                // 反序列化顶点
-                if let Some(vertex) =
-                    Vertex::from_bytes(&page.data[offset + 4..offset + 4 + entry_len])
-                {
-                    let id = vertex.id();
-                    // 更新索引
-                    self.vertex_index
-                        .add_label(vertex.label().clone(), id);
-                    if let Some(addr) = vertex.address() {
-                        self.vertex_index.add_address(addr.to_string(), id);
+                // 删除标记移除先前的记录；更新会追加新记录，后出现的记录覆盖先前的
+                if header & TOMBSTONE_FLAG != 0 {
+                    if let Ok(id) = entry.try_into().map(u64::from_le_bytes) {
+                        self.vertex_cache.write().remove(&VertexId::new(id));
                     }
-                    // 添加到缓存
-                    self.vertex_cache.write().insert(id, vertex);
+                } else if let Some(vertex) = Vertex::from_bytes(entry) {
+                    self.vertex_cache.write().insert(vertex.id(), vertex);
                 }
 
                 offset += 4 + entry_len;
@@ -244,22 +491,21 @@ impl Graph {
             let mut offset = 0;
             while offset + 4 <= page.free_offset as usize {
                 // 读取条目长度
-                let entry_len =
-                    u32::from_le_bytes(page.data[offset..offset + 4].try_into().unwrap()) as usize;
+                let header = u32::from_le_bytes(page.data[offset..offset + 4].try_into().unwrap());
+                let entry_len = (header & !TOMBSTONE_FLAG) as usize;
 
                 if entry_len == 0 || offset + 4 + entry_len > page.free_offset as usize {
                     break;
                 }
+                let entry = &page.data[offset + 4..offset + 4 + entry_len];
 
-                // 反序列化边
-                if let Some(edge) = Edge::from_bytes(&page.data[offset + 4..offset + 4 + entry_len])
-                {
-                    let id = edge.id();
-                    // 更新索引
-                    self.edge_index
-                        .add_edge(id, edge.src(), edge.dst(), edge.label().clone());
-                    // 添加到缓存
-                    self.edge_cache.write().insert(id, edge);
+                // 删除标记移除先前的记录；更新会追加新记录，后出现的记录覆盖先前的
+                if header & TOMBSTONE_FLAG != 0 {
+                    if let Ok(id) = entry.try_into().map(u64::from_le_bytes) {
+                        self.edge_cache.write().remove(&EdgeId::new(id));
+                    }
+                } else if let Some(edge) = Edge::from_bytes(entry) {
+                    self.edge_cache.write().insert(edge.id(), edge);
                 }
 
                 offset += 4 + entry_len;
@@ -319,26 +565,51 @@ impl Graph {
         Ok(())
     }
 
-    /// 将顶点写入磁盘页面
+    /// 将顶点记录追加到顶点页面；同一顶点的多条记录以最后一条为准
     fn write_vertex_to_disk(&self, vertex: &Vertex) -> Result<()> {
-        let data = vertex.to_bytes();
+        self.append_entry(PageType::Vertex, &vertex.to_bytes(), 0)
+    }
+
+    /// 将边记录追加到边页面；同一条边的多条记录以最后一条为准
+    fn write_edge_to_disk(&self, edge: &Edge) -> Result<()> {
+        self.append_entry(PageType::Edge, &edge.to_bytes(), 0)
+    }
+
+    /// 追加删除标记，重新打开时丢弃该 ID 之前的记录
+    fn write_tombstone(&self, page_type: PageType, id: u64) -> Result<()> {
+        self.append_entry(page_type, &id.to_le_bytes(), TOMBSTONE_FLAG)
+    }
+
+    /// 在顶点或边页面末尾追加一条带长度前缀的记录，当前页面放不下时分配新页面；
+    /// `flags` 与长度一起写入前缀
+    fn append_entry(&self, page_type: PageType, data: &[u8], flags: u32) -> Result<()> {
         let entry_size = 4 + data.len(); // 4 字节长度 + 数据
+        if entry_size > PAGE_DATA_SIZE {
+            return Err(Error::StorageError(format!(
+                "记录大小 {} 字节超过页面容量 {} 字节",
+                entry_size, PAGE_DATA_SIZE
+            )));
+        }
 
-        let mut current_space = self.current_vertex_page_space.write();
-        let mut vertex_pages = self.vertex_pages.write();
+        let (space, pages) = match page_type {
+            PageType::Vertex => (&self.current_vertex_page_space, &self.vertex_pages),
+            _ => (&self.current_edge_page_space, &self.edge_pages),
+        };
+        let mut current_space = space.write();
+        let mut pages = pages.write();
 
         // 检查当前页面是否有足够空间
-        if *current_space < entry_size || vertex_pages.is_empty() {
+        if *current_space < entry_size || pages.is_empty() {
             // 需要新页面
-            let handle = self.buffer_pool.new_page(PageType::Vertex)?;
+            let handle = self.buffer_pool.new_page(page_type)?;
             let page_id = handle.page_id();
-            vertex_pages.push(page_id);
+            pages.push(page_id);
             *current_space = PAGE_DATA_SIZE;
             handle.mark_dirty();
         }
 
         // 写入到当前页面
-        let page_id = *vertex_pages.last().unwrap();
+        let page_id = *pages.last().unwrap();
         let handle = self.buffer_pool.fetch_page(page_id)?;
 
         {
@@ -347,11 +618,11 @@ impl Graph {
                 let offset = page.free_offset as usize;
 
                 // 写入长度前缀
-                let len = data.len() as u32;
+                let len = data.len() as u32 | flags;
                 page.data[offset..offset + 4].copy_from_slice(&len.to_le_bytes());
 
                 // 写入数据
-                page.data[offset + 4..offset + 4 + data.len()].copy_from_slice(&data);
+                page.data[offset + 4..offset + 4 + data.len()].copy_from_slice(data);
 
                 page.free_offset += entry_size as u16;
                 page.item_count += 1;
@@ -366,62 +637,94 @@ impl Graph {
         Ok(())
     }
 
-    /// 将边写入磁盘页面
-    fn write_edge_to_disk(&self, edge: &Edge) -> Result<()> {
-        let data = edge.to_bytes();
-        let entry_size = 4 + data.len();
+    /// 设置图 schema（来自 CREATE GRAPH 的内联 schema）
+    pub fn set_schema(&self, s: StoredGraphSchema) {
+        *self.schema.write() = Some(s);
+        *self.dirty.write() = true;
+    }
 
-        let mut current_space = self.current_edge_page_space.write();
-        let mut edge_pages = self.edge_pages.write();
+    /// 获取当前图的 schema（如果有）
+    pub fn get_schema(&self) -> Option<StoredGraphSchema> {
+        self.schema.read().clone()
+    }
 
-        // 检查当前页面是否有足够空间
-        if *current_space < entry_size || edge_pages.is_empty() {
-            // 需要新页面
-            let handle = self.buffer_pool.new_page(PageType::Edge)?;
-            let page_id = handle.page_id();
-            edge_pages.push(page_id);
-            *current_space = PAGE_DATA_SIZE;
-            handle.mark_dirty();
-        }
+    // ==================== 属性索引 ====================
 
-        // 写入到当前页面
-        let page_id = *edge_pages.last().unwrap();
-        let handle = self.buffer_pool.fetch_page(page_id)?;
+    /// 为 `label` 顶点的 `property` 属性创建等值索引，定义立即持久化
+    pub fn create_index(&self, label: &str, property: &str) -> Result<()> {
+        let definition = IndexDefinition {
+            label: label.to_string(),
+            property: property.to_string(),
+        };
+        let created = self
+            .property_index
+            .create(definition, self.vertex_cache.read().values());
+        if !created {
+            return Err(Error::QueryError(format!(
+                "索引已存在: {}({})",
+                label, property
+            )));
+        }
+        self.save_property_indexes()
+    }
 
-        {
-            let mut guard = handle.write();
-            if let Some(page) = guard.page_mut() {
-                let offset = page.free_offset as usize;
+    /// 删除属性索引，定义立即持久化
+    pub fn drop_index(&self, label: &str, property: &str) -> Result<()> {
+        let definition = IndexDefinition {
+            label: label.to_string(),
+            property: property.to_string(),
+        };
+        if !self.property_index.drop(&definition) {
+            return Err(Error::NotFound(format!("索引 {}({})", label, property)));
+        }
+        self.save_property_indexes()
+    }
 
-                // 写入长度前缀
-                let len = data.len() as u32;
-                page.data[offset..offset + 4].copy_from_slice(&len.to_le_bytes());
+    /// 列出属性索引（按标签、属性排序）
+    pub fn list_indexes(&self) -> Vec<IndexInfo> {
+        self.property_index.list()
+    }
 
-                // 写入数据
-                page.data[offset + 4..offset + 4 + data.len()].copy_from_slice(&data);
+    /// 通过属性索引等值查找顶点；没有对应索引时返回 None
+    pub fn lookup_index(
+        &self,
+        label: &str,
+        property: &str,
+        value: &PropertyValue,
+    ) -> Option<Vec<VertexId>> {
+        self.property_index.lookup(label, property, value)
+    }
 
-                page.free_offset += entry_size as u16;
-                page.item_count += 1;
-                page.is_dirty = true;
-            }
-        }
+    // ==================== 地址标记 ====================
 
-        *current_space -= entry_size;
-        handle.mark_dirty();
-        *self.dirty.write() = true;
+    /// 为地址设置标记（覆盖已有标记），立即持久化；地址不必已在图中
+    pub fn set_address_tag(&self, address: &str, tag: &str) -> Result<()> {
+        let mut tags = self.address_tags.write();
+        tags.insert(address.to_string(), tag.to_string());
+        self.bump_version();
+        self.save_address_tags(&tags)
+    }
 
-        Ok(())
+    /// 移除地址标记，返回是否存在过标记
+    pub fn remove_address_tag(&self, address: &str) -> Result<bool> {
+        let mut tags = self.address_tags.write();
+        if tags.remove(address).is_none() {
+            return Ok(false);
+        }
+        self.bump_version();
+        self.save_address_tags(&tags)?;
+        Ok(true)
     }
 
-    /// 设置图 schema（来自 CREATE GRAPH 的内联 schema）
-    pub fn set_schema(&self, s: StoredGraphSchema) {
-        *self.schema.write() = Some(s);
-        *self.dirty.write() = true;
+    /// 获取地址标记
+    pub fn get_address_tag(&self, address: &str) -> Option<String> {
+        self.address_tags.read().get(address).cloned()
     }
 
-    /// 获取当前图的 schema（如果有）
-    pub fn get_schema(&self) -> Option<StoredGraphSchema> {
-        self.schema.read().clone()
+    /// 获取顶点地址的标记；没有地址的顶点返回 None
+    pub fn vertex_tag(&self, id: VertexId) -> Option<String> {
+        let vertex = self.get_vertex(id)?;
+        self.get_address_tag(vertex.address()?)
     }
 
     /// 创建内存图（用于测试）
@@ -438,11 +741,20 @@ impl Graph {
         Self::open(temp_dir, Some(1024))
     }
 
+    /// 取当前时刻的读快照：只包含已完整写入（数据、索引、缓存）的顶点和边
+    pub fn snapshot(&self) -> ReadSnapshot {
+        ReadSnapshot::new(
+            self.pending_vertices.watermark(&self.next_vertex_id),
+            self.pending_edges.watermark(&self.next_edge_id),
+        )
+    }
+
     // ==================== 顶点操作 ====================
 
     /// 添加顶点
     pub fn add_vertex(&self, label: VertexLabel) -> Result<VertexId> {
-        let id = VertexId::new(self.next_vertex_id.fetch_add(1, Ordering::SeqCst));
+        let pending = self.pending_vertices.allocate(&self.next_vertex_id);
+        let id = VertexId::new(pending.id());
         let vertex = Vertex::new(id, label.clone());
 
         // 写入磁盘
@@ -450,10 +762,12 @@ impl Graph {
 
         // 添加到索引
         self.vertex_index.add_label(label, id);
+        self.property_index.insert_vertex(&vertex);
 
         // 添加到缓存
         self.vertex_cache.write().insert(id, vertex);
 
+        self.bump_version();
         Ok(id)
     }
 
@@ -464,7 +778,8 @@ impl Graph {
             return Ok(existing_id);
         }
 
-        let id = VertexId::new(self.next_vertex_id.fetch_add(1, Ordering::SeqCst));
+        let pending = self.pending_vertices.allocate(&self.next_vertex_id);
+        let id = VertexId::new(pending.id());
         let vertex = Vertex::new_account(id, address.clone());
 
         // 写入磁盘
@@ -473,10 +788,12 @@ impl Graph {
         // 添加到索引
         self.vertex_index.add_address(address, id);
         self.vertex_index.add_label(VertexLabel::Account, id);
+        self.property_index.insert_vertex(&vertex);
 
         // 添加到缓存
         self.vertex_cache.write().insert(id, vertex);
 
+        self.bump_version();
         Ok(id)
     }
 
@@ -486,7 +803,8 @@ impl Graph {
             return Ok(existing_id);
         }
 
-        let id = VertexId::new(self.next_vertex_id.fetch_add(1, Ordering::SeqCst));
+        let pending = self.pending_vertices.allocate(&self.next_vertex_id);
+        let id = VertexId::new(pending.id());
         let vertex = Vertex::new_contract(id, address.clone());
 
         // 写入磁盘
@@ -494,8 +812,32 @@ impl Graph {
 
         self.vertex_index.add_address(address, id);
         self.vertex_index.add_label(VertexLabel::Contract, id);
+        self.property_index.insert_vertex(&vertex);
         self.vertex_cache.write().insert(id, vertex);
 
+        self.bump_version();
+        Ok(id)
+    }
+
+    /// 添加代币顶点（已存在相同地址的顶点时直接复用）
+    pub fn add_token(&self, address: String, symbol: String) -> Result<VertexId> {
+        if let Some(existing_id) = self.vertex_index.get_by_address(&address) {
+            return Ok(existing_id);
+        }
+
+        let pending = self.pending_vertices.allocate(&self.next_vertex_id);
+        let id = VertexId::new(pending.id());
+        let vertex = Vertex::new_token(id, address.clone(), symbol);
+
+        // 写入磁盘
+        self.write_vertex_to_disk(&vertex)?;
+
+        self.vertex_index.add_address(address, id);
+        self.vertex_index.add_label(VertexLabel::Token, id);
+        self.property_index.insert_vertex(&vertex);
+        self.vertex_cache.write().insert(id, vertex);
+
+        self.bump_version();
         Ok(id)
     }
 
@@ -504,6 +846,11 @@ impl Graph {
         self.vertex_cache.read().get(&id).cloned()
     }
 
+    /// 顶点是否存在：只查内存索引，不复制顶点数据
+    pub fn vertex_exists(&self, id: VertexId) -> bool {
+        self.vertex_cache.read().contains_key(&id)
+    }
+
     /// 通过地址获取顶点
     pub fn get_vertex_by_address(&self, address: &str) -> Option<Vertex> {
         let id = self.vertex_index.get_by_address(address)?;
@@ -521,34 +868,79 @@ impl Graph {
 
     /// 更新顶点
     pub fn update_vertex(&self, vertex: Vertex) -> Result<()> {
+        if let Some(schema) = self.schema.read().as_ref() {
+            schema.check_vertex_properties(vertex.label().as_str(), vertex.properties())?;
+        }
         let id = vertex.id();
-        if !self.vertex_cache.read().contains_key(&id) {
-            return Err(Error::NotFound(format!("顶点 {:?} 不存在", id)));
+        let old = match self.vertex_cache.read().get(&id) {
+            Some(old) => old.clone(),
+            None => return Err(Error::NotFound(format!("顶点 {:?} 不存在", id))),
+        };
+        // 新地址不能属于其他顶点
+        let address_changed = old.address() != vertex.address();
+        if let (true, Some(address)) = (address_changed, vertex.address()) {
+            if let Some(other) = self.vertex_index.get_by_address(address) {
+                if other != id {
+                    return Err(Error::VertexAlreadyExists(format!(
+                        "地址 {} 已属于顶点 {}",
+                        address,
+                        other.as_u64()
+                    )));
+                }
+            }
         }
-        // 注意：当前实现不支持原地更新磁盘上的顶点
-        // 更新只会影响内存缓存，需要重建持久化数据才能生效
+
+        // 追加新版本记录，重新打开时覆盖旧记录
+        self.write_vertex_to_disk(&vertex)?;
+
+        // 地址或标签变化时同步索引
+        if address_changed {
+            if let Some(address) = old.address() {
+                self.vertex_index.remove_address(address, id);
+            }
+            if let Some(address) = vertex.address() {
+                self.vertex_index.add_address(address.to_string(), id);
+            }
+        }
+        if old.label() != vertex.label() {
+            self.vertex_index.remove_label(old.label(), id);
+            self.vertex_index.add_label(vertex.label().clone(), id);
+        }
+        self.property_index.remove_vertex(&old);
+        self.property_index.insert_vertex(&vertex);
         self.vertex_cache.write().insert(id, vertex);
         *self.dirty.write() = true;
+        self.bump_version();
         Ok(())
     }
 
     /// 删除顶点
     pub fn remove_vertex(&self, id: VertexId) -> Result<()> {
+        let outgoing = self.edge_index.get_outgoing(id);
+        let incoming = self.edge_index.get_incoming(id);
+
+        // 先落删除标记（关联边在前），写入失败时内存状态不变
+        for edge_id in outgoing.iter().chain(&incoming) {
+            self.write_tombstone(PageType::Edge, edge_id.as_u64())?;
+        }
+        if self.vertex_exists(id) {
+            self.write_tombstone(PageType::Vertex, id.as_u64())?;
+        }
+
         // 获取顶点信息
         let vertex = self.vertex_cache.write().remove(&id);
         if let Some(v) = vertex {
             self.vertex_index.remove(id, v.address(), Some(v.label()));
+            self.property_index.remove_vertex(&v);
         }
 
         // 删除相关的边
-        let outgoing = self.edge_index.get_outgoing(id);
-        let incoming = self.edge_index.get_incoming(id);
-
-        for edge_id in outgoing.into_iter().chain(incoming.into_iter()) {
-            self.remove_edge(edge_id)?;
+        for edge_id in outgoing.into_iter().chain(incoming) {
+            self.unlink_edge(edge_id);
         }
 
         *self.dirty.write() = true;
+        self.bump_version();
         Ok(())
     }
 
@@ -569,7 +961,8 @@ impl Graph {
             return Err(Error::NotFound(format!("目标顶点 {:?} 不存在", dst)));
         }
 
-        let id = EdgeId::new(self.next_edge_id.fetch_add(1, Ordering::SeqCst));
+        let pending = self.pending_edges.allocate(&self.next_edge_id);
+        let id = EdgeId::new(pending.id());
         let edge = Edge::new(id, label.clone(), src, dst);
 
         // 写入磁盘
@@ -581,6 +974,7 @@ impl Graph {
         // 添加到缓存
         self.edge_cache.write().insert(id, edge);
 
+        self.bump_version();
         Ok(id)
     }
 
@@ -591,6 +985,80 @@ impl Graph {
         dst: VertexId,
         amount: crate::types::TokenAmount,
         block_number: u64,
+    ) -> Result<EdgeId> {
+        self.add_transfer_with_properties(src, dst, amount, block_number, Vec::new())
+    }
+
+    /// 添加带附加属性的转账边（如代币地址、交易哈希）
+    pub fn add_transfer_with_properties(
+        &self,
+        src: VertexId,
+        dst: VertexId,
+        amount: crate::types::TokenAmount,
+        block_number: u64,
+        properties: Vec<(String, PropertyValue)>,
+    ) -> Result<EdgeId> {
+        self.insert_transfer(src, dst, Some(amount), block_number, properties)
+    }
+
+    /// 添加未记录金额的转账边：`amount()` 为 None，
+    /// 统计时可与显式的零金额转账区分
+    pub fn add_transfer_without_amount(
+        &self,
+        src: VertexId,
+        dst: VertexId,
+        block_number: u64,
+        properties: Vec<(String, PropertyValue)>,
+    ) -> Result<EdgeId> {
+        self.insert_transfer(src, dst, None, block_number, properties)
+    }
+
+    /// 幂等地添加转账边：已有相同 (tx_hash, log_index) 的边时不再写入，
+    /// 返回 None。重复导入重叠的区块范围时不会重复计数
+    pub fn add_transfer_unique(
+        &self,
+        src: VertexId,
+        dst: VertexId,
+        amount: crate::types::TokenAmount,
+        block_number: u64,
+        tx_hash: TxHash,
+        log_index: u64,
+    ) -> Result<Option<EdgeId>> {
+        let key = (tx_hash, log_index);
+        self.add_transfer_unique_with_properties(src, dst, amount, block_number, key, Vec::new())
+    }
+
+    /// 带附加属性的 `add_transfer_unique`，`key` 为 (tx_hash, log_index)
+    pub fn add_transfer_unique_with_properties(
+        &self,
+        src: VertexId,
+        dst: VertexId,
+        amount: crate::types::TokenAmount,
+        block_number: u64,
+        key: (TxHash, u64),
+        mut properties: Vec<(String, PropertyValue)>,
+    ) -> Result<Option<EdgeId>> {
+        let _guard = self.unique_transfer_lock.lock();
+        if self.edge_index.get_by_transfer_key(&key).is_some() {
+            return Ok(None);
+        }
+        let (tx_hash, log_index) = key;
+        properties.push(("tx_hash".to_string(), PropertyValue::TxHash(tx_hash)));
+        properties.push((
+            "log_index".to_string(),
+            PropertyValue::Integer(log_index as i64),
+        ));
+        self.insert_transfer(src, dst, Some(amount), block_number, properties)
+            .map(Some)
+    }
+
+    fn insert_transfer(
+        &self,
+        src: VertexId,
+        dst: VertexId,
+        amount: Option<crate::types::TokenAmount>,
+        block_number: u64,
+        properties: Vec<(String, PropertyValue)>,
     ) -> Result<EdgeId> {
         if !self.vertex_cache.read().contains_key(&src) {
             return Err(Error::NotFound(format!("源顶点 {:?} 不存在", src)));
@@ -599,15 +1067,30 @@ impl Graph {
             return Err(Error::NotFound(format!("目标顶点 {:?} 不存在", dst)));
         }
 
-        let id = EdgeId::new(self.next_edge_id.fetch_add(1, Ordering::SeqCst));
-        let edge = Edge::new_transfer(id, src, dst, amount, block_number);
+        let pending = self.pending_edges.allocate(&self.next_edge_id);
+        let id = EdgeId::new(pending.id());
+        let mut edge = match amount {
+            Some(amount) => Edge::new_transfer(id, src, dst, amount, block_number),
+            None => {
+                let mut edge = Edge::new(id, EdgeLabel::Transfer, src, dst);
+                edge.set_property(
+                    "block_number".to_string(),
+                    PropertyValue::Integer(block_number as i64),
+                );
+                edge
+            }
+        };
+        for (key, value) in properties {
+            edge.set_property(key, value);
+        }
 
         // 写入磁盘
         self.write_edge_to_disk(&edge)?;
 
-        self.edge_index.add_edge(id, src, dst, EdgeLabel::Transfer);
+        self.index_edge(&edge);
         self.edge_cache.write().insert(id, edge);
 
+        self.bump_version();
         Ok(id)
     }
 
@@ -616,31 +1099,48 @@ impl Graph {
         self.edge_cache.read().get(&id).cloned()
     }
 
+    /// 边是否存在：只查内存索引，不复制边数据
+    pub fn edge_exists(&self, id: EdgeId) -> bool {
+        self.edge_cache.read().contains_key(&id)
+    }
+
+    /// 当前的邻接边返回顺序
+    pub fn adjacency_order(&self) -> AdjacencyOrder {
+        *self.adjacency_order.read()
+    }
+
+    /// 设置邻接边返回顺序（影响 `get_outgoing_edges` / `get_incoming_edges` /
+    /// `get_edges_between`）
+    pub fn set_adjacency_order(&self, order: AdjacencyOrder) {
+        *self.adjacency_order.write() = order;
+    }
+
+    /// 按当前邻接顺序取出边
+    fn edges_in_order(&self, ids: &[EdgeId]) -> Vec<Edge> {
+        let mut edges: Vec<Edge> = ids.iter().filter_map(|&id| self.get_edge(id)).collect();
+        match self.adjacency_order() {
+            AdjacencyOrder::Insertion => {}
+            AdjacencyOrder::EdgeId => edges.sort_by_key(|e| e.id().as_u64()),
+            AdjacencyOrder::BlockNumber => {
+                edges.sort_by_key(|e| (e.block_number().unwrap_or(u64::MAX), e.id().as_u64()))
+            }
+        }
+        edges
+    }
+
     /// 获取两点之间的所有边
     pub fn get_edges_between(&self, src: VertexId, dst: VertexId) -> Vec<Edge> {
-        self.edge_index
-            .get_edges_between(src, dst)
-            .iter()
-            .filter_map(|&id| self.get_edge(id))
-            .collect()
+        self.edges_in_order(&self.edge_index.get_edges_between(src, dst))
     }
 
     /// 获取顶点的所有出边
     pub fn get_outgoing_edges(&self, vertex_id: VertexId) -> Vec<Edge> {
-        self.edge_index
-            .get_outgoing(vertex_id)
-            .iter()
-            .filter_map(|&id| self.get_edge(id))
-            .collect()
+        self.edges_in_order(&self.edge_index.get_outgoing(vertex_id))
     }
 
     /// 获取顶点的所有入边
     pub fn get_incoming_edges(&self, vertex_id: VertexId) -> Vec<Edge> {
-        self.edge_index
-            .get_incoming(vertex_id)
-            .iter()
-            .filter_map(|&id| self.get_edge(id))
-            .collect()
+        self.edges_in_order(&self.edge_index.get_incoming(vertex_id))
     }
 
     /// 获取标签下的所有边
@@ -654,23 +1154,58 @@ impl Graph {
 
     /// 更新边
     pub fn update_edge(&self, edge: Edge) -> Result<()> {
+        if let Some(schema) = self.schema.read().as_ref() {
+            schema.check_edge_properties(edge.label().as_str(), edge.properties())?;
+        }
         let id = edge.id();
-        if !self.edge_cache.read().contains_key(&id) {
-            return Err(Error::NotFound(format!("边 {:?} 不存在", id)));
+        let old = match self.edge_cache.read().get(&id) {
+            Some(old) => old.clone(),
+            None => return Err(Error::NotFound(format!("边 {:?} 不存在", id))),
+        };
+        if (old.src(), old.dst()) != (edge.src(), edge.dst()) {
+            return Err(Error::QueryError(format!("边 {:?} 的端点不能修改", id)));
+        }
+        // 追加新版本记录，重新打开时覆盖旧记录
+        self.write_edge_to_disk(&edge)?;
+        // 标签或 (tx_hash, log_index) 变化时同步边索引
+        if old.label() != edge.label() {
+            self.edge_index
+                .relabel(id, old.label(), edge.label().clone());
+        }
+        if old.transfer_key() != edge.transfer_key() {
+            if let Some(key) = old.transfer_key() {
+                self.edge_index.remove_transfer_key(&key, id);
+            }
+            if let Some(key) = edge.transfer_key() {
+                self.edge_index.add_transfer_key(key, id);
+            }
         }
         self.edge_cache.write().insert(id, edge);
         *self.dirty.write() = true;
+        self.bump_version();
         Ok(())
     }
 
     /// 删除边
     pub fn remove_edge(&self, id: EdgeId) -> Result<()> {
+        if self.edge_exists(id) {
+            self.write_tombstone(PageType::Edge, id.as_u64())?;
+        }
+        self.unlink_edge(id);
+        *self.dirty.write() = true;
+        self.bump_version();
+        Ok(())
+    }
+
+    /// 从缓存和索引中移除边
+    fn unlink_edge(&self, id: EdgeId) {
         let edge = self.edge_cache.write().remove(&id);
         if let Some(e) = edge {
             self.edge_index.remove(id, Some(e.label()));
+            if let Some(key) = e.transfer_key() {
+                self.edge_index.remove_transfer_key(&key, id);
+            }
         }
-        *self.dirty.write() = true;
-        Ok(())
     }
 
     /// 获取边数量
@@ -704,10 +1239,155 @@ impl Graph {
 
     /// 刷新到磁盘
     pub fn flush(&self) -> Result<()> {
+        self.checkpoint().map(|_| ())
+    }
+
+    /// 检查点：保存元数据并把所有脏页写回磁盘，返回写回的页面数与耗时
+    pub fn checkpoint(&self) -> Result<CheckpointStats> {
+        let start = std::time::Instant::now();
         // 保存元数据
         self.save_meta()?;
         // 刷新所有脏页到磁盘
-        self.buffer_pool.flush_all()
+        let pages_flushed = self.buffer_pool.flush_all()?;
+        *self.dirty.write() = false;
+        Ok(CheckpointStats {
+            pages_flushed,
+            duration_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// 将当前图（含属性索引定义与地址标记）写入另一个数据目录，
+    /// 之后可用 `Graph::open` 重新加载；常用于保存 `in_memory` 图的快照
+    ///
+    /// 目标目录必须不存在或为空。
+    pub fn persist_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let target = path.as_ref();
+        if target.exists() && std::fs::read_dir(target)?.next().is_some() {
+            return Err(Error::StorageError(format!(
+                "目标目录非空: {}",
+                target.display()
+            )));
+        }
+
+        self.flush()?;
+        std::fs::create_dir_all(target)?;
+        // 单文件布局的图同样写成目录布局，附属文件去掉前缀
+        std::fs::copy(
+            self.buffer_pool.data_path(),
+            DiskStorage::data_file_in(target),
+        )?;
+        for name in [INDEX_FILE, ADDRESS_TAG_FILE] {
+            let source = self.buffer_pool.side_file(name);
+            if source.exists() {
+                std::fs::copy(source, target.join(name))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 把另一个图的全部顶点和边导入当前图，用于汇总多个数据源的数据
+    ///
+    /// 顶点按地址（账户/合约/代币）或交易哈希（交易顶点）去重：当前图已有
+    /// 相同键的顶点时直接复用并保留其属性，其余顶点连同属性复制为新顶点。
+    /// 边全部追加，端点映射到当前图中对应的顶点。
+    pub fn merge_from(&self, other: &Graph) -> Result<MergeStats> {
+        let tx_hash = |vertex: &Vertex| match vertex.property("tx_hash") {
+            Some(PropertyValue::TxHash(hash)) => Some(*hash),
+            _ => None,
+        };
+        let mut transactions: HashMap<TxHash, VertexId> = self
+            .get_vertices_by_label(&VertexLabel::Transaction)
+            .iter()
+            .filter_map(|v| Some((tx_hash(v)?, v.id())))
+            .collect();
+
+        let mut vertices: Vec<Vertex> = other.vertex_cache.read().values().cloned().collect();
+        vertices.sort_by_key(|v| v.id().as_u64());
+        let mut stats = MergeStats::default();
+        let mut mapping: HashMap<VertexId, VertexId> = HashMap::new();
+        for vertex in vertices {
+            let existing = match (vertex.address(), tx_hash(&vertex)) {
+                (Some(address), _) => self.vertex_index.get_by_address(address),
+                (None, Some(hash)) => transactions.get(&hash).copied(),
+                (None, None) => None,
+            };
+            let id = match existing {
+                Some(id) => {
+                    stats.vertices_deduplicated += 1;
+                    id
+                }
+                None => {
+                    let id = self.insert_vertex_copy(&vertex)?;
+                    if let Some(hash) = tx_hash(&vertex) {
+                        transactions.insert(hash, id);
+                    }
+                    stats.vertices_added += 1;
+                    id
+                }
+            };
+            mapping.insert(vertex.id(), id);
+        }
+
+        let mut edges: Vec<Edge> = other.edge_cache.read().values().cloned().collect();
+        edges.sort_by_key(|e| e.id().as_u64());
+        let _guard = self.unique_transfer_lock.lock();
+        for edge in edges {
+            let (Some(&src), Some(&dst)) = (mapping.get(&edge.src()), mapping.get(&edge.dst()))
+            else {
+                continue;
+            };
+            // 同一笔转账只保留一条，重复合并同一数据源不会重复计数
+            if let Some(key) = edge.transfer_key() {
+                if self.edge_index.get_by_transfer_key(&key).is_some() {
+                    stats.edges_deduplicated += 1;
+                    continue;
+                }
+            }
+            let pending = self.pending_edges.allocate(&self.next_edge_id);
+            let id = EdgeId::new(pending.id());
+            let mut copy = Edge::new(id, edge.label().clone(), src, dst);
+            for (key, value) in edge.properties() {
+                copy.set_property(key.clone(), value.clone());
+            }
+            self.write_edge_to_disk(&copy)?;
+            self.index_edge(&copy);
+            self.edge_cache.write().insert(id, copy);
+            stats.edges_added += 1;
+        }
+
+        *self.dirty.write() = true;
+        self.bump_version();
+        Ok(stats)
+    }
+
+    /// 把边加入邻接与标签索引，带 (tx_hash, log_index) 的转账同时登记唯一键
+    fn index_edge(&self, edge: &Edge) {
+        self.edge_index
+            .add_edge(edge.id(), edge.src(), edge.dst(), edge.label().clone());
+        if let Some(key) = edge.transfer_key() {
+            self.edge_index.add_transfer_key(key, edge.id());
+        }
+    }
+
+    /// 以新 ID 写入顶点的副本（标签与属性不变）
+    fn insert_vertex_copy(&self, vertex: &Vertex) -> Result<VertexId> {
+        let pending = self.pending_vertices.allocate(&self.next_vertex_id);
+        let id = VertexId::new(pending.id());
+        let mut copy = Vertex::new(id, vertex.label().clone());
+        for (key, value) in vertex.properties() {
+            copy.set_property(key.clone(), value.clone());
+        }
+
+        self.write_vertex_to_disk(&copy)?;
+
+        if let Some(address) = copy.address() {
+            self.vertex_index.add_address(address.to_string(), id);
+        }
+        self.vertex_index.add_label(copy.label().clone(), id);
+        self.property_index.insert_vertex(&copy);
+        self.vertex_cache.write().insert(id, copy);
+
+        Ok(id)
     }
 
     /// 获取缓冲池引用
@@ -734,6 +1414,149 @@ impl Graph {
     pub fn is_dirty(&self) -> bool {
         *self.dirty.read()
     }
+
+    /// 数据版本：每次增删改顶点、边或地址标记后加一，只读操作不改变版本。
+    /// 版本不变说明数据未变，可据此判断缓存的结果是否过期；打开图时从 0 开始
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+
+    /// 本次打开的实例编号：同名图删除后重建、或重新打开时编号不同，
+    /// 与 `version` 一起唯一标识一份数据
+    pub fn instance_id(&self) -> u64 {
+        self.instance
+    }
+
+    /// 写入完成（数据、索引、缓存均已可见）后更新版本
+    fn bump_version(&self) {
+        self.version.fetch_add(1, Ordering::Release);
+    }
+
+    // ==================== 索引维护 ====================
+
+    /// 从全部顶点、边记录重建标签、地址和邻接索引
+    ///
+    /// 返回重建后索引的 (顶点数, 边数)
+    pub fn rebuild_indexes(&self) -> (usize, usize) {
+        let vertices = self.vertex_cache.read();
+        let edges = self.edge_cache.read();
+
+        self.vertex_index.clear();
+        self.property_index.clear_entries();
+        let mut vertex_ids: Vec<VertexId> = vertices.keys().copied().collect();
+        vertex_ids.sort_by_key(|id| id.as_u64());
+        for id in vertex_ids {
+            let vertex = &vertices[&id];
+            self.vertex_index.add_label(vertex.label().clone(), id);
+            if let Some(addr) = vertex.address() {
+                self.vertex_index.add_address(addr.to_string(), id);
+            }
+            self.property_index.insert_vertex(vertex);
+        }
+
+        // 按边 ID 登记，邻接表保持写入顺序
+        self.edge_index.clear();
+        let mut edge_ids: Vec<EdgeId> = edges.keys().copied().collect();
+        edge_ids.sort_by_key(|id| id.as_u64());
+        for id in edge_ids {
+            self.index_edge(&edges[&id]);
+        }
+
+        (vertices.len(), edges.len())
+    }
+
+    /// 对比 ID 计数器、索引与实际记录，只报告不一致项，不做修复
+    pub fn check_consistency(&self) -> ConsistencyReport {
+        let vertices = self.vertex_cache.read();
+        let edges = self.edge_cache.read();
+        let mut issues = Vec::new();
+
+        // 计数器必须大于所有已分配的 ID
+        let next_vertex_id = self.next_vertex_id.load(Ordering::SeqCst);
+        if let Some(max) = vertices.keys().map(|id| id.as_u64()).max() {
+            if max >= next_vertex_id {
+                issues.push(format!(
+                    "顶点 ID 计数器 {} 不大于已有最大顶点 ID {}",
+                    next_vertex_id, max
+                ));
+            }
+        }
+        let next_edge_id = self.next_edge_id.load(Ordering::SeqCst);
+        if let Some(max) = edges.keys().map(|id| id.as_u64()).max() {
+            if max >= next_edge_id {
+                issues.push(format!(
+                    "边 ID 计数器 {} 不大于已有最大边 ID {}",
+                    next_edge_id, max
+                ));
+            }
+        }
+
+        // 顶点标签索引
+        let vertex_labels = self.vertex_index.label_entries();
+        for (&id, vertex) in vertices.iter() {
+            if !self
+                .vertex_index
+                .get_by_label(vertex.label())
+                .contains(&id)
+            {
+                issues.push(format!("顶点 {} 缺少标签索引 {}", id.as_u64(), vertex.label()));
+            }
+            if let Some(addr) = vertex.address() {
+                if self.vertex_index.get_by_address(addr) != Some(id) {
+                    issues.push(format!("顶点 {} 的地址 {} 未正确索引", id.as_u64(), addr));
+                }
+            }
+        }
+        for (label, id) in vertex_labels {
+            match vertices.get(&id) {
+                None => issues.push(format!("标签索引 {} 指向不存在的顶点 {}", label, id.as_u64())),
+                Some(v) if v.label() != &label => {
+                    issues.push(format!("顶点 {} 被错误地索引在标签 {} 下", id.as_u64(), label))
+                }
+                _ => {}
+            }
+        }
+        for (addr, id) in self.vertex_index.address_entries() {
+            if vertices.get(&id).and_then(|v| v.address()) != Some(addr.as_str()) {
+                issues.push(format!("地址索引 {} 指向不匹配的顶点 {}", addr, id.as_u64()));
+            }
+        }
+
+        // 边索引
+        for (&id, edge) in edges.iter() {
+            if !vertices.contains_key(&edge.src()) || !vertices.contains_key(&edge.dst()) {
+                issues.push(format!("边 {} 的端点顶点不存在", id.as_u64()));
+            }
+            if self.edge_index.get_endpoints(id) != Some((edge.src(), edge.dst())) {
+                issues.push(format!("边 {} 缺少端点索引", id.as_u64()));
+            }
+            if !self.edge_index.get_outgoing(edge.src()).contains(&id) {
+                issues.push(format!("边 {} 缺少出边索引", id.as_u64()));
+            }
+            if !self.edge_index.get_incoming(edge.dst()).contains(&id) {
+                issues.push(format!("边 {} 缺少入边索引", id.as_u64()));
+            }
+            if !self.edge_index.get_by_label(edge.label()).contains(&id) {
+                issues.push(format!("边 {} 缺少标签索引 {}", id.as_u64(), edge.label()));
+            }
+        }
+        for (id, _) in self.edge_index.endpoint_entries() {
+            if !edges.contains_key(&id) {
+                issues.push(format!("端点索引指向不存在的边 {}", id.as_u64()));
+            }
+        }
+        for (label, id) in self.edge_index.label_entries() {
+            if !edges.contains_key(&id) {
+                issues.push(format!("标签索引 {} 指向不存在的边 {}", label, id.as_u64()));
+            }
+        }
+
+        ConsistencyReport {
+            vertices_checked: vertices.len(),
+            edges_checked: edges.len(),
+            issues,
+        }
+    }
 }
 
 impl Drop for Graph {
@@ -845,6 +1668,90 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_updates_persist_across_restarts() {
+        let dir = tempdir().unwrap();
+        let (a, edge) = {
+            let graph = Graph::open(dir.path(), Some(512)).unwrap();
+            let a = graph.add_account("0xa".to_string()).unwrap();
+            let b = graph.add_account("0xb".to_string()).unwrap();
+            let edge = graph
+                .add_transfer(a, b, TokenAmount::from_u64(1), 1)
+                .unwrap();
+
+            let mut vertex = graph.get_vertex(a).unwrap();
+            vertex.set_property("balance".to_string(), PropertyValue::Integer(5000));
+            vertex.set_property(
+                "address".to_string(),
+                PropertyValue::String("0xz".to_string()),
+            );
+            graph.update_vertex(vertex).unwrap();
+            let mut transfer = graph.get_edge(edge).unwrap();
+            transfer.set_property("memo".to_string(), PropertyValue::String("x".to_string()));
+            graph.update_edge(transfer).unwrap();
+            graph.flush().unwrap();
+            (a, edge)
+        };
+
+        // 重新打开后读到最后一次写入的版本，索引按最终版本建立
+        let graph = Graph::open(dir.path(), Some(512)).unwrap();
+        assert_eq!(graph.vertex_count(), 2);
+        let vertex = graph.get_vertex(a).unwrap();
+        assert_eq!(
+            vertex.property("balance"),
+            Some(&PropertyValue::Integer(5000))
+        );
+        assert!(graph.get_vertex_by_address("0xa").is_none());
+        assert_eq!(graph.get_vertex_by_address("0xz").unwrap().id(), a);
+        let transfer = graph.get_edge(edge).unwrap();
+        assert_eq!(
+            transfer.property("memo"),
+            Some(&PropertyValue::String("x".to_string()))
+        );
+        assert_eq!(graph.get_outgoing_edges(a).len(), 1);
+        assert!(graph.check_consistency().is_consistent());
+    }
+
+    #[test]
+    fn test_removals_persist_across_restarts() {
+        let dir = tempdir().unwrap();
+        let copy = tempdir().unwrap();
+        let (a, b, c, kept) = {
+            let graph = Graph::open(dir.path(), Some(512)).unwrap();
+            let a = graph.add_account("0xa".to_string()).unwrap();
+            let b = graph.add_account("0xb".to_string()).unwrap();
+            let c = graph.add_account("0xc".to_string()).unwrap();
+            graph
+                .add_transfer(a, b, TokenAmount::from_u64(1), 1)
+                .unwrap();
+            let dropped = graph
+                .add_transfer(b, c, TokenAmount::from_u64(2), 2)
+                .unwrap();
+            let kept = graph
+                .add_transfer(c, b, TokenAmount::from_u64(3), 3)
+                .unwrap();
+
+            graph.remove_vertex(a).unwrap();
+            graph.remove_edge(dropped).unwrap();
+            graph.flush().unwrap();
+            graph.persist_to(copy.path().join("copy")).unwrap();
+            (a, b, c, kept)
+        };
+
+        // 原目录与持久化副本重新打开后都不再包含已删除的顶点和边
+        for path in [dir.path().to_path_buf(), copy.path().join("copy")] {
+            let graph = Graph::open(&path, Some(512)).unwrap();
+            assert!(!graph.vertex_exists(a));
+            assert!(graph.get_vertex_by_address("0xa").is_none());
+            assert_eq!(graph.vertex_count(), 2);
+            assert_eq!(graph.edge_count(), 1);
+            assert!(graph.edge_exists(kept));
+            assert_eq!(graph.get_outgoing_edges(b).len(), 0);
+            assert_eq!(graph.get_outgoing_edges(c).len(), 1);
+            assert!(graph.check_consistency().is_consistent());
+        }
+    }
+
     #[test]
     fn test_schema_persistence() {
         let dir = tempdir().unwrap();
@@ -881,4 +1788,419 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_check_consistency_and_rebuild_indexes() {
+        let graph = Graph::in_memory().unwrap();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        let b = graph.add_account("0xbbb".to_string()).unwrap();
+        let e = graph.add_transfer(a, b, TokenAmount::from_u64(1), 1).unwrap();
+        assert!(graph.check_consistency().is_consistent());
+
+        // 人为破坏索引
+        graph.vertex_index().remove_label(&VertexLabel::Account, a);
+        graph.edge_index().remove(e, Some(&EdgeLabel::Transfer));
+        graph.vertex_index().add_label(VertexLabel::Token, VertexId::new(999));
+
+        let report = graph.check_consistency();
+        assert!(!report.is_consistent());
+        assert_eq!(report.vertices_checked, 2);
+        assert_eq!(report.edges_checked, 1);
+        assert!(report.issues.iter().any(|i| i.contains("缺少标签索引")));
+        assert!(report.issues.iter().any(|i| i.contains("出边索引")));
+        assert!(report.issues.iter().any(|i| i.contains("不存在的顶点")));
+
+        assert_eq!(graph.rebuild_indexes(), (2, 1));
+        assert!(graph.check_consistency().is_consistent());
+        assert_eq!(graph.get_outgoing_edges(a).len(), 1);
+        assert!(graph.get_vertices_by_label(&VertexLabel::Token).is_empty());
+    }
+
+    #[test]
+    fn test_adjacency_order_is_deterministic() {
+        // 两次运行以不同顺序写入同一组转账
+        let run = |blocks: &[u64]| -> Vec<Option<u64>> {
+            let graph = Graph::in_memory().unwrap();
+            graph.set_adjacency_order(AdjacencyOrder::BlockNumber);
+            let src = graph.add_account("0xsrc".to_string()).unwrap();
+            for &block in blocks {
+                let dst = graph.add_account(format!("0xdst{}", block)).unwrap();
+                graph
+                    .add_transfer(src, dst, TokenAmount::from_u64(1), block)
+                    .unwrap();
+            }
+            graph
+                .get_outgoing_edges(src)
+                .iter()
+                .map(|e| e.block_number())
+                .collect()
+        };
+        let first = run(&[30, 10, 20]);
+        let second = run(&[20, 30, 10]);
+        assert_eq!(first, second);
+        assert_eq!(first, vec![Some(10), Some(20), Some(30)]);
+
+        // EdgeId 顺序在重新加载后保持不变
+        let dir = tempdir().unwrap();
+        let graph = Graph::open(dir.path(), Some(512)).unwrap();
+        graph.set_adjacency_order(AdjacencyOrder::EdgeId);
+        let hub = graph.add_account("0xhub".to_string()).unwrap();
+        for i in 0..5 {
+            let v = graph.add_account(format!("0x{}", i)).unwrap();
+            graph.add_edge(EdgeLabel::Transfer, v, hub).unwrap();
+        }
+        let ids = |g: &Graph| -> Vec<EdgeId> {
+            g.get_incoming_edges(hub).iter().map(|e| e.id()).collect()
+        };
+        let before = ids(&graph);
+        assert!(before.windows(2).all(|w| w[0].as_u64() < w[1].as_u64()));
+        graph.flush().unwrap();
+        drop(graph);
+
+        let reopened = Graph::open(dir.path(), Some(512)).unwrap();
+        assert_eq!(reopened.adjacency_order(), AdjacencyOrder::Insertion);
+        reopened.set_adjacency_order(AdjacencyOrder::EdgeId);
+        assert_eq!(ids(&reopened), before);
+
+        assert_eq!(
+            "block_number".parse::<AdjacencyOrder>().unwrap(),
+            AdjacencyOrder::BlockNumber
+        );
+        assert!("random".parse::<AdjacencyOrder>().is_err());
+    }
+
+    #[test]
+    fn test_update_keeps_indexes_in_sync() {
+        let graph = Graph::in_memory().unwrap();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        let amount = TokenAmount::from_u64(1);
+        let edge = graph.add_transfer(a, b, amount, 1).unwrap();
+
+        // 修改地址后按新地址可以找到顶点，旧地址不再指向它
+        let mut vertex = graph.get_vertex(a).unwrap();
+        vertex.set_property(
+            "address".to_string(),
+            PropertyValue::String("0xz".to_string()),
+        );
+        graph.update_vertex(vertex).unwrap();
+        assert!(graph.get_vertex_by_address("0xa").is_none());
+        assert_eq!(graph.get_vertex_by_address("0xz").unwrap().id(), a);
+
+        // 不能改成其他顶点已有的地址
+        let mut vertex = graph.get_vertex(a).unwrap();
+        vertex.set_property(
+            "address".to_string(),
+            PropertyValue::String("0xb".to_string()),
+        );
+        assert!(matches!(
+            graph.update_vertex(vertex),
+            Err(Error::VertexAlreadyExists(_))
+        ));
+        assert_eq!(graph.get_vertex_by_address("0xb").unwrap().id(), b);
+
+        // 边换标签后标签索引随之变化，端点不能修改
+        let mut relabeled = Edge::new(edge, EdgeLabel::Call, a, b);
+        relabeled.set_property("note".to_string(), PropertyValue::Bool(true));
+        graph.update_edge(relabeled).unwrap();
+        assert!(graph.get_edges_by_label(&EdgeLabel::Transfer).is_empty());
+        assert_eq!(graph.get_edges_by_label(&EdgeLabel::Call)[0].id(), edge);
+        let moved = Edge::new(edge, EdgeLabel::Call, b, a);
+        assert!(graph.update_edge(moved).is_err());
+
+        assert!(graph.check_consistency().is_consistent());
+    }
+
+    #[test]
+    fn test_version_increments_once_per_mutation() {
+        let graph = Graph::in_memory().unwrap();
+        assert_eq!(graph.version(), 0);
+        let mut expected = 0;
+        let mut mutated = |graph: &Graph| {
+            expected += 1;
+            assert_eq!(graph.version(), expected);
+        };
+
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        mutated(&graph);
+        let b = graph.add_account("0xbbb".to_string()).unwrap();
+        mutated(&graph);
+        let c = graph.add_vertex(VertexLabel::Account).unwrap();
+        mutated(&graph);
+        let amount = crate::types::TokenAmount::from_u64(1);
+        let edge = graph.add_transfer(a, b, amount, 1).unwrap();
+        mutated(&graph);
+        graph.add_transfer(b, c, amount, 2).unwrap();
+        mutated(&graph);
+        let mut vertex = graph.get_vertex(a).unwrap();
+        vertex.set_property("tag".to_string(), PropertyValue::Bool(true));
+        graph.update_vertex(vertex).unwrap();
+        mutated(&graph);
+        graph.set_address_tag("0xaaa", "exchange").unwrap();
+        mutated(&graph);
+        graph.remove_edge(edge).unwrap();
+        mutated(&graph);
+        // 连带删除关联边也只算一次
+        graph.remove_vertex(b).unwrap();
+        mutated(&graph);
+
+        // 只读操作以及重复添加已有地址不改变版本
+        let version = graph.version();
+        graph.add_account("0xaaa".to_string()).unwrap();
+        graph.get_outgoing_edges(a);
+        graph.get_vertex(c);
+        graph.vertex_tag(a);
+        assert_eq!(graph.version(), version);
+
+        let other = Graph::in_memory().unwrap();
+        assert_ne!(graph.instance_id(), other.instance_id());
+    }
+
+    #[test]
+    fn test_address_tags() {
+        let dir = tempdir().unwrap();
+        {
+            let graph = Graph::open(dir.path(), Some(512)).unwrap();
+            let a = graph.add_account("0xaaa".to_string()).unwrap();
+            graph.set_address_tag("0xaaa", "exchange").unwrap();
+            // 尚未出现在图中的地址也可以预先标记
+            graph.set_address_tag("0xbbb", "mixer").unwrap();
+            graph.set_address_tag("0xaaa", "exchange:hot").unwrap();
+            assert_eq!(graph.vertex_tag(a).as_deref(), Some("exchange:hot"));
+            assert!(graph.remove_address_tag("0xbbb").unwrap());
+            assert!(!graph.remove_address_tag("0xbbb").unwrap());
+        }
+
+        // 标记跨重启保留
+        let graph = Graph::open(dir.path(), Some(512)).unwrap();
+        assert_eq!(graph.get_address_tag("0xaaa").as_deref(), Some("exchange:hot"));
+        assert_eq!(graph.get_address_tag("0xbbb"), None);
+    }
+
+    #[test]
+    fn test_persist_in_memory_graph() {
+        let graph = Graph::in_memory().unwrap();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        let b = graph.add_account("0xbbb".to_string()).unwrap();
+        let transfer = graph
+            .add_transfer(a, b, TokenAmount::from_u64(42), 7)
+            .unwrap();
+        graph.create_index("Account", "address").unwrap();
+        graph.set_address_tag("0xbbb", "exchange").unwrap();
+
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("snapshot");
+        graph.persist_to(&target).unwrap();
+        // 目标目录非空时拒绝覆盖
+        assert!(graph.persist_to(&target).is_err());
+
+        let reopened = Graph::open(&target, Some(512)).unwrap();
+        assert_eq!(reopened.vertex_count(), graph.vertex_count());
+        assert_eq!(reopened.edge_count(), graph.edge_count());
+        for id in [a, b] {
+            assert_eq!(
+                reopened.get_vertex(id).unwrap().address(),
+                graph.get_vertex(id).unwrap().address()
+            );
+        }
+        let edge = reopened.get_edge(transfer).unwrap();
+        assert_eq!((edge.src(), edge.dst()), (a, b));
+        assert_eq!(edge.amount(), Some(&TokenAmount::from_u64(42)));
+        assert_eq!(edge.block_number(), Some(7));
+        assert_eq!(reopened.vertex_tag(b).as_deref(), Some("exchange"));
+        let address = PropertyValue::String("0xaaa".to_string());
+        let indexed = reopened.lookup_index("Account", "address", &address);
+        assert_eq!(indexed, Some(vec![a]));
+
+        // 快照与原图相互独立
+        let c = reopened.add_account("0xccc".to_string()).unwrap();
+        assert!(graph.get_vertex(c).is_none());
+    }
+
+    #[test]
+    fn test_merge_overlapping_graphs() {
+        let hash = TxHash::from_hex(&format!("0x{}", "ab".repeat(32))).unwrap();
+        let first = Graph::in_memory().unwrap();
+        let a = first.add_account("0xaaa".to_string()).unwrap();
+        let b = first.add_account("0xbbb".to_string()).unwrap();
+        let tx = first.add_vertex(VertexLabel::Transaction).unwrap();
+        let mut vertex = first.get_vertex(tx).unwrap();
+        vertex.set_property("tx_hash".to_string(), PropertyValue::TxHash(hash));
+        first.update_vertex(vertex).unwrap();
+        first
+            .add_transfer(a, b, TokenAmount::from_u64(5), 1)
+            .unwrap();
+
+        // 第二个数据源与第一个共享 0xbbb 和同一笔交易
+        let second = Graph::in_memory().unwrap();
+        let b2 = second.add_account("0xbbb".to_string()).unwrap();
+        let c2 = second.add_contract("0xccc".to_string()).unwrap();
+        let tx2 = second.add_vertex(VertexLabel::Transaction).unwrap();
+        let mut vertex = second.get_vertex(tx2).unwrap();
+        vertex.set_property("tx_hash".to_string(), PropertyValue::TxHash(hash));
+        second.update_vertex(vertex).unwrap();
+        let anonymous = second.add_vertex(VertexLabel::Block).unwrap();
+        second
+            .add_transfer_unique(b2, c2, TokenAmount::from_u64(7), 2, hash, 0)
+            .unwrap();
+        second.add_edge(EdgeLabel::Call, c2, anonymous).unwrap();
+
+        let stats = first.merge_from(&second).unwrap();
+        assert_eq!(
+            stats,
+            MergeStats {
+                vertices_added: 2,
+                vertices_deduplicated: 2,
+                edges_added: 2,
+                edges_deduplicated: 0,
+            }
+        );
+        assert_eq!(first.vertex_count(), 5);
+        assert_eq!(first.edge_count(), 3);
+
+        // 重复地址复用原顶点，新边接到原顶点上
+        let c = first.get_vertex_by_address("0xccc").unwrap();
+        assert_eq!(c.label(), &VertexLabel::Contract);
+        let edges = first.get_edges_between(b, c.id());
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].amount(), Some(&TokenAmount::from_u64(7)));
+        assert_eq!(first.get_outgoing_edges(a).len(), 1);
+        assert_eq!(first.get_vertices_by_label(&VertexLabel::Transaction).len(), 1);
+        assert_eq!(first.get_outgoing_edges(c.id()).len(), 1);
+
+        // 再次合并同一数据源：转账按 (tx_hash, log_index) 跳过，无键的边照常追加
+        let stats = first.merge_from(&second).unwrap();
+        assert_eq!(
+            stats,
+            MergeStats {
+                vertices_added: 1,
+                vertices_deduplicated: 3,
+                edges_added: 1,
+                edges_deduplicated: 1,
+            }
+        );
+        assert_eq!(first.edge_count(), 4);
+        assert_eq!(first.get_edges_between(b, c.id()).len(), 1);
+        assert_eq!(first.get_outgoing_edges(c.id()).len(), 2);
+    }
+
+    #[test]
+    fn test_existence_checks_read_no_pages() {
+        let dir = tempdir().unwrap();
+        let (a, transfer) = {
+            let graph = Graph::open(dir.path(), Some(64)).unwrap();
+            let a = graph.add_account("0xaaa".to_string()).unwrap();
+            let b = graph.add_account("0xbbb".to_string()).unwrap();
+            let transfer = graph
+                .add_transfer(a, b, TokenAmount::from_u64(1), 1)
+                .unwrap();
+            graph.flush().unwrap();
+            (a, transfer)
+        };
+
+        let graph = Graph::open(dir.path(), Some(64)).unwrap();
+        let reads = graph.buffer_pool().page_reads();
+        assert!(graph.vertex_exists(a));
+        assert!(!graph.vertex_exists(VertexId::new(999)));
+        assert!(graph.edge_exists(transfer));
+        assert!(!graph.edge_exists(EdgeId::new(999)));
+        assert_eq!(graph.buffer_pool().page_reads(), reads);
+
+        graph.remove_vertex(a).unwrap();
+        assert!(!graph.vertex_exists(a));
+        assert!(!graph.edge_exists(transfer));
+    }
+
+    #[test]
+    fn test_add_transfer_unique_is_idempotent() {
+        let hash = TxHash::from_hex(&format!("0x{}", "cd".repeat(32))).unwrap();
+        let dir = tempdir().unwrap();
+        let (a, b, first) = {
+            let graph = Graph::open(dir.path(), Some(64)).unwrap();
+            let a = graph.add_account("0xaaa".to_string()).unwrap();
+            let b = graph.add_account("0xbbb".to_string()).unwrap();
+            let amount = TokenAmount::from_u64(10);
+            let first = graph.add_transfer_unique(a, b, amount, 1, hash, 0).unwrap();
+            assert!(first.is_some());
+            // 同一交易的另一条日志是另一笔转账
+            assert!(graph
+                .add_transfer_unique(a, b, amount, 1, hash, 1)
+                .unwrap()
+                .is_some());
+            assert_eq!(
+                graph.add_transfer_unique(a, b, amount, 1, hash, 0).unwrap(),
+                None
+            );
+            assert_eq!(graph.edge_count(), 2);
+            graph.flush().unwrap();
+            (a, b, first.unwrap())
+        };
+
+        // 重新打开后唯一键索引从边数据重建
+        let graph = Graph::open(dir.path(), Some(64)).unwrap();
+        let amount = TokenAmount::from_u64(10);
+        let key = graph.get_edge(first).unwrap().transfer_key();
+        assert_eq!(key, Some((hash, 0)));
+        assert_eq!(
+            graph.add_transfer_unique(a, b, amount, 1, hash, 1).unwrap(),
+            None
+        );
+        assert_eq!(graph.edge_count(), 2);
+
+        // 删除后可以重新写入
+        graph.remove_edge(first).unwrap();
+        assert!(graph
+            .add_transfer_unique(a, b, amount, 1, hash, 0)
+            .unwrap()
+            .is_some());
+        assert_eq!(graph.edge_count(), 2);
+    }
+
+    #[test]
+    fn test_property_indexes() {
+        let address = |s: &str| PropertyValue::String(s.to_string());
+        let lookup = |graph: &Graph, s: &str| graph.lookup_index("Account", "address", &address(s));
+
+        let graph = Graph::in_memory().unwrap();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        graph.create_index("Account", "address").unwrap();
+        assert!(graph.create_index("Account", "address").is_err());
+        let b = graph.add_account("0xbbb".to_string()).unwrap();
+        assert_eq!(lookup(&graph, "0xaaa"), Some(vec![a]));
+        assert_eq!(lookup(&graph, "0xbbb"), Some(vec![b]));
+        assert!(graph
+            .lookup_index("Account", "balance", &address("0xaaa"))
+            .is_none());
+
+        // 更新和删除同步索引
+        let mut vertex = graph.get_vertex(b).unwrap();
+        vertex.set_property("address".to_string(), address("0xccc"));
+        graph.update_vertex(vertex).unwrap();
+        assert_eq!(lookup(&graph, "0xbbb"), Some(vec![]));
+        assert_eq!(lookup(&graph, "0xccc"), Some(vec![b]));
+        graph.remove_vertex(a).unwrap();
+        assert_eq!(lookup(&graph, "0xaaa"), Some(vec![]));
+
+        graph.drop_index("Account", "address").unwrap();
+        assert!(graph.drop_index("Account", "address").is_err());
+        assert!(graph.list_indexes().is_empty());
+        assert!(lookup(&graph, "0xccc").is_none());
+
+        // 定义跨重启保留，条目由加载的顶点重建
+        let dir = tempdir().unwrap();
+        {
+            let graph = Graph::open(dir.path(), Some(512)).unwrap();
+            graph.add_account("0xaaa".to_string()).unwrap();
+            graph.add_account("0xbbb".to_string()).unwrap();
+            graph.create_index("Account", "address").unwrap();
+            graph.flush().unwrap();
+        }
+        let graph = Graph::open(dir.path(), Some(512)).unwrap();
+        let indexes = graph.list_indexes();
+        assert_eq!(indexes.len(), 1);
+        assert_eq!(indexes[0].label, "Account");
+        assert_eq!(indexes[0].property, "address");
+        assert_eq!(indexes[0].entries, 2);
+        assert_eq!(lookup(&graph, "0xbbb").map(|ids| ids.len()), Some(1));
+    }
 }
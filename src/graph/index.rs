@@ -3,11 +3,12 @@
 //! 顶点和边的内存索引，支持快速查找
 
 use crate::graph::edge::EdgeId;
-use crate::graph::vertex::VertexId;
-use crate::types::{EdgeLabel, VertexLabel};
+use crate::graph::vertex::{Vertex, VertexId};
+use crate::types::{EdgeLabel, PropertyValue, TxHash, VertexLabel};
 use std::collections::HashMap as StdHashMap;
 use parking_lot::RwLock;
-use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// 顶点索引
 pub struct VertexIndex {
@@ -34,6 +35,14 @@ impl VertexIndex {
         self.address_to_id.write().insert(address, vertex_id);
     }
 
+    /// 移除地址索引，仅当它仍指向该顶点时
+    pub fn remove_address(&self, address: &str, vertex_id: VertexId) {
+        let mut addresses = self.address_to_id.write();
+        if addresses.get(address) == Some(&vertex_id) {
+            addresses.remove(address);
+        }
+    }
+
     /// 通过地址查找顶点
     pub fn get_by_address(&self, address: &str) -> Option<VertexId> {
         self.address_to_id.read().get(address).copied()
@@ -44,10 +53,17 @@ impl VertexIndex {
         self.label_to_ids
             .write()
             .entry(label)
-            .or_insert_with(HashSet::new)
+            .or_default()
             .insert(vertex_id);
     }
 
+    /// 移除标签索引
+    pub fn remove_label(&self, label: &VertexLabel, vertex_id: VertexId) {
+        if let Some(set) = self.label_to_ids.write().get_mut(label) {
+            set.remove(&vertex_id);
+        }
+    }
+
     /// 获取标签下的所有顶点
     pub fn get_by_label(&self, label: &VertexLabel) -> Vec<VertexId> {
         self.label_to_ids
@@ -57,6 +73,16 @@ impl VertexIndex {
             .unwrap_or_default()
     }
 
+    /// 各标签下的顶点数量（不含空标签）
+    pub fn label_counts(&self) -> HashMap<VertexLabel, usize> {
+        self.label_to_ids
+            .read()
+            .iter()
+            .filter(|(_, ids)| !ids.is_empty())
+            .map(|(label, ids)| (label.clone(), ids.len()))
+            .collect()
+    }
+
     /// 设置页面位置
     pub fn set_location(&self, vertex_id: VertexId, page_id: u64, offset: u32) {
         self.id_to_location
@@ -91,6 +117,31 @@ impl VertexIndex {
     pub fn vertex_count(&self) -> usize {
         self.id_to_location.read().len()
     }
+
+    /// 所有地址索引条目
+    pub fn address_entries(&self) -> Vec<(String, VertexId)> {
+        self.address_to_id
+            .read()
+            .iter()
+            .map(|(addr, &id)| (addr.clone(), id))
+            .collect()
+    }
+
+    /// 所有标签索引条目
+    pub fn label_entries(&self) -> Vec<(VertexLabel, VertexId)> {
+        self.label_to_ids
+            .read()
+            .iter()
+            .flat_map(|(label, ids)| ids.iter().map(move |&id| (label.clone(), id)))
+            .collect()
+    }
+
+    /// 清空全部索引（用于重建）
+    pub fn clear(&self) {
+        self.address_to_id.write().clear();
+        self.label_to_ids.write().clear();
+        self.id_to_location.write().clear();
+    }
 }
 
 impl Default for VertexIndex {
@@ -113,6 +164,8 @@ pub struct EdgeIndex {
     id_to_location: RwLock<HashMap<EdgeId, (u64, u32)>>,
     /// (src, dst) 到边 ID 列表的映射（支持多重边）
     pair_to_edges: RwLock<HashMap<(VertexId, VertexId), Vec<EdgeId>>>,
+    /// (tx_hash, log_index) 到转账边 ID 的映射
+    transfer_keys: RwLock<HashMap<(TxHash, u64), EdgeId>>,
 }
 
 impl EdgeIndex {
@@ -125,6 +178,7 @@ impl EdgeIndex {
             edge_endpoints: RwLock::new(HashMap::new()),
             id_to_location: RwLock::new(HashMap::new()),
             pair_to_edges: RwLock::new(HashMap::new()),
+            transfer_keys: RwLock::new(HashMap::new()),
         }
     }
 
@@ -134,21 +188,21 @@ impl EdgeIndex {
         self.outgoing
             .write()
             .entry(src)
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(edge_id);
 
         // 入边索引
         self.incoming
             .write()
             .entry(dst)
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(edge_id);
 
         // 标签索引
         self.label_to_ids
             .write()
             .entry(label)
-            .or_insert_with(HashSet::new)
+            .or_default()
             .insert(edge_id);
 
         // 端点映射
@@ -158,10 +212,37 @@ impl EdgeIndex {
         self.pair_to_edges
             .write()
             .entry((src, dst))
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(edge_id);
     }
 
+    /// 把边从一个标签移到另一个标签下
+    pub fn relabel(&self, edge_id: EdgeId, old: &EdgeLabel, new: EdgeLabel) {
+        let mut labels = self.label_to_ids.write();
+        if let Some(set) = labels.get_mut(old) {
+            set.remove(&edge_id);
+        }
+        labels.entry(new).or_default().insert(edge_id);
+    }
+
+    /// 登记转账边的 (tx_hash, log_index)
+    pub fn add_transfer_key(&self, key: (TxHash, u64), edge_id: EdgeId) {
+        self.transfer_keys.write().insert(key, edge_id);
+    }
+
+    /// 按 (tx_hash, log_index) 查找转账边
+    pub fn get_by_transfer_key(&self, key: &(TxHash, u64)) -> Option<EdgeId> {
+        self.transfer_keys.read().get(key).copied()
+    }
+
+    /// 移除 (tx_hash, log_index) 登记，仅当它仍指向该边时
+    pub fn remove_transfer_key(&self, key: &(TxHash, u64), edge_id: EdgeId) {
+        let mut keys = self.transfer_keys.write();
+        if keys.get(key) == Some(&edge_id) {
+            keys.remove(key);
+        }
+    }
+
     /// 获取顶点的出边
     pub fn get_outgoing(&self, vertex_id: VertexId) -> Vec<EdgeId> {
         self.outgoing
@@ -203,6 +284,16 @@ impl EdgeIndex {
             .unwrap_or_default()
     }
 
+    /// 各标签下的边数量（不含空标签）
+    pub fn label_counts(&self) -> HashMap<EdgeLabel, usize> {
+        self.label_to_ids
+            .read()
+            .iter()
+            .filter(|(_, ids)| !ids.is_empty())
+            .map(|(label, ids)| (label.clone(), ids.len()))
+            .collect()
+    }
+
     /// 设置页面位置
     pub fn set_location(&self, edge_id: EdgeId, page_id: u64, offset: u32) {
         self.id_to_location
@@ -246,6 +337,35 @@ impl EdgeIndex {
         self.edge_endpoints.read().len()
     }
 
+    /// 所有端点索引条目
+    pub fn endpoint_entries(&self) -> Vec<(EdgeId, (VertexId, VertexId))> {
+        self.edge_endpoints
+            .read()
+            .iter()
+            .map(|(&id, &endpoints)| (id, endpoints))
+            .collect()
+    }
+
+    /// 所有标签索引条目
+    pub fn label_entries(&self) -> Vec<(EdgeLabel, EdgeId)> {
+        self.label_to_ids
+            .read()
+            .iter()
+            .flat_map(|(label, ids)| ids.iter().map(move |&id| (label.clone(), id)))
+            .collect()
+    }
+
+    /// 清空全部索引（用于重建）
+    pub fn clear(&self) {
+        self.outgoing.write().clear();
+        self.incoming.write().clear();
+        self.label_to_ids.write().clear();
+        self.edge_endpoints.write().clear();
+        self.id_to_location.write().clear();
+        self.pair_to_edges.write().clear();
+        self.transfer_keys.write().clear();
+    }
+
     /// 获取顶点的出度
     pub fn out_degree(&self, vertex_id: VertexId) -> usize {
         self.outgoing
@@ -287,6 +407,163 @@ impl Default for EdgeIndex {
     }
 }
 
+/// 属性索引定义（顶点标签 + 属性名）
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct IndexDefinition {
+    pub label: String,
+    pub property: String,
+}
+
+/// 属性索引信息（用于列出索引）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IndexInfo {
+    pub label: String,
+    pub property: String,
+    /// 已索引的顶点数
+    pub entries: usize,
+}
+
+/// 属性值到顶点 ID 集合的映射（值以 Debug 表示为键，区分类型）
+type PropertyEntries = HashMap<String, HashSet<VertexId>>;
+
+/// 顶点属性索引
+///
+/// 每个定义维护一张等值查找表，随顶点写入、更新、删除同步
+pub struct PropertyIndex {
+    indexes: RwLock<BTreeMap<IndexDefinition, PropertyEntries>>,
+}
+
+impl PropertyIndex {
+    /// 创建空的属性索引
+    pub fn new() -> Self {
+        Self {
+            indexes: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    fn value_key(value: &PropertyValue) -> String {
+        format!("{:?}", value)
+    }
+
+    /// 新建索引并用已有顶点填充；已存在时返回 false
+    pub fn create<'a>(
+        &self,
+        definition: IndexDefinition,
+        vertices: impl IntoIterator<Item = &'a Vertex>,
+    ) -> bool {
+        let mut indexes = self.indexes.write();
+        if indexes.contains_key(&definition) {
+            return false;
+        }
+        let mut entries = PropertyEntries::new();
+        for vertex in vertices {
+            if vertex.label().as_str() != definition.label {
+                continue;
+            }
+            if let Some(value) = vertex.property(&definition.property) {
+                entries
+                    .entry(Self::value_key(value))
+                    .or_default()
+                    .insert(vertex.id());
+            }
+        }
+        indexes.insert(definition, entries);
+        true
+    }
+
+    /// 删除索引；不存在时返回 false
+    pub fn drop(&self, definition: &IndexDefinition) -> bool {
+        self.indexes.write().remove(definition).is_some()
+    }
+
+    /// 所有索引定义（按标签、属性排序）
+    pub fn definitions(&self) -> Vec<IndexDefinition> {
+        self.indexes.read().keys().cloned().collect()
+    }
+
+    /// 所有索引及其条目数
+    pub fn list(&self) -> Vec<IndexInfo> {
+        self.indexes
+            .read()
+            .iter()
+            .map(|(definition, entries)| IndexInfo {
+                label: definition.label.clone(),
+                property: definition.property.clone(),
+                entries: entries.values().map(HashSet::len).sum(),
+            })
+            .collect()
+    }
+
+    /// 等值查找；没有对应索引时返回 None
+    pub fn lookup(
+        &self,
+        label: &str,
+        property: &str,
+        value: &PropertyValue,
+    ) -> Option<Vec<VertexId>> {
+        let definition = IndexDefinition {
+            label: label.to_string(),
+            property: property.to_string(),
+        };
+        let indexes = self.indexes.read();
+        let entries = indexes.get(&definition)?;
+        let mut ids: Vec<VertexId> = entries
+            .get(&Self::value_key(value))
+            .map(|ids| ids.iter().copied().collect())
+            .unwrap_or_default();
+        ids.sort_by_key(|id| id.as_u64());
+        Some(ids)
+    }
+
+    /// 写入顶点的索引条目
+    pub fn insert_vertex(&self, vertex: &Vertex) {
+        let mut indexes = self.indexes.write();
+        for (definition, entries) in indexes.iter_mut() {
+            if vertex.label().as_str() != definition.label {
+                continue;
+            }
+            if let Some(value) = vertex.property(&definition.property) {
+                entries
+                    .entry(Self::value_key(value))
+                    .or_default()
+                    .insert(vertex.id());
+            }
+        }
+    }
+
+    /// 移除顶点的索引条目
+    pub fn remove_vertex(&self, vertex: &Vertex) {
+        let mut indexes = self.indexes.write();
+        for (definition, entries) in indexes.iter_mut() {
+            if vertex.label().as_str() != definition.label {
+                continue;
+            }
+            if let Some(value) = vertex.property(&definition.property) {
+                let key = Self::value_key(value);
+                if let Some(ids) = entries.get_mut(&key) {
+                    ids.remove(&vertex.id());
+                    if ids.is_empty() {
+                        entries.remove(&key);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 清空所有索引条目（保留定义）
+    pub fn clear_entries(&self) {
+        for entries in self.indexes.write().values_mut() {
+            entries.clear();
+        }
+    }
+}
+
+impl Default for PropertyIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -322,4 +599,38 @@ mod tests {
         assert_eq!(index.neighbors(src), vec![dst]);
         assert_eq!(index.predecessors(dst), vec![src]);
     }
+
+    #[test]
+    fn test_property_index() {
+        let index = PropertyIndex::new();
+        let mut a = Vertex::new_account(VertexId::new(1), "0xa".to_string());
+        let b = Vertex::new_account(VertexId::new(2), "0xb".to_string());
+        let contract = Vertex::new_contract(VertexId::new(3), "0xa".to_string());
+        let definition = IndexDefinition {
+            label: "Account".to_string(),
+            property: "address".to_string(),
+        };
+        let address = |s: &str| PropertyValue::String(s.to_string());
+        let lookup = |s: &str| index.lookup("Account", "address", &address(s));
+
+        assert!(lookup("0xa").is_none());
+        assert!(index.create(definition.clone(), [&a, &b, &contract]));
+        assert!(!index.create(definition.clone(), []));
+        // 只索引同标签的顶点
+        assert_eq!(lookup("0xa"), Some(vec![a.id()]));
+        assert_eq!(lookup("0xc"), Some(vec![]));
+        assert_eq!(index.list()[0].entries, 2);
+
+        // 更新 = 移除旧条目 + 写入新条目
+        index.remove_vertex(&a);
+        a.set_property("address".to_string(), address("0xc"));
+        index.insert_vertex(&a);
+        assert_eq!(lookup("0xa"), Some(vec![]));
+        assert_eq!(lookup("0xc"), Some(vec![a.id()]));
+
+        assert_eq!(index.definitions(), vec![definition.clone()]);
+        assert!(index.drop(&definition));
+        assert!(!index.drop(&definition));
+        assert!(index.list().is_empty());
+    }
 }
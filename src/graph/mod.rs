@@ -3,14 +3,21 @@
 //! 定义顶点、边和图的核心数据结构
 
 mod edge;
+#[allow(clippy::module_inception)]
 mod graph;
 mod catalog;
 mod index;
+mod snapshot;
+mod transaction;
 mod vertex;
+mod view;
 
 pub use edge::{Edge, EdgeId};
-pub use graph::Graph;
+pub use graph::{AdjacencyOrder, CheckpointStats, ConsistencyReport, Graph, MergeStats};
 pub use catalog::GraphCatalog;
 pub use graph::{StoredGraphSchema, StoredPropertySpec};
-pub use index::{EdgeIndex, VertexIndex};
+pub use index::{EdgeIndex, IndexDefinition, IndexInfo, PropertyIndex, VertexIndex};
+pub use snapshot::ReadSnapshot;
+pub use transaction::{TransactionInfo, TransactionManager, TransactionMode};
 pub use vertex::{Vertex, VertexId};
+pub use view::{AsUndirected, GraphView};
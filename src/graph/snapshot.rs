@@ -0,0 +1,122 @@
+//! 读快照
+//!
+//! 顶点和边 ID 单调递增分配，因此"某时刻已完整写入的数据"可以用一对 ID 水位表示：
+//! 水位以下的 ID 都已写入完成，之后分配的 ID 对该快照不可见。
+//! 查询开始时取一次快照，即可在并发导入期间看到一致的时间点视图。
+//!
+//! 快照只隔离新增的顶点和边；属性更新与删除仍然立即可见。
+
+use parking_lot::Mutex;
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::edge::EdgeId;
+use super::vertex::VertexId;
+
+/// 某一时刻的只读视图
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadSnapshot {
+    /// 可见顶点 ID 的上界（不含）
+    vertex_watermark: u64,
+    /// 可见边 ID 的上界（不含）
+    edge_watermark: u64,
+}
+
+impl ReadSnapshot {
+    pub(crate) fn new(vertex_watermark: u64, edge_watermark: u64) -> Self {
+        Self {
+            vertex_watermark,
+            edge_watermark,
+        }
+    }
+
+    /// 顶点水位
+    pub fn vertex_watermark(&self) -> u64 {
+        self.vertex_watermark
+    }
+
+    /// 边水位
+    pub fn edge_watermark(&self) -> u64 {
+        self.edge_watermark
+    }
+
+    /// 顶点在快照中是否可见
+    pub fn sees_vertex(&self, id: VertexId) -> bool {
+        id.as_u64() < self.vertex_watermark
+    }
+
+    /// 边在快照中是否可见
+    pub fn sees_edge(&self, id: EdgeId) -> bool {
+        id.as_u64() < self.edge_watermark
+    }
+}
+
+/// 已分配但尚未写入完成的 ID 集合
+#[derive(Debug, Default)]
+pub(crate) struct PendingIds {
+    ids: Mutex<BTreeSet<u64>>,
+}
+
+impl PendingIds {
+    /// 从计数器分配新 ID 并登记为写入中；返回的守卫释放时完成登记
+    pub(crate) fn allocate<'a>(&'a self, next: &AtomicU64) -> PendingGuard<'a> {
+        let mut ids = self.ids.lock();
+        let id = next.fetch_add(1, Ordering::SeqCst);
+        ids.insert(id);
+        PendingGuard { pending: self, id }
+    }
+
+    /// 当前水位：最小的写入中 ID，没有写入中的 ID 时为下一个待分配 ID
+    pub(crate) fn watermark(&self, next: &AtomicU64) -> u64 {
+        let ids = self.ids.lock();
+        ids.first()
+            .copied()
+            .unwrap_or_else(|| next.load(Ordering::SeqCst))
+    }
+}
+
+/// 写入中的 ID；写入成功或失败后释放
+pub(crate) struct PendingGuard<'a> {
+    pending: &'a PendingIds,
+    id: u64,
+}
+
+impl PendingGuard<'_> {
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.pending.ids.lock().remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watermark_waits_for_pending_writes() {
+        let next = AtomicU64::new(1);
+        let pending = PendingIds::default();
+        assert_eq!(pending.watermark(&next), 1);
+
+        let first = pending.allocate(&next);
+        let second = pending.allocate(&next);
+        assert_eq!((first.id(), second.id()), (1, 2));
+
+        // 后分配的 ID 先完成，水位仍停在未完成的 1
+        drop(second);
+        assert_eq!(pending.watermark(&next), 1);
+
+        drop(first);
+        assert_eq!(pending.watermark(&next), 3);
+
+        let snapshot = ReadSnapshot::new(3, 1);
+        assert!(snapshot.sees_vertex(VertexId::new(2)));
+        assert!(!snapshot.sees_vertex(VertexId::new(3)));
+        assert!(!snapshot.sees_edge(EdgeId::new(1)));
+    }
+}
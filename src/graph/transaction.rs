@@ -0,0 +1,123 @@
+//! 事务跟踪
+//!
+//! 记录通过 START TRANSACTION 打开、尚未 COMMIT / ROLLBACK 的事务，
+//! 供 SHOW TRANSACTIONS 和 `/transactions/active` 观测使用
+
+use crate::error::{Error, Result};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// 事务访问模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionMode {
+    /// 只读
+    ReadOnly,
+    /// 读写
+    ReadWrite,
+}
+
+impl TransactionMode {
+    /// 模式名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionMode::ReadOnly => "READ ONLY",
+            TransactionMode::ReadWrite => "READ WRITE",
+        }
+    }
+}
+
+/// 活跃事务信息
+#[derive(Debug, Clone)]
+pub struct TransactionInfo {
+    /// 事务 ID
+    pub id: u64,
+    /// 访问模式
+    pub mode: TransactionMode,
+    /// 开始时间
+    pub started_at: Instant,
+}
+
+impl TransactionInfo {
+    /// 事务已打开的时长
+    pub fn age(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+}
+
+/// 事务管理器
+#[derive(Debug)]
+pub struct TransactionManager {
+    next_id: AtomicU64,
+    /// 按开始顺序排列的活跃事务
+    active: Mutex<Vec<TransactionInfo>>,
+}
+
+impl Default for TransactionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TransactionManager {
+    /// 创建事务管理器
+    pub fn new() -> Self {
+        Self {
+            next_id: AtomicU64::new(1),
+            active: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 开始事务，返回事务 ID
+    pub fn begin(&self, mode: TransactionMode) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.active.lock().push(TransactionInfo {
+            id,
+            mode,
+            started_at: Instant::now(),
+        });
+        id
+    }
+
+    /// 结束指定事务
+    pub fn finish(&self, id: u64) -> Result<TransactionInfo> {
+        let mut active = self.active.lock();
+        match active.iter().position(|tx| tx.id == id) {
+            Some(pos) => Ok(active.remove(pos)),
+            None => Err(Error::QueryError(format!("事务 {} 不存在或已结束", id))),
+        }
+    }
+
+    /// 活跃事务列表（按开始顺序）
+    pub fn active(&self) -> Vec<TransactionInfo> {
+        self.active.lock().clone()
+    }
+
+    /// 活跃事务数量
+    pub fn active_count(&self) -> usize {
+        self.active.lock().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_begin_and_finish() {
+        let manager = TransactionManager::new();
+        let first = manager.begin(TransactionMode::ReadWrite);
+        let second = manager.begin(TransactionMode::ReadOnly);
+        assert_eq!(manager.active_count(), 2);
+
+        // 只结束指定的事务
+        let finished = manager.finish(first).unwrap();
+        assert_eq!(finished.id, first);
+        assert_eq!(finished.mode, TransactionMode::ReadWrite);
+        assert_eq!(manager.active()[0].id, second);
+
+        manager.finish(second).unwrap();
+        assert!(manager.active().is_empty());
+        assert!(manager.finish(second).is_err());
+    }
+}
@@ -6,8 +6,9 @@ use crate::types::{PropertyValue, TxHash, VertexLabel};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-/// 顶点 ID（全局唯一）
+/// 顶点 ID（全局唯一），序列化为裸 u64
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct VertexId(pub u64);
 
 impl VertexId {
@@ -113,6 +114,11 @@ impl Vertex {
         &self.label
     }
 
+    /// 修改标签
+    pub fn set_label(&mut self, label: VertexLabel) {
+        self.label = label;
+    }
+
     /// 获取属性
     pub fn property(&self, key: &str) -> Option<&PropertyValue> {
         self.properties.get(key)
@@ -207,4 +213,14 @@ mod tests {
         assert_eq!(v.id(), restored.id());
         assert_eq!(v.label(), restored.label());
     }
+
+    #[test]
+    fn test_vertex_id_json_is_plain_integer() {
+        assert_eq!(serde_json::to_string(&VertexId::new(42)).unwrap(), "42");
+        assert_eq!(serde_json::from_str::<VertexId>("42").unwrap(), VertexId::new(42));
+
+        let v = Vertex::new_account(VertexId::new(42), "0xabc".to_string());
+        let json = serde_json::to_value(&v).unwrap();
+        assert_eq!(json["id"], 42);
+    }
 }
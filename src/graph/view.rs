@@ -0,0 +1,60 @@
+//! 图视图
+//!
+//! 连通分量、中心性等算法通过 `GraphView` 读取邻接关系，
+//! 同一份存储可以按有向或无向方式解释
+
+use super::{Graph, VertexId};
+
+/// 算法读取顶点与邻接关系的接口
+pub trait GraphView {
+    /// 所有顶点 ID，按 ID 升序
+    fn vertex_ids(&self) -> Vec<VertexId>;
+
+    /// 后继顶点
+    fn neighbors(&self, vertex_id: VertexId) -> Vec<VertexId>;
+
+    /// 前驱顶点
+    fn predecessors(&self, vertex_id: VertexId) -> Vec<VertexId>;
+}
+
+impl GraphView for Graph {
+    fn vertex_ids(&self) -> Vec<VertexId> {
+        let mut ids: Vec<VertexId> = self
+            .vertex_index()
+            .label_entries()
+            .into_iter()
+            .map(|(_, id)| id)
+            .collect();
+        ids.sort_by_key(|id| id.as_u64());
+        ids.dedup();
+        ids
+    }
+
+    fn neighbors(&self, vertex_id: VertexId) -> Vec<VertexId> {
+        Graph::neighbors(self, vertex_id)
+    }
+
+    fn predecessors(&self, vertex_id: VertexId) -> Vec<VertexId> {
+        Graph::predecessors(self, vertex_id)
+    }
+}
+
+/// 把有向图当作无向图读取：后继与前驱都是出边和入边另一端的并集，
+/// 直接查询原有的邻接索引，不复制存储
+pub struct AsUndirected<'a>(pub &'a Graph);
+
+impl GraphView for AsUndirected<'_> {
+    fn vertex_ids(&self) -> Vec<VertexId> {
+        GraphView::vertex_ids(self.0)
+    }
+
+    fn neighbors(&self, vertex_id: VertexId) -> Vec<VertexId> {
+        let mut all = self.0.neighbors(vertex_id);
+        all.extend(self.0.predecessors(vertex_id));
+        all
+    }
+
+    fn predecessors(&self, vertex_id: VertexId) -> Vec<VertexId> {
+        self.neighbors(vertex_id)
+    }
+}
@@ -4,9 +4,14 @@
 
 use crate::error::{Error, Result};
 use crate::graph::{Graph, VertexId};
+use crate::storage::WatermarkStatus;
 use crate::types::{PropertyValue, TokenAmount, TxHash, VertexLabel};
+use parking_lot::Mutex;
+use primitive_types::U256;
 use rayon::prelude::*;
+use rayon::ThreadPool;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
@@ -20,12 +25,139 @@ pub struct ImportStats {
     pub edges_imported: usize,
     pub errors: usize,
     pub duration_ms: u64,
+    /// 出错的行号（从 1 开始，含表头）及错误信息，仅 validate 填充
+    pub line_errors: Vec<(usize, String)>,
+    /// 因缓冲池水位偏高而提前刷盘的次数
+    pub flushes: usize,
+    /// 启用去重时跳过的重复转账数
+    pub duplicates: usize,
+}
+
+/// 判定重复转账的依据
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupKey {
+    /// 交易哈希相同即视为重复；没有交易哈希的记录不去重
+    TxHash,
+    /// 交易哈希、转出方、转入方、金额（以及代币地址）全部相同才视为重复；
+    /// 没有交易哈希的记录无法区分重复付款，不去重
+    Transfer,
+}
+
+/// 已导入转账的去重键
+#[derive(PartialEq, Eq, Hash)]
+enum SeenTransfer {
+    TxHash(TxHash),
+    Transfer(TxHash, String, String, U256, Option<String>),
+}
+
+/// 内存压力下批次缩小的下限
+pub const MIN_BATCH_SIZE: usize = 16;
+
+/// 随缓冲池水位调整大小的批次
+///
+/// 每批结束时检查水位：危险时批次减半并立即刷盘，警告时刷盘，
+/// 恢复正常后批次逐步增大回配置值
+struct AdaptiveBatch {
+    max_size: usize,
+    size: usize,
+    filled: usize,
+}
+
+impl AdaptiveBatch {
+    fn new(max_size: usize) -> Self {
+        let max_size = max_size.max(1);
+        Self {
+            max_size,
+            size: max_size,
+            filled: 0,
+        }
+    }
+
+    /// 记录一条已处理的记录，批次写满时按水位调整下一批的大小
+    fn record(&mut self, graph: &Graph, stats: &mut ImportStats) -> Result<()> {
+        self.filled += 1;
+        if self.filled < self.size {
+            return Ok(());
+        }
+        self.filled = 0;
+
+        match graph.buffer_pool_watermark().status {
+            WatermarkStatus::Critical => {
+                graph.flush()?;
+                stats.flushes += 1;
+                self.size = (self.size / 2).max(MIN_BATCH_SIZE.min(self.max_size));
+            }
+            WatermarkStatus::Warning => {
+                graph.flush()?;
+                stats.flushes += 1;
+            }
+            WatermarkStatus::Normal => {
+                self.size = self.size.saturating_mul(2).min(self.max_size);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 读取的一行：不是合法 UTF-8 的行返回 `None`，由调用方计为错误行后继续读取；
+/// 其他 I/O 错误直接返回
+fn decode_line(line: std::io::Result<String>) -> Result<Option<String>> {
+    match line {
+        Ok(line) => Ok(Some(line)),
+        Err(e) if e.kind() == std::io::ErrorKind::InvalidData => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 解析后尚未写入图的转账
+struct ParsedTransfer {
+    from: String,
+    to: String,
+    amount: TokenAmount,
+    block_number: u64,
+    /// ERC-20 代币合约地址（原生币转账为空）
+    token_address: Option<String>,
+    /// 所属交易哈希
+    tx_hash: Option<TxHash>,
+    /// 区块时间（UTC 秒）
+    timestamp: Option<i64>,
+    /// 交易内的事件日志序号，与交易哈希一起唯一确定一笔转账
+    log_index: Option<u64>,
+}
+
+impl ParsedTransfer {
+    /// 写入后涉及的顶点数（from、to 及可选的代币顶点）
+    fn vertex_count(&self) -> usize {
+        2 + usize::from(self.token_address.is_some())
+    }
+
+    fn seen_key(&self, key: DedupKey) -> Option<SeenTransfer> {
+        match key {
+            DedupKey::TxHash => self.tx_hash.map(SeenTransfer::TxHash),
+            DedupKey::Transfer => self.tx_hash.map(|hash| {
+                SeenTransfer::Transfer(
+                    hash,
+                    self.from.clone(),
+                    self.to.clone(),
+                    self.amount.0,
+                    self.token_address.clone(),
+                )
+            }),
+        }
+    }
 }
 
 /// 批量导入器
 pub struct BatchImporter {
     graph: Arc<Graph>,
     batch_size: usize,
+    dedup: Option<DedupKey>,
+    /// 导入前按地址排序预先创建顶点，使顶点 ID 与导入顺序无关
+    deterministic_ids: bool,
+    /// 已导入的转账；同一导入器依次导入多个文件时跨文件生效
+    seen: Mutex<HashSet<SeenTransfer>>,
+    /// 并行导入使用的线程池，未设置时使用 rayon 全局线程池
+    thread_pool: Option<Arc<ThreadPool>>,
 }
 
 impl BatchImporter {
@@ -34,50 +166,110 @@ impl BatchImporter {
         Self {
             graph,
             batch_size: 10000,
+            dedup: None,
+            deterministic_ids: false,
+            seen: Mutex::new(HashSet::new()),
+            thread_pool: None,
         }
     }
 
-    /// 设置批次大小
+    /// 设置批次大小（上限）；缓冲池水位偏高时实际批次会自动缩小
     pub fn with_batch_size(mut self, size: usize) -> Self {
         self.batch_size = size;
         self
     }
 
+    /// 跳过重复的转账并计入 `ImportStats::duplicates`；默认不去重，全部追加
+    pub fn with_dedup(mut self, on: DedupKey) -> Self {
+        self.dedup = Some(on);
+        self
+    }
+
+    /// 按地址顺序分配顶点 ID：导入前先扫描一遍文件，按地址排序创建新出现的
+    /// 账户顶点（随后是代币顶点），顺序导入与并行导入得到相同的 ID→地址映射。
+    /// 图中已有的顶点保持原 ID
+    pub fn with_deterministic_ids(mut self, enabled: bool) -> Self {
+        self.deterministic_ids = enabled;
+        self
+    }
+
+    /// 设置并行导入使用的线程池，限制导入占用的线程数
+    pub fn with_thread_pool(mut self, pool: Arc<ThreadPool>) -> Self {
+        self.thread_pool = Some(pool);
+        self
+    }
+
+    /// 确定性 ID 模式下，按地址排序预先创建文件中涉及的顶点；无法解析的行忽略
+    fn preassign_vertex_ids<P, F>(&self, path: P, skip: usize, parse: F) -> Result<()>
+    where
+        P: AsRef<Path>,
+        F: Fn(&str) -> Result<ParsedTransfer>,
+    {
+        if !self.deterministic_ids {
+            return Ok(());
+        }
+        let reader = BufReader::new(File::open(path)?);
+        let mut accounts = BTreeSet::new();
+        let mut tokens = BTreeSet::new();
+        for line in reader.lines().skip(skip) {
+            let Some(line) = decode_line(line)? else {
+                continue;
+            };
+            let Ok(transfer) = parse(&line) else {
+                continue;
+            };
+            accounts.insert(transfer.from);
+            accounts.insert(transfer.to);
+            tokens.extend(transfer.token_address);
+        }
+        for address in accounts {
+            self.graph.add_account(address)?;
+        }
+        for address in tokens {
+            self.graph.add_token(address, String::new())?;
+        }
+        Ok(())
+    }
+
     /// 从 CSV 导入转账记录
     pub fn import_transfers_csv<P: AsRef<Path>>(&self, path: P) -> Result<ImportStats> {
         let start = std::time::Instant::now();
+        self.preassign_vertex_ids(&path, 1, Self::parse_transfer_line)?;
         let file = File::open(path)?;
         let reader = BufReader::new(file);
 
         let mut stats = ImportStats::default();
-        let mut lines = Vec::new();
+        let mut batch = AdaptiveBatch::new(self.batch_size);
 
+        // 跳过表头
         for line in reader.lines().skip(1) {
-            // 跳过表头
-            if let Ok(line) = line {
-                lines.push(line);
-            }
-        }
-
-        // 批量处理
-        for chunk in lines.chunks(self.batch_size) {
-            for line in chunk {
-                match self.parse_and_import_transfer(line) {
-                    Ok(_) => {
-                        stats.vertices_imported += 2; // from + to
-                        stats.edges_imported += 1;
-                    }
-                    Err(_) => stats.errors += 1,
+            let Some(line) = decode_line(line)? else {
+                stats.errors += 1;
+                continue;
+            };
+            match self.parse_and_import_transfer(&line) {
+                Ok(true) => {
+                    stats.vertices_imported += 2; // from + to
+                    stats.edges_imported += 1;
                 }
+                Ok(false) => stats.duplicates += 1,
+                Err(_) => stats.errors += 1,
             }
+            batch.record(&self.graph, &mut stats)?;
         }
 
         stats.duration_ms = start.elapsed().as_millis() as u64;
         Ok(stats)
     }
 
-    /// 解析并导入单条转账
-    fn parse_and_import_transfer(&self, line: &str) -> Result<()> {
+    /// 解析并导入单条转账，重复而被跳过时返回 `false`
+    fn parse_and_import_transfer(&self, line: &str) -> Result<bool> {
+        let transfer = Self::parse_transfer_line(line)?;
+        self.import_transfer(transfer)
+    }
+
+    /// 解析单行 CSV 转账：`from,to,value,block_number[,tx_hash[,timestamp]]`
+    fn parse_transfer_line(line: &str) -> Result<ParsedTransfer> {
         let parts: Vec<&str> = line.split(',').collect();
         if parts.len() < 4 {
             return Err(Error::ImportError("CSV 格式错误".to_string()));
@@ -92,87 +284,263 @@ impl BatchImporter {
             .map(TokenAmount::from_u64)
             .unwrap_or_else(|_| TokenAmount::from_u64(0));
         let block_number = parts[3].trim().parse::<u64>().unwrap_or(0);
+        let tx_hash = match parts.get(4).map(|s| s.trim()) {
+            Some(hash) if !hash.is_empty() => Some(TxHash::from_hex(hash)?),
+            _ => None,
+        };
+        let timestamp = match parts.get(5).map(|s| s.trim()) {
+            Some(time) if !time.is_empty() => Some(parse_timestamp(time)?),
+            _ => None,
+        };
 
-        let from_id = self.graph.add_account(from_addr)?;
-        let to_id = self.graph.add_account(to_addr)?;
-        self.graph
-            .add_transfer(from_id, to_id, amount, block_number)?;
+        Ok(ParsedTransfer {
+            from: from_addr,
+            to: to_addr,
+            amount,
+            block_number,
+            token_address: None,
+            tx_hash,
+            timestamp,
+            log_index: None,
+        })
+    }
 
-        Ok(())
+    /// 将解析后的转账写入图，重复而被跳过时返回 `false`
+    ///
+    /// 带代币地址的转账会创建（或复用）对应的 Token 顶点，
+    /// 并在转账边上记录 `token` 属性
+    fn import_transfer(&self, transfer: ParsedTransfer) -> Result<bool> {
+        if let Some(key) = self.dedup.and_then(|on| transfer.seen_key(on)) {
+            if !self.seen.lock().insert(key) {
+                return Ok(false);
+            }
+        }
+
+        let from_id = self.graph.add_account(transfer.from)?;
+        let to_id = self.graph.add_account(transfer.to)?;
+
+        let mut properties = Vec::new();
+        if let Some(token) = transfer.token_address {
+            self.graph.add_token(token.clone(), String::new())?;
+            properties.push(("token".to_string(), PropertyValue::String(token)));
+        }
+        if let Some(timestamp) = transfer.timestamp {
+            properties.push(("timestamp".to_string(), PropertyValue::Timestamp(timestamp)));
+        }
+
+        // 带日志序号的转账按 (tx_hash, log_index) 幂等写入，重复导入时跳过
+        if let (Some(tx_hash), Some(log_index)) = (transfer.tx_hash, transfer.log_index) {
+            let inserted = self.graph.add_transfer_unique_with_properties(
+                from_id,
+                to_id,
+                transfer.amount,
+                transfer.block_number,
+                (tx_hash, log_index),
+                properties,
+            )?;
+            return Ok(inserted.is_some());
+        }
+        if let Some(tx_hash) = transfer.tx_hash {
+            properties.push(("tx_hash".to_string(), PropertyValue::TxHash(tx_hash)));
+        }
+
+        self.graph.add_transfer_with_properties(
+            from_id,
+            to_id,
+            transfer.amount,
+            transfer.block_number,
+            properties,
+        )?;
+
+        Ok(true)
     }
 
     /// 从 JSON Lines 导入
     pub fn import_jsonl<P: AsRef<Path>>(&self, path: P) -> Result<ImportStats> {
         let start = std::time::Instant::now();
+        self.preassign_vertex_ids(&path, 0, Self::parse_json_line)?;
         let file = File::open(path)?;
         let reader = BufReader::new(file);
 
         let mut stats = ImportStats::default();
+        let mut batch = AdaptiveBatch::new(self.batch_size);
 
         for line in reader.lines() {
-            if let Ok(line) = line {
-                match self.parse_and_import_json(&line) {
-                    Ok((v, e)) => {
-                        stats.vertices_imported += v;
-                        stats.edges_imported += e;
-                    }
-                    Err(_) => stats.errors += 1,
+            let Some(line) = decode_line(line)? else {
+                stats.errors += 1;
+                continue;
+            };
+            match self.parse_and_import_json(&line) {
+                Ok(Some((v, e))) => {
+                    stats.vertices_imported += v;
+                    stats.edges_imported += e;
                 }
+                Ok(None) => stats.duplicates += 1,
+                Err(_) => stats.errors += 1,
             }
+            batch.record(&self.graph, &mut stats)?;
         }
 
         stats.duration_ms = start.elapsed().as_millis() as u64;
         Ok(stats)
     }
 
-    /// 解析并导入 JSON 记录
-    fn parse_and_import_json(&self, line: &str) -> Result<(usize, usize)> {
+    /// 解析并导入 JSON 记录，重复而被跳过时返回 `None`
+    fn parse_and_import_json(&self, line: &str) -> Result<Option<(usize, usize)>> {
+        let transfer = Self::parse_json_line(line)?;
+        let vertices = transfer.vertex_count();
+        if !self.import_transfer(transfer)? {
+            return Ok(None);
+        }
+
+        Ok(Some((vertices, 1)))
+    }
+
+    /// 解析单行 JSON 转账记录
+    fn parse_json_line(line: &str) -> Result<ParsedTransfer> {
         let record: TransferRecord = serde_json::from_str(line)
             .map_err(|e| Error::ImportError(format!("JSON 解析错误: {}", e)))?;
 
+        let tx_hash = match record.tx_hash.as_deref() {
+            Some(hash) if !hash.is_empty() => Some(TxHash::from_hex(hash)?),
+            _ => None,
+        };
+        let timestamp = match &record.timestamp {
+            Some(serde_json::Value::Number(secs)) => Some(
+                secs.as_i64()
+                    .ok_or_else(|| Error::ImportError(format!("无效的时间戳: {}", secs)))?,
+            ),
+            Some(serde_json::Value::String(time)) => Some(parse_timestamp(time)?),
+            Some(serde_json::Value::Null) | None => None,
+            Some(other) => {
+                return Err(Error::ImportError(format!("无效的时间戳: {}", other)));
+            }
+        };
+
         // JSON records contain address strings
-        let from_addr = record.from.clone();
-        let to_addr = record.to.clone();
-        let amount = TokenAmount::from_u64(record.value.parse().unwrap_or(0));
+        Ok(ParsedTransfer {
+            amount: TokenAmount::from_u64(record.value.parse().unwrap_or(0)),
+            from: record.from,
+            to: record.to,
+            block_number: record.block_number,
+            token_address: record.token_address.filter(|addr| !addr.is_empty()),
+            tx_hash,
+            timestamp,
+            log_index: record.log_index,
+        })
+    }
 
-        let from_id = self.graph.add_account(from_addr)?;
-        let to_id = self.graph.add_account(to_addr)?;
-        self.graph
-            .add_transfer(from_id, to_id, amount, record.block_number)?;
+    /// 试运行：按文件扩展名（.json / .jsonl 为 JSON Lines，其余为转账 CSV）
+    /// 完整走一遍解析流程并统计，但不写入图
+    pub fn validate<P: AsRef<Path>>(&self, path: P) -> Result<ImportStats> {
+        let is_json = matches!(
+            path.as_ref().extension().and_then(|ext| ext.to_str()),
+            Some("json") | Some("jsonl")
+        );
+        if is_json {
+            self.validate_jsonl(path)
+        } else {
+            self.validate_transfers_csv(path)
+        }
+    }
+
+    /// 试运行转账 CSV 导入
+    pub fn validate_transfers_csv<P: AsRef<Path>>(&self, path: P) -> Result<ImportStats> {
+        // 跳过表头，行号从第 2 行开始
+        Self::validate_lines(path, 1, Self::parse_transfer_line)
+    }
 
-        Ok((2, 1))
+    /// 试运行 JSON Lines 导入
+    pub fn validate_jsonl<P: AsRef<Path>>(&self, path: P) -> Result<ImportStats> {
+        Self::validate_lines(path, 0, Self::parse_json_line)
+    }
+
+    fn validate_lines<P, F>(path: P, skip: usize, parse: F) -> Result<ImportStats>
+    where
+        P: AsRef<Path>,
+        F: Fn(&str) -> Result<ParsedTransfer>,
+    {
+        let start = std::time::Instant::now();
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+
+        let mut stats = ImportStats::default();
+
+        for (idx, line) in reader.lines().enumerate().skip(skip) {
+            let Some(line) = decode_line(line)? else {
+                stats.errors += 1;
+                stats
+                    .line_errors
+                    .push((idx + 1, "不是合法的 UTF-8 文本".to_string()));
+                continue;
+            };
+            match parse(&line) {
+                Ok(transfer) => {
+                    stats.vertices_imported += transfer.vertex_count();
+                    stats.edges_imported += 1;
+                }
+                Err(e) => {
+                    stats.errors += 1;
+                    stats.line_errors.push((idx + 1, e.to_string()));
+                }
+            }
+        }
+
+        stats.duration_ms = start.elapsed().as_millis() as u64;
+        Ok(stats)
     }
 
     /// 并行导入（适合大文件）
     pub fn import_transfers_csv_parallel<P: AsRef<Path>>(&self, path: P) -> Result<ImportStats> {
         let start = std::time::Instant::now();
+        self.preassign_vertex_ids(&path, 1, Self::parse_transfer_line)?;
         let file = File::open(path)?;
         let reader = BufReader::new(file);
 
-        let lines: Vec<String> = reader.lines().skip(1).filter_map(|l| l.ok()).collect();
+        let mut lines = Vec::new();
+        let mut unreadable = 0;
+        for line in reader.lines().skip(1) {
+            match decode_line(line)? {
+                Some(line) => lines.push(line),
+                None => unreadable += 1,
+            }
+        }
 
         let vertices_count = AtomicUsize::new(0);
         let edges_count = AtomicUsize::new(0);
-        let errors_count = AtomicUsize::new(0);
+        let errors_count = AtomicUsize::new(unreadable);
+        let duplicates_count = AtomicUsize::new(0);
 
         // 并行处理
-        lines
-            .par_iter()
-            .for_each(|line| match self.parse_and_import_transfer(line) {
-                Ok(_) => {
-                    vertices_count.fetch_add(2, Ordering::Relaxed);
-                    edges_count.fetch_add(1, Ordering::Relaxed);
-                }
-                Err(_) => {
-                    errors_count.fetch_add(1, Ordering::Relaxed);
-                }
-            });
+        let import_all = || {
+            lines
+                .par_iter()
+                .for_each(|line| match self.parse_and_import_transfer(line) {
+                    Ok(true) => {
+                        vertices_count.fetch_add(2, Ordering::Relaxed);
+                        edges_count.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Ok(false) => {
+                        duplicates_count.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(_) => {
+                        errors_count.fetch_add(1, Ordering::Relaxed);
+                    }
+                })
+        };
+        match &self.thread_pool {
+            Some(pool) => pool.install(import_all),
+            None => import_all(),
+        }
 
         Ok(ImportStats {
             vertices_imported: vertices_count.load(Ordering::Relaxed),
             edges_imported: edges_count.load(Ordering::Relaxed),
             errors: errors_count.load(Ordering::Relaxed),
             duration_ms: start.elapsed().as_millis() as u64,
+            line_errors: Vec::new(),
+            flushes: 0,
+            duplicates: duplicates_count.load(Ordering::Relaxed),
         })
     }
 
@@ -183,16 +551,20 @@ impl BatchImporter {
         let reader = BufReader::new(file);
 
         let mut stats = ImportStats::default();
+        let mut batch = AdaptiveBatch::new(self.batch_size);
 
         for line in reader.lines().skip(1) {
-            if let Ok(line) = line {
-                match self.parse_and_import_transaction(&line) {
-                    Ok(_) => {
-                        stats.vertices_imported += 1;
-                    }
-                    Err(_) => stats.errors += 1,
+            let Some(line) = decode_line(line)? else {
+                stats.errors += 1;
+                continue;
+            };
+            match self.parse_and_import_transaction(&line) {
+                Ok(_) => {
+                    stats.vertices_imported += 1;
                 }
+                Err(_) => stats.errors += 1,
             }
+            batch.record(&self.graph, &mut stats)?;
         }
 
         stats.duration_ms = start.elapsed().as_millis() as u64;
@@ -234,6 +606,21 @@ struct TransferRecord {
     tx_hash: Option<String>,
     #[serde(default)]
     token_address: Option<String>,
+    /// UTC 秒数或 RFC 3339 字符串
+    #[serde(default)]
+    timestamp: Option<serde_json::Value>,
+    #[serde(default)]
+    log_index: Option<u64>,
+}
+
+/// 解析时间戳：UTC 秒数，或 RFC 3339 字符串（如 `2024-01-01T00:00:00Z`）
+fn parse_timestamp(text: &str) -> Result<i64> {
+    if let Ok(secs) = text.parse::<i64>() {
+        return Ok(secs);
+    }
+    chrono::DateTime::parse_from_rfc3339(text)
+        .map(|time| time.timestamp())
+        .map_err(|_| Error::ImportError(format!("无效的时间戳: {}", text)))
 }
 
 /// 从 Etherscan 风格的 CSV 导入
@@ -274,6 +661,267 @@ mod tests {
         assert_eq!(stats.errors, 0);
     }
 
+    #[test]
+    fn test_import_continues_past_invalid_utf8_lines() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "from,to,value,block_number").unwrap();
+        writeln!(file, "0xaaa,0xbbb,1,1").unwrap();
+        file.write_all(b"0xbbb,\xff\xfe,2,2\n").unwrap();
+        writeln!(file, "0xccc,0xddd,3,3").unwrap();
+
+        // 非 UTF-8 的行计为错误，之后的行照常导入
+        let importer = BatchImporter::new(Graph::in_memory().unwrap());
+        let stats = importer.import_transfers_csv(file.path()).unwrap();
+        assert_eq!(stats.edges_imported, 2);
+        assert_eq!(stats.errors, 1);
+
+        let importer = BatchImporter::new(Graph::in_memory().unwrap());
+        let stats = importer.import_transfers_csv_parallel(file.path()).unwrap();
+        assert_eq!(stats.edges_imported, 2);
+        assert_eq!(stats.errors, 1);
+
+        let stats = importer.validate_transfers_csv(file.path()).unwrap();
+        assert_eq!(stats.edges_imported, 2);
+        assert_eq!(stats.line_errors.len(), 1);
+        assert_eq!(stats.line_errors[0].0, 3);
+
+        // 确定性 ID 的预分配同样跳过该行
+        let graph = Graph::in_memory().unwrap();
+        let importer = BatchImporter::new(graph.clone()).with_deterministic_ids(true);
+        let stats = importer.import_transfers_csv(file.path()).unwrap();
+        assert_eq!(stats.edges_imported, 2);
+        assert_eq!(graph.vertex_count(), 4);
+    }
+
+    #[test]
+    fn test_import_flushes_under_buffer_pool_pressure() {
+        let write_transfers = |count: usize| {
+            let mut file = NamedTempFile::new().unwrap();
+            writeln!(file, "from,to,value,block_number").unwrap();
+            for i in 0..count {
+                writeln!(file, "0x{:040x},0x{:040x},{},{}", i, i + 1, i, i).unwrap();
+            }
+            file
+        };
+        let file = write_transfers(2000);
+
+        // 缓冲池充足：水位保持正常，不提前刷盘
+        let dir = tempfile::TempDir::new().unwrap();
+        let graph = Graph::open(dir.path(), Some(4096)).unwrap();
+        let stats = BatchImporter::new(graph.clone())
+            .with_batch_size(256)
+            .import_transfers_csv(file.path())
+            .unwrap();
+        assert_eq!(stats.errors, 0);
+        assert_eq!(stats.flushes, 0);
+
+        // 极小的缓冲池：水位进入危险区后批次缩小、频繁刷盘，导入仍然完整
+        let dir = tempfile::TempDir::new().unwrap();
+        let graph = Graph::open(dir.path(), Some(8)).unwrap();
+        let stats = BatchImporter::new(graph.clone())
+            .with_batch_size(256)
+            .import_transfers_csv(file.path())
+            .unwrap();
+        assert_eq!(stats.errors, 0);
+        assert_eq!(stats.edges_imported, 2000);
+        assert_eq!(graph.edge_count(), 2000);
+        assert_eq!(
+            graph.buffer_pool_watermark().status,
+            WatermarkStatus::Critical
+        );
+        // 不缩小批次时最多刷盘 2000 / 256 次
+        assert!(stats.flushes > 2000 / 256, "flushes: {}", stats.flushes);
+    }
+
+    #[test]
+    fn test_import_timestamps_and_filter_by_window() {
+        use crate::graph::GraphCatalog;
+        use crate::query::{GqlParser, QueryExecutor};
+        use crate::types::EdgeLabel;
+
+        let mut csv = NamedTempFile::new().unwrap();
+        writeln!(csv, "from,to,value,block_number,tx_hash,timestamp").unwrap();
+        writeln!(csv, "0xaaa,0xbbb,1,1,,1704067200").unwrap();
+        writeln!(csv, "0xbbb,0xccc,2,2,,2024-01-02T00:00:00Z").unwrap();
+        writeln!(csv, "0xccc,0xddd,3,3").unwrap();
+        let mut jsonl = NamedTempFile::new().unwrap();
+        writeln!(
+            jsonl,
+            r#"{{"from":"0xddd","to":"0xeee","value":"4","block_number":4,"timestamp":1704240000}}"#
+        )
+        .unwrap();
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let importer = BatchImporter::new(catalog.current_graph());
+        assert_eq!(importer.import_transfers_csv(csv.path()).unwrap().errors, 0);
+        assert_eq!(importer.import_jsonl(jsonl.path()).unwrap().errors, 0);
+
+        let graph = catalog.current_graph();
+        let mut timestamps: Vec<Option<i64>> = graph
+            .get_edges_by_label(&EdgeLabel::Transfer)
+            .iter()
+            .map(|edge| edge.timestamp())
+            .collect();
+        timestamps.sort();
+        assert_eq!(
+            timestamps,
+            [
+                None,
+                Some(1_704_067_200),
+                Some(1_704_153_600),
+                Some(1_704_240_000)
+            ]
+        );
+
+        // 按时间窗口过滤：2024-01-02 当天
+        let executor = QueryExecutor::new(catalog);
+        let query = "MATCH (a)-[t:Transfer]->(b) \
+                     WHERE t.timestamp >= 1704153600 AND t.timestamp < 1704240000 \
+                     RETURN a.address, b.address";
+        let statement = GqlParser::new(query).parse().unwrap();
+        let result = executor.execute(&statement).unwrap();
+        assert_eq!(result.rows.len(), 1);
+        let from = format!("{:?}", result.rows[0][0]);
+        assert!(from.contains("0xbbb"), "{}", from);
+
+        let mut bad = NamedTempFile::new().unwrap();
+        writeln!(bad, "from,to,value,block_number,tx_hash,timestamp").unwrap();
+        writeln!(bad, "0xaaa,0xbbb,1,1,,yesterday").unwrap();
+        let stats = BatchImporter::new(Graph::in_memory().unwrap())
+            .import_transfers_csv(bad.path())
+            .unwrap();
+        assert_eq!(stats.errors, 1);
+    }
+
+    #[test]
+    fn test_import_dedup() {
+        let hash = "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060";
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "from,to,value,block_number,tx_hash").unwrap();
+        writeln!(file, "0xaaa,0xbbb,1000,1,{}", hash).unwrap();
+        writeln!(file, "0xaaa,0xbbb,1000,1,{}", hash).unwrap();
+        // 同一交易内的另一笔转账
+        writeln!(file, "0xaaa,0xccc,5,1,{}", hash).unwrap();
+        writeln!(file, "0xbbb,0xccc,7,2").unwrap();
+
+        // 默认全部追加
+        let graph = Graph::in_memory().unwrap();
+        let stats = BatchImporter::new(graph.clone())
+            .import_transfers_csv(file.path())
+            .unwrap();
+        assert_eq!((stats.edges_imported, stats.duplicates), (4, 0));
+        assert_eq!(graph.edge_count(), 4);
+
+        let graph = Graph::in_memory().unwrap();
+        let importer = BatchImporter::new(graph.clone()).with_dedup(DedupKey::Transfer);
+        let stats = importer.import_transfers_csv(file.path()).unwrap();
+        assert_eq!((stats.edges_imported, stats.duplicates), (3, 1));
+        assert_eq!(graph.edge_count(), 3);
+        // 跨文件同样去重，无哈希的记录照常导入
+        let stats = importer.import_transfers_csv(file.path()).unwrap();
+        assert_eq!((stats.edges_imported, stats.duplicates), (1, 3));
+        assert_eq!(graph.edge_count(), 4);
+
+        // 按交易哈希去重：同一交易只保留第一笔，无哈希的记录照常导入
+        let graph = Graph::in_memory().unwrap();
+        let stats = BatchImporter::new(graph.clone())
+            .with_dedup(DedupKey::TxHash)
+            .import_transfers_csv(file.path())
+            .unwrap();
+        assert_eq!((stats.edges_imported, stats.duplicates), (2, 2));
+        assert_eq!(stats.errors, 0);
+    }
+
+    #[test]
+    fn test_dedup_keeps_repeat_payments_without_hash() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "from,to,amount,block").unwrap();
+        writeln!(file, "0xa,0xb,100,1").unwrap();
+        writeln!(file, "0xa,0xb,100,2").unwrap();
+
+        // 没有交易哈希时相同的两笔付款都是真实转账
+        let graph = Graph::in_memory().unwrap();
+        let stats = BatchImporter::new(graph.clone())
+            .with_dedup(DedupKey::Transfer)
+            .import_transfers_csv(file.path())
+            .unwrap();
+        assert_eq!((stats.edges_imported, stats.duplicates), (2, 0));
+        assert_eq!(graph.edge_count(), 2);
+    }
+
+    #[test]
+    fn test_reimport_with_log_index_is_idempotent() {
+        let hash = "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060";
+        let mut file = NamedTempFile::new().unwrap();
+        for (to, log_index) in [("0xbbb", 0), ("0xccc", 1), ("0xddd", 2)] {
+            writeln!(
+                file,
+                r#"{{"from":"0xaaa","to":"{}","value":"5","block_number":1,"tx_hash":"{}","log_index":{}}}"#,
+                to, hash, log_index
+            )
+            .unwrap();
+        }
+
+        let graph = Graph::in_memory().unwrap();
+        let stats = BatchImporter::new(graph.clone())
+            .import_jsonl(file.path())
+            .unwrap();
+        assert_eq!((stats.edges_imported, stats.duplicates), (3, 0));
+
+        // 新的导入器（没有跨文件去重状态）再次导入同一区块范围
+        let stats = BatchImporter::new(graph.clone())
+            .import_jsonl(file.path())
+            .unwrap();
+        assert_eq!((stats.edges_imported, stats.duplicates), (0, 3));
+        assert_eq!(graph.edge_count(), 3);
+    }
+
+    #[test]
+    fn test_deterministic_ids_match_across_import_paths() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "from,to,value,block_number").unwrap();
+        for i in 0..200u64 {
+            // 地址出现顺序与字典序不同
+            let (from, to) = ((i * 37) % 101, (i * 53) % 97);
+            writeln!(file, "0x{:040x},0x{:040x},{},{}", from, to, i, i).unwrap();
+        }
+        let mapping = |graph: &Graph| {
+            let mut ids: Vec<(u64, String)> = graph
+                .get_vertices_by_label(&VertexLabel::Account)
+                .iter()
+                .map(|v| (v.id().as_u64(), v.address().unwrap().to_string()))
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        let sequential = Graph::in_memory().unwrap();
+        BatchImporter::new(sequential.clone())
+            .with_deterministic_ids(true)
+            .import_transfers_csv(file.path())
+            .unwrap();
+        let parallel = Graph::in_memory().unwrap();
+        BatchImporter::new(parallel.clone())
+            .with_deterministic_ids(true)
+            .import_transfers_csv_parallel(file.path())
+            .unwrap();
+
+        let ids = mapping(&sequential);
+        assert_eq!(ids, mapping(&parallel));
+        assert_eq!(parallel.edge_count(), 200);
+        // ID 顺序即地址顺序
+        let addresses: Vec<&String> = ids.iter().map(|(_, address)| address).collect();
+        assert!(addresses.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // 默认按首次出现的顺序分配
+        let default = Graph::in_memory().unwrap();
+        BatchImporter::new(default.clone())
+            .import_transfers_csv(file.path())
+            .unwrap();
+        assert_ne!(mapping(&default), ids);
+    }
+
     #[test]
     fn test_import_jsonl() {
         let graph = Graph::in_memory().unwrap();
@@ -291,4 +939,100 @@ mod tests {
         assert_eq!(stats.vertices_imported, 2);
         assert_eq!(stats.edges_imported, 1);
     }
+
+    #[test]
+    fn test_validate_reports_errors_without_importing() {
+        let graph = Graph::in_memory().unwrap();
+        let importer = BatchImporter::new(graph.clone());
+
+        let mut file = tempfile::Builder::new().suffix(".jsonl").tempfile().unwrap();
+        writeln!(
+            file,
+            r#"{{"from":"0xaaa","to":"0xbbb","value":"1000","block_number":1}}"#
+        )
+        .unwrap();
+        writeln!(file, "not json").unwrap();
+        writeln!(file, r#"{{"from":"0xbbb","to":"0xccc"}}"#).unwrap();
+
+        let stats = importer.validate(file.path()).unwrap();
+        assert_eq!(stats.edges_imported, 1);
+        assert_eq!(stats.errors, 2);
+        let lines: Vec<usize> = stats.line_errors.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, vec![2, 3]);
+
+        assert_eq!(graph.vertex_count(), 0);
+        assert_eq!(graph.edge_count(), 0);
+    }
+
+    #[test]
+    fn test_import_jsonl_links_token_contract() {
+        let graph = Graph::in_memory().unwrap();
+        let importer = BatchImporter::new(graph.clone());
+
+        let usdt = "0xdAC17F958D2ee523a2206206994597C13D831ec7";
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"{{"from":"0xaaa","to":"0xbbb","value":"1000","block_number":1,"token_address":"{}"}}"#,
+            usdt
+        )
+        .unwrap();
+        writeln!(
+            file,
+            r#"{{"from":"0xbbb","to":"0xccc","value":"500","block_number":2,"token_address":"{}"}}"#,
+            usdt
+        )
+        .unwrap();
+        writeln!(
+            file,
+            r#"{{"from":"0xccc","to":"0xaaa","value":"1","block_number":3}}"#
+        )
+        .unwrap();
+
+        let stats = importer.import_jsonl(file.path()).unwrap();
+        assert_eq!(stats.errors, 0);
+        assert_eq!(stats.edges_imported, 3);
+
+        // 同一代币合约只创建一个 Token 顶点
+        let tokens = graph.get_vertices_by_label(&VertexLabel::Token);
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].address(), Some(usdt));
+
+        let token_edges: Vec<_> = graph
+            .get_edges_by_label(&crate::types::EdgeLabel::Transfer)
+            .into_iter()
+            .filter(|e| e.property("token") == Some(&PropertyValue::String(usdt.to_string())))
+            .collect();
+        assert_eq!(token_edges.len(), 2);
+    }
+
+    #[test]
+    fn test_import_attaches_tx_hash_to_transfer_edge() {
+        let hash = "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060";
+        let expected = PropertyValue::TxHash(TxHash::from_hex(hash).unwrap());
+
+        let graph = Graph::in_memory().unwrap();
+        let importer = BatchImporter::new(graph.clone());
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"{{"from":"0xaaa","to":"0xbbb","value":"1000","block_number":1,"tx_hash":"{}"}}"#,
+            hash
+        )
+        .unwrap();
+        importer.import_jsonl(file.path()).unwrap();
+        let edges = graph.get_edges_by_label(&crate::types::EdgeLabel::Transfer);
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].property("tx_hash"), Some(&expected));
+
+        let graph = Graph::in_memory().unwrap();
+        let importer = BatchImporter::new(graph.clone());
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "from,to,value,block_number,tx_hash").unwrap();
+        writeln!(file, "0xaaa,0xbbb,1000,1,{}", hash).unwrap();
+        importer.import_transfers_csv(file.path()).unwrap();
+        let edges = graph.get_edges_by_label(&crate::types::EdgeLabel::Transfer);
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].property("tx_hash"), Some(&expected));
+    }
 }
@@ -16,6 +16,10 @@ pub struct Metrics {
     buffer_pool_stats: BufferPoolStats,
     /// 图操作统计
     graph_stats: GraphStats,
+    /// 实时负载
+    gauges: Gauges,
+    /// 算法结果缓存统计
+    result_cache_stats: ResultCacheStats,
     /// 启动时间
     start_time: Instant,
 }
@@ -61,6 +65,24 @@ struct GraphStats {
     edges_queried: AtomicU64,
 }
 
+/// 算法结果缓存统计
+#[derive(Debug)]
+struct ResultCacheStats {
+    /// 命中数
+    hits: AtomicU64,
+    /// 未命中数
+    misses: AtomicU64,
+}
+
+/// 实时负载（gauge，随请求增减，不参与 reset）
+#[derive(Debug)]
+struct Gauges {
+    /// 正在执行的查询数
+    active_queries: AtomicU64,
+    /// 当前打开的 HTTP 连接数（正在处理的请求）
+    open_connections: AtomicU64,
+}
+
 /// 可导出的指标快照
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetricsSnapshot {
@@ -85,6 +107,14 @@ pub struct MetricsSnapshot {
     pub vertices_queried: u64,
     pub edges_queried: u64,
     
+    // 算法结果缓存指标
+    pub result_cache_hits: u64,
+    pub result_cache_misses: u64,
+    
+    // 实时负载
+    pub active_queries: u64,
+    pub open_connections: u64,
+    
     // 系统指标
     pub uptime_seconds: u64,
 }
@@ -118,6 +148,14 @@ impl Metrics {
                 vertices_queried: AtomicU64::new(0),
                 edges_queried: AtomicU64::new(0),
             },
+            gauges: Gauges {
+                active_queries: AtomicU64::new(0),
+                open_connections: AtomicU64::new(0),
+            },
+            result_cache_stats: ResultCacheStats {
+                hits: AtomicU64::new(0),
+                misses: AtomicU64::new(0),
+            },
             start_time: Instant::now(),
         }
     }
@@ -125,12 +163,14 @@ impl Metrics {
     /// 记录查询开始
     pub fn record_query_start(&self) -> QueryTimer {
         self.query_stats.total_queries.fetch_add(1, Ordering::Relaxed);
+        self.gauges.active_queries.fetch_add(1, Ordering::Relaxed);
         QueryTimer::new()
     }
 
     /// 记录查询完成
     pub fn record_query_complete(&self, timer: QueryTimer, success: bool) {
         let duration = timer.elapsed();
+        self.gauges.active_queries.fetch_sub(1, Ordering::Relaxed);
         
         if success {
             self.query_stats.success_queries.fetch_add(1, Ordering::Relaxed);
@@ -188,6 +228,24 @@ impl Metrics {
         self.graph_stats.edges_queried.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// 记录算法结果缓存命中
+    pub fn record_result_cache_hit(&self) {
+        self.result_cache_stats.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录算法结果缓存未命中
+    pub fn record_result_cache_miss(&self) {
+        self.result_cache_stats.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录连接打开，返回的守卫被丢弃时记录连接关闭
+    pub fn record_connection_open(self: &Arc<Self>) -> ConnectionGuard {
+        self.gauges.open_connections.fetch_add(1, Ordering::Relaxed);
+        ConnectionGuard {
+            metrics: self.clone(),
+        }
+    }
+
     /// 获取指标快照
     pub fn snapshot(&self) -> MetricsSnapshot {
         let total_queries = self.query_stats.total_queries.load(Ordering::Relaxed);
@@ -237,6 +295,10 @@ impl Metrics {
             edges_inserted: self.graph_stats.edges_inserted.load(Ordering::Relaxed),
             vertices_queried: self.graph_stats.vertices_queried.load(Ordering::Relaxed),
             edges_queried: self.graph_stats.edges_queried.load(Ordering::Relaxed),
+            result_cache_hits: self.result_cache_stats.hits.load(Ordering::Relaxed),
+            result_cache_misses: self.result_cache_stats.misses.load(Ordering::Relaxed),
+            active_queries: self.gauges.active_queries.load(Ordering::Relaxed),
+            open_connections: self.gauges.open_connections.load(Ordering::Relaxed),
             uptime_seconds: uptime,
         }
     }
@@ -298,6 +360,24 @@ impl Metrics {
         content.push_str("# TYPE chaingraph_edges_inserted_total counter\n");
         content.push_str(&format!("chaingraph_edges_inserted_total {}\n", snapshot.edges_inserted));
         
+        // 算法结果缓存指标
+        content.push_str("# HELP chaingraph_result_cache_hits_total Algorithm result cache hits\n");
+        content.push_str("# TYPE chaingraph_result_cache_hits_total counter\n");
+        content.push_str(&format!("chaingraph_result_cache_hits_total {}\n", snapshot.result_cache_hits));
+        
+        content.push_str("# HELP chaingraph_result_cache_misses_total Algorithm result cache misses\n");
+        content.push_str("# TYPE chaingraph_result_cache_misses_total counter\n");
+        content.push_str(&format!("chaingraph_result_cache_misses_total {}\n", snapshot.result_cache_misses));
+        
+        // 实时负载
+        content.push_str("# HELP chaingraph_active_queries Number of queries currently executing\n");
+        content.push_str("# TYPE chaingraph_active_queries gauge\n");
+        content.push_str(&format!("chaingraph_active_queries {}\n", snapshot.active_queries));
+        
+        content.push_str("# HELP chaingraph_open_connections Number of HTTP connections currently being served\n");
+        content.push_str("# TYPE chaingraph_open_connections gauge\n");
+        content.push_str(&format!("chaingraph_open_connections {}\n", snapshot.open_connections));
+        
         // 系统指标
         content.push_str("# HELP chaingraph_uptime_seconds System uptime in seconds\n");
         content.push_str("# TYPE chaingraph_uptime_seconds counter\n");
@@ -323,6 +403,9 @@ impl Metrics {
         self.graph_stats.edges_inserted.store(0, Ordering::Relaxed);
         self.graph_stats.vertices_queried.store(0, Ordering::Relaxed);
         self.graph_stats.edges_queried.store(0, Ordering::Relaxed);
+        
+        self.result_cache_stats.hits.store(0, Ordering::Relaxed);
+        self.result_cache_stats.misses.store(0, Ordering::Relaxed);
     }
 }
 
@@ -349,6 +432,99 @@ impl QueryTimer {
     }
 }
 
+/// 连接守卫，丢弃时减少打开连接数
+pub struct ConnectionGuard {
+    metrics: Arc<Metrics>,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.metrics
+            .gauges
+            .open_connections
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// 每个 2 的幂区间划分的子桶数（相对误差不超过 1/16）
+const HISTOGRAM_SUB_BUCKETS: u64 = 16;
+/// 桶总数：[0, 16) 逐微秒一个桶，之后每个 2 的幂区间 16 个桶
+const HISTOGRAM_BUCKETS: usize = (HISTOGRAM_SUB_BUCKETS * 61) as usize;
+
+/// 延迟直方图（微秒精度，对数分桶）
+///
+/// 用固定数量的原子计数器记录延迟分布，可并发写入；分位数取所在桶的上界
+#[derive(Debug)]
+pub struct LatencyHistogram {
+    buckets: Vec<AtomicU64>,
+    count: AtomicU64,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self {
+            buckets: (0..HISTOGRAM_BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+        }
+    }
+
+    /// 记录一次延迟
+    pub fn record(&self, latency: Duration) {
+        let micros = latency.as_micros().min(u64::MAX as u128) as u64;
+        self.buckets[Self::bucket_index(micros)].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 已记录的样本数
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// 分位数（`quantile` 取 0.0..=1.0），没有样本时返回 0
+    pub fn percentile(&self, quantile: f64) -> Duration {
+        let count = self.count();
+        if count == 0 {
+            return Duration::ZERO;
+        }
+        let rank = ((quantile.clamp(0.0, 1.0) * count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket.load(Ordering::Relaxed);
+            if seen >= rank {
+                return Duration::from_micros(Self::bucket_upper_bound(index));
+            }
+        }
+        Duration::from_micros(u64::MAX)
+    }
+
+    fn bucket_index(micros: u64) -> usize {
+        if micros < HISTOGRAM_SUB_BUCKETS {
+            return micros as usize;
+        }
+        let exponent = 63 - micros.leading_zeros() as u64;
+        let shift = exponent - 4;
+        let sub = (micros >> shift) - HISTOGRAM_SUB_BUCKETS;
+        (HISTOGRAM_SUB_BUCKETS + shift * HISTOGRAM_SUB_BUCKETS + sub) as usize
+    }
+
+    fn bucket_upper_bound(index: usize) -> u64 {
+        let index = index as u64;
+        if index < HISTOGRAM_SUB_BUCKETS {
+            return index;
+        }
+        let shift = (index - HISTOGRAM_SUB_BUCKETS) / HISTOGRAM_SUB_BUCKETS;
+        let sub = (index - HISTOGRAM_SUB_BUCKETS) % HISTOGRAM_SUB_BUCKETS;
+        let upper = ((HISTOGRAM_SUB_BUCKETS + sub + 1) as u128) << shift;
+        (upper - 1).min(u64::MAX as u128) as u64
+    }
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// 全局指标实例
 static METRICS: once_cell::sync::Lazy<Arc<Metrics>> = once_cell::sync::Lazy::new(|| {
     Arc::new(Metrics::new())
@@ -390,5 +566,67 @@ mod tests {
         let prom = metrics.to_prometheus();
         assert!(prom.content.contains("chaingraph_queries_total"));
         assert!(prom.content.contains("chaingraph_buffer_pool_hits_total"));
+        assert!(prom.content.contains("chaingraph_result_cache_hits_total"));
+    }
+
+    #[test]
+    fn test_active_query_gauge() {
+        let metrics = Metrics::new();
+
+        let timer = metrics.record_query_start();
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(metrics.snapshot().active_queries, 1);
+        let prom = metrics.to_prometheus();
+        assert!(prom.content.contains("# TYPE chaingraph_active_queries gauge"));
+        assert!(prom.content.contains("chaingraph_active_queries 1\n"));
+
+        metrics.record_query_complete(timer, true);
+        assert_eq!(metrics.snapshot().active_queries, 0);
+        assert!(metrics.to_prometheus().content.contains("chaingraph_active_queries 0\n"));
+    }
+
+    #[test]
+    fn test_open_connection_gauge() {
+        let metrics = Arc::new(Metrics::new());
+
+        let guard = metrics.record_connection_open();
+        assert_eq!(metrics.snapshot().open_connections, 1);
+        assert!(metrics
+            .to_prometheus()
+            .content
+            .contains("# TYPE chaingraph_open_connections gauge"));
+
+        drop(guard);
+        assert_eq!(metrics.snapshot().open_connections, 0);
+    }
+
+    #[test]
+    fn test_latency_histogram_percentiles() {
+        let histogram = LatencyHistogram::new();
+        assert_eq!(histogram.percentile(0.5), Duration::ZERO);
+
+        for micros in 1..=1000 {
+            histogram.record(Duration::from_micros(micros));
+        }
+        assert_eq!(histogram.count(), 1000);
+
+        // 分位数取桶上界，误差不超过 1/16
+        for (quantile, exact) in [(0.5, 500.0), (0.95, 950.0), (0.99, 990.0)] {
+            let value = histogram.percentile(quantile).as_micros() as f64;
+            assert!(
+                value >= exact && value <= exact * 17.0 / 16.0,
+                "{}: {}",
+                quantile,
+                value
+            );
+        }
+        assert_eq!(histogram.percentile(0.0), Duration::from_micros(1));
+
+        // 小于 16 微秒的延迟精确记录，极大值也不越界
+        let small = LatencyHistogram::new();
+        small.record(Duration::from_micros(7));
+        assert_eq!(small.percentile(1.0), Duration::from_micros(7));
+        small.record(Duration::from_secs(u64::MAX));
+        assert!(small.percentile(1.0) > Duration::from_secs(3600));
     }
 }
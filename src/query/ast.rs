@@ -59,6 +59,38 @@ pub enum GqlStatement {
     Session(SessionStatement),
     /// Transaction statement
     Transaction(TransactionStatement),
+    /// EXPLAIN [ANALYZE] statement
+    Explain(ExplainStatement),
+}
+
+impl GqlStatement {
+    /// Whether the statement can remove data in bulk: a DELETE whose matched
+    /// elements are not narrowed by a WHERE clause, or DROP GRAPH.
+    pub fn is_destructive(&self) -> bool {
+        match self {
+            GqlStatement::Delete(stmt) => stmt
+                .match_clause
+                .as_ref()
+                .is_none_or(|m| m.where_clause.is_none()),
+            GqlStatement::DropGraph(_) => true,
+            GqlStatement::Explain(stmt) => stmt.analyze && stmt.statement.is_destructive(),
+            _ => false,
+        }
+    }
+}
+
+/// EXPLAIN statement - show the operator plan of a statement.
+/// With ANALYZE the statement is executed and each operator is annotated
+/// with its actual row count, scan counters and timing.
+/// Examples:
+///   EXPLAIN MATCH (a:Account)-[:Transfer]->(b) RETURN b
+///   EXPLAIN ANALYZE MATCH (a:Account)-[:Transfer]->(b) RETURN b
+#[derive(Debug, Clone)]
+pub struct ExplainStatement {
+    /// Execute the statement and report runtime counters
+    pub analyze: bool,
+    /// Statement being explained
+    pub statement: Box<GqlStatement>,
 }
 
 // ============================================================================
@@ -75,6 +107,8 @@ pub struct MatchStatement {
     pub match_mode: Option<MatchMode>,
     /// Graph pattern to match
     pub graph_pattern: GraphPattern,
+    /// Planner hints (`USING INDEX` / `USING SCAN`)
+    pub hints: Vec<MatchHint>,
     /// WHERE clause
     pub where_clause: Option<Expression>,
     /// RETURN clause
@@ -93,6 +127,7 @@ impl MatchStatement {
             optional: false,
             match_mode: None,
             graph_pattern,
+            hints: Vec::new(),
             where_clause: None,
             return_clause: Vec::new(),
             order_by: None,
@@ -117,6 +152,34 @@ impl MatchStatement {
     }
 }
 
+/// Planner hint following the MATCH pattern, deciding how a node variable's
+/// candidates are found
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatchHint {
+    /// `USING INDEX n:Account(address)` - look candidates up in the property
+    /// index, using an equality on `n.address` from the pattern or WHERE
+    Index {
+        variable: String,
+        label: VertexLabel,
+        property: String,
+    },
+    /// `USING SCAN n:Account` - scan the label even if an index applies
+    Scan { variable: String, label: VertexLabel },
+}
+
+impl fmt::Display for MatchHint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchHint::Index {
+                variable,
+                label,
+                property,
+            } => write!(f, "USING INDEX {}:{}({})", variable, label, property),
+            MatchHint::Scan { variable, label } => write!(f, "USING SCAN {}:{}", variable, label),
+        }
+    }
+}
+
 /// Match mode (ISO GQL 39075)
 /// matchMode: repeatableElementsMatchMode | differentEdgesMatchMode
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -167,6 +230,29 @@ impl GraphPattern {
         self.keep_clause = Some(keep);
         self
     }
+
+    /// Named path, node and edge variables in order of first appearance
+    pub fn variables(&self) -> Vec<String> {
+        let mut vars: Vec<String> = Vec::new();
+        let mut push = |var: &Option<String>| {
+            if let Some(var) = var {
+                if !vars.contains(var) {
+                    vars.push(var.clone());
+                }
+            }
+        };
+        for path in &self.paths {
+            push(&path.variable);
+            for element in &path.elements {
+                match element {
+                    PathElement::Node(node) => push(&node.variable),
+                    PathElement::Edge(edge) => push(&edge.variable),
+                    PathElement::ParenthesizedPath(paren) => push(&paren.subpath_variable),
+                }
+            }
+        }
+        vars
+    }
 }
 
 /// KEEP clause for path filtering (ISO GQL 39075)
@@ -241,6 +327,85 @@ impl Default for PathPattern {
     }
 }
 
+impl fmt::Display for PathPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(ref var) = self.variable {
+            write!(f, "{} = ", var)?;
+        }
+        if let Some(ref search) = self.search_prefix {
+            write!(f, "{} ", search)?;
+        }
+        if let Some(ref mode) = self.path_mode {
+            write!(f, "{} ", mode)?;
+        }
+        write_elements(f, &self.elements)?;
+        if let Some(ref quantifier) = self.quantifier {
+            write!(f, "{}", quantifier)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_elements(f: &mut fmt::Formatter<'_>, elements: &[PathElement]) -> fmt::Result {
+    for element in elements {
+        match element {
+            PathElement::Node(node) => write!(f, "{}", node)?,
+            PathElement::Edge(edge) => write!(f, "{}", edge)?,
+            PathElement::ParenthesizedPath(paren) => {
+                write!(f, "(")?;
+                if let Some(ref var) = paren.subpath_variable {
+                    write!(f, "{} = ", var)?;
+                }
+                let (alternatives, separator) = match &paren.path_pattern {
+                    PathPatternExpression::Term(elements) => (std::slice::from_ref(elements), ""),
+                    PathPatternExpression::Union(alternatives) => (alternatives.as_slice(), " | "),
+                    PathPatternExpression::MultisetAlternation(alternatives) => {
+                        (alternatives.as_slice(), " |+| ")
+                    }
+                };
+                for (i, alternative) in alternatives.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "{}", separator)?;
+                    }
+                    write_elements(f, alternative)?;
+                }
+                write!(f, ")")?;
+                if let Some(ref quantifier) = paren.quantifier {
+                    write!(f, "{}", quantifier)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write ` {key: value, ...}` for a pattern property filter
+fn write_properties(
+    f: &mut fmt::Formatter<'_>,
+    properties: &[(String, PropertyValue)],
+) -> fmt::Result {
+    if properties.is_empty() {
+        return Ok(());
+    }
+    let items: Vec<String> = properties
+        .iter()
+        .map(|(k, v)| format!("{}: {}", k, literal_text(v)))
+        .collect();
+    write!(f, " {{{}}}", items.join(", "))
+}
+
+/// GQL-like text of a literal value
+fn literal_text(value: &PropertyValue) -> String {
+    match value {
+        PropertyValue::String(s) => format!("'{}'", s),
+        PropertyValue::Int(n) | PropertyValue::Integer(n) => n.to_string(),
+        PropertyValue::Float(x) => x.to_string(),
+        PropertyValue::Bool(b) | PropertyValue::Boolean(b) => b.to_string(),
+        PropertyValue::Null => "NULL".to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
 /// Path element: node, edge, or parenthesized path pattern
 #[derive(Debug, Clone)]
 pub enum PathElement {
@@ -309,9 +474,10 @@ impl Default for PathPatternExpression {
 
 /// Path mode prefix (ISO GQL 39075)
 /// pathModePrefix: WALK | TRAIL | SIMPLE | ACYCLIC
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum PathMode {
     /// WALK - any path, vertices and edges can repeat (default)
+    #[default]
     Walk,
     /// TRAIL - edges must be distinct, vertices may repeat
     Trail,
@@ -321,12 +487,6 @@ pub enum PathMode {
     Acyclic,
 }
 
-impl Default for PathMode {
-    fn default() -> Self {
-        PathMode::Walk
-    }
-}
-
 impl fmt::Display for PathMode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -418,8 +578,14 @@ pub struct NodePattern {
     pub variable: Option<String>,
     /// Label expression
     pub label_expr: Option<LabelExpression>,
+    /// Exact label matching (`(n:=Account)`): the vertex may carry no labels
+    /// beyond those named in `label_expr`. Default is subset matching.
+    pub exact_labels: bool,
     /// Property filter
     pub properties: Vec<(String, PropertyValue)>,
+    /// Property values given as expressions (INSERT only, e.g.
+    /// `{address: r.from}`), evaluated before the element is written
+    pub property_exprs: Vec<(String, Expression)>,
     /// WHERE predicate within the pattern
     pub where_clause: Option<Box<Expression>>,
 }
@@ -429,7 +595,9 @@ impl NodePattern {
         NodePattern {
             variable: None,
             label_expr: None,
+            exact_labels: false,
             properties: Vec::new(),
+            property_exprs: Vec::new(),
             where_clause: None,
         }
     }
@@ -473,6 +641,25 @@ impl NodePattern {
             .map(|e| e.to_vertex_labels())
             .unwrap_or_default()
     }
+
+    /// Check a vertex's label set against the label expression.
+    ///
+    /// Subset semantics by default: `(n:Account)` matches any vertex that has
+    /// the Account label. With `exact_labels`, every label of the vertex must
+    /// also be named in the expression.
+    pub fn matches_labels(&self, labels: &[VertexLabel]) -> bool {
+        let Some(ref expr) = self.label_expr else {
+            return true;
+        };
+        if !expr.matches_vertex_labels(labels) {
+            return false;
+        }
+        if self.exact_labels {
+            let named = expr.to_vertex_labels();
+            return labels.iter().all(|label| named.contains(label));
+        }
+        true
+    }
 }
 
 impl Default for NodePattern {
@@ -497,6 +684,8 @@ pub struct EdgePattern {
     pub direction: EdgeDirection,
     /// Property filter
     pub properties: Vec<(String, PropertyValue)>,
+    /// Property values given as expressions (INSERT only)
+    pub property_exprs: Vec<(String, Expression)>,
     /// Quantifier for variable-length paths
     pub quantifier: Option<PatternQuantifier>,
     /// WHERE predicate within the pattern
@@ -510,6 +699,7 @@ impl EdgePattern {
             label_expr: None,
             direction,
             properties: Vec::new(),
+            property_exprs: Vec::new(),
             quantifier: None,
             where_clause: None,
         }
@@ -555,6 +745,60 @@ impl EdgePattern {
             .map(|e| e.to_edge_labels())
             .unwrap_or_default()
     }
+
+    /// Check an edge's label against the label expression; no expression
+    /// matches every edge.
+    pub fn matches_label(&self, label: &EdgeLabel) -> bool {
+        self.label_expr
+            .as_ref()
+            .is_none_or(|expr| expr.matches_edge_label(label))
+    }
+}
+
+impl fmt::Display for NodePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(")?;
+        if let Some(ref var) = self.variable {
+            write!(f, "{}", var)?;
+        }
+        if let Some(ref labels) = self.label_expr {
+            let text = labels.to_string();
+            if self.exact_labels {
+                write!(f, ":={}", text.trim_start_matches(':'))?;
+            } else {
+                write!(f, "{}", text)?;
+            }
+        }
+        write_properties(f, &self.properties)?;
+        write!(f, ")")
+    }
+}
+
+impl fmt::Display for EdgePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (left, right) = match self.direction {
+            EdgeDirection::Outgoing => ("-", "->"),
+            EdgeDirection::Incoming => ("<-", "-"),
+            EdgeDirection::Undirected => ("~", "~"),
+            EdgeDirection::AnyDirection => ("-", "-"),
+            EdgeDirection::LeftOrUndirected => ("<~", "~"),
+            EdgeDirection::UndirectedOrRight => ("~", "~>"),
+            EdgeDirection::LeftOrRight => ("<-", "->"),
+        };
+        write!(f, "{}[", left)?;
+        if let Some(ref var) = self.variable {
+            write!(f, "{}", var)?;
+        }
+        if let Some(ref labels) = self.label_expr {
+            write!(f, "{}", labels)?;
+        }
+        write_properties(f, &self.properties)?;
+        write!(f, "]{}", right)?;
+        if let Some(ref quantifier) = self.quantifier {
+            write!(f, "{}", quantifier)?;
+        }
+        Ok(())
+    }
 }
 
 /// Edge direction (ISO GQL 39075)
@@ -577,6 +821,19 @@ pub enum EdgeDirection {
     LeftOrRight,
 }
 
+impl EdgeDirection {
+    /// Direction of the same edge pattern when the path is traversed backwards
+    pub fn reversed(self) -> Self {
+        match self {
+            EdgeDirection::Outgoing => EdgeDirection::Incoming,
+            EdgeDirection::Incoming => EdgeDirection::Outgoing,
+            EdgeDirection::LeftOrUndirected => EdgeDirection::UndirectedOrRight,
+            EdgeDirection::UndirectedOrRight => EdgeDirection::LeftOrUndirected,
+            other => other,
+        }
+    }
+}
+
 impl fmt::Display for EdgeDirection {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -627,16 +884,67 @@ impl LabelExpression {
         }
     }
 
+    /// Evaluate the expression against a vertex's label set
+    pub fn matches_vertex_labels(&self, labels: &[VertexLabel]) -> bool {
+        match self {
+            LabelExpression::Label(label) => labels.contains(label),
+            LabelExpression::EdgeLabel(_) => false,
+            LabelExpression::Wildcard => !labels.is_empty(),
+            LabelExpression::Negation(expr) => !expr.matches_vertex_labels(labels),
+            LabelExpression::Conjunction(exprs) => {
+                exprs.iter().all(|e| e.matches_vertex_labels(labels))
+            }
+            LabelExpression::Disjunction(exprs) => {
+                exprs.iter().any(|e| e.matches_vertex_labels(labels))
+            }
+        }
+    }
+
     /// Convert to edge labels (for compatibility)
+    ///
+    /// Edge patterns parse their labels with the vertex label rules, so a
+    /// name like `Call` arrives as `Label(Custom("Call"))` and is mapped back
+    /// to the built-in edge label by name here.
     pub fn to_edge_labels(&self) -> Vec<EdgeLabel> {
         match self {
             LabelExpression::EdgeLabel(label) => vec![label.clone()],
+            LabelExpression::Label(label) => vec![Self::edge_label_named(label.as_str())],
             LabelExpression::Conjunction(exprs) | LabelExpression::Disjunction(exprs) => {
                 exprs.iter().flat_map(|e| e.to_edge_labels()).collect()
             }
             _ => vec![],
         }
     }
+
+    /// Evaluate the expression against an edge's single label.
+    ///
+    /// `%` matches any edge, so a conjunction of two different names never
+    /// matches while `!Approve` matches every non-Approve edge.
+    pub fn matches_edge_label(&self, label: &EdgeLabel) -> bool {
+        match self {
+            LabelExpression::EdgeLabel(expected) => expected == label,
+            LabelExpression::Label(name) => &Self::edge_label_named(name.as_str()) == label,
+            LabelExpression::Wildcard => true,
+            LabelExpression::Negation(expr) => !expr.matches_edge_label(label),
+            LabelExpression::Conjunction(exprs) => {
+                exprs.iter().all(|e| e.matches_edge_label(label))
+            }
+            LabelExpression::Disjunction(exprs) => {
+                exprs.iter().any(|e| e.matches_edge_label(label))
+            }
+        }
+    }
+
+    fn edge_label_named(name: &str) -> EdgeLabel {
+        match name.to_uppercase().as_str() {
+            "TRANSFER" => EdgeLabel::Transfer,
+            "CALL" => EdgeLabel::Call,
+            "CREATE" => EdgeLabel::Create,
+            "APPROVE" => EdgeLabel::Approve,
+            "INBLOCK" => EdgeLabel::InBlock,
+            _ => EdgeLabel::Custom(name.to_string()),
+        }
+    }
 }
 
 impl fmt::Display for LabelExpression {
@@ -671,6 +979,10 @@ pub enum Expression {
     Variable(String),
     /// Property access (variable, property_name)
     Property(String, String),
+    /// Property access on a computed element, e.g. `p[0].address` or `last(p).address`
+    ElementProperty(Box<Expression>, String),
+    /// Subscript into a path or list (`p[0]`, `p[-1]`)
+    Index(Box<Expression>, Box<Expression>),
     /// Function call
     FunctionCall(String, Vec<Expression>),
     /// Binary operation
@@ -704,6 +1016,38 @@ pub enum Expression {
     Null,
 }
 
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |exprs: &[Expression]| {
+            exprs
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            Expression::Literal(value) => write!(f, "{}", literal_text(value)),
+            Expression::Variable(name) => write!(f, "{}", name),
+            Expression::Property(var, prop) => write!(f, "{}.{}", var, prop),
+            Expression::ElementProperty(base, prop) => write!(f, "{}.{}", base, prop),
+            Expression::Index(base, index) => write!(f, "{}[{}]", base, index),
+            Expression::FunctionCall(name, args) => write!(f, "{}({})", name, join(args)),
+            Expression::BinaryOp(lhs, BinaryOperator::IsNull, _) => write!(f, "{} IS NULL", lhs),
+            Expression::BinaryOp(lhs, BinaryOperator::IsNotNull, _) => {
+                write!(f, "{} IS NOT NULL", lhs)
+            }
+            Expression::BinaryOp(lhs, op, rhs) => write!(f, "({} {} {})", lhs, op, rhs),
+            Expression::UnaryOp(UnaryOperator::Neg, expr) => write!(f, "-{}", expr),
+            Expression::UnaryOp(UnaryOperator::Not, expr) => write!(f, "NOT {}", expr),
+            Expression::UnaryOp(op, expr) => write!(f, "{} {}", expr, op),
+            Expression::List(items) => write!(f, "[{}]", join(items)),
+            Expression::Parameter(name) => write!(f, "${}", name),
+            Expression::Null => write!(f, "NULL"),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
 /// Binary operators
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BinaryOperator {
@@ -846,6 +1190,19 @@ pub struct InsertStatement {
     pub nodes: Vec<NodePattern>,
     /// Edges to insert
     pub edges: Vec<InsertEdge>,
+    /// RETURN clause over the inserted elements (empty when absent)
+    pub return_clause: Vec<ReturnItem>,
+    /// Leading `UNWIND list AS var`: the insert runs once per list item
+    pub unwind: Option<UnwindClause>,
+}
+
+/// `UNWIND expression AS variable`
+#[derive(Debug, Clone)]
+pub struct UnwindClause {
+    /// List to iterate (usually a parameter such as `$rows`)
+    pub expression: Expression,
+    /// Variable bound to the current item
+    pub variable: String,
 }
 
 /// Edge to insert with source and target references
@@ -864,9 +1221,11 @@ pub struct InsertEdge {
 // ============================================================================
 
 /// DELETE statement
-/// deleteStatement: (DETACH | NODETACH)? DELETE deleteItemList
+/// deleteStatement: [MATCH graphPattern [WHERE expr]] (DETACH | NODETACH)? DELETE deleteItemList
 #[derive(Debug, Clone)]
 pub struct DeleteStatement {
+    /// Preceding MATCH that binds the variables to delete
+    pub match_clause: Option<Box<MatchStatement>>,
     /// Variables to delete
     pub variables: Vec<String>,
     /// DETACH DELETE mode (also delete connected edges)
@@ -878,13 +1237,19 @@ pub struct DeleteStatement {
 // ============================================================================
 
 /// SET statement
+/// setStatement: [MATCH graphPattern [WHERE expr]] SET setItemList
 #[derive(Debug, Clone)]
 pub struct SetStatement {
+    /// Preceding MATCH whose bindings the items are applied to
+    pub match_clause: Option<Box<MatchStatement>>,
     /// Set items
     pub items: Vec<SetItem>,
 }
 
 /// SET item
+///
+/// When the variable is bound to a path, the item is applied to every
+/// vertex along the path.
 #[derive(Debug, Clone)]
 pub enum SetItem {
     /// SET n.property = value
@@ -904,13 +1269,20 @@ pub enum SetItem {
 // ============================================================================
 
 /// REMOVE statement
+/// removeStatement: [MATCH graphPattern [WHERE expr]] REMOVE removeItemList
 #[derive(Debug, Clone)]
 pub struct RemoveStatement {
+    /// Preceding MATCH whose bindings the items are applied to
+    pub match_clause: Option<Box<MatchStatement>>,
     /// Remove items
     pub items: Vec<RemoveItem>,
 }
 
 /// REMOVE item
+///
+/// Vertices carry exactly one label, so removing the label a vertex has is
+/// an error (relabel with `SET n:Label` instead); removing a label it does
+/// not have changes nothing.
 #[derive(Debug, Clone)]
 pub enum RemoveItem {
     /// REMOVE n.property
@@ -1249,6 +1621,8 @@ pub enum ShowType {
     Indexes,
     /// SHOW CONSTRAINTS - list all constraints
     Constraints,
+    /// SHOW TRANSACTIONS - list open transactions
+    Transactions,
 }
 
 /// DESCRIBE statement - show details of a database object
@@ -1371,6 +1745,34 @@ mod tests {
         assert_eq!(node.labels().len(), 1);
     }
 
+    #[test]
+    fn test_node_label_subset_matching() {
+        let node = NodePattern::new().with_label(VertexLabel::Account);
+
+        assert!(node.matches_labels(&[VertexLabel::Account]));
+        assert!(node.matches_labels(&[VertexLabel::Account, VertexLabel::Token]));
+        assert!(!node.matches_labels(&[VertexLabel::Token]));
+    }
+
+    #[test]
+    fn test_node_label_exact_matching() {
+        let mut node = NodePattern::new().with_labels(vec![VertexLabel::Account, VertexLabel::Token]);
+        node.exact_labels = true;
+
+        assert!(node.matches_labels(&[VertexLabel::Account, VertexLabel::Token]));
+        assert!(!node.matches_labels(&[VertexLabel::Account]));
+        assert!(!node.matches_labels(&[
+            VertexLabel::Account,
+            VertexLabel::Token,
+            VertexLabel::Contract
+        ]));
+
+        let mut single = NodePattern::new().with_label(VertexLabel::Account);
+        single.exact_labels = true;
+        assert!(single.matches_labels(&[VertexLabel::Account]));
+        assert!(!single.matches_labels(&[VertexLabel::Account, VertexLabel::Token]));
+    }
+
     #[test]
     fn test_edge_pattern_builder() {
         let edge = EdgePattern::new(EdgeDirection::Outgoing)
@@ -0,0 +1,79 @@
+//! Result cache for algorithm `CALL`s
+//!
+//! Traces, clustering and max-flow over a mostly-static dataset are often
+//! requested repeatedly with identical arguments. Entries are keyed by the
+//! call and the graph instance and version (`Graph::instance_id`,
+//! `Graph::version`), so any write makes older entries unreachable; they age
+//! out of the LRU without explicit invalidation.
+
+use super::executor::QueryResult;
+use crate::metrics;
+use lru::LruCache;
+use parking_lot::Mutex;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Identifies one cached result: the graph instance and version it was
+/// computed against and a canonical rendering of the call (procedure,
+/// arguments, and the executor settings that shape its output)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ResultKey {
+    pub graph: u64,
+    pub version: u64,
+    pub call: String,
+}
+
+/// Bounded LRU cache of algorithm results, shared by the executors of one
+/// server. Hits and misses are counted here and in the global metrics.
+pub struct ResultCache {
+    entries: Mutex<LruCache<ResultKey, QueryResult>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ResultCache {
+    /// Keep at most `capacity` results (at least one)
+    pub fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn get(&self, key: &ResultKey) -> Option<QueryResult> {
+        let hit = self.entries.lock().get(key).cloned();
+        if hit.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            metrics::global_metrics().record_result_cache_hit();
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            metrics::global_metrics().record_result_cache_miss();
+        }
+        hit
+    }
+
+    pub(crate) fn insert(&self, key: ResultKey, result: QueryResult) {
+        self.entries.lock().put(key, result);
+    }
+
+    /// Cached results, including ones made stale by later writes
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Lookups answered from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Lookups that had to run the algorithm
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
@@ -3,12 +3,19 @@
 //! Executes GQL AST and returns query results.
 
 use super::ast::*;
+use super::cache::{ResultCache, ResultKey};
+use super::parser::GqlParser;
+use crate::algorithm::{CancellationToken, TraceDirection, DEFAULT_MAX_FLOW_EDGES};
 use crate::error::{Error, Result};
-use crate::graph::{Edge, Graph, GraphCatalog, Vertex, VertexId};
+use crate::graph::{
+    Edge, EdgeId, Graph, GraphCatalog, ReadSnapshot, TransactionMode, Vertex, VertexId,
+};
 use crate::metrics;
 use crate::types::{EdgeLabel, PropertyValue, TokenAmount, VertexLabel};
+use chrono::{DateTime, FixedOffset};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 
 /// Query result
@@ -57,10 +64,38 @@ pub struct QueryStats {
     pub edges_scanned: usize,
     pub rows_returned: usize,
     pub execution_time_ms: u64,
+    /// Rows were cut off at the executor's `max_rows` cap
+    #[serde(default)]
+    pub truncated: bool,
+    /// `Graph::version` of the current graph after the statement ran
+    #[serde(default)]
+    pub graph_version: u64,
+}
+
+/// Outcome of a pattern-based bulk delete (`delete_matches`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeleteSummary {
+    /// Vertices removed (or that would be removed in preview mode)
+    pub vertices: usize,
+    /// Edges removed, including edges detached from deleted vertices
+    pub edges: usize,
+    pub stats: QueryStats,
 }
 
 type Bindings = HashMap<String, BindingValue>;
 
+/// A variable-length expansion result: vertex path, end vertex and traversed edges
+type ExpandedPath = (Vec<VertexId>, Vertex, Vec<Edge>);
+
+/// How a hinted node variable's candidates are found
+#[derive(Debug, Clone)]
+enum ScanChoice {
+    /// `USING INDEX`: the vertices the property index lookup returned
+    Index(Vec<VertexId>),
+    /// `USING SCAN`: a label scan, ignoring any applicable index
+    Scan(VertexLabel),
+}
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 enum BindingValue {
@@ -70,14 +105,464 @@ enum BindingValue {
     Path(Vec<VertexId>),
 }
 
+/// A graph element targeted by a data-modifying statement
+#[derive(Debug, Clone, Copy)]
+enum BoundElement {
+    Vertex(VertexId),
+    Edge(EdgeId),
+}
+
+/// One step of a MATCH pipeline with its runtime counters (EXPLAIN ANALYZE)
+#[derive(Debug, Clone)]
+struct PlanOperator {
+    operator: &'static str,
+    detail: String,
+    rows: usize,
+    vertices_scanned: usize,
+    edges_scanned: usize,
+    elapsed: std::time::Duration,
+}
+
+/// Snapshot of the scan counters and clock taken when an operator starts
+struct OperatorProbe {
+    started: std::time::Instant,
+    vertices_scanned: usize,
+    edges_scanned: usize,
+}
+
+impl OperatorProbe {
+    fn start(stats: &QueryStats) -> Self {
+        Self {
+            started: std::time::Instant::now(),
+            vertices_scanned: stats.vertices_scanned,
+            edges_scanned: stats.edges_scanned,
+        }
+    }
+
+    fn finish(
+        self,
+        operator: &'static str,
+        detail: String,
+        rows: usize,
+        stats: &QueryStats,
+    ) -> PlanOperator {
+        PlanOperator {
+            operator,
+            detail,
+            rows,
+            vertices_scanned: stats.vertices_scanned - self.vertices_scanned,
+            edges_scanned: stats.edges_scanned - self.edges_scanned,
+            elapsed: self.started.elapsed(),
+        }
+    }
+}
+
+/// Default cap on intermediate MATCH bindings held in memory
+pub const DEFAULT_MAX_BINDINGS: usize = 1_000_000;
+
+/// Default graph size up to which an unlabeled node pattern may scan every
+/// vertex without a LIMIT
+pub const DEFAULT_MAX_UNLABELED_SCAN: usize = 100_000;
+
+/// Default cap on rows returned by a MATCH or SELECT without LIMIT
+pub const DEFAULT_MAX_ROWS: usize = 100_000;
+
+/// Default cap on variable-length expansion steps across one MATCH pattern
+pub const DEFAULT_MAX_EXPANSION_STEPS: usize = 1_000_000;
+
+/// Session parameter toggling case-insensitive string equality
+const CASE_INSENSITIVE_SETTING: &str = "case_insensitive";
+
+/// Session parameter setting the decimal places of floats in results
+const FLOAT_PRECISION_SETTING: &str = "float_precision";
+
+/// Upper bound for `float_precision`; f64 carries at most 17 significant digits
+const MAX_FLOAT_PRECISION: u32 = 17;
+
+/// Upper bound for the bucket count of `db.property_histogram`
+const MAX_HISTOGRAM_BUCKETS: i64 = 1000;
+
+/// Session parameter choosing how INSERT records a missing transfer amount
+const MISSING_AMOUNT_SETTING: &str = "missing_amount";
+
+/// What INSERT stores for a Transfer edge written without `amount` / `value`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissingAmount {
+    /// Store an amount of 0
+    #[default]
+    Zero,
+    /// Leave the amount unset, so volume analytics can tell the edge apart
+    /// from a transfer of exactly 0
+    Absent,
+}
+
+impl MissingAmount {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "zero" | "0" => Some(MissingAmount::Zero),
+            "absent" | "null" => Some(MissingAmount::Absent),
+            _ => None,
+        }
+    }
+}
+
+fn is_case_insensitive_setting(name: &str) -> bool {
+    name.eq_ignore_ascii_case(CASE_INSENSITIVE_SETTING)
+}
+
+/// Render one result value under the session's output settings
+fn render_value(
+    value: &mut PropertyValue,
+    time_zone: Option<&FixedOffset>,
+    precision: Option<u32>,
+) {
+    match value {
+        PropertyValue::Timestamp(secs) => {
+            if let Some(offset) = time_zone {
+                if let Some(utc) = DateTime::from_timestamp(*secs, 0) {
+                    *value = PropertyValue::String(utc.with_timezone(offset).to_rfc3339());
+                }
+            }
+        }
+        PropertyValue::Float(f) => {
+            if let Some(digits) = precision {
+                let scale = 10f64.powi(digits as i32);
+                let rounded = (*f * scale).round() / scale;
+                // Very large values overflow when scaled; keep them as they are
+                if rounded.is_finite() {
+                    *f = rounded;
+                }
+            }
+        }
+        PropertyValue::List(items) => {
+            for item in items {
+                render_value(item, time_zone, precision);
+            }
+        }
+        PropertyValue::Map(entries) => {
+            for item in entries.values_mut() {
+                render_value(item, time_zone, precision);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Parse a session time zone: `UTC`, `Z`, or a fixed offset such as
+/// `+08:00`, `-0530` or `UTC+8`
+fn parse_time_zone(tz: &str) -> Result<FixedOffset> {
+    let invalid = || Error::QueryError(format!("Invalid time zone: {}", tz));
+    let trimmed = tz.trim();
+    let offset = trimmed
+        .strip_prefix("UTC")
+        .or_else(|| trimmed.strip_prefix("utc"))
+        .unwrap_or(trimmed);
+    if offset.is_empty() || offset.eq_ignore_ascii_case("Z") {
+        return Ok(FixedOffset::east_opt(0).expect("zero offset"));
+    }
+    let (sign, rest) = match offset.as_bytes()[0] {
+        b'+' => (1, &offset[1..]),
+        b'-' => (-1, &offset[1..]),
+        _ => return Err(invalid()),
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((h, m)) => (h, m),
+        None if rest.len() == 4 => rest.split_at(2),
+        None => (rest, "0"),
+    };
+    let hours: i32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 14 || minutes >= 60 {
+        return Err(invalid());
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).ok_or_else(invalid)
+}
+
 /// Query executor
 pub struct QueryExecutor {
     catalog: Arc<GraphCatalog>,
+    cancel: CancellationToken,
+    max_bindings: usize,
+    /// Largest graph an unlabeled node pattern may scan in full
+    max_unlabeled_scan: usize,
+    /// LIMIT pushed down into the single-node scan of the current MATCH
+    scan_limit: parking_lot::Mutex<Option<usize>>,
+    /// `USING` hints of the current MATCH, by node variable
+    scan_hints: parking_lot::Mutex<HashMap<String, ScanChoice>>,
+    /// Flow edges listed by `CALL max_flow`
+    max_flow_edges: usize,
+    /// Rows a MATCH or SELECT without LIMIT may return
+    max_rows: usize,
+    /// Variable-length expansion steps one MATCH pattern may take in total
+    max_expansion_steps: usize,
+    /// Expansion steps taken so far by the current MATCH
+    expansion_steps: AtomicUsize,
+    /// Snapshot pinned by the caller; otherwise one is taken per statement
+    pinned_snapshot: Option<ReadSnapshot>,
+    /// Snapshot of the statement currently executing
+    snapshot: parking_lot::Mutex<Option<ReadSnapshot>>,
+    /// Compare strings case-insensitively in `=` / `<>`
+    case_insensitive: AtomicBool,
+    /// Values set with `SESSION SET name = value`, resolved as `$name`
+    parameters: parking_lot::RwLock<HashMap<String, PropertyValue>>,
+    /// Zone set with `SESSION SET TIME ZONE`; timestamps in results are
+    /// rendered in it, otherwise left as raw UTC epoch seconds
+    time_zone: parking_lot::RwLock<Option<FixedOffset>>,
+    /// Decimal places floats in results are rounded to; full precision if unset
+    float_precision: parking_lot::RwLock<Option<u32>>,
+    /// Amount INSERT stores when a Transfer edge has none
+    missing_amount: parking_lot::RwLock<MissingAmount>,
+    /// Shared cache for algorithm `CALL` results, if enabled
+    result_cache: Option<Arc<ResultCache>>,
+    /// Transaction started by this executor and not yet committed or rolled back
+    transaction: parking_lot::Mutex<Option<u64>>,
 }
 
 impl QueryExecutor {
     pub fn new(catalog: Arc<GraphCatalog>) -> Self {
-        Self { catalog }
+        Self {
+            catalog,
+            cancel: CancellationToken::new(),
+            max_bindings: DEFAULT_MAX_BINDINGS,
+            max_unlabeled_scan: DEFAULT_MAX_UNLABELED_SCAN,
+            scan_limit: parking_lot::Mutex::new(None),
+            scan_hints: parking_lot::Mutex::new(HashMap::new()),
+            max_flow_edges: DEFAULT_MAX_FLOW_EDGES,
+            max_rows: DEFAULT_MAX_ROWS,
+            max_expansion_steps: DEFAULT_MAX_EXPANSION_STEPS,
+            expansion_steps: AtomicUsize::new(0),
+            pinned_snapshot: None,
+            snapshot: parking_lot::Mutex::new(None),
+            case_insensitive: AtomicBool::new(false),
+            parameters: parking_lot::RwLock::new(HashMap::new()),
+            time_zone: parking_lot::RwLock::new(None),
+            float_precision: parking_lot::RwLock::new(None),
+            missing_amount: parking_lot::RwLock::new(MissingAmount::Zero),
+            result_cache: None,
+            transaction: parking_lot::Mutex::new(None),
+        }
+    }
+
+    /// Attach a cancellation token checked by traversals and graph algorithms.
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Abort a MATCH once its intermediate bindings exceed `limit` rows,
+    /// instead of growing memory without bound.
+    pub fn with_max_bindings(mut self, limit: usize) -> Self {
+        self.max_bindings = limit;
+        self
+    }
+
+    /// Reject node patterns without a label, such as `MATCH (n)`, once the
+    /// graph holds more than `limit` vertices, unless a LIMIT bounds the
+    /// scan. Pass `usize::MAX` to always allow full scans.
+    pub fn with_max_unlabeled_scan(mut self, limit: usize) -> Self {
+        self.max_unlabeled_scan = limit;
+        self
+    }
+
+    /// List at most `limit` flow edges in `CALL max_flow`, largest first;
+    /// a trailing `truncated` row tells whether any were left out.
+    pub fn with_max_flow_edges(mut self, limit: usize) -> Self {
+        self.max_flow_edges = limit;
+        self
+    }
+
+    /// Return at most `limit` rows from a MATCH or SELECT that has no LIMIT
+    /// of its own, setting `QueryStats::truncated` when rows were dropped.
+    /// An explicit LIMIT is always honored as written.
+    pub fn with_max_rows(mut self, limit: usize) -> Self {
+        self.max_rows = limit;
+        self
+    }
+
+    /// Abort a MATCH once its variable-length segments have together
+    /// visited more than `limit` vertices. The budget spans the whole
+    /// pattern, so chained segments such as `->{1,5}(b)->{1,5}` that
+    /// multiply each other's work are caught, not just one long segment.
+    pub fn with_max_expansion_steps(mut self, limit: usize) -> Self {
+        self.max_expansion_steps = limit;
+        self
+    }
+
+    /// Cut `rows` down to `max_rows` unless the statement has its own LIMIT
+    fn cap_rows(&self, rows: &mut Vec<Vec<ResultValue>>, has_limit: bool, stats: &mut QueryStats) {
+        if !has_limit && rows.len() > self.max_rows {
+            rows.truncate(self.max_rows);
+            stats.truncated = true;
+        }
+    }
+
+    /// Evaluate every statement against `snapshot` instead of taking a fresh
+    /// one per statement, e.g. to run several queries on the same view.
+    pub fn with_snapshot(mut self, snapshot: ReadSnapshot) -> Self {
+        self.pinned_snapshot = Some(snapshot);
+        self
+    }
+
+    /// Make `=` and `<>` ignore case when both sides are strings. Sessions can
+    /// toggle this with `SESSION SET case_insensitive = true`.
+    pub fn with_case_insensitive(self, enabled: bool) -> Self {
+        self.case_insensitive.store(enabled, Ordering::Relaxed);
+        self
+    }
+
+    /// Round floats in results to `digits` decimal places, e.g. to print
+    /// computed rates as `0.3` rather than `0.30000000000000004`. Sessions can
+    /// change this with `SESSION SET float_precision = n`.
+    pub fn with_float_precision(self, digits: Option<u32>) -> Self {
+        *self.float_precision.write() = digits;
+        self
+    }
+
+    /// Choose what INSERT stores for a Transfer edge without `amount` /
+    /// `value`. Sessions can change this with
+    /// `SESSION SET missing_amount = 'absent'`.
+    pub fn with_missing_amount(self, missing: MissingAmount) -> Self {
+        *self.missing_amount.write() = missing;
+        self
+    }
+
+    /// Answer repeated algorithm `CALL`s with identical arguments from
+    /// `cache` until the graph is written to; `None` always recomputes.
+    pub fn with_result_cache(mut self, cache: Option<Arc<ResultCache>>) -> Self {
+        self.result_cache = cache;
+        self
+    }
+
+    /// Bind a query parameter such as `$rows`, as `SESSION SET $rows = ...`
+    /// would, so callers can feed lists to `UNWIND` without building GQL text.
+    pub fn set_parameter(&self, name: impl Into<String>, value: PropertyValue) {
+        self.parameters.write().insert(name.into(), value);
+    }
+
+    /// Value currently bound to a query parameter, if any.
+    pub fn parameter(&self, name: &str) -> Option<PropertyValue> {
+        self.parameters.read().get(name).cloned()
+    }
+
+    /// Unbind a query parameter, as `SESSION RESET name` would.
+    pub fn remove_parameter(&self, name: &str) {
+        self.parameters.write().remove(name);
+    }
+
+    /// Change float rounding on a shared executor between statements, as
+    /// `SESSION SET float_precision = n` would.
+    pub fn set_float_precision(&self, digits: Option<u32>) {
+        *self.float_precision.write() = digits;
+    }
+
+    /// Current float rounding of results; `None` keeps full precision.
+    pub fn float_precision(&self) -> Option<u32> {
+        *self.float_precision.read()
+    }
+
+    fn strings_equal(&self, left: &PropertyValue, right: &PropertyValue) -> bool {
+        match (left, right) {
+            (PropertyValue::String(a), PropertyValue::String(b))
+                if self.case_insensitive.load(Ordering::Relaxed) =>
+            {
+                a.to_lowercase() == b.to_lowercase()
+            }
+            _ => left == right,
+        }
+    }
+
+    /// Run `f` under a read snapshot so that vertices and edges inserted
+    /// concurrently stay invisible until the statement finishes. Nested
+    /// statements (composite queries) share the outermost snapshot.
+    fn in_snapshot<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let outermost = {
+            let mut current = self.snapshot.lock();
+            if current.is_none() {
+                *current = Some(
+                    self.pinned_snapshot
+                        .unwrap_or_else(|| self.graph().snapshot()),
+                );
+                true
+            } else {
+                false
+            }
+        };
+        let result = f();
+        if outermost {
+            *self.snapshot.lock() = None;
+        }
+        result
+    }
+
+    fn sees_vertex(&self, id: VertexId) -> bool {
+        self.snapshot.lock().is_none_or(|s| s.sees_vertex(id))
+    }
+
+    fn sees_edge(&self, id: EdgeId) -> bool {
+        self.snapshot.lock().is_none_or(|s| s.sees_edge(id))
+    }
+
+    /// Fetch a vertex if it is visible in the current snapshot
+    fn visible_vertex(&self, id: VertexId) -> Option<Vertex> {
+        if self.sees_vertex(id) {
+            self.graph().get_vertex(id)
+        } else {
+            None
+        }
+    }
+
+    /// Apply the session's output settings to `result`: timestamps become
+    /// RFC 3339 strings in the session time zone, and floats are rounded to
+    /// the configured precision. Without either setting values pass through.
+    fn render_results(&self, result: &mut QueryResult) {
+        let time_zone = *self.time_zone.read();
+        let float_precision = *self.float_precision.read();
+        if time_zone.is_none() && float_precision.is_none() {
+            return;
+        }
+        let render =
+            |value: &mut PropertyValue| render_value(value, time_zone.as_ref(), float_precision);
+        let render_all = |properties: &mut HashMap<String, PropertyValue>| {
+            properties.values_mut().for_each(render)
+        };
+        for value in result.rows.iter_mut().flatten() {
+            match value {
+                ResultValue::Scalar(v) => render(v),
+                ResultValue::Vertex(v) => render_all(&mut v.properties),
+                ResultValue::Edge(e) => render_all(&mut e.properties),
+                ResultValue::Path(p) => {
+                    for v in &mut p.vertices {
+                        render_all(&mut v.properties);
+                    }
+                    for e in &mut p.edges {
+                        render_all(&mut e.properties);
+                    }
+                }
+                ResultValue::Null => {}
+            }
+        }
+    }
+
+    fn check_bindings_limit(&self, count: usize) -> Result<()> {
+        if count > self.max_bindings {
+            return Err(Error::QueryError(format!(
+                "Query result too large: more than {} intermediate bindings; narrow the pattern with labels or properties, or raise the bindings limit",
+                self.max_bindings
+            )));
+        }
+        Ok(())
+    }
+
+    /// Count one variable-length expansion step against the MATCH budget
+    fn charge_expansion_step(&self) -> Result<()> {
+        let steps = self.expansion_steps.fetch_add(1, Ordering::Relaxed) + 1;
+        if steps > self.max_expansion_steps {
+            return Err(Error::QueryError(format!(
+                "Pattern too expensive: variable-length expansion took more than {} steps; bound the quantifiers (e.g. {{1,3}}), add labels or properties, or raise the expansion step limit",
+                self.max_expansion_steps
+            )));
+        }
+        Ok(())
     }
 
     fn graph(&self) -> Arc<Graph> {
@@ -88,7 +573,7 @@ impl QueryExecutor {
         let start = std::time::Instant::now();
         let timer = metrics::global_metrics().record_query_start();
 
-        let result = match stmt {
+        let result = self.in_snapshot(|| match stmt {
             GqlStatement::Match(query) => self.execute_match(query),
             GqlStatement::Insert(stmt) => self.execute_insert(stmt),
             GqlStatement::Delete(stmt) => self.execute_delete(stmt),
@@ -108,78 +593,401 @@ impl QueryExecutor {
             GqlStatement::Select(stmt) => self.execute_select(stmt),
             GqlStatement::Session(stmt) => self.execute_session(stmt),
             GqlStatement::Transaction(stmt) => self.execute_transaction(stmt),
-        };
+            GqlStatement::Explain(stmt) => self.execute_explain(stmt),
+        });
 
         // 记录查询完成
         let success = result.is_ok();
         metrics::global_metrics().record_query_complete(timer, success);
 
         let mut result = result?;
+        self.render_results(&mut result);
         result.stats.execution_time_ms = start.elapsed().as_millis() as u64;
+        result.stats.graph_version = self.graph().version();
         Ok(result)
     }
 
     /// Execute MATCH statement with GQL path modes and search prefixes
     fn execute_match(&self, query: &MatchStatement) -> Result<QueryResult> {
+        self.execute_match_profiled(query, None)
+    }
+
+    /// Execute a MATCH, optionally recording one `PlanOperator` per pipeline
+    /// step (pattern, WHERE, SKIP, LIMIT, RETURN) for EXPLAIN ANALYZE
+    fn execute_match_profiled(
+        &self,
+        query: &MatchStatement,
+        mut profile: Option<&mut Vec<PlanOperator>>,
+    ) -> Result<QueryResult> {
         let mut stats = QueryStats::default();
 
-        // 1. Match graph pattern
-        let bindings_list = self.match_graph_pattern(&query.graph_pattern, &mut stats)?;
+        // 1-4. Pattern, WHERE, SKIP, LIMIT
+        let limited = self.match_bindings_profiled(query, &mut stats, profile.as_deref_mut())?;
+
+        // 5. Build RETURN result
+        let probe = OperatorProbe::start(&stats);
+        let return_clause = Self::return_items(query)?;
+        let (columns, mut rows) = self.build_return(&return_clause, &limited)?;
+        self.cap_rows(&mut rows, query.limit.is_some(), &mut stats);
+        stats.rows_returned = rows.len();
+        if let Some(profile) = profile {
+            profile.push(probe.finish("Project", columns.join(", "), rows.len(), &stats));
+        }
+
+        Ok(QueryResult {
+            columns,
+            rows,
+            stats,
+        })
+    }
+
+    /// RETURN items of a MATCH; a MATCH without RETURN returns every named
+    /// pattern variable, as if written `RETURN *`
+    fn return_items(query: &MatchStatement) -> Result<std::borrow::Cow<'_, [ReturnItem]>> {
+        if !query.return_clause.is_empty() {
+            return Ok(std::borrow::Cow::Borrowed(&query.return_clause));
+        }
+        let implicit: Vec<ReturnItem> = query
+            .graph_pattern
+            .variables()
+            .into_iter()
+            .map(|var| ReturnItem::new(Expression::Variable(var)))
+            .collect();
+        if implicit.is_empty() {
+            return Err(Error::QueryError(
+                "MATCH without RETURN needs at least one named variable, e.g. MATCH (n:Account) or add a RETURN clause".to_string(),
+            ));
+        }
+        Ok(std::borrow::Cow::Owned(implicit))
+    }
+
+    /// Evaluate the pattern, WHERE, SKIP and LIMIT of a MATCH into bindings
+    fn match_bindings(
+        &self,
+        query: &MatchStatement,
+        stats: &mut QueryStats,
+    ) -> Result<Vec<Bindings>> {
+        self.match_bindings_profiled(query, stats, None)
+    }
+
+    fn match_bindings_profiled(
+        &self,
+        query: &MatchStatement,
+        stats: &mut QueryStats,
+        mut profile: Option<&mut Vec<PlanOperator>>,
+    ) -> Result<Vec<Bindings>> {
+        // 1. Match graph pattern. A lone node pattern with nothing to filter
+        //    on can stop scanning once SKIP + LIMIT vertices are found.
+        let pushdown = match (&query.graph_pattern.paths[..], query.limit) {
+            ([path], Some(limit))
+                if query.where_clause.is_none() && path.search_prefix.is_none() =>
+            {
+                match &path.elements[..] {
+                    [PathElement::Node(node)] if node.properties.is_empty() => {
+                        Some(query.skip.unwrap_or(0).saturating_add(limit))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        *self.scan_limit.lock() = pushdown;
+        *self.scan_hints.lock() = self.resolve_hints(query)?;
+        self.expansion_steps.store(0, Ordering::Relaxed);
+        let matched = self.match_graph_pattern(&query.graph_pattern, stats, profile.as_deref_mut());
+        *self.scan_limit.lock() = None;
+        self.scan_hints.lock().clear();
+        let bindings_list = matched?;
 
         // 2. Apply WHERE filter
         let filtered: Vec<Bindings> = if let Some(ref where_clause) = query.where_clause {
-            bindings_list
+            let probe = OperatorProbe::start(stats);
+            let filtered: Vec<Bindings> = bindings_list
                 .into_iter()
                 .filter(|bindings| self.evaluate_bool(where_clause, bindings).unwrap_or(false))
-                .collect()
+                .collect();
+            if let Some(profile) = profile.as_deref_mut() {
+                profile.push(probe.finish(
+                    "Filter",
+                    where_clause.to_string(),
+                    filtered.len(),
+                    stats,
+                ));
+            }
+            filtered
         } else {
             bindings_list
         };
 
         // 3. SKIP
         let skipped: Vec<Bindings> = if let Some(skip) = query.skip {
-            filtered.into_iter().skip(skip).collect()
+            let probe = OperatorProbe::start(stats);
+            let skipped: Vec<Bindings> = filtered.into_iter().skip(skip).collect();
+            if let Some(profile) = profile.as_deref_mut() {
+                profile.push(probe.finish("Skip", skip.to_string(), skipped.len(), stats));
+            }
+            skipped
         } else {
             filtered
         };
 
         // 4. LIMIT
         let limited: Vec<Bindings> = if let Some(limit) = query.limit {
-            skipped.into_iter().take(limit).collect()
+            let probe = OperatorProbe::start(stats);
+            let limited: Vec<Bindings> = skipped.into_iter().take(limit).collect();
+            if let Some(profile) = profile {
+                profile.push(probe.finish("Limit", limit.to_string(), limited.len(), stats));
+            }
+            limited
         } else {
             skipped
         };
 
-        // 5. Build RETURN result
-        let (columns, rows) = self.build_return(&query.return_clause, &limited)?;
-        stats.rows_returned = rows.len();
-
-        Ok(QueryResult {
-            columns,
-            rows,
-            stats,
-        })
+        Ok(limited)
     }
 
     fn match_graph_pattern(
         &self,
         pattern: &GraphPattern,
         stats: &mut QueryStats,
+        mut profile: Option<&mut Vec<PlanOperator>>,
     ) -> Result<Vec<Bindings>> {
         let mut result = vec![HashMap::new()];
 
-        for path in &pattern.paths {
+        for path in &self.plan_join_order(&pattern.paths) {
+            let probe = OperatorProbe::start(stats);
             let mut new_result = Vec::new();
             for bindings in result {
                 let path_bindings = self.match_path_pattern(path, bindings, stats)?;
                 new_result.extend(path_bindings);
+                self.check_bindings_limit(new_result.len())?;
             }
             result = new_result;
+            if let Some(profile) = profile.as_deref_mut() {
+                profile.push(probe.finish("PatternMatch", path.to_string(), result.len(), stats));
+            }
         }
 
         Ok(result)
     }
 
+    /// Execute EXPLAIN [ANALYZE]. Only MATCH statements have an operator plan.
+    fn execute_explain(&self, stmt: &ExplainStatement) -> Result<QueryResult> {
+        let query = match stmt.statement.as_ref() {
+            GqlStatement::Match(query) => query,
+            _ => {
+                return Err(Error::QueryError(
+                    "EXPLAIN currently supports MATCH statements only".to_string(),
+                ))
+            }
+        };
+
+        if !stmt.analyze {
+            let mut rows = Vec::new();
+            let mut push = |operator: &str, detail: String| {
+                rows.push(vec![
+                    ResultValue::Scalar(PropertyValue::String(operator.to_string())),
+                    ResultValue::Scalar(PropertyValue::String(detail)),
+                ]);
+            };
+            for hint in &query.hints {
+                push("Hint", hint.to_string());
+            }
+            for path in self.plan_join_order(&query.graph_pattern.paths) {
+                push("PatternMatch", path.to_string());
+            }
+            if let Some(ref where_clause) = query.where_clause {
+                push("Filter", where_clause.to_string());
+            }
+            if let Some(skip) = query.skip {
+                push("Skip", skip.to_string());
+            }
+            if let Some(limit) = query.limit {
+                push("Limit", limit.to_string());
+            }
+            let columns = Self::return_column_names(&Self::return_items(query)?);
+            push("Project", columns.join(", "));
+
+            return Ok(QueryResult {
+                columns: vec!["operator".to_string(), "detail".to_string()],
+                rows,
+                stats: QueryStats::default(),
+            });
+        }
+
+        let mut profile = Vec::new();
+        let result = self.execute_match_profiled(query, Some(&mut profile))?;
+        let rows = profile
+            .into_iter()
+            .map(|op| {
+                vec![
+                    ResultValue::Scalar(PropertyValue::String(op.operator.to_string())),
+                    ResultValue::Scalar(PropertyValue::String(op.detail)),
+                    ResultValue::Scalar(PropertyValue::Integer(op.rows as i64)),
+                    ResultValue::Scalar(PropertyValue::Integer(op.vertices_scanned as i64)),
+                    ResultValue::Scalar(PropertyValue::Integer(op.edges_scanned as i64)),
+                    ResultValue::Scalar(PropertyValue::Float(op.elapsed.as_secs_f64() * 1000.0)),
+                ]
+            })
+            .collect();
+
+        Ok(QueryResult {
+            columns: vec![
+                "operator".to_string(),
+                "detail".to_string(),
+                "rows".to_string(),
+                "vertices_scanned".to_string(),
+                "edges_scanned".to_string(),
+                "time_ms".to_string(),
+            ],
+            rows,
+            stats: result.stats,
+        })
+    }
+
+    /// Reorder comma-separated path patterns so that each one starts from a
+    /// vertex already bound by an earlier pattern when possible. A simple
+    /// chain whose first node is unbound but which mentions a bound node
+    /// further along is split at that node into a forward half and a
+    /// reversed half, both anchored on the bound vertex. This turns the
+    /// label scan + cross product into a traversal from the join vertex.
+    ///
+    /// A chain with no bound node at all is anchored the same way on its
+    /// most selective named node (smallest label count, see
+    /// `estimate_candidates`), so a small label is scanned instead of a
+    /// large one.
+    fn plan_join_order(&self, paths: &[PathPattern]) -> Vec<PathPattern> {
+        let mut bound: std::collections::HashSet<&str> = std::collections::HashSet::new();
+        let mut planned = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            let pivot = if Self::is_reorderable(path) {
+                self.choose_pivot(path, &bound)
+            } else {
+                None
+            };
+            match pivot {
+                Some(pivot) => {
+                    let (forward, backward) = Self::split_at(path, pivot);
+                    planned.push(forward);
+                    planned.push(backward);
+                }
+                None => planned.push(path.clone()),
+            }
+            for element in &path.elements {
+                if let PathElement::Node(NodePattern { variable: Some(var), .. }) = element {
+                    bound.insert(var.as_str());
+                }
+            }
+        }
+
+        planned
+    }
+
+    /// Whether a path is a simple node/edge chain that can be matched from
+    /// any of its nodes. Path variables, path modes, search prefixes,
+    /// quantified paths and parenthesized sub-paths depend on traversal
+    /// order and are left untouched.
+    fn is_reorderable(path: &PathPattern) -> bool {
+        path.variable.is_none()
+            && path.path_mode.is_none()
+            && path.search_prefix.is_none()
+            && path.quantifier.is_none()
+            && !path
+                .elements
+                .iter()
+                .any(|e| matches!(e, PathElement::ParenthesizedPath(_)))
+    }
+
+    /// Pick the element index to start matching from: the first bound node
+    /// if any, otherwise the named node with the fewest candidates. Returns
+    /// None when matching should start from the first element as written.
+    fn choose_pivot(
+        &self,
+        path: &PathPattern,
+        bound: &std::collections::HashSet<&str>,
+    ) -> Option<usize> {
+        fn named(element: &PathElement) -> Option<&str> {
+            match element {
+                PathElement::Node(node) => node.variable.as_deref(),
+                _ => None,
+            }
+        }
+        if path
+            .elements
+            .first()
+            .and_then(named)
+            .is_some_and(|var| bound.contains(var))
+        {
+            return None;
+        }
+        if let Some(pivot) = path
+            .elements
+            .iter()
+            .position(|e| named(e).is_some_and(|var| bound.contains(var)))
+        {
+            return Some(pivot);
+        }
+
+        // Nodes with a property filter win ties: fewer rows survive the scan.
+        let cost = |element: &PathElement| match element {
+            PathElement::Node(node) => {
+                Some((self.estimate_candidates(node), node.properties.is_empty()))
+            }
+            _ => None,
+        };
+        let first_cost = path.elements.first().and_then(cost)?;
+        let (pivot, pivot_cost) = path
+            .elements
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| named(e).is_some())
+            .filter_map(|(i, e)| cost(e).map(|c| (i, c)))
+            .min_by_key(|&(i, c)| (c, i))?;
+        (pivot > 0 && pivot_cost < first_cost).then_some(pivot)
+    }
+
+    /// Number of vertices an index lookup or label scan for `node` would visit
+    fn estimate_candidates(&self, node: &NodePattern) -> usize {
+        if let Some(ids) = self.index_lookup(node) {
+            return ids.len();
+        }
+        let graph = self.graph();
+        let labels = node.labels();
+        if labels.is_empty() {
+            return graph.vertex_count();
+        }
+        let counts = graph.vertex_index().label_counts();
+        labels
+            .iter()
+            .map(|label| counts.get(label).copied().unwrap_or(0))
+            .sum()
+    }
+
+    /// Split a chain at element `pivot` into a forward half starting at the
+    /// pivot and a reversed half walking back from it to the first element.
+    fn split_at(path: &PathPattern, pivot: usize) -> (PathPattern, PathPattern) {
+        let forward = PathPattern {
+            elements: path.elements[pivot..].to_vec(),
+            ..path.clone()
+        };
+        let backward = PathPattern {
+            elements: path.elements[..=pivot]
+                .iter()
+                .rev()
+                .map(|element| match element {
+                    PathElement::Edge(edge) => PathElement::Edge(EdgePattern {
+                        direction: edge.direction.reversed(),
+                        ..edge.clone()
+                    }),
+                    other => other.clone(),
+                })
+                .collect(),
+            ..path.clone()
+        };
+        (forward, backward)
+    }
+
     fn match_path_pattern(
         &self,
         path: &PathPattern,
@@ -206,9 +1014,9 @@ impl QueryExecutor {
                     let mut new_bindings = Vec::new();
                     for (bindings, path_vertices) in current {
                         let candidates =
-                            self.get_candidate_vertices(node_pattern, &bindings, stats);
+                            self.get_candidate_vertices(node_pattern, &bindings, stats)?;
                         for vertex in candidates {
-                            if self.match_node_properties(node_pattern, &vertex) {
+                            if self.match_node_pattern(node_pattern, &vertex) {
                                 let mut new_bind = bindings.clone();
                                 if let Some(ref var) = node_pattern.variable {
                                     new_bind
@@ -218,6 +1026,7 @@ impl QueryExecutor {
                                 let mut new_path = path_vertices.clone();
                                 new_path.push(vertex.id());
                                 new_bindings.push((new_bind, new_path));
+                                self.check_bindings_limit(new_bindings.len())?;
                             }
                         }
                     }
@@ -248,6 +1057,7 @@ impl QueryExecutor {
                         path.path_mode,
                         stats,
                     )?;
+                    self.check_bindings_limit(current.len())?;
                     i += 1;
                 }
                 PathElement::ParenthesizedPath(paren_path) => {
@@ -345,7 +1155,16 @@ impl QueryExecutor {
         let mut new_bindings = Vec::new();
 
         for (bindings, path_vertices) in current {
-            let source_vertices: Vec<Vertex> = self.get_bound_vertices(bindings);
+            // Expand from the vertex the path has reached so far
+            let source_vertices: Vec<Vertex> = match path_vertices.last() {
+                Some(id) => self.graph().get_vertex(*id).into_iter().collect(),
+                None => self.get_bound_vertices(bindings),
+            };
+            // A target already bound by an earlier pattern acts as a join condition
+            let bound_target = target.variable.as_ref().and_then(|var| match bindings.get(var) {
+                Some(BindingValue::Vertex(v)) => Some(v.id()),
+                _ => None,
+            });
 
             for source in source_vertices {
                 // Handle variable-length patterns
@@ -354,6 +1173,9 @@ impl QueryExecutor {
                         &source, edge, target, quantifier, path_mode, stats,
                     )?;
                     for (var_path_vertices, final_vertex, _path_edges) in paths {
+                        if bound_target.is_some_and(|id| id != final_vertex.id()) {
+                            continue;
+                        }
                         let mut new_bind = bindings.clone();
                         if let Some(ref var) = edge.variable {
                             new_bind.insert(var.clone(), BindingValue::Path(var_path_vertices.clone()));
@@ -377,7 +1199,10 @@ impl QueryExecutor {
                         }
 
                         let target_id = self.get_edge_target(&e, &source, edge.direction);
-                        if let Some(target_vertex) = self.graph().get_vertex(target_id) {
+                        if bound_target.is_some_and(|id| id != target_id) {
+                            continue;
+                        }
+                        if let Some(target_vertex) = self.visible_vertex(target_id) {
                             if self.match_node_pattern(target, &target_vertex) {
                                 let mut new_bind = bindings.clone();
                                 if let Some(ref var) = edge.variable {
@@ -424,10 +1249,28 @@ impl QueryExecutor {
             return Ok(vec![initial]);
         };
 
-        let source_vertices = self.get_candidate_vertices(source_pattern, &initial, stats);
-        let target_vertices = self.get_candidate_vertices(target_pattern, &initial, stats);
+        let mut source_vertices = self.get_candidate_vertices(source_pattern, &initial, stats)?;
+        source_vertices.retain(|v| self.match_node_pattern(source_pattern, v));
+        let mut target_vertices = self.get_candidate_vertices(target_pattern, &initial, stats)?;
+        target_vertices.retain(|v| self.match_node_pattern(target_pattern, v));
 
-        let finder = PathFinder::new(self.graph());
+        // Only follow edges the pattern's edge elements allow, in their direction
+        let edge_patterns: Vec<EdgePattern> = path
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                PathElement::Edge(edge) => Some(edge.clone()),
+                _ => None,
+            })
+            .collect();
+        let direction = Self::path_search_direction(&edge_patterns);
+        let finder = PathFinder::new(self.graph())
+            .with_cancellation(self.cancel.clone())
+            .with_direction(direction)
+            .with_edge_filter(move |edge| {
+                edge_patterns.is_empty()
+                    || edge_patterns.iter().any(|p| p.matches_label(edge.label()))
+            });
         let mut results = Vec::new();
 
         for source in &source_vertices {
@@ -468,7 +1311,7 @@ impl QueryExecutor {
                         }
                     }
                     PathSearchPrefix::All => {
-                        let paths = finder.all_paths(source.id(), target.id(), 10);
+                        let paths = finder.all_paths(source.id(), target.id(), 10)?;
                         for found_path in paths {
                             let mut bindings = initial.clone();
                             if let Some(ref var) = source_pattern.variable {
@@ -486,7 +1329,7 @@ impl QueryExecutor {
                     }
                     PathSearchPrefix::Any => {
                         // Return any single matching path
-                        let paths = finder.all_paths(source.id(), target.id(), 1);
+                        let paths = finder.all_paths(source.id(), target.id(), 1)?;
                         if let Some(found_path) = paths.into_iter().next() {
                             let mut bindings = initial.clone();
                             if let Some(ref var) = source_pattern.variable {
@@ -504,7 +1347,7 @@ impl QueryExecutor {
                     }
                     PathSearchPrefix::AnyK(k) => {
                         // Return k matching paths (not necessarily shortest)
-                        let paths = finder.all_paths(source.id(), target.id(), *k as usize);
+                        let paths = finder.all_paths(source.id(), target.id(), *k as usize)?;
                         for found_path in paths {
                             let mut bindings = initial.clone();
                             if let Some(ref var) = source_pattern.variable {
@@ -521,7 +1364,7 @@ impl QueryExecutor {
                         }
                     }
                     PathSearchPrefix::ShortestK(k) => {
-                        let paths = finder.k_shortest_paths(source.id(), target.id(), *k as usize);
+                        let paths = finder.k_shortest_paths(source.id(), target.id(), *k as usize)?;
                         for found_path in paths {
                             let mut bindings = initial.clone();
                             if let Some(ref var) = source_pattern.variable {
@@ -540,7 +1383,7 @@ impl QueryExecutor {
                     PathSearchPrefix::ShortestKGroups(k) => {
                         // Return paths grouped by length (k groups)
                         // Uses k_shortest_paths and groups by path length
-                        let paths = finder.k_shortest_paths(source.id(), target.id(), (*k as usize) * 5);
+                        let paths = finder.k_shortest_paths(source.id(), target.id(), (*k as usize) * 5)?;
                         let mut groups: std::collections::HashMap<usize, Vec<_>> = std::collections::HashMap::new();
                         
                         for found_path in paths {
@@ -552,7 +1395,7 @@ impl QueryExecutor {
                         let mut sorted_lengths: Vec<_> = groups.keys().copied().collect();
                         sorted_lengths.sort();
                         
-                        for (_group_idx, len) in sorted_lengths.into_iter().take(*k as usize).enumerate() {
+                        for len in sorted_lengths.into_iter().take(*k as usize) {
                             if let Some(group_paths) = groups.remove(&len) {
                                 for found_path in group_paths {
                                     let mut bindings = initial.clone();
@@ -581,6 +1424,11 @@ impl QueryExecutor {
 
 
 
+    /// Expand a quantified edge pattern from `source`.
+    ///
+    /// The search backtracks over one shared path instead of cloning the path,
+    /// edge list and visited set for every branch, so memory grows with
+    /// depth × fanout of pending steps rather than with the copied state.
     fn expand_variable_length(
         &self,
         source: &Vertex,
@@ -589,7 +1437,7 @@ impl QueryExecutor {
         quantifier: &PatternQuantifier,
         path_mode: Option<PathMode>,
         stats: &mut QueryStats,
-    ) -> Result<Vec<(Vec<VertexId>, Vertex, Vec<Edge>)>> {
+    ) -> Result<Vec<ExpandedPath>> {
         let (min, max) = match quantifier {
             PatternQuantifier::ZeroOrMore => (0, 10),
             PatternQuantifier::OneOrMore => (1, 10),
@@ -600,56 +1448,80 @@ impl QueryExecutor {
             PatternQuantifier::Range(n, m) => (*n as usize, *m as usize),
         };
 
+        // Depth-first backtracking over a single shared path: each frame holds
+        // the not-yet-explored steps of one vertex, so no per-branch copies of
+        // the path, edges or visited set are made.
+        let mode = path_mode.unwrap_or(PathMode::Walk);
         let mut results = Vec::new();
-        let mut queue: Vec<(
-            Vec<VertexId>,
-            Vertex,
-            Vec<Edge>,
-            std::collections::HashSet<VertexId>,
-        )> = vec![(
-            vec![source.id()],
-            source.clone(),
-            Vec::new(),
-            std::collections::HashSet::from([source.id()]),
-        )];
+        let mut path = vec![source.id()];
+        let mut path_edges: Vec<Edge> = Vec::new();
+        let mut visited: HashMap<VertexId, usize> = HashMap::from([(source.id(), 1)]);
+        let mut frames: Vec<Vec<(Edge, Vertex)>> = Vec::new();
+        let mut entered = Some(source.clone());
+
+        loop {
+            if let Some(current) = entered.take() {
+                self.cancel.check()?;
+                self.charge_expansion_step()?;
+                let depth = path.len() - 1;
+
+                if depth >= min && self.match_node_pattern(target, &current) {
+                    results.push((path.clone(), current.clone(), path_edges.clone()));
+                    self.check_bindings_limit(results.len())?;
+                }
 
-        while let Some((path, current, edges, visited)) = queue.pop() {
-            let depth = path.len() - 1;
+                let mut steps = Vec::new();
+                if depth < max {
+                    let next_edges = self.get_edges_by_direction(&current, edge.direction);
+                    stats.edges_scanned += next_edges.len();
 
-            if depth >= min && self.match_node_pattern(target, &current) {
-                results.push((path.clone(), current.clone(), edges.clone()));
-            }
+                    for e in next_edges {
+                        if !self.match_edge_labels(edge, &e) {
+                            continue;
+                        }
 
-            if depth >= max {
-                continue;
-            }
+                        let next_id = self.get_edge_target(&e, &current, edge.direction);
 
-            let next_edges = self.get_edges_by_direction(&current, edge.direction);
-            stats.edges_scanned += next_edges.len();
+                        // Apply path mode constraints
+                        let can_visit = match mode {
+                            PathMode::Walk => true,
+                            PathMode::Trail => !path_edges.iter().any(|edge| edge.id() == e.id()),
+                            PathMode::Simple | PathMode::Acyclic => !visited.contains_key(&next_id),
+                        };
 
-            for e in next_edges {
-                if !self.match_edge_labels(edge, &e) {
-                    continue;
+                        if can_visit {
+                            if let Some(next_vertex) = self.visible_vertex(next_id) {
+                                steps.push((e, next_vertex));
+                            }
+                        }
+                    }
                 }
+                frames.push(steps);
+            }
 
-                let next_id = self.get_edge_target(&e, &current, edge.direction);
-
-                // Apply path mode constraints
-                let can_visit = match path_mode.unwrap_or(PathMode::Walk) {
-                    PathMode::Walk => true,
-                    PathMode::Trail => !edges.iter().any(|edge| edge.id() == e.id()),
-                    PathMode::Simple | PathMode::Acyclic => !visited.contains(&next_id),
-                };
-
-                if can_visit {
-                    if let Some(next_vertex) = self.graph().get_vertex(next_id) {
-                        let mut new_path = path.clone();
-                        new_path.push(next_id);
-                        let mut new_edges = edges.clone();
-                        new_edges.push(e.clone());
-                        let mut new_visited = visited.clone();
-                        new_visited.insert(next_id);
-                        queue.push((new_path, next_vertex, new_edges, new_visited));
+            let Some(steps) = frames.last_mut() else {
+                break;
+            };
+            // Steps are taken from the back, preserving the previous LIFO result order
+            match steps.pop() {
+                Some((e, next_vertex)) => {
+                    path.push(next_vertex.id());
+                    *visited.entry(next_vertex.id()).or_insert(0) += 1;
+                    path_edges.push(e);
+                    entered = Some(next_vertex);
+                }
+                None => {
+                    frames.pop();
+                    if frames.is_empty() {
+                        break;
+                    }
+                    let left = path.pop().expect("non-root frame has a path step");
+                    path_edges.pop();
+                    if let Some(count) = visited.get_mut(&left) {
+                        *count -= 1;
+                        if *count == 0 {
+                            visited.remove(&left);
+                        }
                     }
                 }
             }
@@ -658,9 +1530,37 @@ impl QueryExecutor {
         Ok(results)
     }
 
+    /// Traversal direction for a path search: the direction shared by all
+    /// edge patterns, or both ways when they disagree.
+    fn path_search_direction(edge_patterns: &[EdgePattern]) -> TraceDirection {
+        let directions: Vec<TraceDirection> = edge_patterns
+            .iter()
+            .map(|pattern| match pattern.direction {
+                EdgeDirection::Outgoing | EdgeDirection::UndirectedOrRight => {
+                    TraceDirection::Forward
+                }
+                EdgeDirection::Incoming | EdgeDirection::LeftOrUndirected => {
+                    TraceDirection::Backward
+                }
+                _ => TraceDirection::Both,
+            })
+            .collect();
+        match directions.split_first() {
+            Some((first, rest)) if rest.iter().all(|d| d == first) => *first,
+            Some(_) => TraceDirection::Both,
+            None => TraceDirection::Forward,
+        }
+    }
+
     /// Get edges by direction (ISO GQL 39075)
     /// Supports all 7 edge direction types
     fn get_edges_by_direction(&self, vertex: &Vertex, direction: EdgeDirection) -> Vec<Edge> {
+        let mut edges = self.get_all_edges_by_direction(vertex, direction);
+        edges.retain(|e| self.sees_edge(e.id()));
+        edges
+    }
+
+    fn get_all_edges_by_direction(&self, vertex: &Vertex, direction: EdgeDirection) -> Vec<Edge> {
         match direction {
             EdgeDirection::Outgoing => self.graph().get_outgoing_edges(vertex.id()),
             EdgeDirection::Incoming => self.graph().get_incoming_edges(vertex.id()),
@@ -703,8 +1603,7 @@ impl QueryExecutor {
     }
 
     fn match_edge_labels(&self, pattern: &EdgePattern, edge: &Edge) -> bool {
-        let labels = pattern.labels();
-        labels.is_empty() || labels.contains(edge.label())
+        pattern.matches_label(edge.label())
     }
 
     fn get_candidate_vertices(
@@ -712,36 +1611,198 @@ impl QueryExecutor {
         pattern: &NodePattern,
         bindings: &Bindings,
         stats: &mut QueryStats,
-    ) -> Vec<Vertex> {
+    ) -> Result<Vec<Vertex>> {
         if let Some(ref var) = pattern.variable {
             if let Some(BindingValue::Vertex(v)) = bindings.get(var) {
-                return vec![v.clone()];
+                return Ok(vec![v.clone()]);
             }
         }
 
-        let labels = pattern.labels();
-        let vertices: Vec<Vertex> = if labels.is_empty() {
-            let mut all = Vec::new();
-            for label in &[
-                VertexLabel::Account,
-                VertexLabel::Contract,
-                VertexLabel::Token,
-                VertexLabel::Transaction,
-                VertexLabel::Block,
-            ] {
-                all.extend(self.graph().get_vertices_by_label(label));
-            }
-            all
-        } else {
-            let mut candidates = Vec::new();
-            for label in &labels {
-                candidates.extend(self.graph().get_vertices_by_label(label));
-            }
-            candidates
+        if let Some(ids) = self.index_lookup(pattern) {
+            let graph = self.graph();
+            let vertices: Vec<Vertex> = ids
+                .into_iter()
+                .filter(|id| self.sees_vertex(*id))
+                .filter_map(|id| graph.get_vertex(id))
+                .collect();
+            stats.vertices_scanned += vertices.len();
+            return Ok(vertices);
+        }
+
+        let hint = pattern
+            .variable
+            .as_ref()
+            .and_then(|var| self.scan_hints.lock().get(var).cloned());
+        let labels = match hint {
+            Some(ScanChoice::Scan(label)) => vec![label],
+            _ => pattern.labels(),
         };
+        if labels.is_empty() {
+            let vertices = self.scan_all_vertices()?;
+            stats.vertices_scanned += vertices.len();
+            return Ok(vertices);
+        }
 
+        let mut vertices = Vec::new();
+        for label in &labels {
+            vertices.extend(self.graph().get_vertices_by_label(label));
+        }
+        vertices.retain(|v| self.sees_vertex(v.id()));
         stats.vertices_scanned += vertices.len();
-        vertices
+        Ok(vertices)
+    }
+
+    /// Vertex IDs for `node` from a property index: the `USING INDEX` hint on
+    /// its variable, otherwise an index on its single label covering one of
+    /// its inline property equalities. None means a label scan, which
+    /// `USING SCAN` forces.
+    fn index_lookup(&self, node: &NodePattern) -> Option<Vec<VertexId>> {
+        let hint = node
+            .variable
+            .as_ref()
+            .and_then(|var| self.scan_hints.lock().get(var).cloned());
+        match hint {
+            Some(ScanChoice::Index(ids)) => return Some(ids),
+            Some(ScanChoice::Scan(_)) => return None,
+            None => {}
+        }
+        let [label] = &node.labels()[..] else {
+            return None;
+        };
+        let graph = self.graph();
+        node.properties
+            .iter()
+            .find_map(|(key, value)| graph.lookup_index(label.as_str(), key, value))
+    }
+
+    /// Check the `USING` hints of a MATCH against its pattern and the graph's
+    /// indexes, and look up the entries an index hint selects
+    fn resolve_hints(&self, query: &MatchStatement) -> Result<HashMap<String, ScanChoice>> {
+        let mut resolved = HashMap::new();
+        for hint in &query.hints {
+            let (variable, label) = match hint {
+                MatchHint::Index {
+                    variable, label, ..
+                } => (variable, label),
+                MatchHint::Scan { variable, label } => (variable, label),
+            };
+            let node = query
+                .graph_pattern
+                .paths
+                .iter()
+                .flat_map(|path| &path.elements)
+                .find_map(|element| match element {
+                    PathElement::Node(node) if node.variable.as_ref() == Some(variable) => {
+                        Some(node)
+                    }
+                    _ => None,
+                })
+                .ok_or_else(|| {
+                    Error::QueryError(format!("{} refers to unknown node variable", hint))
+                })?;
+            let choice = match hint {
+                MatchHint::Scan { .. } => ScanChoice::Scan(label.clone()),
+                MatchHint::Index { property, .. } => {
+                    let graph = self.graph();
+                    let exists = graph
+                        .list_indexes()
+                        .iter()
+                        .any(|index| index.label == label.as_str() && index.property == *property);
+                    if !exists {
+                        return Err(Error::QueryError(format!(
+                            "Index not found: {}({})",
+                            label, property
+                        )));
+                    }
+                    let value = node
+                        .properties
+                        .iter()
+                        .find(|(key, _)| key == property)
+                        .map(|(_, value)| value.clone())
+                        .or_else(|| {
+                            let where_clause = query.where_clause.as_ref()?;
+                            self.equality_value(where_clause, variable, property)
+                        })
+                        .ok_or_else(|| {
+                            Error::QueryError(format!(
+                                "{} needs an equality on {}.{} in the pattern or WHERE",
+                                hint, variable, property
+                            ))
+                        })?;
+                    let ids = graph.lookup_index(label.as_str(), property, &value);
+                    ScanChoice::Index(ids.unwrap_or_default())
+                }
+            };
+            resolved.insert(variable.clone(), choice);
+        }
+        Ok(resolved)
+    }
+
+    /// Constant compared for equality with `var.property` in a top-level
+    /// AND conjunct of a WHERE clause
+    fn equality_value(
+        &self,
+        expr: &Expression,
+        var: &str,
+        property: &str,
+    ) -> Option<PropertyValue> {
+        match expr {
+            Expression::BinaryOp(left, BinaryOperator::And, right) => self
+                .equality_value(left, var, property)
+                .or_else(|| self.equality_value(right, var, property)),
+            Expression::BinaryOp(left, BinaryOperator::Eq, right) => {
+                let is_target = |e: &Expression| match e {
+                    Expression::Property(v, p) => v == var && p == property,
+                    _ => false,
+                };
+                let constant = if is_target(left) {
+                    right
+                } else if is_target(right) {
+                    left
+                } else {
+                    return None;
+                };
+                self.evaluate(constant, &Bindings::new()).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Candidates for an unlabeled node pattern: every visible vertex, or
+    /// only the first ones when a LIMIT was pushed down. Without a LIMIT the
+    /// scan is refused on graphs larger than `max_unlabeled_scan`.
+    fn scan_all_vertices(&self) -> Result<Vec<Vertex>> {
+        let graph = self.graph();
+        let scan_limit = *self.scan_limit.lock();
+        if scan_limit.is_none() && graph.vertex_count() > self.max_unlabeled_scan {
+            return Err(Error::QueryError(format!(
+                "Unlabeled node pattern would scan all {} vertices (more than {}); add a label such as (n:Account), add a LIMIT, or raise the unlabeled scan limit",
+                graph.vertex_count(),
+                self.max_unlabeled_scan
+            )));
+        }
+
+        let mut vertices = Vec::new();
+        for label in &[
+            VertexLabel::Account,
+            VertexLabel::Contract,
+            VertexLabel::Token,
+            VertexLabel::Transaction,
+            VertexLabel::Block,
+        ] {
+            for id in graph.vertex_index().get_by_label(label) {
+                if scan_limit.is_some_and(|limit| vertices.len() >= limit) {
+                    return Ok(vertices);
+                }
+                if !self.sees_vertex(id) {
+                    continue;
+                }
+                if let Some(vertex) = graph.get_vertex(id) {
+                    vertices.push(vertex);
+                }
+            }
+        }
+        Ok(vertices)
     }
 
     fn match_node_properties(&self, pattern: &NodePattern, vertex: &Vertex) -> bool {
@@ -755,8 +1816,7 @@ impl QueryExecutor {
     }
 
     fn match_node_pattern(&self, pattern: &NodePattern, vertex: &Vertex) -> bool {
-        let labels = pattern.labels();
-        if !labels.is_empty() && !labels.contains(vertex.label()) {
+        if !pattern.matches_labels(std::slice::from_ref(vertex.label())) {
             return false;
         }
         self.match_node_properties(pattern, vertex)
@@ -798,8 +1858,48 @@ impl QueryExecutor {
                     .property(prop)
                     .cloned()
                     .ok_or_else(|| Error::QueryError(format!("Property not found: {}", prop))),
+                Some(BindingValue::Scalar(PropertyValue::Map(map))) => map
+                    .get(prop)
+                    .cloned()
+                    .ok_or_else(|| Error::QueryError(format!("Property not found: {}", prop))),
                 _ => Err(Error::QueryError(format!("Variable not found: {}", var))),
             },
+            Expression::ElementProperty(base, prop) => {
+                if let Some(vertex) = self.path_element(base, bindings)? {
+                    return vertex
+                        .property(prop)
+                        .cloned()
+                        .ok_or_else(|| Error::QueryError(format!("Property not found: {}", prop)));
+                }
+                if let Expression::Index(path, _) = base.as_ref() {
+                    if Self::path_vertices(path, bindings).is_some() {
+                        return Ok(PropertyValue::String(String::new()));
+                    }
+                }
+                match self.evaluate(base, bindings)? {
+                    PropertyValue::Map(map) => map
+                        .get(prop)
+                        .cloned()
+                        .ok_or_else(|| Error::QueryError(format!("Property not found: {}", prop))),
+                    _ => Ok(PropertyValue::String(String::new())),
+                }
+            }
+            Expression::Index(base, index) => {
+                if Self::path_vertices(base, bindings).is_some() {
+                    return Ok(self
+                        .path_element(expr, bindings)?
+                        .map(|v| PropertyValue::Integer(v.id().as_u64() as i64))
+                        .unwrap_or(PropertyValue::String(String::new())));
+                }
+                let list = match self.evaluate(base, bindings)? {
+                    PropertyValue::List(items) => items,
+                    _ => return Ok(PropertyValue::String(String::new())),
+                };
+                let position = self.evaluate(index, bindings)?;
+                Ok(Self::list_position(list.len(), &position)
+                    .map(|i| list[i].clone())
+                    .unwrap_or(PropertyValue::String(String::new())))
+            }
             Expression::BinaryOp(left, op, right) => {
                 let l = self.evaluate(left, bindings)?;
                 let r = self.evaluate(right, bindings)?;
@@ -810,22 +1910,37 @@ impl QueryExecutor {
                 self.apply_unary_op(*op, &val)
             }
             Expression::FunctionCall(name, args) => {
+                if let Some(value) = Self::element_function(name, args, bindings) {
+                    return Ok(value);
+                }
+                if let Some(value) = self.path_function(name, args, bindings) {
+                    return Ok(value);
+                }
+                if name.eq_ignore_ascii_case("tag") {
+                    return self.address_tag(args, bindings);
+                }
                 let evaluated: Result<Vec<PropertyValue>> =
                     args.iter().map(|a| self.evaluate(a, bindings)).collect();
                 self.call_function(name, &evaluated?)
             }
-            Expression::List(items) => {
-                let _values: Result<Vec<PropertyValue>> =
-                    items.iter().map(|i| self.evaluate(i, bindings)).collect();
-                Ok(PropertyValue::String("[list]".to_string()))
-            }
-            Expression::Map(entries) => Ok(PropertyValue::String(format!(
-                "{{map with {} entries}}",
-                entries.len()
-            ))),
-            Expression::Parameter(name) => {
-                Err(Error::QueryError(format!("Parameter not bound: ${}", name)))
-            }
+            Expression::List(items) => Ok(PropertyValue::List(
+                items
+                    .iter()
+                    .map(|i| self.evaluate(i, bindings))
+                    .collect::<Result<_>>()?,
+            )),
+            Expression::Map(entries) => Ok(PropertyValue::Map(
+                entries
+                    .iter()
+                    .map(|(k, v)| Ok((k.clone(), self.evaluate(v, bindings)?)))
+                    .collect::<Result<_>>()?,
+            )),
+            Expression::Parameter(name) => self
+                .parameters
+                .read()
+                .get(name)
+                .cloned()
+                .ok_or_else(|| Error::QueryError(format!("Parameter not bound: ${}", name))),
             _ => Ok(PropertyValue::String(String::new())),
         }
     }
@@ -837,8 +1952,8 @@ impl QueryExecutor {
         right: &PropertyValue,
     ) -> Result<PropertyValue> {
         match op {
-            BinaryOperator::Eq => Ok(PropertyValue::Boolean(left == right)),
-            BinaryOperator::Ne => Ok(PropertyValue::Boolean(left != right)),
+            BinaryOperator::Eq => Ok(PropertyValue::Boolean(self.strings_equal(left, right))),
+            BinaryOperator::Ne => Ok(PropertyValue::Boolean(!self.strings_equal(left, right))),
             BinaryOperator::Lt => self.compare_values(left, right, |a, b| a < b),
             BinaryOperator::Le => self.compare_values(left, right, |a, b| a <= b),
             BinaryOperator::Gt => self.compare_values(left, right, |a, b| a > b),
@@ -910,9 +2025,11 @@ impl QueryExecutor {
         F: Fn(i64, i64) -> bool,
     {
         match (left, right) {
-            (PropertyValue::Integer(a), PropertyValue::Integer(b)) => {
-                Ok(PropertyValue::Boolean(cmp(*a, *b)))
-            }
+            // Timestamps compare with each other and with integer epoch seconds
+            (
+                PropertyValue::Integer(a) | PropertyValue::Timestamp(a),
+                PropertyValue::Integer(b) | PropertyValue::Timestamp(b),
+            ) => Ok(PropertyValue::Boolean(cmp(*a, *b))),
             (PropertyValue::Float(a), PropertyValue::Float(b)) => {
                 Ok(PropertyValue::Boolean(cmp(*a as i64, *b as i64)))
             }
@@ -970,8 +2087,178 @@ impl QueryExecutor {
         }
     }
 
-    fn call_function(&self, name: &str, args: &[PropertyValue]) -> Result<PropertyValue> {
+    /// Functions over a bound vertex or edge itself rather than its value:
+    /// `id(n)`, `label(n)`, `type(r)`, `properties(n)`, `keys(n)` and
+    /// `labels(n)`. None for any other call.
+    fn element_function(
+        name: &str,
+        args: &[Expression],
+        bindings: &Bindings,
+    ) -> Option<PropertyValue> {
+        let [Expression::Variable(var)] = args else {
+            return None;
+        };
+        let (id, label, properties, is_edge) = match bindings.get(var)? {
+            BindingValue::Vertex(v) => (v.id().as_u64(), v.label().as_str(), v.properties(), false),
+            BindingValue::Edge(e) => (e.id().as_u64(), e.label().as_str(), e.properties(), true),
+            _ => return None,
+        };
+        match name.to_uppercase().as_str() {
+            "ID" => Some(PropertyValue::Integer(id as i64)),
+            "LABEL" => Some(PropertyValue::String(label.to_string())),
+            "TYPE" if is_edge => Some(PropertyValue::String(label.to_string())),
+            "PROPERTIES" => Some(PropertyValue::Map(properties.clone())),
+            "KEYS" => {
+                let mut keys: Vec<&String> = properties.keys().collect();
+                keys.sort();
+                let keys = keys.into_iter().map(|k| PropertyValue::String(k.clone()));
+                Some(PropertyValue::List(keys.collect()))
+            }
+            "LABELS" => Some(PropertyValue::List(vec![PropertyValue::String(
+                label.to_string(),
+            )])),
+            _ => None,
+        }
+    }
+
+    /// Vertex IDs of the path an expression denotes: a path variable or
+    /// `nodes(p)`
+    fn path_vertices<'b>(expr: &Expression, bindings: &'b Bindings) -> Option<&'b [VertexId]> {
+        match expr {
+            Expression::Variable(var) => match bindings.get(var)? {
+                BindingValue::Path(ids) => Some(ids),
+                _ => None,
+            },
+            Expression::FunctionCall(name, args) if name.eq_ignore_ascii_case("nodes") => {
+                match &args[..] {
+                    [path] => Self::path_vertices(path, bindings),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The vertex a path element expression refers to: `p[i]` (negative
+    /// indexes count from the end), `head(p)` or `last(p)`. `Ok(None)` when
+    /// the expression is not over a path or the index is out of range.
+    fn path_element(&self, expr: &Expression, bindings: &Bindings) -> Result<Option<Vertex>> {
+        let id = match expr {
+            Expression::Index(base, index) => {
+                let Some(ids) = Self::path_vertices(base, bindings) else {
+                    return Ok(None);
+                };
+                let position = self.evaluate(index, bindings)?;
+                Self::list_position(ids.len(), &position).map(|i| ids[i])
+            }
+            Expression::FunctionCall(name, args) => {
+                let upper = name.to_uppercase();
+                let ([path], "HEAD" | "LAST") = (&args[..], upper.as_str()) else {
+                    return Ok(None);
+                };
+                let Some(ids) = Self::path_vertices(path, bindings) else {
+                    return Ok(None);
+                };
+                if upper == "HEAD" {
+                    ids.first().copied()
+                } else {
+                    ids.last().copied()
+                }
+            }
+            _ => return Ok(None),
+        };
+        Ok(id.and_then(|id| self.graph().get_vertex(id)))
+    }
+
+    /// Position in a list of `len` items for an integer index; negative
+    /// indexes count from the end
+    fn list_position(len: usize, index: &PropertyValue) -> Option<usize> {
+        let PropertyValue::Integer(index) = index else {
+            return None;
+        };
+        let position = if *index < 0 {
+            len.checked_sub(index.unsigned_abs() as usize)?
+        } else {
+            *index as usize
+        };
+        (position < len).then_some(position)
+    }
+
+    /// `nodes(p)`, `relationships(p)`, `length(p)`, `head(p)` and `last(p)`
+    /// over a path variable; vertices and edges are given by ID. A path
+    /// records its vertices, so each relationship is the first edge between
+    /// consecutive vertices, in either direction.
+    fn path_function(
+        &self,
+        name: &str,
+        args: &[Expression],
+        bindings: &Bindings,
+    ) -> Option<PropertyValue> {
+        let [path] = args else {
+            return None;
+        };
+        let ids = Self::path_vertices(path, bindings)?;
+        let id_value = |id: u64| PropertyValue::Integer(id as i64);
         match name.to_uppercase().as_str() {
+            "NODES" => Some(PropertyValue::List(
+                ids.iter().map(|id| id_value(id.as_u64())).collect(),
+            )),
+            "RELATIONSHIPS" => {
+                let graph = self.graph();
+                let edges = ids
+                    .windows(2)
+                    .filter_map(|pair| {
+                        let mut between = graph.get_edges_between(pair[0], pair[1]);
+                        if between.is_empty() {
+                            between = graph.get_edges_between(pair[1], pair[0]);
+                        }
+                        between.first().map(|e| id_value(e.id().as_u64()))
+                    })
+                    .collect();
+                Some(PropertyValue::List(edges))
+            }
+            "LENGTH" => Some(id_value(ids.len().saturating_sub(1) as u64)),
+            "HEAD" => ids.first().map(|id| id_value(id.as_u64())),
+            "LAST" => ids.last().map(|id| id_value(id.as_u64())),
+            _ => None,
+        }
+    }
+
+    /// `tag(n)` or `tag('0x...')`: the address tag set with
+    /// `Graph::set_address_tag`, or an empty string (NULL) when untagged.
+    fn address_tag(&self, args: &[Expression], bindings: &Bindings) -> Result<PropertyValue> {
+        let [arg] = args else {
+            return Err(Error::QueryError(
+                "tag() expects exactly one argument".to_string(),
+            ));
+        };
+        let vertex = match arg {
+            Expression::Variable(var) => match bindings.get(var) {
+                Some(BindingValue::Vertex(v)) => Some(v),
+                _ => None,
+            },
+            _ => None,
+        };
+        let address = match vertex {
+            Some(v) => v.address().map(str::to_string),
+            None => match self.evaluate(arg, bindings)? {
+                PropertyValue::String(s) => Some(s),
+                PropertyValue::Address(a) => Some(a.to_string()),
+                other => {
+                    return Err(Error::QueryError(format!(
+                        "tag() expects a vertex or an address, got {}",
+                        other.type_name()
+                    )))
+                }
+            },
+        };
+        let tag = address.and_then(|address| self.graph().get_address_tag(&address));
+        Ok(PropertyValue::String(tag.unwrap_or_default()))
+    }
+
+    fn call_function(&self, name: &str, args: &[PropertyValue]) -> Result<PropertyValue> {
+        let upper = name.to_uppercase();
+        match upper.as_str() {
             "COUNT" => Ok(PropertyValue::Integer(args.len() as i64)),
             "SUM" => {
                 let sum: f64 = args
@@ -1001,7 +2288,36 @@ impl QueryExecutor {
                     ))
                 }
             }
-            "MIN" | "MAX" | "TOSTRING" | "TOINTEGER" | "ID" | "LABEL" => Ok(args
+            "ID" | "LABEL" | "PROPERTIES" | "KEYS" | "LABELS" => Err(Error::QueryError(format!(
+                "{}() expects a vertex or edge variable",
+                name.to_lowercase()
+            ))),
+            "TYPE" => Err(Error::QueryError(
+                "type() expects an edge variable".to_string(),
+            )),
+            "HEAD" | "LAST" => match args {
+                [PropertyValue::List(items)] => {
+                    let item = if upper == "HEAD" {
+                        items.first()
+                    } else {
+                        items.last()
+                    };
+                    Ok(item.cloned().unwrap_or(PropertyValue::String(String::new())))
+                }
+                _ => Err(Error::QueryError(format!(
+                    "{}() expects a path or a list",
+                    name.to_lowercase()
+                ))),
+            },
+            "LENGTH" | "SIZE" => match args {
+                [PropertyValue::List(items)] => Ok(PropertyValue::Integer(items.len() as i64)),
+                [PropertyValue::String(s)] => Ok(PropertyValue::Integer(s.chars().count() as i64)),
+                _ => Err(Error::QueryError(format!(
+                    "{}() expects a path, a list or a string",
+                    name.to_lowercase()
+                ))),
+            },
+            "MIN" | "MAX" | "TOSTRING" | "TOINTEGER" => Ok(args
                 .first()
                 .cloned()
                 .unwrap_or(PropertyValue::String(String::new()))),
@@ -1009,6 +2325,52 @@ impl QueryExecutor {
         }
     }
 
+    /// Column name for an unaliased expression: its query text, without the
+    /// parentheses `Display` puts around a top-level binary operation
+    fn default_column_name(expression: &Expression) -> String {
+        match expression {
+            Expression::BinaryOp(lhs, op, rhs)
+                if !matches!(op, BinaryOperator::IsNull | BinaryOperator::IsNotNull) =>
+            {
+                format!("{} {} {}", lhs, op, rhs)
+            }
+            other => other.to_string(),
+        }
+    }
+
+    /// Column names for projected items; unaliased expressions that would
+    /// repeat an earlier column get a `_2`, `_3`, ... suffix
+    fn column_names<'a>(
+        items: impl IntoIterator<Item = (&'a Expression, Option<&'a String>)>,
+    ) -> Vec<String> {
+        let mut columns: Vec<String> = Vec::new();
+        for (expression, alias) in items {
+            let name = match alias {
+                Some(alias) => alias.clone(),
+                None => {
+                    let base = Self::default_column_name(expression);
+                    let mut name = base.clone();
+                    let mut n = 1;
+                    while columns.contains(&name) {
+                        n += 1;
+                        name = format!("{}_{}", base, n);
+                    }
+                    name
+                }
+            };
+            columns.push(name);
+        }
+        columns
+    }
+
+    fn return_column_names(items: &[ReturnItem]) -> Vec<String> {
+        Self::column_names(
+            items
+                .iter()
+                .map(|item| (&item.expression, item.alias.as_ref())),
+        )
+    }
+
     fn build_return(
         &self,
         return_clause: &[ReturnItem],
@@ -1018,18 +2380,15 @@ impl QueryExecutor {
             return Ok((Vec::new(), Vec::new()));
         }
 
-        let columns: Vec<String> = return_clause
+        let columns = Self::return_column_names(return_clause);
+
+        if return_clause
             .iter()
-            .map(|item| {
-                item.alias
-                    .clone()
-                    .unwrap_or_else(|| match &item.expression {
-                        Expression::Variable(name) => name.clone(),
-                        Expression::Property(var, prop) => format!("{}.{}", var, prop),
-                        _ => "expr".to_string(),
-                    })
-            })
-            .collect();
+            .any(|item| Self::contains_aggregate(&item.expression))
+        {
+            let rows = self.build_grouped_return(return_clause, bindings_list)?;
+            return Ok((columns, rows));
+        }
 
         let mut rows = Vec::new();
         for bindings in bindings_list {
@@ -1043,6 +2402,177 @@ impl QueryExecutor {
         Ok((columns, rows))
     }
 
+    fn is_aggregate_function(name: &str) -> bool {
+        matches!(
+            name.to_uppercase().as_str(),
+            "COUNT" | "SUM" | "AVG" | "MIN" | "MAX" | "COLLECT"
+        )
+    }
+
+    fn contains_aggregate(expr: &Expression) -> bool {
+        match expr {
+            Expression::FunctionCall(name, args) => {
+                Self::is_aggregate_function(name) || args.iter().any(Self::contains_aggregate)
+            }
+            Expression::BinaryOp(lhs, _, rhs) => {
+                Self::contains_aggregate(lhs) || Self::contains_aggregate(rhs)
+            }
+            Expression::UnaryOp(_, operand) => Self::contains_aggregate(operand),
+            Expression::ElementProperty(base, _) => Self::contains_aggregate(base),
+            Expression::Index(base, index) => {
+                Self::contains_aggregate(base) || Self::contains_aggregate(index)
+            }
+            Expression::List(items) => items.iter().any(Self::contains_aggregate),
+            Expression::Map(entries) => entries.iter().any(|(_, e)| Self::contains_aggregate(e)),
+            Expression::Case {
+                operand,
+                when_clauses,
+                else_clause,
+            } => {
+                operand.as_deref().is_some_and(Self::contains_aggregate)
+                    || when_clauses
+                        .iter()
+                        .any(|(w, t)| Self::contains_aggregate(w) || Self::contains_aggregate(t))
+                    || else_clause.as_deref().is_some_and(Self::contains_aggregate)
+            }
+            Expression::Quantified {
+                list, predicate, ..
+            } => Self::contains_aggregate(list) || Self::contains_aggregate(predicate),
+            _ => false,
+        }
+    }
+
+    /// RETURN with aggregates. The items without an aggregate are implicit
+    /// grouping keys: one row per distinct key, in order of first
+    /// appearance, with each aggregate folded over that key's bindings.
+    /// Without keys the aggregates cover every binding, so an empty match
+    /// still yields one row. An aggregate must be a whole RETURN item; one
+    /// nested in a larger expression, like `n.balance + count(n)`, mixes
+    /// per-row and per-group values and is rejected.
+    fn build_grouped_return(
+        &self,
+        return_clause: &[ReturnItem],
+        bindings_list: &[Bindings],
+    ) -> Result<Vec<Vec<ResultValue>>> {
+        let mut keys = Vec::new();
+        for item in return_clause {
+            match &item.expression {
+                Expression::FunctionCall(name, args) if Self::is_aggregate_function(name) => {
+                    if args.iter().any(Self::contains_aggregate) {
+                        return Err(Error::QueryError(format!(
+                            "Aggregate functions cannot be nested: {}",
+                            item.expression
+                        )));
+                    }
+                }
+                expr if Self::contains_aggregate(expr) => {
+                    return Err(Error::QueryError(format!(
+                        "Ambiguous RETURN item {}: an aggregate must be a whole RETURN item; return it and the values to group by as separate items",
+                        Self::default_column_name(expr)
+                    )));
+                }
+                expr => keys.push(expr),
+            }
+        }
+
+        let mut groups: Vec<(Vec<ResultValue>, Vec<&Bindings>)> = Vec::new();
+        let mut group_index: HashMap<String, usize> = HashMap::new();
+        for bindings in bindings_list {
+            let key_values = keys
+                .iter()
+                .map(|expr| self.build_result_value(expr, bindings))
+                .collect::<Result<Vec<_>>>()?;
+            let key = format!("{:?}", key_values);
+            match group_index.get(&key) {
+                Some(&index) => groups[index].1.push(bindings),
+                None => {
+                    group_index.insert(key, groups.len());
+                    groups.push((key_values, vec![bindings]));
+                }
+            }
+        }
+        if keys.is_empty() && groups.is_empty() {
+            groups.push((Vec::new(), Vec::new()));
+        }
+
+        let mut rows = Vec::with_capacity(groups.len());
+        for (key_values, members) in groups {
+            let mut key_values = key_values.into_iter();
+            let mut row = Vec::with_capacity(return_clause.len());
+            for item in return_clause {
+                match &item.expression {
+                    Expression::FunctionCall(name, args) if Self::is_aggregate_function(name) => {
+                        row.push(ResultValue::Scalar(self.aggregate(name, args, &members)?));
+                    }
+                    _ => row.push(key_values.next().unwrap_or(ResultValue::Null)),
+                }
+            }
+            rows.push(row);
+        }
+        Ok(rows)
+    }
+
+    /// Fold an aggregate over the bindings of one group, skipping NULL
+    /// (missing) inputs; vertices and edges contribute their IDs
+    fn aggregate(
+        &self,
+        name: &str,
+        args: &[Expression],
+        members: &[&Bindings],
+    ) -> Result<PropertyValue> {
+        let [arg] = args else {
+            return Err(Error::QueryError(format!(
+                "{}() expects exactly one argument",
+                name.to_lowercase()
+            )));
+        };
+        let mut values = Vec::with_capacity(members.len());
+        for bindings in members {
+            let value = match self.build_result_value(arg, bindings)? {
+                ResultValue::Scalar(PropertyValue::String(s)) if s.is_empty() => continue,
+                ResultValue::Scalar(PropertyValue::Null) | ResultValue::Null => continue,
+                ResultValue::Scalar(value) => value,
+                ResultValue::Vertex(v) => PropertyValue::Integer(v.id as i64),
+                ResultValue::Edge(e) => PropertyValue::Integer(e.id as i64),
+                ResultValue::Path(p) => PropertyValue::List(
+                    p.vertices
+                        .iter()
+                        .map(|v| PropertyValue::Integer(v.id as i64))
+                        .collect(),
+                ),
+            };
+            values.push(value);
+        }
+
+        let upper = name.to_uppercase();
+        match upper.as_str() {
+            "COUNT" => Ok(PropertyValue::Integer(values.len() as i64)),
+            "COLLECT" => Ok(PropertyValue::List(values)),
+            "MIN" | "MAX" => {
+                let wanted = if upper == "MIN" {
+                    std::cmp::Ordering::Less
+                } else {
+                    std::cmp::Ordering::Greater
+                };
+                let order = |a: &PropertyValue, b: &PropertyValue| match (a.as_f64(), b.as_f64()) {
+                    (Some(x), Some(y)) => x.partial_cmp(&y),
+                    _ => Some(a.as_string()?.cmp(b.as_string()?)),
+                };
+                Ok(values
+                    .into_iter()
+                    .reduce(|best, v| {
+                        if order(&v, &best) == Some(wanted) {
+                            v
+                        } else {
+                            best
+                        }
+                    })
+                    .unwrap_or(PropertyValue::String(String::new())))
+            }
+            _ => self.call_function(name, &values),
+        }
+    }
+
     fn build_result_value(&self, expr: &Expression, bindings: &Bindings) -> Result<ResultValue> {
         match expr {
             Expression::Variable(name) => match bindings.get(name) {
@@ -1085,18 +2615,184 @@ impl QueryExecutor {
                         .cloned()
                         .unwrap_or(PropertyValue::String(String::new())),
                 )),
+                Some(BindingValue::Scalar(PropertyValue::Map(_))) => {
+                    Ok(ResultValue::Scalar(self.evaluate(expr, bindings)?))
+                }
                 _ => Ok(ResultValue::Null),
             },
+            Expression::Index(..) | Expression::FunctionCall(..) => {
+                match self.path_element(expr, bindings)? {
+                    Some(v) => Ok(ResultValue::Vertex(VertexData {
+                        id: v.id().as_u64(),
+                        label: format!("{:?}", v.label()),
+                        properties: v.properties().clone(),
+                    })),
+                    None => Ok(ResultValue::Scalar(self.evaluate(expr, bindings)?)),
+                }
+            }
             _ => Ok(ResultValue::Scalar(self.evaluate(expr, bindings)?)),
         }
     }
 
     fn execute_insert(&self, stmt: &InsertStatement) -> Result<QueryResult> {
-        let mut inserted_vertices = 0;
-        let mut inserted_edges = 0;
-        let mut var_to_id: HashMap<String, VertexId> = HashMap::new();
-
-        for (idx, node) in stmt.nodes.iter().enumerate() {
+        // One binding row per insert: the UNWIND item, or nothing
+        let rows: Vec<Bindings> = match &stmt.unwind {
+            None => vec![Bindings::new()],
+            Some(unwind) => match self.evaluate(&unwind.expression, &Bindings::new())? {
+                PropertyValue::List(items) => items
+                    .into_iter()
+                    .map(|item| {
+                        let mut bindings = Bindings::new();
+                        bindings.insert(unwind.variable.clone(), BindingValue::Scalar(item));
+                        bindings
+                    })
+                    .collect(),
+                PropertyValue::Null => Vec::new(),
+                other => {
+                    return Err(Error::QueryError(format!(
+                        "UNWIND expects a list, got {}",
+                        other.type_name()
+                    )))
+                }
+            },
+        };
+
+        // Resolve and check every row before the first write, so a bad row
+        // leaves the graph untouched
+        let rows = rows
+            .into_iter()
+            .map(|bindings| {
+                let resolved = self.resolve_insert_properties(stmt, &bindings)?;
+                self.check_insert(&resolved)?;
+                Ok((resolved, bindings))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut inserted_vertices = 0;
+        let mut inserted_edges = 0;
+        let mut returned = Vec::new();
+        for (resolved, bindings) in rows {
+            let (vertices, edges, bindings) = self.insert_once(&resolved, bindings)?;
+            inserted_vertices += vertices;
+            inserted_edges += edges;
+            returned.push(bindings);
+        }
+
+        if !stmt.return_clause.is_empty() {
+            let (columns, rows) = self.build_return(&stmt.return_clause, &returned)?;
+            let stats = QueryStats {
+                rows_returned: rows.len(),
+                ..QueryStats::default()
+            };
+            return Ok(QueryResult {
+                columns,
+                rows,
+                stats,
+            });
+        }
+
+        Ok(QueryResult {
+            columns: vec![
+                "inserted_vertices".to_string(),
+                "inserted_edges".to_string(),
+            ],
+            rows: vec![vec![
+                ResultValue::Scalar(PropertyValue::Integer(inserted_vertices)),
+                ResultValue::Scalar(PropertyValue::Integer(inserted_edges)),
+            ]],
+            stats: QueryStats::default(),
+        })
+    }
+
+    /// Evaluate the expression-valued properties of an INSERT against
+    /// `bindings`, returning a copy that only carries literal properties
+    fn resolve_insert_properties(
+        &self,
+        stmt: &InsertStatement,
+        bindings: &Bindings,
+    ) -> Result<InsertStatement> {
+        let resolve = |props: &mut Vec<(String, PropertyValue)>,
+                       exprs: &mut Vec<(String, Expression)>|
+         -> Result<()> {
+            for (key, expr) in exprs.drain(..) {
+                props.push((key, self.evaluate(&expr, bindings)?));
+            }
+            Ok(())
+        };
+        let mut resolved = stmt.clone();
+        for node in &mut resolved.nodes {
+            resolve(&mut node.properties, &mut node.property_exprs)?;
+        }
+        for edge in &mut resolved.edges {
+            resolve(&mut edge.edge.properties, &mut edge.edge.property_exprs)?;
+        }
+        Ok(resolved)
+    }
+
+    /// Reject an INSERT with property values the schema does not accept or
+    /// edges between undeclared variables; nothing has been written yet
+    fn check_insert(&self, stmt: &InsertStatement) -> Result<()> {
+        if let Some(schema) = self.graph().get_schema() {
+            for node in &stmt.nodes {
+                if let Some(label) = node.labels().first() {
+                    let properties: HashMap<String, PropertyValue> =
+                        node.properties.iter().cloned().collect();
+                    schema.check_vertex_properties(label.as_str(), &properties)?;
+                }
+            }
+            for edge_insert in &stmt.edges {
+                let label = edge_insert
+                    .edge
+                    .labels()
+                    .first()
+                    .cloned()
+                    .unwrap_or(EdgeLabel::Transfer);
+                let properties: HashMap<String, PropertyValue> =
+                    edge_insert.edge.properties.iter().cloned().collect();
+                schema.check_edge_properties(label.as_str(), &properties)?;
+            }
+        }
+        let declared: Vec<String> = stmt
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| {
+                node.variable
+                    .clone()
+                    .unwrap_or_else(|| format!("_anon_{}", idx))
+            })
+            .collect();
+        for edge_insert in &stmt.edges {
+            if !declared.contains(&edge_insert.source) {
+                return Err(Error::QueryError(format!(
+                    "Unknown source variable: {}",
+                    edge_insert.source
+                )));
+            }
+            if !declared.contains(&edge_insert.target) {
+                return Err(Error::QueryError(format!(
+                    "Unknown target variable: {}",
+                    edge_insert.target
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Insert the patterns once, already checked with `check_insert`;
+    /// returns the vertex and edge counts and `bindings` extended with the
+    /// named inserted elements
+    fn insert_once(
+        &self,
+        stmt: &InsertStatement,
+        mut bindings: Bindings,
+    ) -> Result<(i64, i64, Bindings)> {
+        let mut inserted_vertices = 0;
+        let mut inserted_edges = 0;
+        let mut var_to_id: HashMap<String, VertexId> = HashMap::new();
+        let mut var_to_edge: HashMap<String, EdgeId> = HashMap::new();
+
+        for (idx, node) in stmt.nodes.iter().enumerate() {
             let labels = node.labels();
             let vertex_id = if !labels.is_empty() {
                 let label = labels[0].clone();
@@ -1108,28 +2804,28 @@ impl QueryExecutor {
                     if let Some(prop_specs) = stored.node_types.get(label_name) {
                         if let Some(addr_spec) = prop_specs.iter().find(|p| p.name == "address") {
                             if addr_spec.data_type.to_lowercase().contains("string") {
-                                if let Some((_, v)) = node.properties.iter().find(|(k, _)| k == "address") {
-                                    if let PropertyValue::String(s) = v {
-                                        address_prop = Some(s.clone());
-                                    }
+                                if let Some((_, PropertyValue::String(s))) =
+                                    node.properties.iter().find(|(k, _)| k == "address")
+                                {
+                                    address_prop = Some(s.clone());
                                 }
                             } else {
                                 // 如果 schema 指定为非-string（比如之前的 Address），
                                 // 我们也退回到字符串处理，不做隐式解析。
-                                if let Some((_, v)) = node.properties.iter().find(|(k, _)| k == "address") {
-                                    if let PropertyValue::String(s) = v {
-                                        address_prop = Some(s.clone());
-                                    }
+                                if let Some((_, PropertyValue::String(s))) =
+                                    node.properties.iter().find(|(k, _)| k == "address")
+                                {
+                                    address_prop = Some(s.clone());
                                 }
                             }
                         }
                     }
                 } else {
                     // 无 schema 情况，按字符串处理
-                    if let Some((_, v)) = node.properties.iter().find(|(k, _)| k == "address") {
-                        if let PropertyValue::String(s) = v {
-                            address_prop = Some(s.clone());
-                        }
+                    if let Some((_, PropertyValue::String(s))) =
+                        node.properties.iter().find(|(k, _)| k == "address")
+                    {
+                        address_prop = Some(s.clone());
                     }
                 }
 
@@ -1186,8 +2882,7 @@ impl QueryExecutor {
                     PropertyValue::Integer(i) => Some(TokenAmount::from_u64(*i as u64)),
                     PropertyValue::String(s) => s.parse::<u64>().ok().map(TokenAmount::from_u64),
                     _ => None,
-                })
-                .unwrap_or_else(|| TokenAmount::from_u64(0));
+                });
 
             let block_number = edge_insert
                 .edge
@@ -1200,57 +2895,426 @@ impl QueryExecutor {
                 })
                 .unwrap_or(0);
 
-            match label {
-                EdgeLabel::Transfer => {
-                    self.graph()
-                        .add_transfer(*src_id, *dst_id, amount, block_number)?;
+            let edge_id = match label {
+                EdgeLabel::Transfer => match (amount, *self.missing_amount.read()) {
+                    (Some(amount), _) => {
+                        self.graph()
+                            .add_transfer(*src_id, *dst_id, amount, block_number)?
+                    }
+                    (None, MissingAmount::Zero) => {
+                        let zero = TokenAmount::from_u64(0);
+                        self.graph()
+                            .add_transfer(*src_id, *dst_id, zero, block_number)?
+                    }
+                    (None, MissingAmount::Absent) => {
+                        self.graph().add_transfer_without_amount(
+                            *src_id,
+                            *dst_id,
+                            block_number,
+                            Vec::new(),
+                        )?
+                    }
+                },
+                _ => self.graph().add_edge(label, *src_id, *dst_id)?,
+            };
+            if let Some(ref var) = edge_insert.edge.variable {
+                var_to_edge.insert(var.clone(), edge_id);
+            }
+            inserted_edges += 1;
+        }
+
+        // Bind the named inserted elements for RETURN
+        if !stmt.return_clause.is_empty() {
+            for (var, id) in &var_to_id {
+                if var.starts_with("_anon_") {
+                    continue;
                 }
-                _ => {
-                    self.graph().add_edge(label, *src_id, *dst_id)?;
+                if let Some(vertex) = self.graph().get_vertex(*id) {
+                    bindings.insert(var.clone(), BindingValue::Vertex(vertex));
+                }
+            }
+            for (var, id) in &var_to_edge {
+                if let Some(edge) = self.graph().get_edge(*id) {
+                    bindings.insert(var.clone(), BindingValue::Edge(edge));
                 }
             }
-            inserted_edges += 1;
         }
 
-        Ok(QueryResult {
-            columns: vec![
-                "inserted_vertices".to_string(),
-                "inserted_edges".to_string(),
-            ],
-            rows: vec![vec![
-                ResultValue::Scalar(PropertyValue::Integer(inserted_vertices)),
-                ResultValue::Scalar(PropertyValue::Integer(inserted_edges)),
-            ]],
-            stats: QueryStats::default(),
-        })
+        Ok((inserted_vertices, inserted_edges, bindings))
     }
 
-    fn execute_delete(&self, _stmt: &DeleteStatement) -> Result<QueryResult> {
+    fn execute_delete(&self, stmt: &DeleteStatement) -> Result<QueryResult> {
+        let mut stats = QueryStats::default();
+        let bindings_list = match stmt.match_clause {
+            Some(ref query) => self.in_snapshot(|| self.match_bindings(query, &mut stats))?,
+            None => vec![HashMap::new()],
+        };
+
+        let (vertices, edges) =
+            self.delete_targets(&bindings_list, &stmt.variables, stmt.detach)?;
+        self.remove_targets(&vertices, &edges)?;
+
+        let deleted = (vertices.len() + edges.len()) as i64;
         Ok(QueryResult {
             columns: vec!["deleted".to_string()],
-            rows: vec![vec![ResultValue::Scalar(PropertyValue::Integer(0))]],
-            stats: QueryStats::default(),
+            rows: vec![vec![ResultValue::Scalar(PropertyValue::Integer(deleted))]],
+            stats,
+        })
+    }
+
+    /// Collect the vertex and edge IDs bound to `variables` across all rows.
+    /// With `detach` the edges incident to a deleted vertex are added;
+    /// otherwise a vertex that would keep any edge is an error.
+    fn delete_targets(
+        &self,
+        bindings_list: &[Bindings],
+        variables: &[String],
+        detach: bool,
+    ) -> Result<(BTreeSet<u64>, BTreeSet<u64>)> {
+        let mut vertices = BTreeSet::new();
+        let mut edges = BTreeSet::new();
+        for bindings in bindings_list {
+            for var in variables {
+                for element in self.bound_elements(var, bindings)? {
+                    match element {
+                        BoundElement::Vertex(id) => vertices.insert(id.as_u64()),
+                        BoundElement::Edge(id) => edges.insert(id.as_u64()),
+                    };
+                }
+            }
+        }
+
+        let graph = self.graph();
+        for &id in &vertices {
+            let id = VertexId::new(id);
+            for edge in graph
+                .get_outgoing_edges(id)
+                .into_iter()
+                .chain(graph.get_incoming_edges(id))
+            {
+                let edge_id = edge.id().as_u64();
+                if detach {
+                    edges.insert(edge_id);
+                } else if !edges.contains(&edge_id) {
+                    return Err(Error::QueryError(format!(
+                        "Cannot delete vertex {} because it still has edges, use DETACH DELETE",
+                        id.as_u64()
+                    )));
+                }
+            }
+        }
+        Ok((vertices, edges))
+    }
+
+    /// Remove edges first so no vertex is removed while an edge still points at it
+    fn remove_targets(&self, vertices: &BTreeSet<u64>, edges: &BTreeSet<u64>) -> Result<()> {
+        let graph = self.graph();
+        for &id in edges {
+            graph.remove_edge(EdgeId::new(id))?;
+        }
+        for &id in vertices {
+            graph.remove_vertex(VertexId::new(id))?;
+        }
+        Ok(())
+    }
+
+    /// Detach-delete every vertex and edge bound to the returned variables
+    /// of a MATCH (all pattern variables when it has no RETURN). With
+    /// `preview` set nothing is removed and only the counts are reported.
+    pub fn delete_matches(&self, query: &MatchStatement, preview: bool) -> Result<DeleteSummary> {
+        let mut stats = QueryStats::default();
+        let bindings_list = self.in_snapshot(|| self.match_bindings(query, &mut stats))?;
+
+        let mut variables = Vec::new();
+        for item in Self::return_items(query)?.iter() {
+            match &item.expression {
+                Expression::Variable(var) => variables.push(var.clone()),
+                other => {
+                    return Err(Error::QueryError(format!(
+                        "Delete targets must be pattern variables, got {}",
+                        other
+                    )))
+                }
+            }
+        }
+
+        // DETACH: edges incident to a deleted vertex go with it
+        let (vertices, edges) = self.delete_targets(&bindings_list, &variables, true)?;
+        if !preview {
+            self.remove_targets(&vertices, &edges)?;
+        }
+
+        Ok(DeleteSummary {
+            vertices: vertices.len(),
+            edges: edges.len(),
+            stats,
         })
     }
 
-    fn execute_set(&self, _stmt: &SetStatement) -> Result<QueryResult> {
+    fn execute_set(&self, stmt: &SetStatement) -> Result<QueryResult> {
+        let mut stats = QueryStats::default();
+        let bindings_list = match stmt.match_clause {
+            Some(ref query) => self.match_bindings(query, &mut stats)?,
+            None => vec![HashMap::new()],
+        };
+
+        let mut updated = 0;
+        for bindings in &bindings_list {
+            for item in &stmt.items {
+                updated += self.apply_set_item(item, bindings)?;
+            }
+        }
+
         Ok(QueryResult {
             columns: vec!["updated".to_string()],
-            rows: vec![vec![ResultValue::Scalar(PropertyValue::Integer(0))]],
-            stats: QueryStats::default(),
+            rows: vec![vec![ResultValue::Scalar(PropertyValue::Integer(updated))]],
+            stats,
         })
     }
 
-    fn execute_remove(&self, _stmt: &RemoveStatement) -> Result<QueryResult> {
+    /// Apply one SET item for a single binding row, returning the number of writes
+    fn apply_set_item(&self, item: &SetItem, bindings: &Bindings) -> Result<i64> {
+        let graph = self.graph();
+        let (variable, elements) = match item {
+            SetItem::Property(var, _, _) => (var, self.bound_elements(var, bindings)?),
+            SetItem::AllProperties { variable, .. } => {
+                (variable, self.bound_elements(variable, bindings)?)
+            }
+            SetItem::Label(var, _) => (var, self.bound_elements(var, bindings)?),
+        };
+
+        let mut writes = 0;
+        for element in elements {
+            match (item, element) {
+                (SetItem::Property(_, prop, expr), BoundElement::Vertex(id)) => {
+                    if let Some(mut vertex) = graph.get_vertex(id) {
+                        vertex.set_property(prop.clone(), self.evaluate(expr, bindings)?);
+                        graph.update_vertex(vertex)?;
+                        writes += 1;
+                    }
+                }
+                (SetItem::Property(_, prop, expr), BoundElement::Edge(id)) => {
+                    if let Some(mut edge) = graph.get_edge(id) {
+                        edge.set_property(prop.clone(), self.evaluate(expr, bindings)?);
+                        graph.update_edge(edge)?;
+                        writes += 1;
+                    }
+                }
+                (
+                    SetItem::AllProperties {
+                        properties, merge, ..
+                    },
+                    BoundElement::Vertex(id),
+                ) => {
+                    if let Some(mut vertex) = graph.get_vertex(id) {
+                        if !*merge {
+                            // address 是索引键，整体替换时保留
+                            let keys: Vec<String> = vertex
+                                .properties()
+                                .keys()
+                                .filter(|k| k.as_str() != "address")
+                                .cloned()
+                                .collect();
+                            for key in keys {
+                                vertex.remove_property(&key);
+                            }
+                        }
+                        for (key, expr) in properties {
+                            vertex.set_property(key.clone(), self.evaluate(expr, bindings)?);
+                            writes += 1;
+                        }
+                        graph.update_vertex(vertex)?;
+                    }
+                }
+                (
+                    SetItem::AllProperties {
+                        properties, merge, ..
+                    },
+                    BoundElement::Edge(id),
+                ) => {
+                    if let Some(mut edge) = graph.get_edge(id) {
+                        if !*merge {
+                            let keys: Vec<String> = edge.properties().keys().cloned().collect();
+                            for key in keys {
+                                edge.remove_property(&key);
+                            }
+                        }
+                        for (key, expr) in properties {
+                            edge.set_property(key.clone(), self.evaluate(expr, bindings)?);
+                            writes += 1;
+                        }
+                        graph.update_edge(edge)?;
+                    }
+                }
+                (SetItem::Label(_, label), BoundElement::Vertex(id)) => {
+                    if let Some(mut vertex) = graph.get_vertex(id) {
+                        vertex.set_label(label.clone());
+                        graph.update_vertex(vertex)?;
+                        writes += 1;
+                    }
+                }
+                (SetItem::Label(..), BoundElement::Edge(_)) => {
+                    return Err(Error::QueryError(format!(
+                        "Cannot set a vertex label on edge variable: {}",
+                        variable
+                    )));
+                }
+            }
+        }
+        Ok(writes)
+    }
+
+    /// Resolve the graph elements a variable refers to.
+    /// A path variable expands to every vertex along the path.
+    fn bound_elements(&self, var: &str, bindings: &Bindings) -> Result<Vec<BoundElement>> {
+        match bindings.get(var) {
+            Some(BindingValue::Vertex(v)) => Ok(vec![BoundElement::Vertex(v.id())]),
+            Some(BindingValue::Edge(e)) => Ok(vec![BoundElement::Edge(e.id())]),
+            Some(BindingValue::Path(ids)) => {
+                let mut seen = std::collections::HashSet::new();
+                Ok(ids
+                    .iter()
+                    .filter(|id| seen.insert(**id))
+                    .map(|id| BoundElement::Vertex(*id))
+                    .collect())
+            }
+            Some(BindingValue::Scalar(_)) => Err(Error::QueryError(format!(
+                "Variable {} is not a graph element",
+                var
+            ))),
+            None => Err(Error::QueryError(format!("Unbound variable: {}", var))),
+        }
+    }
+
+    fn execute_remove(&self, stmt: &RemoveStatement) -> Result<QueryResult> {
+        let mut stats = QueryStats::default();
+        let bindings_list = match stmt.match_clause {
+            Some(ref query) => self.in_snapshot(|| self.match_bindings(query, &mut stats))?,
+            None => vec![HashMap::new()],
+        };
+
+        // Validate every item against every row first so a rejected item
+        // leaves the graph untouched
+        for bindings in &bindings_list {
+            for item in &stmt.items {
+                self.check_remove_item(item, bindings)?;
+            }
+        }
+
+        let mut removed = 0;
+        for bindings in &bindings_list {
+            for item in &stmt.items {
+                removed += self.apply_remove_item(item, bindings)?;
+            }
+        }
+
         Ok(QueryResult {
             columns: vec!["removed".to_string()],
-            rows: vec![vec![ResultValue::Scalar(PropertyValue::Integer(0))]],
-            stats: QueryStats::default(),
+            rows: vec![vec![ResultValue::Scalar(PropertyValue::Integer(removed))]],
+            stats,
         })
     }
 
+    /// Reject a REMOVE item that cannot be applied to a binding row
+    fn check_remove_item(&self, item: &RemoveItem, bindings: &Bindings) -> Result<()> {
+        let graph = self.graph();
+        let variable = match item {
+            RemoveItem::Property(var, _) | RemoveItem::Label(var, _) => var,
+        };
+
+        for element in self.bound_elements(variable, bindings)? {
+            match (item, element) {
+                // address 是索引键，不能移除
+                (RemoveItem::Property(_, prop), BoundElement::Vertex(_)) if prop == "address" => {
+                    return Err(Error::QueryError(
+                        "Cannot remove the address property of a vertex".to_string(),
+                    ));
+                }
+                (RemoveItem::Property(..), _) => {}
+                (RemoveItem::Label(_, label), BoundElement::Vertex(id)) => {
+                    // 顶点只有一个标签，移除后将没有标签
+                    if graph.get_vertex(id).is_some_and(|v| v.label() == label) {
+                        return Err(Error::QueryError(format!(
+                            "Cannot remove label {} from {}: a vertex must keep its only label, use SET {}:<Label> to relabel",
+                            label.as_str(),
+                            variable,
+                            variable
+                        )));
+                    }
+                }
+                (RemoveItem::Label(..), BoundElement::Edge(_)) => {
+                    return Err(Error::QueryError(format!(
+                        "Cannot remove a vertex label from edge variable: {}",
+                        variable
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply one validated REMOVE item for a single binding row, returning
+    /// the number of properties actually removed. Label items that pass
+    /// validation name a label the vertex does not have and are no-ops
+    fn apply_remove_item(&self, item: &RemoveItem, bindings: &Bindings) -> Result<i64> {
+        let graph = self.graph();
+        let (variable, prop) = match item {
+            RemoveItem::Property(var, prop) => (var, prop),
+            RemoveItem::Label(..) => return Ok(0),
+        };
+
+        let mut removed = 0;
+        for element in self.bound_elements(variable, bindings)? {
+            match element {
+                BoundElement::Vertex(id) => {
+                    if let Some(mut vertex) = graph.get_vertex(id) {
+                        if vertex.remove_property(prop).is_some() {
+                            graph.update_vertex(vertex)?;
+                            removed += 1;
+                        }
+                    }
+                }
+                BoundElement::Edge(id) => {
+                    if let Some(mut edge) = graph.get_edge(id) {
+                        if edge.remove_property(prop).is_some() {
+                            graph.update_edge(edge)?;
+                            removed += 1;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Run a procedure, going through the result cache for algorithm calls.
+    /// `db.*` procedures inspect or repair storage and always run.
     fn execute_call(&self, stmt: &CallStatement) -> Result<QueryResult> {
-        use crate::algorithm::{EdmondsKarp, PathFinder, TraceDirection};
+        let cache = match &self.result_cache {
+            Some(cache) if !stmt.procedure_name.to_lowercase().starts_with("db.") => cache,
+            _ => return self.call_procedure(stmt),
+        };
+        // Read the version before running: a write racing with the call moves
+        // the graph to a newer version, so a result that missed it is never
+        // served for that version
+        let graph = self.graph();
+        let key = ResultKey {
+            graph: graph.instance_id(),
+            version: graph.version(),
+            call: format!("{:?} max_flow_edges={}", stmt, self.max_flow_edges),
+        };
+        if let Some(result) = cache.get(&key) {
+            return Ok(result);
+        }
+        let result = self.call_procedure(stmt)?;
+        cache.insert(key, result.clone());
+        Ok(result)
+    }
+
+    fn call_procedure(&self, stmt: &CallStatement) -> Result<QueryResult> {
+        use crate::algorithm::{
+            AddressCluster, ClusterParams, Counterparty, MaxFlowAlgorithm, NetBalance, PathFinder,
+        };
 
         let proc_name = stmt.procedure_name.to_lowercase();
 
@@ -1264,7 +3328,7 @@ impl QueryExecutor {
                 let source = self.eval_to_int(&stmt.arguments[0])?;
                 let target = self.eval_to_int(&stmt.arguments[1])?;
 
-                let finder = PathFinder::new(self.graph());
+                let finder = PathFinder::new(self.graph()).with_cancellation(self.cancel.clone());
                 if let Some(path) =
                     finder.shortest_path(VertexId::new(source as u64), VertexId::new(target as u64))
                 {
@@ -1312,12 +3376,12 @@ impl QueryExecutor {
                     10
                 };
 
-                let finder = PathFinder::new(self.graph());
+                let finder = PathFinder::new(self.graph()).with_cancellation(self.cancel.clone());
                 let paths = finder.all_paths(
                     VertexId::new(source as u64),
                     VertexId::new(target as u64),
                     max_depth,
-                );
+                )?;
 
                 let rows: Vec<Vec<ResultValue>> = paths
                     .iter()
@@ -1374,7 +3438,7 @@ impl QueryExecutor {
                     5
                 };
 
-                let finder = PathFinder::new(self.graph());
+                let finder = PathFinder::new(self.graph()).with_cancellation(self.cancel.clone());
                 let traces = finder.trace(VertexId::new(start as u64), direction, max_depth, None);
 
                 let rows: Vec<Vec<ResultValue>> = traces
@@ -1406,42 +3470,365 @@ impl QueryExecutor {
                 })
             }
 
-            "max_flow" | "algo.max_flow" => {
-                if stmt.arguments.len() < 2 {
+            "trace_value" | "algo.trace_value" => {
+                if stmt.arguments.len() < 3 {
                     return Err(Error::QueryError(
-                        "max_flow requires 2 arguments".to_string(),
+                        "trace_value requires 3 arguments (start, target, tolerance)".to_string(),
                     ));
                 }
-                let source = self.eval_to_int(&stmt.arguments[0])?;
-                let sink = self.eval_to_int(&stmt.arguments[1])?;
-
-                let algo = EdmondsKarp::new(self.graph());
-                let result =
-                    algo.max_flow(VertexId::new(source as u64), VertexId::new(sink as u64));
+                let start = self.eval_to_int(&stmt.arguments[0])?;
+                let target = self.eval_to_int(&stmt.arguments[1])?;
+                let tolerance = self.eval_to_float(&stmt.arguments[2])?;
+                if !(tolerance > 0.0 && tolerance <= 1.0) {
+                    return Err(Error::QueryError(
+                        "trace_value tolerance must be in (0, 1]".to_string(),
+                    ));
+                }
+                let max_depth = if stmt.arguments.len() > 3 {
+                    self.eval_to_int(&stmt.arguments[3])? as usize
+                } else {
+                    10
+                };
 
-                let mut rows = vec![vec![
-                    ResultValue::Scalar(PropertyValue::String("max_flow_value".to_string())),
-                    ResultValue::Scalar(PropertyValue::Float(result.value)),
-                ]];
+                let finder = PathFinder::new(self.graph()).with_cancellation(self.cancel.clone());
+                let mut paths = finder.trace_value(
+                    VertexId::new(start as u64),
+                    VertexId::new(target as u64),
+                    tolerance,
+                    max_depth,
+                )?;
+                paths.sort_by(|a, b| b.fraction.total_cmp(&a.fraction));
 
-                for ((u, v), flow) in result.flow.iter().take(20) {
-                    rows.push(vec![
-                        ResultValue::Scalar(PropertyValue::String(format!(
-                            "{} -> {}",
-                            u.as_u64(),
-                            v.as_u64()
-                        ))),
-                        ResultValue::Scalar(PropertyValue::Float(*flow)),
-                    ]);
-                }
+                let rows: Vec<Vec<ResultValue>> = paths
+                    .iter()
+                    .take(100)
+                    .map(|flow| {
+                        vec![
+                            ResultValue::Scalar(PropertyValue::String(
+                                flow.path
+                                    .vertices
+                                    .iter()
+                                    .map(|v| format!("{}", v.as_u64()))
+                                    .collect::<Vec<_>>()
+                                    .join(" -> "),
+                            )),
+                            ResultValue::Scalar(PropertyValue::Integer(flow.path.length as i64)),
+                            ResultValue::Scalar(PropertyValue::Float(flow.fraction)),
+                        ]
+                    })
+                    .collect();
 
                 Ok(QueryResult {
-                    columns: vec!["edge".to_string(), "flow".to_string()],
+                    columns: vec![
+                        "path".to_string(),
+                        "length".to_string(),
+                        "fraction".to_string(),
+                    ],
                     rows,
                     stats: QueryStats::default(),
                 })
             }
 
+            "reachability" | "algo.reachability" => {
+                if stmt.arguments.is_empty() {
+                    return Err(Error::QueryError(
+                        "reachability requires at least 1 argument (list of vertex ids)"
+                            .to_string(),
+                    ));
+                }
+                let seeds: Vec<VertexId> = self
+                    .eval_to_int_list(&stmt.arguments[0])?
+                    .into_iter()
+                    .map(|id| VertexId::new(id as u64))
+                    .collect();
+                let max_depth = if stmt.arguments.len() > 1 {
+                    self.eval_to_int(&stmt.arguments[1])? as usize
+                } else {
+                    3
+                };
+                let direction = if stmt.arguments.len() > 2 {
+                    match self
+                        .eval_to_string(&stmt.arguments[2])?
+                        .to_lowercase()
+                        .as_str()
+                    {
+                        "backward" | "back" => TraceDirection::Backward,
+                        "both" => TraceDirection::Both,
+                        _ => TraceDirection::Forward,
+                    }
+                } else {
+                    TraceDirection::Forward
+                };
+
+                let finder = PathFinder::new(self.graph()).with_cancellation(self.cancel.clone());
+                let rows = finder
+                    .reachability(&seeds, max_depth, direction)?
+                    .into_iter()
+                    .map(|r| {
+                        vec![
+                            ResultValue::Scalar(PropertyValue::Integer(r.from.as_u64() as i64)),
+                            ResultValue::Scalar(PropertyValue::Integer(r.to.as_u64() as i64)),
+                            ResultValue::Scalar(PropertyValue::Boolean(r.reachable)),
+                        ]
+                    })
+                    .collect();
+
+                Ok(QueryResult {
+                    columns: vec![
+                        "from".to_string(),
+                        "to".to_string(),
+                        "reachable".to_string(),
+                    ],
+                    rows,
+                    stats: QueryStats::default(),
+                })
+            }
+
+            "multi_shortest" | "algo.multi_shortest" => {
+                if stmt.arguments.len() < 2 {
+                    return Err(Error::QueryError(
+                        "multi_shortest requires 2 arguments (list of source ids, target)"
+                            .to_string(),
+                    ));
+                }
+                let sources: Vec<VertexId> = self
+                    .eval_to_int_list(&stmt.arguments[0])?
+                    .into_iter()
+                    .map(|id| VertexId::new(id as u64))
+                    .collect();
+                let target = VertexId::new(self.eval_to_int(&stmt.arguments[1])? as u64);
+
+                let finder = PathFinder::new(self.graph()).with_cancellation(self.cancel.clone());
+                let result = finder.multi_source_shortest(&sources, target)?;
+                let rows = sources
+                    .iter()
+                    .zip(result.paths)
+                    .map(|(source, path)| {
+                        let (distance, vertices) = match path {
+                            Some(path) => (
+                                PropertyValue::Integer(path.length as i64),
+                                PropertyValue::String(
+                                    path.vertices
+                                        .iter()
+                                        .map(|v| v.as_u64().to_string())
+                                        .collect::<Vec<_>>()
+                                        .join(" -> "),
+                                ),
+                            ),
+                            None => (PropertyValue::Null, PropertyValue::Null),
+                        };
+                        vec![
+                            ResultValue::Scalar(PropertyValue::Integer(source.as_u64() as i64)),
+                            ResultValue::Scalar(distance),
+                            ResultValue::Scalar(vertices),
+                        ]
+                    })
+                    .collect();
+
+                Ok(QueryResult {
+                    columns: vec![
+                        "source".to_string(),
+                        "distance".to_string(),
+                        "path".to_string(),
+                    ],
+                    rows,
+                    stats: QueryStats::default(),
+                })
+            }
+
+            "net_balance" | "algo.net_balance" => {
+                if stmt.arguments.is_empty() {
+                    return Err(Error::QueryError(
+                        "net_balance requires at least 1 argument (vertex_id)".to_string(),
+                    ));
+                }
+                let vertex = self.eval_to_int(&stmt.arguments[0])?;
+                let block_arg = |idx: usize| -> Result<Option<u64>> {
+                    stmt.arguments
+                        .get(idx)
+                        .map(|arg| self.eval_to_int(arg).map(|b| b as u64))
+                        .transpose()
+                };
+                let balance = NetBalance::compute(
+                    &self.graph(),
+                    VertexId::new(vertex as u64),
+                    block_arg(1)?,
+                    block_arg(2)?,
+                )?;
+
+                Ok(QueryResult {
+                    columns: vec![
+                        "inflow".to_string(),
+                        "outflow".to_string(),
+                        "net".to_string(),
+                        "inflow_count".to_string(),
+                        "outflow_count".to_string(),
+                    ],
+                    rows: vec![vec![
+                        ResultValue::Scalar(PropertyValue::TokenAmount(balance.inflow)),
+                        ResultValue::Scalar(PropertyValue::TokenAmount(balance.outflow)),
+                        ResultValue::Scalar(PropertyValue::String(balance.net())),
+                        ResultValue::Scalar(PropertyValue::Integer(balance.inflow_count as i64)),
+                        ResultValue::Scalar(PropertyValue::Integer(balance.outflow_count as i64)),
+                    ]],
+                    stats: QueryStats::default(),
+                })
+            }
+
+            "top_counterparties" | "algo.top_counterparties" => {
+                if stmt.arguments.is_empty() {
+                    return Err(Error::QueryError(
+                        "top_counterparties requires at least 1 argument (vertex_id)".to_string(),
+                    ));
+                }
+                let vertex = self.eval_to_int(&stmt.arguments[0])?;
+                let k = match stmt.arguments.get(1) {
+                    Some(arg) => self.eval_to_int(arg)?.max(0) as usize,
+                    None => 10,
+                };
+                let direction = match stmt.arguments.get(2) {
+                    Some(arg) => match self.eval_to_string(arg)?.to_lowercase().as_str() {
+                        "forward" | "out" => TraceDirection::Forward,
+                        "backward" | "back" | "in" => TraceDirection::Backward,
+                        _ => TraceDirection::Both,
+                    },
+                    None => TraceDirection::Both,
+                };
+                let rank = match stmt.arguments.get(3) {
+                    Some(arg) => self.eval_to_string(arg)?.parse()?,
+                    None => Default::default(),
+                };
+
+                let rows = Counterparty::top(
+                    &self.graph(),
+                    VertexId::new(vertex as u64),
+                    k,
+                    direction,
+                    rank,
+                )?
+                .into_iter()
+                .map(|c| {
+                    let address = self
+                        .graph()
+                        .get_vertex(c.vertex)
+                        .and_then(|v| v.address().map(str::to_string))
+                        .unwrap_or_default();
+                    vec![
+                        ResultValue::Scalar(PropertyValue::Integer(c.vertex.as_u64() as i64)),
+                        ResultValue::Scalar(PropertyValue::String(address)),
+                        ResultValue::Scalar(PropertyValue::Integer(c.transfer_count as i64)),
+                        ResultValue::Scalar(PropertyValue::TokenAmount(c.volume)),
+                    ]
+                })
+                .collect();
+
+                Ok(QueryResult {
+                    columns: vec![
+                        "counterparty".to_string(),
+                        "address".to_string(),
+                        "transfer_count".to_string(),
+                        "volume".to_string(),
+                    ],
+                    rows,
+                    stats: QueryStats::default(),
+                })
+            }
+
+            "cluster_addresses" | "algo.cluster_addresses" => {
+                if stmt.arguments.is_empty() {
+                    return Err(Error::QueryError(
+                        "cluster_addresses requires at least 1 argument (seed)".to_string(),
+                    ));
+                }
+                let seed = self.eval_to_int(&stmt.arguments[0])?;
+                let hops = match stmt.arguments.get(1) {
+                    Some(arg) => self.eval_to_int(arg)?.max(0) as usize,
+                    None => 2,
+                };
+                let mut params = ClusterParams::default();
+                if let Some(arg) = stmt.arguments.get(2) {
+                    params.min_shared = self.eval_to_int(arg)?.max(1) as usize;
+                }
+                if let Some(arg) = stmt.arguments.get(3) {
+                    params.block_window = self.eval_to_int(arg)?.max(0) as u64;
+                }
+
+                let graph = self.graph();
+                let seed = VertexId::new(seed as u64);
+                let id_value = |id: VertexId| PropertyValue::Integer(id.as_u64() as i64);
+                let rows = AddressCluster::compute(&graph, seed, hops, params)?
+                    .into_iter()
+                    .map(|c| {
+                        let address = graph
+                            .get_vertex(c.vertex)
+                            .and_then(|v| v.address().map(str::to_string))
+                            .unwrap_or_default();
+                        vec![
+                            ResultValue::Scalar(id_value(c.vertex)),
+                            ResultValue::Scalar(PropertyValue::String(address)),
+                            ResultValue::Scalar(id_value(c.cluster)),
+                        ]
+                    })
+                    .collect();
+
+                Ok(QueryResult {
+                    columns: vec![
+                        "vertex".to_string(),
+                        "address".to_string(),
+                        "cluster".to_string(),
+                    ],
+                    rows,
+                    stats: QueryStats::default(),
+                })
+            }
+
+            "max_flow" | "algo.max_flow" => {
+                if stmt.arguments.len() < 2 {
+                    return Err(Error::QueryError(
+                        "max_flow requires 2 arguments".to_string(),
+                    ));
+                }
+                let source = self.eval_to_int(&stmt.arguments[0])?;
+                let sink = self.eval_to_int(&stmt.arguments[1])?;
+                let algorithm: MaxFlowAlgorithm = match stmt.arguments.get(2) {
+                    Some(arg) => self.eval_to_string(arg)?.parse()?,
+                    None => MaxFlowAlgorithm::default(),
+                };
+
+                let result = algorithm.max_flow(
+                    self.graph(),
+                    self.cancel.clone(),
+                    VertexId::new(source as u64),
+                    VertexId::new(sink as u64),
+                )?;
+
+                let mut rows = vec![vec![
+                    ResultValue::Scalar(PropertyValue::String("max_flow_value".to_string())),
+                    ResultValue::Scalar(PropertyValue::Float(result.value)),
+                ]];
+
+                let (edges, truncated) = result.top_flows(self.max_flow_edges);
+                for edge in edges {
+                    rows.push(vec![
+                        ResultValue::Scalar(PropertyValue::String(format!(
+                            "{} -> {}",
+                            edge.source.as_u64(),
+                            edge.target.as_u64()
+                        ))),
+                        ResultValue::Scalar(PropertyValue::Float(edge.flow)),
+                    ]);
+                }
+
+                Ok(QueryResult {
+                    columns: vec!["edge".to_string(), "flow".to_string()],
+                    rows,
+                    stats: QueryStats {
+                        truncated,
+                        ..QueryStats::default()
+                    },
+                })
+            }
+
             "neighbors" | "algo.neighbors" => {
                 if stmt.arguments.is_empty() {
                     return Err(Error::QueryError(
@@ -1542,10 +3929,16 @@ impl QueryExecutor {
                 let source = self.eval_to_int(&stmt.arguments[0])?;
                 let target = self.eval_to_int(&stmt.arguments[1])?;
 
-                let finder = PathFinder::new(self.graph());
-                let connected = finder
-                    .shortest_path(VertexId::new(source as u64), VertexId::new(target as u64))
-                    .is_some();
+                let graph = self.graph();
+                let source_id = VertexId::new(source as u64);
+                let target_id = VertexId::new(target as u64);
+                // Missing endpoints are never connected, not even to themselves
+                let connected = graph.vertex_exists(source_id)
+                    && graph.vertex_exists(target_id)
+                    && PathFinder::new(graph)
+                        .with_cancellation(self.cancel.clone())
+                        .shortest_path(source_id, target_id)
+                        .is_some();
 
                 Ok(QueryResult {
                     columns: vec![
@@ -1562,6 +3955,141 @@ impl QueryExecutor {
                 })
             }
 
+            "db.rebuild_indexes" => {
+                let (vertices, edges) = self.graph().rebuild_indexes();
+
+                Ok(QueryResult {
+                    columns: vec!["vertices_indexed".to_string(), "edges_indexed".to_string()],
+                    rows: vec![vec![
+                        ResultValue::Scalar(PropertyValue::Integer(vertices as i64)),
+                        ResultValue::Scalar(PropertyValue::Integer(edges as i64)),
+                    ]],
+                    stats: QueryStats::default(),
+                })
+            }
+
+            "db.check_consistency" => {
+                let report = self.graph().check_consistency();
+                let item = |name: &str, value: PropertyValue| {
+                    vec![
+                        ResultValue::Scalar(PropertyValue::String(name.to_string())),
+                        ResultValue::Scalar(value),
+                    ]
+                };
+
+                let mut rows = vec![
+                    item("consistent", PropertyValue::Boolean(report.is_consistent())),
+                    item(
+                        "vertices_checked",
+                        PropertyValue::Integer(report.vertices_checked as i64),
+                    ),
+                    item(
+                        "edges_checked",
+                        PropertyValue::Integer(report.edges_checked as i64),
+                    ),
+                ];
+                for issue in report.issues {
+                    rows.push(item("issue", PropertyValue::String(issue)));
+                }
+
+                Ok(QueryResult {
+                    columns: vec!["item".to_string(), "value".to_string()],
+                    rows,
+                    stats: QueryStats::default(),
+                })
+            }
+
+            "db.property_histogram" => {
+                if stmt.arguments.len() < 2 {
+                    return Err(Error::QueryError(
+                        "db.property_histogram requires at least 2 arguments".to_string(),
+                    ));
+                }
+                let label_name = self.eval_to_string(&stmt.arguments[0])?;
+                let label = GqlParser::parse_vertex_label(&label_name)
+                    .ok_or_else(|| Error::QueryError(format!("Unknown label: {}", label_name)))?;
+                let property = self.eval_to_string(&stmt.arguments[1])?;
+                let buckets = if stmt.arguments.len() > 2 {
+                    self.eval_to_int(&stmt.arguments[2])?
+                } else {
+                    10
+                };
+                if buckets <= 0 {
+                    return Err(Error::QueryError(
+                        "db.property_histogram buckets must be positive".to_string(),
+                    ));
+                }
+                if buckets > MAX_HISTOGRAM_BUCKETS {
+                    return Err(Error::QueryError(format!(
+                        "db.property_histogram buckets must be at most {}",
+                        MAX_HISTOGRAM_BUCKETS
+                    )));
+                }
+                let buckets = buckets as usize;
+
+                // Single label scan; missing, non-numeric or non-finite values are skipped
+                let values: Vec<f64> = self
+                    .graph()
+                    .get_vertices_by_label(&label)
+                    .iter()
+                    .filter_map(|v| v.property(&property).and_then(PropertyValue::as_f64))
+                    .filter(|v| v.is_finite())
+                    .collect();
+
+                let bound = |expr: &Expression| -> Result<f64> {
+                    let value = self.eval_to_float(expr)?;
+                    if !value.is_finite() {
+                        return Err(Error::QueryError(
+                            "db.property_histogram min and max must be finite".to_string(),
+                        ));
+                    }
+                    Ok(value)
+                };
+                let min = match stmt.arguments.get(3) {
+                    Some(expr) => bound(expr)?,
+                    None => values.iter().copied().fold(f64::INFINITY, f64::min),
+                };
+                let max = match stmt.arguments.get(4) {
+                    Some(expr) => bound(expr)?,
+                    None => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                };
+
+                let mut rows = Vec::new();
+                if min <= max {
+                    let width = (max - min) / buckets as f64;
+                    let mut counts = vec![0i64; buckets];
+                    for value in values.iter().filter(|v| (min..=max).contains(*v)) {
+                        let idx = if width > 0.0 {
+                            (((value - min) / width) as usize).min(buckets - 1)
+                        } else {
+                            0
+                        };
+                        counts[idx] += 1;
+                    }
+                    for (i, count) in counts.into_iter().enumerate() {
+                        let lower = min + width * i as f64;
+                        let upper = if i + 1 == buckets { max } else { lower + width };
+                        rows.push(vec![
+                            ResultValue::Scalar(PropertyValue::Integer(i as i64)),
+                            ResultValue::Scalar(PropertyValue::Float(lower)),
+                            ResultValue::Scalar(PropertyValue::Float(upper)),
+                            ResultValue::Scalar(PropertyValue::Integer(count)),
+                        ]);
+                    }
+                }
+
+                Ok(QueryResult {
+                    columns: vec![
+                        "bucket".to_string(),
+                        "lower".to_string(),
+                        "upper".to_string(),
+                        "count".to_string(),
+                    ],
+                    rows,
+                    stats: QueryStats::default(),
+                })
+            }
+
             _ => Err(Error::QueryError(format!(
                 "Unknown procedure: {}",
                 stmt.procedure_name
@@ -1839,41 +4367,39 @@ impl QueryExecutor {
                 })
             }
             ShowType::EdgeTypes => {
-                // Return builtin edge types from the system
-                use crate::builtin_types::BuiltinEdgeType;
-                
+                // Report the edge labels actually present in the graph, including custom ones
                 let columns = vec![
-                    "type".to_string(), 
-                    "source".to_string(),
-                    "target".to_string(),
-                    "properties".to_string(),
-                    "description".to_string()
+                    "type".to_string(),
+                    "count".to_string(),
+                    "description".to_string(),
                 ];
-                
-                let mut rows = Vec::new();
-                for edge_type in BuiltinEdgeType::all() {
-                    let props: Vec<String> = edge_type
-                        .properties()
-                        .iter()
-                        .map(|p| format!("{}: {}", p.name, p.data_type))
-                        .collect();
-                    
-                    let description = match edge_type {
-                        BuiltinEdgeType::Transfer => "Aggregated token transfer",
-                        BuiltinEdgeType::TxRel => "Account-Transaction relationship",
-                        BuiltinEdgeType::Call => "Contract call",
-                        BuiltinEdgeType::Approval => "ERC20 token approval",
-                        BuiltinEdgeType::TokenTransfer => "Single token transfer",
-                    };
-                    
-                    rows.push(vec![
-                        ResultValue::Scalar(PropertyValue::String(edge_type.name().to_string())),
-                        ResultValue::Scalar(PropertyValue::String(edge_type.source_type().to_string())),
-                        ResultValue::Scalar(PropertyValue::String(edge_type.target_type().to_string())),
-                        ResultValue::Scalar(PropertyValue::String(props.join(", "))),
-                        ResultValue::Scalar(PropertyValue::String(description.to_string())),
-                    ]);
-                }
+
+                let mut counts: Vec<(EdgeLabel, usize)> = self
+                    .graph()
+                    .edge_index()
+                    .label_counts()
+                    .into_iter()
+                    .collect();
+                counts.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+
+                let rows = counts
+                    .into_iter()
+                    .map(|(label, count)| {
+                        let description = match label {
+                            EdgeLabel::Transfer => "Token transfer",
+                            EdgeLabel::Call => "Contract call",
+                            EdgeLabel::Create => "Contract creation",
+                            EdgeLabel::Approve => "Token approval",
+                            EdgeLabel::InBlock => "Transaction included in block",
+                            EdgeLabel::Custom(_) => "Custom edge type",
+                        };
+                        vec![
+                            ResultValue::Scalar(PropertyValue::String(label.to_string())),
+                            ResultValue::Scalar(PropertyValue::Integer(count as i64)),
+                            ResultValue::Scalar(PropertyValue::String(description.to_string())),
+                        ]
+                    })
+                    .collect();
 
                 Ok(QueryResult {
                     columns,
@@ -1987,59 +4513,109 @@ impl QueryExecutor {
                         )),
                     ],
                     vec![
-                        ResultValue::Scalar(PropertyValue::String("max_flow".to_string())),
+                        ResultValue::Scalar(PropertyValue::String("trace_value".to_string())),
                         ResultValue::Scalar(PropertyValue::String(
-                            "(source, sink) -> Flow".to_string(),
+                            "(start, target, tolerance, max_depth?) -> List<Path>".to_string(),
                         )),
                         ResultValue::Scalar(PropertyValue::String(
-                            "Calculate maximum flow".to_string(),
+                            "Trace proportional fund flow delivering at least tolerance"
+                                .to_string(),
                         )),
                     ],
                     vec![
-                        ResultValue::Scalar(PropertyValue::String("neighbors".to_string())),
+                        ResultValue::Scalar(PropertyValue::String("reachability".to_string())),
                         ResultValue::Scalar(PropertyValue::String(
-                            "(vertex_id, direction?) -> List<Vertex>".to_string(),
+                            "(ids, max_depth?, direction?) -> List<Pair>".to_string(),
                         )),
                         ResultValue::Scalar(PropertyValue::String(
-                            "Get neighbors of a vertex".to_string(),
+                            "Pairwise reachability among a small vertex set".to_string(),
                         )),
                     ],
                     vec![
-                        ResultValue::Scalar(PropertyValue::String("degree".to_string())),
+                        ResultValue::Scalar(PropertyValue::String("multi_shortest".to_string())),
                         ResultValue::Scalar(PropertyValue::String(
-                            "(vertex_id) -> Degree".to_string(),
+                            "(source_ids, target) -> List<Path>".to_string(),
                         )),
                         ResultValue::Scalar(PropertyValue::String(
-                            "Get degree of a vertex".to_string(),
+                            "Shortest path from each source to one target in a single search"
+                                .to_string(),
                         )),
                     ],
                     vec![
-                        ResultValue::Scalar(PropertyValue::String("connected".to_string())),
+                        ResultValue::Scalar(PropertyValue::String("net_balance".to_string())),
                         ResultValue::Scalar(PropertyValue::String(
-                            "(source, target) -> Boolean".to_string(),
+                            "(vertex_id, from_block?, to_block?) -> Balance".to_string(),
                         )),
                         ResultValue::Scalar(PropertyValue::String(
-                            "Check if two vertices are connected".to_string(),
+                            "Net transfer flow (inbound minus outbound)".to_string(),
                         )),
                     ],
-                ];
-                Ok(QueryResult {
-                    columns,
-                    rows,
-                    stats: QueryStats::default(),
-                })
-            }
-            ShowType::Indexes => {
-                let columns = vec![
-                    "name".to_string(),
-                    "type".to_string(),
-                    "properties".to_string(),
-                ];
-                let rows = vec![vec![
-                    ResultValue::Scalar(PropertyValue::String("address_idx".to_string())),
-                    ResultValue::Scalar(PropertyValue::String("HASH".to_string())),
-                    ResultValue::Scalar(PropertyValue::String("address".to_string())),
-                ]];
+                    vec![
+                        ResultValue::Scalar(PropertyValue::String(
+                            "top_counterparties".to_string(),
+                        )),
+                        ResultValue::Scalar(PropertyValue::String(
+                            "(vertex_id, k?, direction?, rank?) -> List<Counterparty>".to_string(),
+                        )),
+                        ResultValue::Scalar(PropertyValue::String(
+                            "Addresses with the most transfers or volume to/from a vertex"
+                                .to_string(),
+                        )),
+                    ],
+                    vec![
+                        ResultValue::Scalar(PropertyValue::String("max_flow".to_string())),
+                        ResultValue::Scalar(PropertyValue::String(
+                            "(source, sink) -> Flow".to_string(),
+                        )),
+                        ResultValue::Scalar(PropertyValue::String(
+                            "Calculate maximum flow".to_string(),
+                        )),
+                    ],
+                    vec![
+                        ResultValue::Scalar(PropertyValue::String("neighbors".to_string())),
+                        ResultValue::Scalar(PropertyValue::String(
+                            "(vertex_id, direction?) -> List<Vertex>".to_string(),
+                        )),
+                        ResultValue::Scalar(PropertyValue::String(
+                            "Get neighbors of a vertex".to_string(),
+                        )),
+                    ],
+                    vec![
+                        ResultValue::Scalar(PropertyValue::String("degree".to_string())),
+                        ResultValue::Scalar(PropertyValue::String(
+                            "(vertex_id) -> Degree".to_string(),
+                        )),
+                        ResultValue::Scalar(PropertyValue::String(
+                            "Get degree of a vertex".to_string(),
+                        )),
+                    ],
+                    vec![
+                        ResultValue::Scalar(PropertyValue::String("connected".to_string())),
+                        ResultValue::Scalar(PropertyValue::String(
+                            "(source, target) -> Boolean".to_string(),
+                        )),
+                        ResultValue::Scalar(PropertyValue::String(
+                            "Check if two vertices are connected".to_string(),
+                        )),
+                    ],
+                ];
+                Ok(QueryResult {
+                    columns,
+                    rows,
+                    stats: QueryStats::default(),
+                })
+            }
+            ShowType::Indexes => {
+                let columns = vec![
+                    "name".to_string(),
+                    "type".to_string(),
+                    "properties".to_string(),
+                ];
+                let rows = vec![vec![
+                    ResultValue::Scalar(PropertyValue::String("address_idx".to_string())),
+                    ResultValue::Scalar(PropertyValue::String("HASH".to_string())),
+                    ResultValue::Scalar(PropertyValue::String("address".to_string())),
+                ]];
                 Ok(QueryResult {
                     columns,
                     rows,
@@ -2063,6 +4639,31 @@ impl QueryExecutor {
                     stats: QueryStats::default(),
                 })
             }
+            ShowType::Transactions => {
+                let columns = vec![
+                    "transaction_id".to_string(),
+                    "access_mode".to_string(),
+                    "age_ms".to_string(),
+                ];
+                let rows = self
+                    .catalog
+                    .transactions()
+                    .active()
+                    .into_iter()
+                    .map(|tx| {
+                        vec![
+                            ResultValue::Scalar(PropertyValue::Integer(tx.id as i64)),
+                            ResultValue::Scalar(PropertyValue::String(tx.mode.as_str().to_string())),
+                            ResultValue::Scalar(PropertyValue::Integer(tx.age().as_millis() as i64)),
+                        ]
+                    })
+                    .collect();
+                Ok(QueryResult {
+                    columns,
+                    rows,
+                    stats: QueryStats::default(),
+                })
+            }
         }
     }
 
@@ -2216,7 +4817,7 @@ impl QueryExecutor {
             Expression::FunctionCall(name, args) if name.to_lowercase() == "range" => {
                 // range(start, end) function
                 let start = args
-                    .get(0)
+                    .first()
                     .and_then(|e| self.evaluate(e, &bindings).ok())
                     .and_then(|v| match v {
                         PropertyValue::Integer(i) => Some(i),
@@ -2310,20 +4911,11 @@ impl QueryExecutor {
         }
 
         // Build columns from select items
-        let columns: Vec<String> = stmt
-            .items
-            .iter()
-            .map(|item| {
-                item.alias
-                    .clone()
-                    .unwrap_or_else(|| match &item.expression {
-                        Expression::Variable(name) => name.clone(),
-                        Expression::Property(var, prop) => format!("{}.{}", var, prop),
-                        Expression::FunctionCall(name, _) => name.clone(),
-                        _ => "expr".to_string(),
-                    })
-            })
-            .collect();
+        let columns = Self::column_names(
+            stmt.items
+                .iter()
+                .map(|item| (&item.expression, item.alias.as_ref())),
+        );
 
         // Build rows
         let mut rows = Vec::new();
@@ -2347,6 +4939,7 @@ impl QueryExecutor {
             });
         }
 
+        self.cap_rows(&mut rows, stmt.limit.is_some(), &mut stats);
         stats.rows_returned = rows.len();
 
         Ok(QueryResult {
@@ -2459,16 +5052,83 @@ impl QueryExecutor {
         let message = match stmt {
             SessionStatement::Set(item) => match item {
                 SessionSetItem::GraphType(name) => format!("Graph Type set to: {}", name),
-                SessionSetItem::Graph(name) => format!("Graph set to: {}", name),
-                SessionSetItem::TimeZone(tz) => format!("Time zone set to: {}", tz),
-                SessionSetItem::Parameter(name, _value) => format!("Parameter {} set", name),
+                SessionSetItem::Graph(name) => {
+                    // Same as USE GRAPH: later statements run against this graph
+                    self.catalog.use_graph(name)?;
+                    format!("Graph set to: {}", name)
+                }
+                SessionSetItem::TimeZone(tz) => {
+                    *self.time_zone.write() = Some(parse_time_zone(tz)?);
+                    format!("Time zone set to: {}", tz)
+                }
+                SessionSetItem::Parameter(name, value) => {
+                    let value = self.evaluate(value, &HashMap::new())?;
+                    if is_case_insensitive_setting(name) {
+                        let PropertyValue::Boolean(enabled) = value else {
+                            return Err(Error::QueryError(format!(
+                                "{} expects TRUE or FALSE, got {:?}",
+                                name, value
+                            )));
+                        };
+                        self.case_insensitive.store(enabled, Ordering::Relaxed);
+                    } else if name.eq_ignore_ascii_case(FLOAT_PRECISION_SETTING) {
+                        let digits = match value {
+                            PropertyValue::Integer(n)
+                                if (0..=MAX_FLOAT_PRECISION as i64).contains(&n) =>
+                            {
+                                n as u32
+                            }
+                            _ => {
+                                return Err(Error::QueryError(format!(
+                                    "{} expects an integer between 0 and {}, got {:?}",
+                                    name, MAX_FLOAT_PRECISION, value
+                                )))
+                            }
+                        };
+                        *self.float_precision.write() = Some(digits);
+                    } else if name.eq_ignore_ascii_case(MISSING_AMOUNT_SETTING) {
+                        let missing = match &value {
+                            PropertyValue::String(s) => MissingAmount::parse(s),
+                            _ => None,
+                        };
+                        let Some(missing) = missing else {
+                            return Err(Error::QueryError(format!(
+                                "{} expects 'zero' or 'absent', got {:?}",
+                                name, value
+                            )));
+                        };
+                        *self.missing_amount.write() = missing;
+                    }
+                    self.parameters.write().insert(name.clone(), value);
+                    format!("Parameter {} set", name)
+                }
             },
             SessionStatement::Reset(item) => match item {
                 SessionResetItem::GraphType => "Graph Type reset to default".to_string(),
                 SessionResetItem::Graph => "Graph reset to default".to_string(),
-                SessionResetItem::TimeZone => "Time zone reset to default".to_string(),
-                SessionResetItem::All => "All session settings reset".to_string(),
-                SessionResetItem::Parameter(name) => format!("Parameter {} reset", name),
+                SessionResetItem::TimeZone => {
+                    *self.time_zone.write() = None;
+                    "Time zone reset to default".to_string()
+                }
+                SessionResetItem::All => {
+                    self.case_insensitive.store(false, Ordering::Relaxed);
+                    self.parameters.write().clear();
+                    *self.time_zone.write() = None;
+                    *self.float_precision.write() = None;
+                    *self.missing_amount.write() = MissingAmount::Zero;
+                    "All session settings reset".to_string()
+                }
+                SessionResetItem::Parameter(name) => {
+                    if is_case_insensitive_setting(name) {
+                        self.case_insensitive.store(false, Ordering::Relaxed);
+                    } else if name.eq_ignore_ascii_case(FLOAT_PRECISION_SETTING) {
+                        *self.float_precision.write() = None;
+                    } else if name.eq_ignore_ascii_case(MISSING_AMOUNT_SETTING) {
+                        *self.missing_amount.write() = MissingAmount::Zero;
+                    }
+                    self.parameters.write().remove(name);
+                    format!("Parameter {} reset", name)
+                }
             },
             SessionStatement::Close => "Session closed".to_string(),
         };
@@ -2480,19 +5140,38 @@ impl QueryExecutor {
         })
     }
 
-    /// Execute Transaction statement - transaction control
+    /// Execute Transaction statement - transaction control.
+    /// Open transactions are tracked in the catalog; each executor holds at
+    /// most one, and COMMIT and ROLLBACK close the one this executor started.
     fn execute_transaction(&self, stmt: &TransactionStatement) -> Result<QueryResult> {
+        let transactions = self.catalog.transactions();
+        let mut current = self.transaction.lock();
         let message = match stmt {
             TransactionStatement::Start(chars) => {
+                if let Some(id) = *current {
+                    return Err(Error::QueryError(format!(
+                        "Transaction {} is already active",
+                        id
+                    )));
+                }
                 let mode = match &chars.access_mode {
-                    Some(TransactionAccessMode::ReadOnly) => "READ ONLY",
-                    Some(TransactionAccessMode::ReadWrite) => "READ WRITE",
-                    None => "DEFAULT",
+                    Some(TransactionAccessMode::ReadOnly) => TransactionMode::ReadOnly,
+                    Some(TransactionAccessMode::ReadWrite) | None => TransactionMode::ReadWrite,
                 };
-                format!("Transaction started ({})", mode)
+                let id = transactions.begin(mode);
+                *current = Some(id);
+                format!("Transaction {} started ({})", id, mode.as_str())
+            }
+            TransactionStatement::Commit | TransactionStatement::Rollback => {
+                let id = current
+                    .take()
+                    .ok_or_else(|| Error::QueryError("No active transaction".to_string()))?;
+                let tx = transactions.finish(id)?;
+                match stmt {
+                    TransactionStatement::Commit => format!("Transaction {} committed", tx.id),
+                    _ => format!("Transaction {} rolled back", tx.id),
+                }
             }
-            TransactionStatement::Commit => "Transaction committed".to_string(),
-            TransactionStatement::Rollback => "Transaction rolled back".to_string(),
         };
 
         Ok(QueryResult {
@@ -2513,6 +5192,17 @@ impl QueryExecutor {
         }
     }
 
+    fn eval_to_float(&self, expr: &Expression) -> Result<f64> {
+        match expr {
+            Expression::Literal(PropertyValue::Integer(i)) => Ok(*i as f64),
+            Expression::Literal(PropertyValue::Float(f)) => Ok(*f),
+            Expression::Literal(PropertyValue::String(s)) => s
+                .parse::<f64>()
+                .map_err(|_| Error::QueryError(format!("Cannot convert '{}' to number", s))),
+            _ => Err(Error::QueryError("Argument must be a number".to_string())),
+        }
+    }
+
     fn eval_to_string(&self, expr: &Expression) -> Result<String> {
         match expr {
             Expression::Literal(PropertyValue::String(s)) => Ok(s.clone()),
@@ -2521,6 +5211,24 @@ impl QueryExecutor {
             _ => Err(Error::QueryError("Argument must be a string".to_string())),
         }
     }
+
+    fn eval_to_int_list(&self, expr: &Expression) -> Result<Vec<i64>> {
+        match expr {
+            Expression::List(items) => items.iter().map(|item| self.eval_to_int(item)).collect(),
+            _ => Err(Error::QueryError(
+                "Argument must be a list of integers".to_string(),
+            )),
+        }
+    }
+}
+
+impl Drop for QueryExecutor {
+    /// A transaction left open when the session ends is rolled back
+    fn drop(&mut self) {
+        if let Some(id) = self.transaction.get_mut().take() {
+            let _ = self.catalog.transactions().finish(id);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -2577,4 +5285,1908 @@ mod tests {
         let result = executor.execute(&stmt).unwrap();
         assert_eq!(result.rows.len(), 1);
     }
+
+    #[test]
+    fn test_path_binding_targets_each_vertex_once() {
+        let executor = QueryExecutor::new(setup_test_catalog());
+        let mut bindings = Bindings::new();
+        bindings.insert(
+            "p".to_string(),
+            BindingValue::Path(vec![VertexId::new(1), VertexId::new(2), VertexId::new(1)]),
+        );
+
+        // 路径变量展开为路径上的每个顶点，重复经过的顶点只写一次
+        let elements = executor.bound_elements("p", &bindings).unwrap();
+        assert!(matches!(
+            elements.as_slice(),
+            [BoundElement::Vertex(a), BoundElement::Vertex(b)]
+                if *a == VertexId::new(1) && *b == VertexId::new(2)
+        ));
+    }
+
+    #[test]
+    fn test_set_property_on_path_vertices() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        let c = graph.add_account("0xc".to_string()).unwrap();
+        let d = graph.add_account("0xd".to_string()).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(10), 1).unwrap();
+        graph.add_transfer(b, c, TokenAmount::from_u64(10), 2).unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let stmt = parse(
+            "MATCH p = (x:Account)-[:Transfer]->{2,2}(y:Account) WHERE x.address = '0xa' SET p.flagged = true",
+        )
+        .unwrap();
+        let result = executor.execute(&stmt).unwrap();
+        assert_eq!(result.columns, vec!["updated".to_string()]);
+        assert!(matches!(
+            result.rows[0][0],
+            ResultValue::Scalar(PropertyValue::Integer(3))
+        ));
+
+        for id in [a, b, c] {
+            let v = graph.get_vertex(id).unwrap();
+            assert_eq!(v.property("flagged"), Some(&PropertyValue::Boolean(true)));
+        }
+        assert!(graph.get_vertex(d).unwrap().property("flagged").is_none());
+    }
+
+    #[test]
+    fn test_set_is_visible_to_later_queries() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let executor = QueryExecutor::new(catalog);
+        let run = |query: &str| executor.execute(&parse(query).unwrap()).unwrap();
+        let scalar = |result: QueryResult| match &result.rows[0][0] {
+            ResultValue::Scalar(v) => v.clone(),
+            other => panic!("expected scalar, got {:?}", other),
+        };
+        run("INSERT (a:Account {address: '0xa', balance: 100})-[:Transfer {amount: 10}]->(b:Account {address: '0xb'})");
+        let a = "MATCH (n:Account) WHERE n.address = '0xa'";
+
+        let result = run(&format!("{} SET n.balance = 5000", a));
+        assert_eq!(scalar(result), PropertyValue::Integer(1));
+        let balance = || scalar(run(&format!("{} RETURN n.balance", a)));
+        assert_eq!(balance(), PropertyValue::Integer(5000));
+
+        // += 合并属性，原有属性保留
+        let result = run(&format!("{} SET n += {{risk: 3, tag: 'hot'}}", a));
+        assert_eq!(scalar(result), PropertyValue::Integer(2));
+        assert_eq!(balance(), PropertyValue::Integer(5000));
+        let risk = scalar(run(&format!("{} RETURN n.risk", a)));
+        assert_eq!(risk, PropertyValue::Integer(3));
+
+        // = 整体替换属性，address 作为索引键保留
+        run(&format!("{} SET n = {{risk: 4}}", a));
+        let vertex = executor.graph().get_vertex_by_address("0xa").unwrap();
+        assert!(vertex.property("balance").is_none());
+        let risk = scalar(run(&format!("{} RETURN n.risk", a)));
+        assert_eq!(risk, PropertyValue::Integer(4));
+
+        // 边属性同样写回图
+        run("MATCH (x:Account)-[t:Transfer]->(y:Account) SET t.flagged = true");
+        let flagged = run("MATCH (x:Account)-[t:Transfer]->(y:Account) RETURN t.flagged");
+        assert_eq!(scalar(flagged), PropertyValue::Boolean(true));
+    }
+
+    #[test]
+    fn test_remove_property_and_label() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let executor = QueryExecutor::new(catalog);
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+        let removed = |result: QueryResult| match &result.rows[0][0] {
+            ResultValue::Scalar(PropertyValue::Integer(n)) => *n,
+            other => panic!("unexpected {:?}", other),
+        };
+        run("INSERT (a:Account {address: '0xa', risk: 9, tag: 'hot'})-[:Transfer {amount: 10}]->(b:Account {address: '0xb'})").unwrap();
+        let a = "MATCH (n:Account) WHERE n.address = '0xa'";
+
+        let result = run(&format!("{} REMOVE n.risk", a)).unwrap();
+        assert_eq!(result.columns, vec!["removed".to_string()]);
+        assert_eq!(removed(result), 1);
+        // 移除后的属性不再出现在查询结果中
+        let result = run(&format!("{} RETURN n", a)).unwrap();
+        match &result.rows[0][0] {
+            ResultValue::Vertex(v) => {
+                assert!(!v.properties.contains_key("risk"));
+                assert!(v.properties.contains_key("tag"));
+            }
+            other => panic!("expected vertex, got {:?}", other),
+        }
+        // 不存在的属性不计数
+        assert_eq!(removed(run(&format!("{} REMOVE n.risk", a)).unwrap()), 0);
+        assert!(run(&format!("{} REMOVE n.address", a)).is_err());
+        // 任一项无效时整条语句不做修改
+        assert!(run(&format!("{} REMOVE n.tag, n.address", a)).is_err());
+        assert!(run(&format!("{} REMOVE n.tag, n:Account", a)).is_err());
+        let vertex = executor.graph().get_vertex_by_address("0xa").unwrap();
+        assert!(vertex.property("tag").is_some());
+
+        let edge = "MATCH (x:Account)-[t:Transfer]->(y:Account)";
+        run(&format!("{} SET t.memo = 'x'", edge)).unwrap();
+        assert_eq!(removed(run(&format!("{} REMOVE t.memo", edge)).unwrap()), 1);
+        let graph = executor.graph();
+        let vertex = graph.get_vertex_by_address("0xa").unwrap();
+        let transfer = &graph.get_outgoing_edges(vertex.id())[0];
+        assert!(transfer.property("memo").is_none());
+
+        // 顶点唯一的标签不能移除；移除顶点没有的标签不做任何修改
+        let err = run(&format!("{} REMOVE n:Account", a)).unwrap_err();
+        assert!(matches!(err, Error::QueryError(_)), "{:?}", err);
+        assert_eq!(removed(run(&format!("{} REMOVE n:Token", a)).unwrap()), 0);
+        assert_eq!(graph.get_vertex(vertex.id()).unwrap().label(), &VertexLabel::Account);
+        assert!(run(&format!("{} REMOVE t:Account", edge)).is_err());
+    }
+
+    #[test]
+    fn test_remove_persists_across_reopen() {
+        let dir = tempfile::TempDir::new().unwrap();
+        {
+            let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+            let executor = QueryExecutor::new(catalog);
+            let run = |query: &str| executor.execute(&parse(query).unwrap()).unwrap();
+            run("INSERT (a:Account {address: '0xa', risk: 9, tag: 'hot'})");
+            run("MATCH (n:Account) WHERE n.address = '0xa' REMOVE n.risk");
+            executor.graph().flush().unwrap();
+        }
+
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let vertex = catalog
+            .current_graph()
+            .get_vertex_by_address("0xa")
+            .unwrap();
+        assert!(vertex.property("risk").is_none());
+        assert!(vertex.property("tag").is_some());
+    }
+
+    #[test]
+    fn test_set_persists_across_reopen() {
+        let dir = tempfile::TempDir::new().unwrap();
+        {
+            let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+            let executor = QueryExecutor::new(catalog);
+            let run = |query: &str| executor.execute(&parse(query).unwrap()).unwrap();
+            run("INSERT (a:Account {address: '0xa', balance: 100, tag: 'hot'})");
+            run("MATCH (n:Account) WHERE n.address = '0xa' SET n.balance = 5000");
+            executor.graph().flush().unwrap();
+        }
+
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let vertex = catalog
+            .current_graph()
+            .get_vertex_by_address("0xa")
+            .unwrap();
+        assert_eq!(
+            vertex.property("balance"),
+            Some(&PropertyValue::Integer(5000))
+        );
+        assert_eq!(
+            vertex.property("tag"),
+            Some(&PropertyValue::String("hot".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_delete_persists_across_reopen() {
+        let dir = tempfile::TempDir::new().unwrap();
+        {
+            let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+            let executor = QueryExecutor::new(catalog);
+            let run = |query: &str| executor.execute(&parse(query).unwrap()).unwrap();
+            run("INSERT (a:Account {address: '0xa'})-[:Transfer {amount: 1}]->(b:Account {address: '0xb'})");
+            run("INSERT (c:Account {address: '0xc'})");
+            run("MATCH (n:Account) WHERE n.address = '0xa' DETACH DELETE n");
+            run("MATCH (n:Account) WHERE n.address = '0xc' DELETE n");
+            executor.graph().flush().unwrap();
+        }
+
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        assert!(graph.get_vertex_by_address("0xa").is_none());
+        assert!(graph.get_vertex_by_address("0xc").is_none());
+        assert!(graph.get_vertex_by_address("0xb").is_some());
+        assert_eq!(graph.vertex_count(), 1);
+        assert_eq!(graph.edge_count(), 0);
+    }
+
+    #[test]
+    fn test_set_on_unbound_variable_fails() {
+        let catalog = setup_test_catalog();
+        let executor = QueryExecutor::new(catalog);
+        let stmt = parse("MATCH (n:Account) SET m.flagged = true").unwrap();
+        assert!(executor.execute(&stmt).is_err());
+    }
+
+    #[test]
+    fn test_delete_requires_detach_for_connected_vertices() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        let c = graph.add_account("0xc".to_string()).unwrap();
+        let lone = graph.add_account("0xd".to_string()).unwrap();
+        graph
+            .add_transfer(a, b, TokenAmount::from_u64(10), 1)
+            .unwrap();
+        graph
+            .add_transfer(c, a, TokenAmount::from_u64(10), 2)
+            .unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+        let deleted = |result: QueryResult| match &result.rows[0][0] {
+            ResultValue::Scalar(PropertyValue::Integer(n)) => *n,
+            other => panic!("unexpected {:?}", other),
+        };
+
+        // 仍有边的顶点不能直接删除，图保持不变
+        let err = run("MATCH (n:Account) WHERE n.address = '0xa' DELETE n").unwrap_err();
+        assert!(matches!(err, Error::QueryError(_)), "{:?}", err);
+        assert!(graph.vertex_exists(a));
+        assert_eq!(graph.edge_count(), 2);
+
+        // 没有边的顶点可以直接删除
+        let result = run("MATCH (n:Account) WHERE n.address = '0xd' DELETE n").unwrap();
+        assert_eq!(result.columns, vec!["deleted".to_string()]);
+        assert_eq!(deleted(result), 1);
+        assert!(!graph.vertex_exists(lone));
+
+        // 同时删除顶点与其全部边时不需要 DETACH
+        let result =
+            run("MATCH (x:Account)-[e:Transfer]->(y:Account) WHERE x.address = '0xc' DELETE e, x")
+                .unwrap();
+        assert_eq!(deleted(result), 2);
+        assert!(!graph.vertex_exists(c));
+
+        // DETACH DELETE 连同相连的边一起删除，计数包含边
+        let result = run("MATCH (n:Account) WHERE n.address = '0xa' DETACH DELETE n").unwrap();
+        assert_eq!(deleted(result), 2);
+        assert!(!graph.vertex_exists(a));
+        assert!(graph.vertex_exists(b));
+        assert_eq!(graph.edge_count(), 0);
+        assert!(graph.get_incoming_edges(b).is_empty());
+    }
+
+    #[test]
+    fn test_zero_length_quantifier_matches_source() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(1), 1).unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let rows = |query: &str| executor.execute(&parse(query).unwrap()).unwrap().rows;
+
+        // {0,0} only matches the zero-length path: b is the source itself
+        let result = rows("MATCH (a:Account {address: '0xa'})-[:Transfer]->{0,0}(b) RETURN b.address");
+        assert_eq!(result.len(), 1);
+        assert!(matches!(
+            &result[0][0],
+            ResultValue::Scalar(PropertyValue::String(addr)) if addr == "0xa"
+        ));
+
+        assert_eq!(
+            rows("MATCH (a:Account {address: '0xa'})-[:Transfer]->{0,1}(b) RETURN b").len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_variable_length_path_modes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        // a -> b -> a 与 a -> b -> c -> a 两个环
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        let c = graph.add_account("0xc".to_string()).unwrap();
+        for (i, (from, to)) in [(a, b), (b, a), (b, c), (c, a)].into_iter().enumerate() {
+            graph
+                .add_transfer(from, to, TokenAmount::from_u64(1), i as u64)
+                .unwrap();
+        }
+
+        let executor = QueryExecutor::new(catalog);
+        let ends = |mode: &str| -> Vec<String> {
+            let query = format!(
+                "MATCH {} (x {{address: '0xa'}})-[:Transfer]->{{1,3}}(y) RETURN y.address",
+                mode
+            );
+            let result = executor.execute(&parse(&query).unwrap()).unwrap();
+            result
+                .rows
+                .iter()
+                .map(|row| match &row[0] {
+                    ResultValue::Scalar(PropertyValue::String(addr)) => addr.clone(),
+                    other => panic!("unexpected value {:?}", other),
+                })
+                .collect()
+        };
+
+        // 回溯展开与逐分支复制的结果及顺序一致
+        assert_eq!(ends("WALK"), ["0xb", "0xc", "0xa", "0xa", "0xb"]);
+        assert_eq!(ends("TRAIL"), ["0xb", "0xc", "0xa", "0xa"]);
+        assert_eq!(ends("SIMPLE"), ["0xb", "0xc"]);
+        assert_eq!(ends("ACYCLIC"), ends("SIMPLE"));
+    }
+
+    #[test]
+    fn test_match_without_return_returns_pattern_variables() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(1), 1).unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+
+        let result = run("MATCH (n:Account)").unwrap();
+        assert_eq!(result.columns, vec!["n"]);
+        assert_eq!(result.rows.len(), 2);
+
+        let result = run("MATCH (x:Account)-[t:Transfer]->(y), (x)").unwrap();
+        assert_eq!(result.columns, vec!["x", "t", "y"]);
+        assert_eq!(result.rows.len(), 1);
+
+        let err = run("MATCH (:Account)").unwrap_err();
+        assert!(err.to_string().contains("RETURN"));
+    }
+
+    #[test]
+    fn test_shared_variable_join_starts_from_bound_vertex() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let accounts: Vec<VertexId> = (0..20)
+            .map(|i| graph.add_account(format!("0x{:02}", i)).unwrap())
+            .collect();
+        for pair in accounts.windows(2) {
+            graph
+                .add_transfer(pair[0], pair[1], TokenAmount::from_u64(1), 1)
+                .unwrap();
+        }
+        // accounts[5] 另有一笔来自 accounts[0] 的入账
+        graph
+            .add_transfer(accounts[0], accounts[5], TokenAmount::from_u64(1), 2)
+            .unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let stmt = parse(
+            "MATCH (a:Account)-[:Transfer]->(b:Account), (c:Account)-[:Transfer]->(b) RETURN a, b, c",
+        )
+        .unwrap();
+        let result = executor.execute(&stmt).unwrap();
+
+        // 每个收款方 b 的 (a, c) 组合：18 个单一入账 + accounts[5] 的 2 x 2
+        assert_eq!(result.rows.len(), 22);
+        // 第二个模式从已绑定的 b 反向遍历，只有第一个模式扫描了 Account 标签
+        let label_size = accounts.len();
+        assert_eq!(result.stats.vertices_scanned, label_size);
+        assert!(result.stats.vertices_scanned < label_size * label_size / 10);
+    }
+
+    #[test]
+    fn test_call_property_histogram() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        for (i, balance) in [5, 15, 25, 35, 95].into_iter().enumerate() {
+            let id = graph.add_account(format!("0x{}", i)).unwrap();
+            let mut vertex = graph.get_vertex(id).unwrap();
+            vertex.set_property("balance".to_string(), PropertyValue::Integer(balance));
+            graph.update_vertex(vertex).unwrap();
+        }
+        // 缺少属性的账户和其他标签的顶点不参与统计
+        graph.add_account("0xnobalance".to_string()).unwrap();
+        let token = graph.add_token("0xtoken".to_string(), "TKN".to_string()).unwrap();
+        let mut vertex = graph.get_vertex(token).unwrap();
+        vertex.set_property("balance".to_string(), PropertyValue::Integer(1000));
+        graph.update_vertex(vertex).unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let counts = |query: &str| -> Vec<i64> {
+            let result = executor.execute(&parse(query).unwrap()).unwrap();
+            assert_eq!(result.columns, vec!["bucket", "lower", "upper", "count"]);
+            result
+                .rows
+                .iter()
+                .map(|row| match &row[3] {
+                    ResultValue::Scalar(PropertyValue::Integer(n)) => *n,
+                    other => panic!("unexpected count: {:?}", other),
+                })
+                .collect()
+        };
+
+        // min/max 自动取 5 和 95，桶宽 30
+        assert_eq!(
+            counts("CALL db.property_histogram('Account', 'balance', 3)"),
+            vec![3, 1, 1]
+        );
+        // 显式指定范围
+        assert_eq!(
+            counts("CALL db.property_histogram('Account', 'balance', 2, 0, 100)"),
+            vec![4, 1]
+        );
+        assert!(counts("CALL db.property_histogram('Account', 'missing', 4)").is_empty());
+    }
+
+    #[test]
+    fn test_call_property_histogram_rejects_bad_arguments() {
+        let catalog = setup_test_catalog();
+        let executor = QueryExecutor::new(catalog);
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+
+        // 桶数超过上限时直接报错，不分配计数数组
+        let err =
+            run("CALL db.property_histogram('Account', 'balance', 1000000000000)").unwrap_err();
+        assert!(err.to_string().contains("at most 1000"), "{}", err);
+        run("CALL db.property_histogram('Account', 'balance', 1000)").unwrap();
+
+        let err =
+            run("CALL db.property_histogram('Account', 'balance', 4, 'NaN', 100)").unwrap_err();
+        assert!(err.to_string().contains("finite"), "{}", err);
+        let err = run("CALL db.property_histogram('Account', 'balance', 4, 0, 'inf')").unwrap_err();
+        assert!(err.to_string().contains("finite"), "{}", err);
+    }
+
+    #[test]
+    fn test_show_edge_types_reports_present_labels() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        graph
+            .add_transfer(a, b, TokenAmount::from_u64(1), 1)
+            .unwrap();
+        graph
+            .add_edge(EdgeLabel::Custom("Swap".to_string()), a, b)
+            .unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let insert = "INSERT (:Account {address: '0xc'})-[:Swap]->(:Account {address: '0xd'})";
+        executor.execute(&parse(insert).unwrap()).unwrap();
+
+        let result = executor
+            .execute(&parse("SHOW EDGE TYPES").unwrap())
+            .unwrap();
+        assert_eq!(result.columns, ["type", "count", "description"]);
+        let types: Vec<(String, i64)> = result
+            .rows
+            .iter()
+            .map(|row| match (&row[0], &row[1]) {
+                (
+                    ResultValue::Scalar(PropertyValue::String(label)),
+                    ResultValue::Scalar(PropertyValue::Integer(count)),
+                ) => (label.clone(), *count),
+                other => panic!("unexpected row {:?}", other),
+            })
+            .collect();
+        // 只列出实际存在的边类型，不含未出现的 Call 等内置类型
+        assert_eq!(types, [("Swap".to_string(), 2), ("Transfer".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_default_column_names_for_expressions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let executor = QueryExecutor::new(catalog);
+        executor
+            .execute(&parse("INSERT (:Account {address: '0xa', balance: 5})").unwrap())
+            .unwrap();
+
+        let query = "MATCH (n:Account) RETURN n.balance + 1, n.balance * 2, n.address";
+        let result = executor.execute(&parse(query).unwrap()).unwrap();
+        assert_eq!(
+            result.columns,
+            ["n.balance + 1", "n.balance * 2", "n.address"]
+        );
+
+        // 重复的未命名表达式加后缀区分，显式别名保持不变
+        let query = "MATCH (n:Account) RETURN n.balance + 1, n.balance + 1, n.address AS a";
+        let result = executor.execute(&parse(query).unwrap()).unwrap();
+        assert_eq!(result.columns, ["n.balance + 1", "n.balance + 1_2", "a"]);
+    }
+
+    #[test]
+    fn test_return_aggregates_group_by_bare_columns() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let executor = QueryExecutor::new(catalog);
+        let accounts = [("0xa", "alice", 5), ("0xb", "bob", 7), ("0xc", "alice", 9)];
+        for (address, owner, balance) in accounts {
+            let insert = format!(
+                "INSERT (:Account {{address: '{}', owner: '{}', balance: {}}})",
+                address, owner, balance
+            );
+            executor.execute(&parse(&insert).unwrap()).unwrap();
+        }
+        let scalar = |value: &ResultValue| match value {
+            ResultValue::Scalar(v) => v.clone(),
+            other => panic!("unexpected value {:?}", other),
+        };
+
+        // 裸列 n.owner 作为隐式分组键，每个键一行
+        let query = "MATCH (n:Account) RETURN n.owner, count(n) AS accounts, max(n.balance)";
+        let result = executor.execute(&parse(query).unwrap()).unwrap();
+        assert_eq!(result.columns, ["n.owner", "accounts", "max(n.balance)"]);
+        let mut rows: Vec<Vec<PropertyValue>> = result
+            .rows
+            .iter()
+            .map(|row| row.iter().map(scalar).collect())
+            .collect();
+        rows.sort_by_key(|row| format!("{:?}", row[0]));
+        assert_eq!(
+            rows,
+            [
+                vec![
+                    PropertyValue::String("alice".to_string()),
+                    PropertyValue::Integer(2),
+                    PropertyValue::Integer(9),
+                ],
+                vec![
+                    PropertyValue::String("bob".to_string()),
+                    PropertyValue::Integer(1),
+                    PropertyValue::Integer(7),
+                ],
+            ]
+        );
+
+        // 没有分组键时对全部绑定聚合，空匹配也返回一行
+        let query = "MATCH (n:Account) WHERE n.balance > 100 RETURN count(n)";
+        let result = executor.execute(&parse(query).unwrap()).unwrap();
+        assert_eq!(result.rows.len(), 1);
+        assert_eq!(scalar(&result.rows[0][0]), PropertyValue::Integer(0));
+
+        // 聚合嵌在逐行表达式中语义不明确，报错
+        let query = "MATCH (n:Account) RETURN n.owner, n.balance + count(n)";
+        let err = executor.execute(&parse(query).unwrap()).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("Ambiguous RETURN item n.balance + count(n)"));
+    }
+
+    #[test]
+    fn test_connected_requires_existing_vertices() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        graph
+            .add_transfer(a, b, TokenAmount::from_u64(1), 1)
+            .unwrap();
+        let executor = QueryExecutor::new(catalog);
+
+        let connected = |source: u64, target: u64| {
+            let query = format!("CALL connected({}, {})", source, target);
+            let result = executor.execute(&parse(&query).unwrap()).unwrap();
+            match &result.rows[0][2] {
+                ResultValue::Scalar(PropertyValue::Boolean(connected)) => *connected,
+                other => panic!("unexpected value {:?}", other),
+            }
+        };
+        assert!(connected(a.as_u64(), b.as_u64()));
+        assert!(!connected(b.as_u64(), a.as_u64()));
+        // 不存在的顶点与自身也不连通
+        assert!(!connected(999, 999));
+    }
+
+    #[test]
+    fn test_unlabeled_scan_requires_limit_on_large_graphs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        for address in ["0xa", "0xb", "0xc", "0xd"] {
+            graph.add_account(address.to_string()).unwrap();
+        }
+        let executor = QueryExecutor::new(catalog).with_max_unlabeled_scan(3);
+
+        // 顶点数超过上限且没有 LIMIT：拒绝全量扫描并给出提示
+        let err = executor
+            .execute(&parse("MATCH (n) RETURN n").unwrap())
+            .unwrap_err();
+        assert!(err.to_string().contains("add a label"), "{}", err);
+
+        // LIMIT 下推到扫描，只读取 SKIP + LIMIT 个顶点
+        let query = "MATCH (n) RETURN n SKIP 1 LIMIT 2";
+        let result = executor.execute(&parse(query).unwrap()).unwrap();
+        assert_eq!(result.rows.len(), 2);
+        assert_eq!(result.stats.vertices_scanned, 3);
+
+        // 带标签的模式不受影响
+        let result = executor
+            .execute(&parse("MATCH (n:Account) RETURN n").unwrap())
+            .unwrap();
+        assert_eq!(result.rows.len(), 4);
+    }
+
+    #[test]
+    fn test_max_rows_caps_unlimited_match_and_select() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        for address in ["0xa", "0xb", "0xc", "0xd", "0xe"] {
+            graph.add_account(address.to_string()).unwrap();
+        }
+        let executor = QueryExecutor::new(catalog).with_max_rows(3);
+        let run = |query: &str| executor.execute(&parse(query).unwrap()).unwrap();
+
+        // 没有 LIMIT 时截断到上限并标记
+        let result = run("MATCH (n:Account) RETURN n.address");
+        assert_eq!(result.rows.len(), 3);
+        assert_eq!(result.stats.rows_returned, 3);
+        assert!(result.stats.truncated);
+        let result = run("SELECT 1 AS one");
+        assert_eq!(result.rows.len(), 3);
+        assert!(result.stats.truncated);
+
+        // 显式 LIMIT 按原样执行，即使超过上限
+        let result = run("MATCH (n:Account) RETURN n.address LIMIT 4");
+        assert_eq!(result.rows.len(), 4);
+        assert!(!result.stats.truncated);
+        let result = run("SELECT 1 AS one LIMIT 5");
+        assert_eq!(result.rows.len(), 5);
+        assert!(!result.stats.truncated);
+
+        // 未超过上限时不标记
+        let result = run("MATCH (n:Account) WHERE n.address = '0xa' RETURN n");
+        assert_eq!(result.rows.len(), 1);
+        assert!(!result.stats.truncated);
+    }
+
+    #[test]
+    fn test_show_transactions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let executor = QueryExecutor::new(catalog);
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+
+        run("START TRANSACTION READ ONLY").unwrap();
+        let listed = run("SHOW TRANSACTIONS").unwrap();
+        assert_eq!(listed.columns, vec!["transaction_id", "access_mode", "age_ms"]);
+        assert_eq!(listed.rows.len(), 1);
+        match &listed.rows[0][1] {
+            ResultValue::Scalar(PropertyValue::String(mode)) => assert_eq!(mode, "READ ONLY"),
+            other => panic!("unexpected access mode: {:?}", other),
+        }
+
+        run("COMMIT").unwrap();
+        assert!(run("SHOW TRANSACTIONS").unwrap().rows.is_empty());
+        assert!(run("COMMIT").is_err());
+    }
+
+    #[test]
+    fn test_transactions_are_per_executor() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let first = QueryExecutor::new(catalog.clone());
+        let second = QueryExecutor::new(catalog.clone());
+        let run = |executor: &QueryExecutor, query: &str| executor.execute(&parse(query).unwrap());
+
+        run(&first, "START TRANSACTION").unwrap();
+        // 每个会话只能有一个活跃事务
+        assert!(run(&first, "START TRANSACTION").is_err());
+        // 其他会话不能结束该事务
+        assert!(run(&second, "COMMIT").is_err());
+        assert_eq!(catalog.transactions().active_count(), 1);
+
+        run(&second, "START TRANSACTION READ ONLY").unwrap();
+        run(&first, "ROLLBACK").unwrap();
+        let active = catalog.transactions().active();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].mode, TransactionMode::ReadOnly);
+
+        // 会话结束时回滚其未结束的事务
+        drop(second);
+        assert_eq!(catalog.transactions().active_count(), 0);
+    }
+
+    #[test]
+    fn test_execute_label_expression_matching() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        graph.add_account("0xa".to_string()).unwrap();
+        graph.add_account("0xb".to_string()).unwrap();
+        graph.add_vertex(VertexLabel::Token).unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let count = |query: &str| executor.execute(&parse(query).unwrap()).unwrap().rows.len();
+
+        assert_eq!(count("MATCH (n:Account) RETURN n"), 2);
+        assert_eq!(count("MATCH (n:=Account) RETURN n"), 2);
+        assert_eq!(count("MATCH (n:=Account|Token) RETURN n"), 3);
+        assert_eq!(count("MATCH (n:!Account) RETURN n"), 1);
+        // Vertices carry a single label, so none has both
+        assert_eq!(count("MATCH (n:Account&Token) RETURN n"), 0);
+    }
+
+    #[test]
+    fn test_execute_edge_label_expression_matching() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(1), 1).unwrap();
+        graph.add_edge(EdgeLabel::Call, a, b).unwrap();
+        graph.add_edge(EdgeLabel::Approve, a, b).unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let count = |query: &str| executor.execute(&parse(query).unwrap()).unwrap().rows.len();
+
+        assert_eq!(count("MATCH (x)-[t]->(y) RETURN t"), 3);
+        assert_eq!(count("MATCH (x)-[t:Transfer|Call]->(y) RETURN t"), 2);
+        assert_eq!(count("MATCH (x)-[t:!Approve]->(y) RETURN t"), 2);
+        assert_eq!(count("MATCH (x)-[t:%]->(y) RETURN t"), 3);
+        assert_eq!(count("MATCH (x)-[t:!(Transfer|Call)]->(y) RETURN t"), 1);
+        // 边只有一个标签
+        assert_eq!(count("MATCH (x)-[t:Transfer&Call]->(y) RETURN t"), 0);
+    }
+
+    #[test]
+    fn test_shortest_path_search_respects_edge_pattern() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        // a -Call-> b 只有一跳，a -Transfer-> c -Transfer-> b 需要两跳
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        let c = graph.add_account("0xc".to_string()).unwrap();
+        graph.add_edge(EdgeLabel::Call, a, b).unwrap();
+        graph
+            .add_transfer(a, c, TokenAmount::from_u64(1), 1)
+            .unwrap();
+        graph
+            .add_transfer(c, b, TokenAmount::from_u64(1), 2)
+            .unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let path_of = |from: &str, edge: &str, to: &str| -> Vec<u64> {
+            let query = format!(
+                "MATCH p = ANY SHORTEST (x {{address: '{}'}}){}(y {{address: '{}'}}) RETURN p",
+                from, edge, to
+            );
+            let result = executor.execute(&parse(&query).unwrap()).unwrap();
+            if result.rows.is_empty() {
+                return Vec::new();
+            }
+            let ResultValue::Path(path) = &result.rows[0][0] else {
+                panic!("expected a path, got {:?}", result.rows[0][0]);
+            };
+            path.vertices.iter().map(|v| v.id).collect()
+        };
+        let ids = |ids: &[VertexId]| ids.iter().map(|id| id.as_u64()).collect::<Vec<_>>();
+
+        assert_eq!(path_of("0xa", "-[]->*", "0xb"), ids(&[a, b]));
+        assert_eq!(path_of("0xa", "-[:Transfer]->*", "0xb"), ids(&[a, c, b]));
+        // 入向边模式沿边反向搜索
+        assert_eq!(path_of("0xb", "<-[:Transfer]-", "0xa"), ids(&[b, c, a]));
+        assert!(path_of("0xb", "-[:Transfer]->*", "0xa").is_empty());
+    }
+
+    #[test]
+    fn test_path_indexing_and_functions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        // a -> b -> c 的转账链
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        let c = graph.add_account("0xc".to_string()).unwrap();
+        let amount = TokenAmount::from_u64(1);
+        let ab = graph.add_transfer(a, b, amount, 1).unwrap();
+        let bc = graph.add_transfer(b, c, amount, 2).unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let query = "MATCH p = ANY SHORTEST (x {address: '0xa'})-[:Transfer]->*(y {address: '0xc'}) \
+                     RETURN p[0].address, last(p).address, p[-2].address, head(p), length(p), \
+                     nodes(p), relationships(p), p[5]";
+        let result = executor.execute(&parse(query).unwrap()).unwrap();
+        assert_eq!(
+            result.columns,
+            vec![
+                "p[0].address",
+                "last(p).address",
+                "p[-2].address",
+                "head(p)",
+                "length(p)",
+                "nodes(p)",
+                "relationships(p)",
+                "p[5]"
+            ]
+        );
+        let row = format!("{:?}", result.rows[0]);
+        let scalar = |value: &ResultValue| format!("{:?}", value);
+        assert_eq!(scalar(&result.rows[0][0]), r#"Scalar(String("0xa"))"#);
+        assert_eq!(scalar(&result.rows[0][1]), r#"Scalar(String("0xc"))"#);
+        assert_eq!(scalar(&result.rows[0][2]), r#"Scalar(String("0xb"))"#);
+        // 单独的路径元素作为顶点返回
+        let ResultValue::Vertex(first) = &result.rows[0][3] else {
+            panic!("expected a vertex: {}", row);
+        };
+        assert_eq!(first.id, a.as_u64());
+        assert_eq!(scalar(&result.rows[0][4]), "Scalar(Integer(2))");
+        let ids = |ids: &[u64]| {
+            let items: Vec<String> = ids.iter().map(|id| format!("Integer({})", id)).collect();
+            format!("Scalar(List([{}]))", items.join(", "))
+        };
+        let nodes = [a, b, c].map(|v| v.as_u64());
+        assert_eq!(scalar(&result.rows[0][5]), ids(&nodes));
+        let edges = [ab, bc].map(|e| e.as_u64());
+        assert_eq!(scalar(&result.rows[0][6]), ids(&edges));
+        // 越界下标为 NULL
+        assert_eq!(scalar(&result.rows[0][7]), r#"Scalar(String(""))"#);
+
+        // 下标同样适用于列表，也可以用在 WHERE 中
+        let query = "MATCH p = ANY SHORTEST (x {address: '0xa'})-[:Transfer]->*(y:Account) \
+                     WHERE last(p).address = '0xb' RETURN [10, 20, 30][-1], head([1, 2])";
+        let result = executor.execute(&parse(query).unwrap()).unwrap();
+        assert_eq!(result.rows.len(), 1);
+        assert_eq!(scalar(&result.rows[0][0]), "Scalar(Integer(30))");
+        assert_eq!(scalar(&result.rows[0][1]), "Scalar(Integer(1))");
+    }
+
+    #[test]
+    fn test_call_check_consistency_and_rebuild_indexes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(1), 1).unwrap();
+        graph.vertex_index().remove_label(&VertexLabel::Account, b);
+
+        let executor = QueryExecutor::new(catalog);
+        let check = |executor: &QueryExecutor| {
+            executor
+                .execute(&parse("CALL db.check_consistency()").unwrap())
+                .unwrap()
+        };
+
+        let report = check(&executor);
+        assert!(matches!(
+            report.rows[0][1],
+            ResultValue::Scalar(PropertyValue::Boolean(false))
+        ));
+        assert!(report.rows.len() > 3);
+
+        let rebuilt = executor
+            .execute(&parse("CALL db.rebuild_indexes()").unwrap())
+            .unwrap();
+        assert!(matches!(
+            rebuilt.rows[0][0],
+            ResultValue::Scalar(PropertyValue::Integer(2))
+        ));
+
+        let report = check(&executor);
+        assert!(matches!(
+            report.rows[0][1],
+            ResultValue::Scalar(PropertyValue::Boolean(true))
+        ));
+        assert_eq!(report.rows.len(), 3);
+    }
+
+    #[test]
+    fn test_explain_analyze_matches_query_stats() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let accounts: Vec<VertexId> = (0..10)
+            .map(|i| graph.add_account(format!("0x{:02}", i)).unwrap())
+            .collect();
+        for (i, pair) in accounts.windows(2).enumerate() {
+            graph
+                .add_transfer(pair[0], pair[1], TokenAmount::from_u64(1), i as u64)
+                .unwrap();
+        }
+
+        let executor = QueryExecutor::new(catalog);
+        let query = "MATCH (a:Account)-[t:Transfer]->(b:Account) WHERE t.block_number >= 3 RETURN a, b LIMIT 4";
+        let plain = executor.execute(&parse(query).unwrap()).unwrap();
+
+        let explained = executor
+            .execute(&parse(&format!("EXPLAIN ANALYZE {}", query)).unwrap())
+            .unwrap();
+        assert_eq!(
+            explained.columns,
+            vec![
+                "operator",
+                "detail",
+                "rows",
+                "vertices_scanned",
+                "edges_scanned",
+                "time_ms"
+            ]
+        );
+        let int = |row: &Vec<ResultValue>, col: usize| match &row[col] {
+            ResultValue::Scalar(PropertyValue::Integer(n)) => *n as usize,
+            other => panic!("unexpected value: {:?}", other),
+        };
+        let operators: Vec<String> = explained
+            .rows
+            .iter()
+            .map(|row| match &row[0] {
+                ResultValue::Scalar(PropertyValue::String(s)) => s.clone(),
+                other => panic!("unexpected operator: {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            operators,
+            vec!["PatternMatch", "Filter", "Limit", "Project"]
+        );
+
+        // 各算子的扫描计数之和与普通执行的 QueryStats 一致
+        let vertices: usize = explained.rows.iter().map(|row| int(row, 3)).sum();
+        let edges: usize = explained.rows.iter().map(|row| int(row, 4)).sum();
+        assert_eq!(vertices, plain.stats.vertices_scanned);
+        assert_eq!(edges, plain.stats.edges_scanned);
+        assert_eq!(
+            explained.stats.vertices_scanned,
+            plain.stats.vertices_scanned
+        );
+        assert_eq!(int(&explained.rows[0], 2), 9);
+        assert_eq!(int(&explained.rows[1], 2), 6);
+        assert_eq!(int(&explained.rows[3], 2), plain.rows.len());
+
+        // 不带 ANALYZE 时只返回计划
+        let plan = executor
+            .execute(&parse(&format!("EXPLAIN {}", query)).unwrap())
+            .unwrap();
+        assert_eq!(plan.columns, vec!["operator", "detail"]);
+        assert_eq!(plan.rows.len(), 4);
+        assert_eq!(plan.stats.vertices_scanned, 0);
+
+        assert!(executor
+            .execute(&parse("EXPLAIN SHOW GRAPHS").unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_bindings_limit_aborts_large_match() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        for i in 0..10 {
+            graph.add_account(format!("0x{:02}", i)).unwrap();
+        }
+
+        let executor = QueryExecutor::new(catalog).with_max_bindings(50);
+        // 10 行，未超过上限
+        let ok = executor
+            .execute(&parse("MATCH (a:Account) RETURN a").unwrap())
+            .unwrap();
+        assert_eq!(ok.rows.len(), 10);
+
+        // 笛卡尔积产生 100 行中间绑定，超过上限时报错而不是继续累积
+        let err = executor
+            .execute(&parse("MATCH (a:Account), (b:Account) RETURN a, b").unwrap())
+            .unwrap_err();
+        assert!(err.to_string().contains("too large"), "{}", err);
+    }
+
+    #[test]
+    fn test_expansion_budget_spans_whole_pattern() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        // 4 个账户两两互转的完全图
+        let accounts: Vec<VertexId> = (0..4)
+            .map(|i| graph.add_account(format!("0x{}", i)).unwrap())
+            .collect();
+        for &src in &accounts {
+            for &dst in &accounts {
+                if src != dst {
+                    graph
+                        .add_transfer(src, dst, TokenAmount::from_u64(1), 1)
+                        .unwrap();
+                }
+            }
+        }
+
+        let executor = QueryExecutor::new(catalog).with_max_expansion_steps(1_000);
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+        let start = "MATCH (a:Account {address: '0x0'})";
+
+        // 单段 {1,4} 约 121 步，未超出预算；连续执行不会累计
+        for _ in 0..2 {
+            let single = run(&format!(
+                "{}-[:Transfer]->{{1,4}}(b:Account) RETURN b",
+                start
+            ));
+            assert_eq!(single.unwrap().rows.len(), 3 + 9 + 27 + 81);
+        }
+        // 有界的两段 {1,2} 合计约 170 步
+        let bounded = run(&format!(
+            "{}-[:Transfer]->{{1,2}}(b:Account)-[:Transfer]->{{1,2}}(c:Account) RETURN c",
+            start
+        ));
+        assert_eq!(bounded.unwrap().rows.len(), 12 * 12);
+
+        // 两段 {1,4} 各自都在预算内，但相乘后超出
+        let chained = run(&format!(
+            "{}-[:Transfer]->{{1,4}}(b:Account)-[:Transfer]->{{1,4}}(c:Account) RETURN c",
+            start
+        ));
+        assert!(chained.unwrap_err().to_string().contains("too expensive"));
+        let unbounded = run(&format!(
+            "{}-[:Transfer]->*(b:Account)-[:Transfer]->*(c:Account) RETURN c",
+            start
+        ));
+        assert!(unbounded.unwrap_err().to_string().contains("too expensive"));
+    }
+
+    #[test]
+    fn test_call_trace_value() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let s = graph.add_account("0xs".to_string()).unwrap();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        let t = graph.add_account("0xt".to_string()).unwrap();
+        graph
+            .add_transfer(s, a, TokenAmount::from_u64(75), 1)
+            .unwrap();
+        graph
+            .add_transfer(s, b, TokenAmount::from_u64(25), 1)
+            .unwrap();
+        graph
+            .add_transfer(a, t, TokenAmount::from_u64(75), 2)
+            .unwrap();
+        graph
+            .add_transfer(b, t, TokenAmount::from_u64(25), 2)
+            .unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let query = format!("CALL algo.trace_value({}, {}, 0.2)", s.as_u64(), t.as_u64());
+        let result = executor.execute(&parse(&query).unwrap()).unwrap();
+        assert_eq!(result.columns, vec!["path", "length", "fraction"]);
+        let fractions: Vec<f64> = result
+            .rows
+            .iter()
+            .map(|row| match &row[2] {
+                ResultValue::Scalar(PropertyValue::Float(f)) => *f,
+                other => panic!("unexpected fraction: {:?}", other),
+            })
+            .collect();
+        assert_eq!(fractions, vec![0.75, 0.25]);
+
+        let query = format!("CALL algo.trace_value({}, {}, 1.5)", s.as_u64(), t.as_u64());
+        assert!(executor.execute(&parse(&query).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_call_reachability() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        let c = graph.add_account("0xc".to_string()).unwrap();
+        graph
+            .add_transfer(a, b, TokenAmount::from_u64(1), 1)
+            .unwrap();
+        graph
+            .add_transfer(b, c, TokenAmount::from_u64(1), 2)
+            .unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let ids = [a, b, c].map(|v| v.as_u64());
+        let query = format!(
+            "CALL algo.reachability([{}, {}, {}], 2, 'forward')",
+            ids[0], ids[1], ids[2]
+        );
+        let result = executor.execute(&parse(&query).unwrap()).unwrap();
+        assert_eq!(result.columns, vec!["from", "to", "reachable"]);
+        assert_eq!(result.rows.len(), 6);
+        let reachable: Vec<(i64, i64)> = result
+            .rows
+            .iter()
+            .filter_map(|row| match (&row[0], &row[1], &row[2]) {
+                (
+                    ResultValue::Scalar(PropertyValue::Integer(from)),
+                    ResultValue::Scalar(PropertyValue::Integer(to)),
+                    ResultValue::Scalar(PropertyValue::Boolean(true)),
+                ) => Some((*from, *to)),
+                _ => None,
+            })
+            .collect();
+        let [a, b, c] = ids.map(|id| id as i64);
+        assert_eq!(reachable, vec![(a, b), (a, c), (b, c)]);
+
+        let too_many = (1..=crate::algorithm::MAX_REACHABILITY_SEEDS as i64 + 1)
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let query = format!("CALL algo.reachability([{}], 2)", too_many);
+        assert!(executor.execute(&parse(&query).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_call_net_balance() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        graph
+            .add_transfer(a, b, TokenAmount::from_u64(100), 10)
+            .unwrap();
+        graph
+            .add_transfer(b, a, TokenAmount::from_u64(40), 20)
+            .unwrap();
+        graph
+            .add_transfer(b, a, TokenAmount::from_u64(90), 30)
+            .unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let call = |query: String| executor.execute(&parse(&query).unwrap()).unwrap();
+        let result = call(format!("CALL algo.net_balance({})", b.as_u64()));
+        assert_eq!(result.columns[..3], ["inflow", "outflow", "net"]);
+        let row = &result.rows[0];
+        assert!(matches!(
+            &row[0],
+            ResultValue::Scalar(PropertyValue::TokenAmount(v)) if *v == TokenAmount::from_u64(100)
+        ));
+        assert!(matches!(&row[2], ResultValue::Scalar(PropertyValue::String(s)) if s == "-30"));
+
+        // 只统计区块 0..=20
+        let result = call(format!("CALL algo.net_balance({}, 0, 20)", b.as_u64()));
+        assert!(
+            matches!(&result.rows[0][2], ResultValue::Scalar(PropertyValue::String(s)) if s == "60")
+        );
+    }
+
+    #[test]
+    fn test_call_multi_shortest() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        let victim = graph.add_account("0xv".to_string()).unwrap();
+        let stranger = graph.add_account("0xs".to_string()).unwrap();
+        graph
+            .add_transfer(a, b, TokenAmount::from_u64(1), 1)
+            .unwrap();
+        graph
+            .add_transfer(b, victim, TokenAmount::from_u64(1), 2)
+            .unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let query = format!(
+            "CALL algo.multi_shortest([{}, {}, {}], {})",
+            a.as_u64(),
+            b.as_u64(),
+            stranger.as_u64(),
+            victim.as_u64()
+        );
+        let result = executor.execute(&parse(&query).unwrap()).unwrap();
+        assert_eq!(result.columns, ["source", "distance", "path"]);
+        let distances: Vec<Option<i64>> = result
+            .rows
+            .iter()
+            .map(|row| match &row[1] {
+                ResultValue::Scalar(PropertyValue::Integer(d)) => Some(*d),
+                ResultValue::Scalar(PropertyValue::Null) => None,
+                other => panic!("unexpected distance {:?}", other),
+            })
+            .collect();
+        // 无法到达的起点距离为 NULL
+        assert_eq!(distances, [Some(2), Some(1), None]);
+        let expected = format!("{} -> {} -> {}", a.as_u64(), b.as_u64(), victim.as_u64());
+        assert!(matches!(
+            &result.rows[0][2],
+            ResultValue::Scalar(PropertyValue::String(path)) if *path == expected
+        ));
+    }
+
+    #[test]
+    fn test_result_cache_reuses_call_until_write() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        graph
+            .add_transfer(a, b, TokenAmount::from_u64(1), 1)
+            .unwrap();
+
+        // 两个执行器共用一个缓存，与服务器上的多个请求相同
+        let cache = Arc::new(ResultCache::new(8));
+        let executor =
+            || QueryExecutor::new(catalog.clone()).with_result_cache(Some(cache.clone()));
+        let degree = |executor: &QueryExecutor| {
+            let query = format!("CALL algo.degree({})", a.as_u64());
+            let result = executor.execute(&parse(&query).unwrap()).unwrap();
+            match &result.rows[0][1] {
+                ResultValue::Scalar(PropertyValue::Integer(out)) => *out,
+                other => panic!("unexpected value {:?}", other),
+            }
+        };
+
+        assert_eq!(degree(&executor()), 1);
+        assert_eq!(degree(&executor()), 1);
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+        // 写入后版本变化，重新计算
+        graph
+            .add_transfer(a, b, TokenAmount::from_u64(2), 2)
+            .unwrap();
+        assert_eq!(degree(&executor()), 2);
+        assert_eq!((cache.hits(), cache.misses()), (1, 2));
+        assert_eq!(degree(&executor()), 2);
+        assert_eq!(cache.hits(), 2);
+
+        // 参数不同不命中；db.* 过程不经过缓存
+        let other = format!("CALL algo.degree({})", b.as_u64());
+        executor().execute(&parse(&other).unwrap()).unwrap();
+        executor()
+            .execute(&parse("CALL db.check_consistency()").unwrap())
+            .unwrap();
+        assert_eq!((cache.hits(), cache.misses()), (2, 3));
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    fn test_call_cluster_addresses() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        let x = graph.add_account("0xx".to_string()).unwrap();
+        let y = graph.add_account("0xy".to_string()).unwrap();
+        // a、b 在相近区块共同转给 x、y
+        for (src, dst, block) in [(a, x, 1), (b, x, 2), (a, y, 3), (b, y, 4)] {
+            graph
+                .add_transfer(src, dst, TokenAmount::from_u64(1), block)
+                .unwrap();
+        }
+
+        let executor = QueryExecutor::new(catalog);
+        let query = format!("CALL algo.cluster_addresses({}, 2)", a.as_u64());
+        let result = executor.execute(&parse(&query).unwrap()).unwrap();
+        assert_eq!(result.columns, ["vertex", "address", "cluster"]);
+        let int = |value: &ResultValue| match value {
+            ResultValue::Scalar(PropertyValue::Integer(id)) => *id as u64,
+            other => panic!("unexpected value {:?}", other),
+        };
+        let clusters = |result: &QueryResult| -> HashMap<u64, u64> {
+            result
+                .rows
+                .iter()
+                .map(|row| (int(&row[0]), int(&row[2])))
+                .collect()
+        };
+        let assigned = clusters(&result);
+        assert_eq!(assigned.len(), 4);
+        assert_eq!(assigned[&b.as_u64()], a.as_u64());
+        assert_eq!(assigned[&x.as_u64()], x.as_u64());
+
+        // 区块窗口为 0 时不再合并
+        let query = format!("CALL algo.cluster_addresses({}, 2, 2, 0)", a.as_u64());
+        let result = executor.execute(&parse(&query).unwrap()).unwrap();
+        let unmerged = clusters(&result);
+        assert!(unmerged.iter().all(|(vertex, cluster)| vertex == cluster));
+    }
+
+    #[test]
+    fn test_call_top_counterparties() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        let c = graph.add_account("0xc".to_string()).unwrap();
+        graph
+            .add_transfer(a, b, TokenAmount::from_u64(5), 1)
+            .unwrap();
+        graph
+            .add_transfer(b, a, TokenAmount::from_u64(5), 2)
+            .unwrap();
+        graph
+            .add_transfer(a, c, TokenAmount::from_u64(900), 3)
+            .unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let addresses = |query: String| -> Vec<String> {
+            let result = executor.execute(&parse(&query).unwrap()).unwrap();
+            result
+                .rows
+                .iter()
+                .map(|row| match &row[1] {
+                    ResultValue::Scalar(PropertyValue::String(addr)) => addr.clone(),
+                    other => panic!("unexpected value {:?}", other),
+                })
+                .collect()
+        };
+
+        // 按笔数 b 在前，按金额 c 在前
+        let id = a.as_u64();
+        let by_count = addresses(format!("CALL algo.top_counterparties({}, 2)", id));
+        assert_eq!(by_count, ["0xb", "0xc"]);
+        let by_volume = format!("CALL algo.top_counterparties({}, 2, 'both', 'volume')", id);
+        assert_eq!(addresses(by_volume), ["0xc", "0xb"]);
+        let incoming = format!("CALL algo.top_counterparties({}, 5, 'backward')", id);
+        assert_eq!(addresses(incoming), ["0xb"]);
+
+        let bad_rank = format!("CALL algo.top_counterparties({}, 2, 'both', 'age')", id);
+        assert!(executor.execute(&parse(&bad_rank).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_match_starts_from_selective_node() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let contracts: Vec<VertexId> = (0..2)
+            .map(|_| graph.add_vertex(VertexLabel::Contract).unwrap())
+            .collect();
+        for i in 0..100 {
+            let account = graph.add_account(format!("0x{:03}", i)).unwrap();
+            graph
+                .add_edge(EdgeLabel::Call, account, contracts[i % 2])
+                .unwrap();
+        }
+
+        let executor = QueryExecutor::new(catalog);
+        // 从左到右：扫描全部 100 个 Account
+        let unplanned = executor
+            .execute(&parse("MATCH (a:Account)-[:Call]->(:Contract) RETURN a").unwrap())
+            .unwrap();
+        assert_eq!(unplanned.stats.vertices_scanned, 100);
+
+        // 命名的 c 只有 2 个候选，从 c 出发反向展开
+        let planned = executor
+            .execute(&parse("MATCH (a:Account)-[:Call]->(c:Contract) RETURN a, c").unwrap())
+            .unwrap();
+        assert_eq!(planned.rows.len(), 100);
+        assert_eq!(planned.stats.vertices_scanned, 2);
+        assert!(planned.stats.vertices_scanned * 10 < unplanned.stats.vertices_scanned);
+    }
+
+    #[test]
+    fn test_match_hints_choose_index_or_scan() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        for i in 0..20 {
+            graph.add_account(format!("0x{:02}", i)).unwrap();
+        }
+        graph.create_index("Account", "address").unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+
+        // WHERE 中的等值条件默认按标签扫描，USING INDEX 强制走索引
+        let scanned = run("MATCH (n:Account) WHERE n.address = '0x05' RETURN n").unwrap();
+        assert_eq!(scanned.stats.vertices_scanned, 20);
+        let hinted = run(
+            "MATCH (n:Account) USING INDEX n:Account(address) WHERE n.address = '0x05' RETURN n",
+        )
+        .unwrap();
+        assert_eq!(hinted.rows.len(), 1);
+        assert_eq!(hinted.stats.vertices_scanned, 1);
+
+        // 内联属性默认走索引，USING SCAN 强制扫描，结果一致
+        let indexed = run("MATCH (n:Account {address: '0x07'}) RETURN n").unwrap();
+        assert_eq!(indexed.stats.vertices_scanned, 1);
+        let forced =
+            run("MATCH (n:Account {address: '0x07'}) USING SCAN n:Account RETURN n").unwrap();
+        assert_eq!(forced.stats.vertices_scanned, 20);
+        assert_eq!(forced.rows.len(), 1);
+
+        // 索引不存在、变量未知或缺少等值条件时报错
+        let missing =
+            run("MATCH (n:Account) USING INDEX n:Account(balance) WHERE n.balance = 1 RETURN n");
+        assert!(missing.unwrap_err().to_string().contains("Index not found"));
+        assert!(run("MATCH (n:Account) USING SCAN m:Account RETURN n").is_err());
+        assert!(run("MATCH (n:Account) USING INDEX n:Account(address) RETURN n").is_err());
+    }
+
+    #[test]
+    fn test_snapshot_hides_concurrent_inserts() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let a = graph.add_account("0xa".to_string()).unwrap();
+        let b = graph.add_account("0xb".to_string()).unwrap();
+        graph
+            .add_transfer(a, b, TokenAmount::from_u64(1), 1)
+            .unwrap();
+
+        // 查询开始时的快照；之后的写入模拟与查询并发的导入
+        let snapshot = graph.snapshot();
+        let c = graph.add_account("0xc".to_string()).unwrap();
+        graph
+            .add_transfer(b, c, TokenAmount::from_u64(1), 2)
+            .unwrap();
+        graph
+            .add_transfer(a, b, TokenAmount::from_u64(1), 3)
+            .unwrap();
+
+        let count = |executor: &QueryExecutor, query: &str| {
+            executor.execute(&parse(query).unwrap()).unwrap().rows.len()
+        };
+        let transfers = "MATCH (x:Account)-[t:Transfer]->(y:Account) RETURN x, t, y";
+        let accounts = "MATCH (n:Account) RETURN n";
+
+        let in_flight = QueryExecutor::new(catalog.clone()).with_snapshot(snapshot);
+        assert_eq!(count(&in_flight, transfers), 1);
+        assert_eq!(count(&in_flight, accounts), 2);
+        let var_length = "MATCH (x:Account)-[:Transfer]->{1,3}(y) RETURN x, y";
+        assert_eq!(count(&in_flight, var_length), 1);
+
+        let fresh = QueryExecutor::new(catalog);
+        assert_eq!(count(&fresh, transfers), 3);
+        assert_eq!(count(&fresh, accounts), 3);
+    }
+
+    #[test]
+    fn test_properties_keys_and_labels_functions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let executor = QueryExecutor::new(catalog);
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+        let row = |query: &str| -> Vec<PropertyValue> {
+            let result = run(query).unwrap();
+            result.rows[0]
+                .iter()
+                .map(|value| match value {
+                    ResultValue::Scalar(v) => v.clone(),
+                    other => panic!("expected scalar, got {:?}", other),
+                })
+                .collect()
+        };
+        run("INSERT (a:Account {address: '0xa', tag: 'exchange'})-[:Transfer {amount: 5}]->(b:Account {address: '0xb'})")
+            .unwrap();
+
+        let values =
+            row("MATCH (n:Account {address: '0xa'}) RETURN properties(n), keys(n), labels(n)");
+        let PropertyValue::Map(props) = &values[0] else {
+            panic!("expected a map, got {:?}", values[0]);
+        };
+        let tag = PropertyValue::String("exchange".to_string());
+        assert_eq!(props.get("tag"), Some(&tag));
+        // keys(n) 按字母序列出 properties(n) 的键
+        let mut keys: Vec<String> = props.keys().cloned().collect();
+        keys.sort();
+        let keys = keys.into_iter().map(PropertyValue::String).collect();
+        assert_eq!(values[1], PropertyValue::List(keys));
+        let account = PropertyValue::String("Account".to_string());
+        assert_eq!(values[2], PropertyValue::List(vec![account]));
+
+        // 边同样适用
+        let values = row("MATCH (a)-[t:Transfer]->(b) RETURN properties(t), labels(t)");
+        let PropertyValue::Map(props) = &values[0] else {
+            panic!("expected a map, got {:?}", values[0]);
+        };
+        let amount = PropertyValue::TokenAmount(TokenAmount::from_u64(5));
+        assert_eq!(props.get("amount"), Some(&amount));
+        let transfer = PropertyValue::String("Transfer".to_string());
+        assert_eq!(values[1], PropertyValue::List(vec![transfer]));
+
+        // 参数不是顶点或边变量时报错
+        assert!(run("MATCH (n:Account) RETURN keys(n.tag)").is_err());
+    }
+
+    #[test]
+    fn test_insert_mixed_named_and_anonymous_nodes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let executor = QueryExecutor::new(catalog);
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+        let ends = |query: &str| -> Vec<(String, String)> {
+            let mut rows: Vec<(String, String)> = run(query)
+                .unwrap()
+                .rows
+                .iter()
+                .map(|row| match (&row[0], &row[1]) {
+                    (
+                        ResultValue::Scalar(PropertyValue::String(src)),
+                        ResultValue::Scalar(PropertyValue::String(dst)),
+                    ) => (src.clone(), dst.clone()),
+                    other => panic!("expected addresses, got {:?}", other),
+                })
+                .collect();
+            rows.sort();
+            rows
+        };
+        let pair = |src: &str, dst: &str| (src.to_string(), dst.to_string());
+
+        // 同一变量在多个模式中复用，匿名顶点各自独立
+        run("INSERT (a:Account {address: '0xa'})-[:Transfer {amount: 1}]->(:Account {address: '0xb'}), \
+             (a)-[:Call]->(:Contract {address: '0xc'})")
+            .unwrap();
+        assert_eq!(
+            ends("MATCH (x)-[t:Transfer]->(y) RETURN x.address, y.address"),
+            vec![pair("0xa", "0xb")]
+        );
+        assert_eq!(
+            ends("MATCH (x)-[c:Call]->(y) RETURN x.address, y.address"),
+            vec![pair("0xa", "0xc")]
+        );
+
+        // 匿名源顶点指向先前声明的变量
+        let inserted = run("INSERT (d:Account {address: '0xd'}), \
+             (:Account {address: '0xe'})-[:Transfer {amount: 2}]->(d), \
+             (:Account {address: '0xf'})-[:Transfer {amount: 3}]->(:Account {address: '0x10'})")
+        .unwrap();
+        assert!(matches!(
+            inserted.rows[0][..],
+            [
+                ResultValue::Scalar(PropertyValue::Integer(4)),
+                ResultValue::Scalar(PropertyValue::Integer(2))
+            ]
+        ));
+        assert_eq!(
+            ends("MATCH (x)-[t:Transfer]->(y) RETURN x.address, y.address"),
+            vec![pair("0xa", "0xb"), pair("0xe", "0xd"), pair("0xf", "0x10")]
+        );
+    }
+
+    #[test]
+    fn test_insert_return() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let executor = QueryExecutor::new(catalog.clone());
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+
+        let result =
+            run("INSERT (n:Account {address: '0xa', tag: 'hot'}) RETURN n, n.tag AS tag").unwrap();
+        assert_eq!(result.columns, vec!["n".to_string(), "tag".to_string()]);
+        assert_eq!(result.rows.len(), 1);
+        let ResultValue::Vertex(vertex) = &result.rows[0][0] else {
+            panic!("expected a vertex, got {:?}", result.rows[0][0]);
+        };
+        // 返回的是图中真实分配的 ID
+        let graph = catalog.current_graph();
+        let stored = graph.get_vertex_by_address("0xa").unwrap();
+        assert_eq!(vertex.id, stored.id().as_u64());
+        assert!(matches!(
+            &result.rows[0][1],
+            ResultValue::Scalar(PropertyValue::String(tag)) if tag == "hot"
+        ));
+
+        // 边变量与 id() 同样可用，匿名顶点不可引用
+        let result =
+            run("INSERT (n)-[t:Transfer {amount: 7}]->(:Account {address: '0xb'}) RETURN id(t), t")
+                .unwrap();
+        let ResultValue::Scalar(PropertyValue::Integer(edge_id)) = result.rows[0][0] else {
+            panic!("expected an id, got {:?}", result.rows[0][0]);
+        };
+        let ResultValue::Edge(edge) = &result.rows[0][1] else {
+            panic!("expected an edge, got {:?}", result.rows[0][1]);
+        };
+        assert_eq!(edge.id, edge_id as u64);
+        assert!(graph.get_edge(EdgeId::new(edge.id)).is_some());
+
+        // 没有 RETURN 时仍返回插入计数
+        let result = run("INSERT (m:Account {address: '0xc'})").unwrap();
+        assert_eq!(result.columns[0], "inserted_vertices");
+    }
+
+    #[test]
+    fn test_unwind_insert() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let executor = QueryExecutor::new(catalog.clone());
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+        let row = |from: &str, to: &str, amount: i64| {
+            PropertyValue::Map(
+                [
+                    ("from".to_string(), PropertyValue::String(from.to_string())),
+                    ("to".to_string(), PropertyValue::String(to.to_string())),
+                    ("amount".to_string(), PropertyValue::Integer(amount)),
+                ]
+                .into_iter()
+                .collect(),
+            )
+        };
+        executor.set_parameter(
+            "rows",
+            PropertyValue::List(vec![row("0xa", "0xb", 5), row("0xc", "0xd", 7)]),
+        );
+
+        let query = "UNWIND $rows AS r INSERT (a:Account {address: r.from})-[t:Transfer {amount: r.amount}]->(b:Account {address: r.to}) RETURN r.from, t.amount";
+        let result = run(query).unwrap();
+        // 每个列表元素一行
+        assert_eq!(result.rows.len(), 2);
+        assert!(matches!(
+            &result.rows[1][0],
+            ResultValue::Scalar(PropertyValue::String(from)) if from == "0xc"
+        ));
+        assert!(matches!(
+            &result.rows[1][1],
+            ResultValue::Scalar(PropertyValue::TokenAmount(amount))
+                if *amount == TokenAmount::from_u64(7)
+        ));
+        let graph = catalog.current_graph();
+        let c = graph.get_vertex_by_address("0xc").unwrap();
+        let edges = graph.get_outgoing_edges(c.id());
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].amount(), Some(&TokenAmount::from_u64(7)));
+
+        // 会话参数同样可用，没有 RETURN 时返回累计计数
+        run("SESSION SET batch = [{address: '0xe'}, {address: '0xf'}, {address: '0x10'}]").unwrap();
+        let result = run("UNWIND $batch AS v INSERT (:Account {address: v.address})").unwrap();
+        assert!(matches!(
+            &result.rows[0][0],
+            ResultValue::Scalar(PropertyValue::Integer(3))
+        ));
+        assert!(graph.get_vertex_by_address("0x10").is_some());
+
+        // 空列表不插入任何内容，非列表报错
+        run("UNWIND [] AS v INSERT (:Account {address: v.address})").unwrap();
+        let err = run("UNWIND 42 AS v INSERT (:Account {address: v})").unwrap_err();
+        assert!(err.to_string().contains("UNWIND expects a list"), "{}", err);
+    }
+
+    #[test]
+    fn test_unwind_insert_rejects_bad_row_before_writing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let executor = QueryExecutor::new(catalog);
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+        run("CREATE GRAPH typed { NODE Account { address String PRIMARY KEY, balance INT64 } }")
+            .unwrap();
+        run("USE GRAPH typed").unwrap();
+
+        // 第三行类型不符，前两行也不写入
+        run("SESSION SET rows = [{a: '0xa', b: 1}, {a: '0xb', b: 2}, {a: '0xc', b: 'lots'}]")
+            .unwrap();
+        let err =
+            run("UNWIND $rows AS r INSERT (:Account {address: r.a, balance: r.b})").unwrap_err();
+        assert!(matches!(err, Error::TypeMismatch(_)), "{}", err);
+        assert_eq!(executor.graph().vertex_count(), 0);
+
+        // 某行的属性表达式求值失败时同样不写入
+        run("SESSION SET rows = [{a: '0xa', b: 1}, 7]").unwrap();
+        assert!(run("UNWIND $rows AS r INSERT (:Account {address: r.a, balance: r.b})").is_err());
+        assert_eq!(executor.graph().vertex_count(), 0);
+    }
+
+    #[test]
+    fn test_id_label_and_type_functions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let executor = QueryExecutor::new(catalog.clone());
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+        let row = |query: &str| -> Vec<PropertyValue> {
+            let result = run(query).unwrap();
+            result.rows[0]
+                .iter()
+                .map(|value| match value {
+                    ResultValue::Scalar(v) => v.clone(),
+                    other => panic!("expected scalar, got {:?}", other),
+                })
+                .collect()
+        };
+        run("INSERT (a:Account {address: '0xa'})-[:Transfer {amount: 5}]->(b:Contract {address: '0xb'})")
+            .unwrap();
+        let graph = catalog.current_graph();
+        let a = graph.get_vertex_by_address("0xa").unwrap();
+        let b = graph.get_vertex_by_address("0xb").unwrap();
+        let edge = graph.get_outgoing_edges(a.id()).remove(0);
+
+        let values =
+            row("MATCH (n)-[t:Transfer]->(m) RETURN id(n), label(m), id(t), type(t), label(t)");
+        assert_eq!(
+            values,
+            vec![
+                PropertyValue::Integer(a.id().as_u64() as i64),
+                PropertyValue::String(b.label().as_str().to_string()),
+                PropertyValue::Integer(edge.id().as_u64() as i64),
+                PropertyValue::String("Transfer".to_string()),
+                PropertyValue::String("Transfer".to_string()),
+            ]
+        );
+        let contract = PropertyValue::String("Contract".to_string());
+        assert_eq!(values[1], contract);
+
+        // id() 可用于结果过滤
+        let b_id = b.id().as_u64();
+        let query = format!("MATCH (n) WHERE id(n) = {} RETURN label(n)", b_id);
+        assert_eq!(row(&query), vec![contract]);
+
+        // type() 只接受边，参数不是元素变量时报错
+        assert!(run("MATCH (n:Account) RETURN type(n)").is_err());
+        assert!(run("MATCH (n:Account) RETURN id(n.address)").is_err());
+    }
+
+    #[test]
+    fn test_case_insensitive_equality() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let executor = QueryExecutor::new(catalog);
+        let run = |query: &str| executor.execute(&parse(query).unwrap()).unwrap();
+        run("INSERT (a:Account {address: '0xa', tag: 'exchange'})");
+        run("INSERT (b:Account {address: '0xb', tag: 'EXCHANGE'})");
+        run("INSERT (c:Account {address: '0xc', tag: 'mixer'})");
+
+        let exchanges = "MATCH (n:Account) WHERE n.tag = 'Exchange' RETURN n";
+        let others = "MATCH (n:Account) WHERE n.tag <> 'Exchange' RETURN n";
+        // 默认区分大小写
+        assert_eq!(run(exchanges).rows.len(), 0);
+
+        run("SESSION SET case_insensitive = true");
+        assert_eq!(run(exchanges).rows.len(), 2);
+        assert_eq!(run(others).rows.len(), 1);
+
+        run("SESSION RESET case_insensitive");
+        assert_eq!(run(exchanges).rows.len(), 0);
+
+        let executor = QueryExecutor::new(executor.catalog.clone()).with_case_insensitive(true);
+        let rows = executor.execute(&parse(exchanges).unwrap()).unwrap().rows;
+        assert_eq!(rows.len(), 2);
+        assert!(executor
+            .execute(&parse("SESSION SET case_insensitive = 'yes'").unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_session_set_graph_switches_graph() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let g2 = catalog.create_graph("g2").unwrap();
+        g2.add_account("0xa".to_string()).unwrap();
+        g2.add_account("0xb".to_string()).unwrap();
+
+        let executor = QueryExecutor::new(catalog.clone());
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+        let accounts = "MATCH (n:Account) RETURN n";
+        assert_eq!(run(accounts).unwrap().rows.len(), 0);
+
+        run("SESSION SET GRAPH g2").unwrap();
+        assert_eq!(catalog.current_graph_name(), "g2");
+        assert_eq!(run(accounts).unwrap().rows.len(), 2);
+
+        assert!(run("SESSION SET GRAPH missing").is_err());
+        assert_eq!(catalog.current_graph_name(), "g2");
+    }
+
+    #[test]
+    fn test_session_time_zone_renders_timestamps() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let graph = catalog.current_graph();
+        let id = graph.add_account("0xa".to_string()).unwrap();
+        let mut vertex = graph.get_vertex(id).unwrap();
+        // 2024-01-01T00:00:00Z
+        vertex.set_property(
+            "first_seen".to_string(),
+            PropertyValue::Timestamp(1_704_067_200),
+        );
+        graph.update_vertex(vertex).unwrap();
+
+        let executor = QueryExecutor::new(catalog);
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+        let first_seen = |result: QueryResult| match &result.rows[0][0] {
+            ResultValue::Vertex(v) => v.properties["first_seen"].clone(),
+            other => panic!("expected vertex, got {:?}", other),
+        };
+        let query = "MATCH (n:Account) RETURN n";
+
+        // 未设置时区时保持原始的 UTC 秒数
+        assert_eq!(
+            first_seen(run(query).unwrap()),
+            PropertyValue::Timestamp(1_704_067_200)
+        );
+
+        run("SESSION SET TIME ZONE '+08:00'").unwrap();
+        assert_eq!(
+            first_seen(run(query).unwrap()),
+            PropertyValue::String("2024-01-01T08:00:00+08:00".to_string())
+        );
+
+        run("SESSION SET TIME ZONE 'UTC-0530'").unwrap();
+        assert_eq!(
+            first_seen(run(query).unwrap()),
+            PropertyValue::String("2023-12-31T18:30:00-05:30".to_string())
+        );
+
+        assert!(run("SESSION SET TIME ZONE 'Mars/Olympus'").is_err());
+        run("SESSION RESET TIME ZONE").unwrap();
+        assert_eq!(
+            first_seen(run(query).unwrap()),
+            PropertyValue::Timestamp(1_704_067_200)
+        );
+    }
+
+    #[test]
+    fn test_float_precision_rounds_results() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let executor = QueryExecutor::new(catalog.clone());
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+        run("INSERT (a:Account {address: '0xa', rate: 0.1})").unwrap();
+        let rate = |result: QueryResult| match &result.rows[0][0] {
+            ResultValue::Scalar(v) => v.clone(),
+            other => panic!("expected scalar, got {:?}", other),
+        };
+        let query = "MATCH (n:Account) RETURN n.rate + 0.2";
+
+        // 默认保持完整精度
+        assert_eq!(rate(run(query).unwrap()), PropertyValue::Float(0.1 + 0.2));
+
+        run("SESSION SET float_precision = 4").unwrap();
+        assert_eq!(rate(run(query).unwrap()), PropertyValue::Float(0.3));
+        assert!(run("SESSION SET float_precision = -1").is_err());
+
+        run("SESSION RESET float_precision").unwrap();
+        assert_eq!(rate(run(query).unwrap()), PropertyValue::Float(0.1 + 0.2));
+
+        let executor = QueryExecutor::new(catalog).with_float_precision(Some(1));
+        let result = executor.execute(&parse(query).unwrap()).unwrap();
+        assert_eq!(rate(result), PropertyValue::Float(0.3));
+    }
+
+    #[test]
+    fn test_schema_rejects_mismatched_property_types() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let executor = QueryExecutor::new(catalog);
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+
+        // 无 schema 时不限制类型
+        run("INSERT (a:Account {address: '0xa', balance: 'lots'})").unwrap();
+
+        run("CREATE GRAPH typed { NODE Account { address String PRIMARY KEY, balance INT64 } }")
+            .unwrap();
+        run("USE GRAPH typed").unwrap();
+        let err = run("INSERT (a:Account {address: '0xa', balance: 'lots'})").unwrap_err();
+        assert!(matches!(err, Error::TypeMismatch(_)), "{}", err);
+        assert!(err.to_string().contains("Account.balance"), "{}", err);
+        assert_eq!(executor.graph().vertex_count(), 0);
+
+        run("INSERT (a:Account {address: '0xa', balance: 100})").unwrap();
+        let err = run("MATCH (n:Account) SET n.balance = 'lots'").unwrap_err();
+        assert!(matches!(err, Error::TypeMismatch(_)), "{}", err);
+        run("MATCH (n:Account) SET n.balance = 200").unwrap();
+
+        // 边属性同样在写入任何顶点之前校验
+        run("CREATE GRAPH typed_edges { NODE Account { address String PRIMARY KEY }, EDGE Transfer (Account)-[{ amount INT64 }]->(Account) }")
+            .unwrap();
+        run("USE GRAPH typed_edges").unwrap();
+        let err = run("INSERT (a:Account {address: '0xa'})-[:Transfer {amount: 'lots'}]->(b:Account {address: '0xb'})")
+            .unwrap_err();
+        assert!(matches!(err, Error::TypeMismatch(_)), "{}", err);
+        assert!(err.to_string().contains("Transfer.amount"), "{}", err);
+        assert_eq!(executor.graph().vertex_count(), 0);
+        assert_eq!(executor.graph().edge_count(), 0);
+    }
+
+    #[test]
+    fn test_session_parameter_binding() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let executor = QueryExecutor::new(catalog);
+        let run = |query: &str| executor.execute(&parse(query).unwrap());
+        run("INSERT (a:Account {address: '0xa', balance: 500})").unwrap();
+        run("INSERT (b:Account {address: '0xb', balance: 2000})").unwrap();
+
+        let rich = "MATCH (n:Account) WHERE n.balance > $threshold RETURN n";
+        // 未绑定的参数使 WHERE 求值失败，不匹配任何行
+        assert_eq!(run(rich).unwrap().rows.len(), 0);
+
+        run("SESSION SET threshold = 1000").unwrap();
+        assert_eq!(run(rich).unwrap().rows.len(), 1);
+        run("SESSION SET threshold = 100").unwrap();
+        assert_eq!(run(rich).unwrap().rows.len(), 2);
+
+        run("SESSION RESET threshold").unwrap();
+        assert_eq!(run(rich).unwrap().rows.len(), 0);
+    }
 }
@@ -11,6 +11,7 @@
 //! - 量化路径模式
 
 mod ast;
+mod cache;
 mod executor;
 mod parser;
 
@@ -60,7 +61,13 @@ pub use ast::{
 };
 
 // 导出执行器
-pub use executor::{QueryExecutor, QueryResult};
+pub use executor::{
+    DeleteSummary, MissingAmount, QueryExecutor, QueryResult, ResultValue, DEFAULT_MAX_BINDINGS,
+    DEFAULT_MAX_EXPANSION_STEPS, DEFAULT_MAX_ROWS, DEFAULT_MAX_UNLABELED_SCAN,
+};
+
+// 导出算法结果缓存
+pub use cache::ResultCache;
 
 // 导出解析器
 pub use parser::GqlParser;
@@ -4,12 +4,15 @@
 
 use crate::error::{Error, Result};
 use crate::query::ast::*;
-use crate::types::{EdgeLabel, PropertyValue, VertexLabel};
+use crate::types::{EdgeLabel, PropertyValue, TokenAmount, VertexLabel};
 
 /// GQL Parser
 pub struct GqlParser {
     input: String,
     pos: usize,
+    /// Set while parsing INSERT patterns, where property values may be
+    /// expressions (`r.from`, `$amount`) rather than literals
+    property_exprs_allowed: bool,
 }
 
 impl GqlParser {
@@ -18,6 +21,7 @@ impl GqlParser {
         Self {
             input: input.to_string(),
             pos: 0,
+            property_exprs_allowed: false,
         }
     }
 
@@ -29,6 +33,7 @@ impl GqlParser {
         match keyword.to_uppercase().as_str() {
             "MATCH" | "OPTIONAL" => self.parse_match(),
             "INSERT" => self.parse_insert(),
+            "UNWIND" => self.parse_unwind(),
             "DELETE" | "DETACH" | "NODETACH" => self.parse_delete(),
             "SET" => self.parse_set(),
             "REMOVE" => self.parse_remove(),
@@ -47,6 +52,7 @@ impl GqlParser {
             "START" => self.parse_transaction_start(),
             "COMMIT" => self.parse_transaction_commit(),
             "ROLLBACK" => self.parse_transaction_rollback(),
+            "EXPLAIN" => self.parse_explain(),
             _ => Err(Error::ParseError(format!(
                 "Unknown statement type: {}",
                 keyword
@@ -54,6 +60,38 @@ impl GqlParser {
         }
     }
 
+    /// Parse a script of statements separated by `;`.
+    /// Empty statements and a trailing `;` are ignored.
+    pub fn parse_statements(&mut self) -> Result<Vec<GqlStatement>> {
+        let mut statements = Vec::new();
+        loop {
+            self.skip_whitespace();
+            while self.peek_char() == Some(';') {
+                self.pos += 1;
+                self.skip_whitespace();
+            }
+            if self.pos >= self.input.len() {
+                break;
+            }
+            statements.push(self.parse()?);
+            self.skip_whitespace();
+            match self.peek_char() {
+                Some(';') => self.pos += 1,
+                None => break,
+                Some(_) => {
+                    return Err(Error::ParseError(format!(
+                        "Expected ';' between statements at position {}",
+                        self.pos
+                    )))
+                }
+            }
+        }
+        if statements.is_empty() {
+            return Err(Error::ParseError("Empty query".to_string()));
+        }
+        Ok(statements)
+    }
+
     // ========================================================================
     // MATCH Statement Parsing (ISO GQL 39075)
     // ========================================================================
@@ -72,6 +110,9 @@ impl GqlParser {
         // Parse graph pattern
         let graph_pattern = self.parse_graph_pattern()?;
 
+        // USING INDEX / USING SCAN hints
+        let hints = self.parse_match_hints()?;
+
         // WHERE clause
         let where_clause = if self.try_keyword("WHERE") {
             Some(self.parse_expression()?)
@@ -79,6 +120,40 @@ impl GqlParser {
             None
         };
 
+        // MATCH ... SET / REMOVE / DELETE: data modification applied to the matched bindings
+        self.skip_whitespace();
+        let is_set = self.peek_keyword_is("SET");
+        let is_remove = self.peek_keyword_is("REMOVE");
+        let is_delete = ["DELETE", "DETACH", "NODETACH"]
+            .iter()
+            .any(|kw| self.peek_keyword_is(kw));
+        if is_set || is_remove || is_delete {
+            let match_clause = Box::new(MatchStatement {
+                optional,
+                match_mode,
+                graph_pattern,
+                hints,
+                where_clause,
+                return_clause: Vec::new(),
+                order_by: None,
+                skip: None,
+                limit: None,
+            });
+            if is_set {
+                let mut stmt = self.parse_set_items()?;
+                stmt.match_clause = Some(match_clause);
+                return Ok(GqlStatement::Set(stmt));
+            }
+            if is_remove {
+                let mut stmt = self.parse_remove_items()?;
+                stmt.match_clause = Some(match_clause);
+                return Ok(GqlStatement::Remove(stmt));
+            }
+            let mut stmt = self.parse_delete_items()?;
+            stmt.match_clause = Some(match_clause);
+            return Ok(GqlStatement::Delete(stmt));
+        }
+
         // RETURN clause
         let return_clause = if self.try_keyword("RETURN") {
             self.parse_return_items()?
@@ -112,6 +187,7 @@ impl GqlParser {
             optional,
             match_mode,
             graph_pattern,
+            hints,
             where_clause,
             return_clause,
             order_by,
@@ -120,6 +196,46 @@ impl GqlParser {
         }))
     }
 
+    /// Parse planner hints after the graph pattern
+    /// hint: USING INDEX var:Label(property) | USING SCAN var:Label
+    fn parse_match_hints(&mut self) -> Result<Vec<MatchHint>> {
+        let mut hints = Vec::new();
+        while self.try_keyword("USING") {
+            let index = if self.try_keyword("INDEX") {
+                true
+            } else if self.try_keyword("SCAN") {
+                false
+            } else {
+                return Err(Error::ParseError(
+                    "Expected INDEX or SCAN after USING".to_string(),
+                ));
+            };
+            let variable = self.parse_identifier()?;
+            self.skip_whitespace();
+            self.expect_char(':')?;
+            self.skip_whitespace();
+            let name = self.parse_identifier()?;
+            let label = Self::parse_vertex_label(&name)
+                .ok_or_else(|| Error::ParseError(format!("Unknown vertex label: {}", name)))?;
+            if index {
+                self.skip_whitespace();
+                self.expect_char('(')?;
+                self.skip_whitespace();
+                let property = self.parse_identifier()?;
+                self.skip_whitespace();
+                self.expect_char(')')?;
+                hints.push(MatchHint::Index {
+                    variable,
+                    label,
+                    property,
+                });
+            } else {
+                hints.push(MatchHint::Scan { variable, label });
+            }
+        }
+        Ok(hints)
+    }
+
     /// Parse match mode
     fn parse_match_mode(&mut self) -> Result<Option<MatchMode>> {
         if self.try_keyword("REPEATABLE") {
@@ -462,12 +578,18 @@ impl GqlParser {
             }
         }
 
-        // Parse label expression
-        node.label_expr = self.parse_label_expression()?;
+        // Parse label expression; `:=` requests exact label-set matching
+        if self.try_str(":=") {
+            node.exact_labels = true;
+            self.skip_whitespace();
+            node.label_expr = Some(self.parse_label_disjunction()?);
+        } else {
+            node.label_expr = self.parse_label_expression()?;
+        }
 
         // Properties (optional)
         if self.try_char('{') {
-            node.properties = self.parse_properties()?;
+            (node.properties, node.property_exprs) = self.parse_properties()?;
             self.expect_char('}')?;
         }
 
@@ -632,7 +754,7 @@ impl GqlParser {
 
             // Properties
             if self.try_char('{') {
-                edge.properties = self.parse_properties()?;
+                (edge.properties, edge.property_exprs) = self.parse_properties()?;
                 self.expect_char('}')?;
             }
 
@@ -717,34 +839,7 @@ impl GqlParser {
         } else if self.try_char('?') {
             Ok(Some(PatternQuantifier::ZeroOrOne))
         } else if self.try_char('{') {
-            self.skip_whitespace();
-
-            let result = if self.peek_char_is_digit() {
-                let min = self.parse_integer()? as u64;
-                self.skip_whitespace();
-
-                if self.try_char(',') {
-                    self.skip_whitespace();
-                    if self.peek_char_is_digit() {
-                        let max = self.parse_integer()? as u64;
-                        PatternQuantifier::Range(min, max)
-                    } else {
-                        PatternQuantifier::AtLeast(min)
-                    }
-                } else {
-                    PatternQuantifier::Exactly(min)
-                }
-            } else if self.try_char(',') {
-                self.skip_whitespace();
-                let max = self.parse_integer()? as u64;
-                PatternQuantifier::AtMost(max)
-            } else {
-                return Err(Error::ParseError("Invalid quantifier".to_string()));
-            };
-
-            self.skip_whitespace();
-            self.expect_char('}')?;
-            Ok(Some(result))
+            self.parse_braced_quantifier().map(Some)
         } else {
             Ok(None)
         }
@@ -769,42 +864,78 @@ impl GqlParser {
         } else if self.try_char('?') {
             Ok(Some(PatternQuantifier::ZeroOrOne))
         } else if self.try_char('{') {
-            self.skip_whitespace();
-
-            let result = if self.peek_char_is_digit() {
-                let min = self.parse_plain_integer()? as u64;
-                self.skip_whitespace();
+            self.parse_braced_quantifier().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
 
-                if self.try_char(',') {
-                    self.skip_whitespace();
-                    if self.peek_char_is_digit() {
-                        let max = self.parse_plain_integer()? as u64;
-                        PatternQuantifier::Range(min, max)
-                    } else {
-                        PatternQuantifier::AtLeast(min)
-                    }
-                } else {
-                    PatternQuantifier::Exactly(min)
+    /// Parse the body of a `{n}`, `{n,}`, `{,m}` or `{n,m}` quantifier after
+    /// the opening brace. Bounds must be non-negative integers and `n <= m`.
+    /// `{0}` / `{0,0}` / `{,0}` match the zero-length path, i.e. the source
+    /// vertex itself when it also satisfies the target node pattern.
+    fn parse_braced_quantifier(&mut self) -> Result<PatternQuantifier> {
+        let min = self.parse_quantifier_bound()?;
+        let quantifier = if self.try_char(',') {
+            match (min, self.parse_quantifier_bound()?) {
+                (Some(min), Some(max)) if min > max => {
+                    return Err(Error::ParseError(format!(
+                        "Invalid quantifier {{{},{}}}: minimum exceeds maximum",
+                        min, max
+                    )))
                 }
-            } else if self.try_char(',') {
-                self.skip_whitespace();
-                let max = self.parse_plain_integer()? as u64;
-                PatternQuantifier::AtMost(max)
-            } else {
-                return Err(Error::ParseError("Invalid quantifier".to_string()));
-            };
-
-            self.skip_whitespace();
-            self.expect_char('}')?;
-            Ok(Some(result))
+                (Some(min), Some(max)) => PatternQuantifier::Range(min, max),
+                (Some(min), None) => PatternQuantifier::AtLeast(min),
+                (None, Some(max)) => PatternQuantifier::AtMost(max),
+                (None, None) => {
+                    return Err(Error::ParseError(
+                        "Invalid quantifier {,}: expected at least one bound".to_string(),
+                    ))
+                }
+            }
         } else {
-            Ok(None)
+            match min {
+                Some(n) => PatternQuantifier::Exactly(n),
+                None => return Err(Error::ParseError("Invalid quantifier".to_string())),
+            }
+        };
+
+        self.skip_whitespace();
+        self.expect_char('}')?;
+        Ok(quantifier)
+    }
+
+    /// Parse an optional quantifier bound, rejecting signs and fractions
+    fn parse_quantifier_bound(&mut self) -> Result<Option<u64>> {
+        self.skip_whitespace();
+        if self.peek_char_is('-') || self.peek_char_is('+') {
+            return Err(Error::ParseError(
+                "Quantifier bounds must be non-negative integers".to_string(),
+            ));
+        }
+        if !self.peek_char_is_digit() {
+            return Ok(None);
         }
+        let bound = self.parse_plain_integer()? as u64;
+        self.skip_whitespace();
+        if self.peek_char_is('.') {
+            return Err(Error::ParseError(
+                "Quantifier bounds must be non-negative integers".to_string(),
+            ));
+        }
+        Ok(Some(bound))
     }
 
     /// Parse properties list
-    fn parse_properties(&mut self) -> Result<Vec<(String, PropertyValue)>> {
+    /// Parse a property map body. Literal values go to the first list; in
+    /// INSERT patterns non-literal values are parsed as expressions into the
+    /// second.
+    #[allow(clippy::type_complexity)]
+    fn parse_properties(
+        &mut self,
+    ) -> Result<(Vec<(String, PropertyValue)>, Vec<(String, Expression)>)> {
         let mut props = Vec::new();
+        let mut exprs = Vec::new();
 
         loop {
             self.skip_whitespace();
@@ -816,9 +947,15 @@ impl GqlParser {
             self.skip_whitespace();
             self.expect_char(':')?;
             self.skip_whitespace();
-            let value = self.parse_property_value()?;
-
-            props.push((key, value));
+            let start = self.pos;
+            match self.parse_property_value() {
+                Ok(value) => props.push((key, value)),
+                Err(_) if self.property_exprs_allowed => {
+                    self.pos = start;
+                    exprs.push((key, self.parse_expression()?));
+                }
+                Err(e) => return Err(e),
+            }
 
             self.skip_whitespace();
             if !self.try_char(',') {
@@ -826,7 +963,7 @@ impl GqlParser {
             }
         }
 
-        Ok(props)
+        Ok((props, exprs))
     }
 
     /// Parse property value
@@ -839,13 +976,19 @@ impl GqlParser {
             // 解析器仅负责将字面量解析为字符串，具体类型转换由执行器
             //（结合当前图的 schema）在插入时进行。
             Ok(PropertyValue::String(s))
-        } else if self.peek_char_is_digit() || self.peek_char_is('-') {
-            let num = self.parse_number()?;
-            if num.contains('.') {
-                Ok(PropertyValue::Float(num.parse().unwrap_or(0.0)))
-            } else {
-                Ok(PropertyValue::Integer(num.parse().unwrap_or(0)))
+        } else if self.peek_char_is('-') {
+            // A property value is a single literal, so a leading '-' is always
+            // the sign of a number; allow whitespace after it like `- 100`.
+            self.pos += 1;
+            self.skip_whitespace();
+            if !(self.peek_char_is_digit() || self.peek_char_is('.')) {
+                return Err(Error::ParseError("Expected number after '-'".to_string()));
             }
+            let num = self.parse_number()?;
+            Self::numeric_literal_value(&format!("-{}", num))
+        } else if self.peek_char_is_digit() {
+            let num = self.parse_number()?;
+            Self::numeric_literal_value(&num)
         } else if self.try_keyword("true") {
             Ok(PropertyValue::Boolean(true))
         } else if self.try_keyword("false") {
@@ -1065,7 +1208,7 @@ impl GqlParser {
                 }
             }
             self.expect_char(']')?;
-            return Ok(Expression::List(items));
+            return self.parse_postfix(Expression::List(items));
         }
 
         // Map expression
@@ -1105,15 +1248,7 @@ impl GqlParser {
         // Number literal
         if self.peek_char_is_digit() || (self.peek_char_is('-') && self.peek_next_char_is_digit()) {
             let num = self.parse_number()?;
-            if num.contains('.') {
-                return Ok(Expression::Literal(PropertyValue::Float(
-                    num.parse().unwrap_or(0.0),
-                )));
-            } else {
-                return Ok(Expression::Literal(PropertyValue::Integer(
-                    num.parse().unwrap_or(0),
-                )));
-            }
+            return Ok(Expression::Literal(Self::numeric_literal_value(&num)?));
         }
 
         // Boolean literals and NULL
@@ -1159,13 +1294,33 @@ impl GqlParser {
                 }
             }
             self.expect_char(')')?;
-            Ok(Expression::FunctionCall(ident, args))
+            self.parse_postfix(Expression::FunctionCall(ident, args))
         } else if self.try_char('.') {
             // Property access
             let prop = self.parse_identifier()?;
-            Ok(Expression::Property(ident, prop))
+            self.parse_postfix(Expression::Property(ident, prop))
         } else {
-            Ok(Expression::Variable(ident))
+            self.parse_postfix(Expression::Variable(ident))
+        }
+    }
+
+    /// Subscripts and property accesses following a primary expression,
+    /// e.g. `p[0].address` or `nodes(p)[-1]`
+    fn parse_postfix(&mut self, mut expr: Expression) -> Result<Expression> {
+        loop {
+            if self.try_char('[') {
+                let index = self.parse_expression()?;
+                self.skip_whitespace();
+                self.expect_char(']')?;
+                expr = Expression::Index(Box::new(expr), Box::new(index));
+            } else if matches!(expr, Expression::Index(..) | Expression::FunctionCall(..))
+                && self.try_char('.')
+            {
+                let prop = self.parse_identifier()?;
+                expr = Expression::ElementProperty(Box::new(expr), prop);
+            } else {
+                return Ok(expr);
+            }
         }
     }
 
@@ -1281,9 +1436,54 @@ impl GqlParser {
     // INSERT Statement
     // ========================================================================
 
+    /// UNWIND list AS var INSERT ...
+    fn parse_unwind(&mut self) -> Result<GqlStatement> {
+        self.expect_keyword("UNWIND")?;
+        let expression = self.parse_expression()?;
+        self.expect_keyword("AS")?;
+        self.skip_whitespace();
+        let variable = self.parse_identifier()?;
+        self.skip_whitespace();
+
+        if !self.peek_keyword()?.eq_ignore_ascii_case("INSERT") {
+            return Err(Error::ParseError(
+                "UNWIND must be followed by INSERT".to_string(),
+            ));
+        }
+        let GqlStatement::Insert(mut insert) = self.parse_insert()? else {
+            unreachable!("parse_insert returns an INSERT statement");
+        };
+        insert.unwind = Some(UnwindClause {
+            expression,
+            variable,
+        });
+        Ok(GqlStatement::Insert(insert))
+    }
+
     fn parse_insert(&mut self) -> Result<GqlStatement> {
         self.expect_keyword("INSERT")?;
 
+        self.property_exprs_allowed = true;
+        let patterns = self.parse_insert_patterns();
+        self.property_exprs_allowed = false;
+        let (nodes, edges) = patterns?;
+
+        let return_clause = if self.try_keyword("RETURN") {
+            self.parse_return_items()?
+        } else {
+            Vec::new()
+        };
+
+        Ok(GqlStatement::Insert(InsertStatement {
+            nodes,
+            edges,
+            return_clause,
+            unwind: None,
+        }))
+    }
+
+    /// Parse the comma-separated node and edge patterns of an INSERT
+    fn parse_insert_patterns(&mut self) -> Result<(Vec<NodePattern>, Vec<InsertEdge>)> {
         let mut nodes = Vec::new();
         let mut edges = Vec::new();
         let mut node_vars: std::collections::HashMap<String, usize> =
@@ -1296,7 +1496,7 @@ impl GqlParser {
             }
 
             let first_node = self.parse_node_pattern()?;
-            let first_var = first_node.variable.clone();
+            let source = Self::record_insert_node(&mut nodes, &mut node_vars, first_node);
 
             self.skip_whitespace();
 
@@ -1306,41 +1506,13 @@ impl GqlParser {
                 self.skip_whitespace();
 
                 let second_node = self.parse_node_pattern()?;
-                let second_var = second_node.variable.clone();
-
-                // Record nodes
-                if let Some(ref var) = first_var {
-                    if !node_vars.contains_key(var) {
-                        node_vars.insert(var.clone(), nodes.len());
-                        nodes.push(first_node.clone());
-                    }
-                } else {
-                    nodes.push(first_node.clone());
-                }
-
-                if let Some(ref var) = second_var {
-                    if !node_vars.contains_key(var) {
-                        node_vars.insert(var.clone(), nodes.len());
-                        nodes.push(second_node.clone());
-                    }
-                } else {
-                    nodes.push(second_node.clone());
-                }
+                let target = Self::record_insert_node(&mut nodes, &mut node_vars, second_node);
 
                 edges.push(InsertEdge {
-                    source: first_var.unwrap_or_else(|| format!("_anon_{}", nodes.len() - 2)),
+                    source,
                     edge: edge_pattern,
-                    target: second_var.unwrap_or_else(|| format!("_anon_{}", nodes.len() - 1)),
+                    target,
                 });
-            } else {
-                if let Some(ref var) = first_var {
-                    if !node_vars.contains_key(var) {
-                        node_vars.insert(var.clone(), nodes.len());
-                        nodes.push(first_node);
-                    }
-                } else {
-                    nodes.push(first_node);
-                }
             }
 
             self.skip_whitespace();
@@ -1349,7 +1521,30 @@ impl GqlParser {
             }
         }
 
-        Ok(GqlStatement::Insert(InsertStatement { nodes, edges }))
+        Ok((nodes, edges))
+    }
+
+    /// Record an INSERT node and return the name edges use to refer to it.
+    /// Named nodes are stored once per variable; anonymous nodes are always
+    /// new and are named `_anon_<index>` after their position in `nodes`.
+    fn record_insert_node(
+        nodes: &mut Vec<NodePattern>,
+        node_vars: &mut std::collections::HashMap<String, usize>,
+        node: NodePattern,
+    ) -> String {
+        match node.variable.clone() {
+            Some(var) => {
+                if !node_vars.contains_key(&var) {
+                    node_vars.insert(var.clone(), nodes.len());
+                    nodes.push(node);
+                }
+                var
+            }
+            None => {
+                nodes.push(node);
+                format!("_anon_{}", nodes.len() - 1)
+            }
+        }
     }
 
     // ========================================================================
@@ -1357,11 +1552,15 @@ impl GqlParser {
     // ========================================================================
 
     fn parse_delete(&mut self) -> Result<GqlStatement> {
+        Ok(GqlStatement::Delete(self.parse_delete_items()?))
+    }
+
+    fn parse_delete_items(&mut self) -> Result<DeleteStatement> {
         let detach = if self.try_keyword("DETACH") {
             true
-        } else if self.try_keyword("NODETACH") {
-            false
         } else {
+            // NODETACH is the default behaviour
+            self.try_keyword("NODETACH");
             false
         };
 
@@ -1377,7 +1576,11 @@ impl GqlParser {
             }
         }
 
-        Ok(GqlStatement::Delete(DeleteStatement { variables, detach }))
+        Ok(DeleteStatement {
+            match_clause: None,
+            variables,
+            detach,
+        })
     }
 
     // ========================================================================
@@ -1385,6 +1588,10 @@ impl GqlParser {
     // ========================================================================
 
     fn parse_set(&mut self) -> Result<GqlStatement> {
+        Ok(GqlStatement::Set(self.parse_set_items()?))
+    }
+
+    fn parse_set_items(&mut self) -> Result<SetStatement> {
         self.expect_keyword("SET")?;
 
         let mut items = Vec::new();
@@ -1438,7 +1645,10 @@ impl GqlParser {
             }
         }
 
-        Ok(GqlStatement::Set(SetStatement { items }))
+        Ok(SetStatement {
+            match_clause: None,
+            items,
+        })
     }
 
     fn parse_property_key_value_pairs(&mut self) -> Result<Vec<(String, Expression)>> {
@@ -1469,6 +1679,10 @@ impl GqlParser {
     // ========================================================================
 
     fn parse_remove(&mut self) -> Result<GqlStatement> {
+        Ok(GqlStatement::Remove(self.parse_remove_items()?))
+    }
+
+    fn parse_remove_items(&mut self) -> Result<RemoveStatement> {
         self.expect_keyword("REMOVE")?;
 
         let mut items = Vec::new();
@@ -1497,7 +1711,10 @@ impl GqlParser {
             }
         }
 
-        Ok(GqlStatement::Remove(RemoveStatement { items }))
+        Ok(RemoveStatement {
+            match_clause: None,
+            items,
+        })
     }
 
     // ========================================================================
@@ -1664,11 +1881,9 @@ impl GqlParser {
                         self.expect_char(')')?;
                         self.expect_char('-')?;
                         self.expect_char('[')?;
-                        if self.peek_char() != Some(']') {
-                            if self.try_char('{') {
-                                let _ = self.parse_property_list()?;
-                                self.expect_char('}')?;
-                            }
+                        if self.peek_char() != Some(']') && self.try_char('{') {
+                            let _ = self.parse_property_list()?;
+                            self.expect_char('}')?;
                         }
                         self.expect_char(']')?;
                         self.expect_char('-')?;
@@ -1881,10 +2096,7 @@ impl GqlParser {
             ShowType::Procedures
         } else if self.try_keyword("LABELS") {
             ShowType::Labels
-        } else if self.try_keyword("EDGE") {
-            self.expect_keyword("TYPES")?;
-            ShowType::EdgeTypes
-        } else if self.try_keyword("RELATIONSHIP") {
+        } else if self.try_keyword("EDGE") || self.try_keyword("RELATIONSHIP") {
             self.expect_keyword("TYPES")?;
             ShowType::EdgeTypes
         } else if self.try_keyword("PROPERTY") {
@@ -1894,6 +2106,8 @@ impl GqlParser {
             ShowType::Indexes
         } else if self.try_keyword("CONSTRAINTS") {
             ShowType::Constraints
+        } else if self.try_keyword("TRANSACTIONS") {
+            ShowType::Transactions
         } else {
             return Err(Error::ParseError("Unknown SHOW target".to_string()));
         };
@@ -2162,6 +2376,12 @@ impl GqlParser {
                         '\\' => result.push('\\'),
                         '\'' => result.push('\''),
                         '"' => result.push('"'),
+                        '0' => result.push('\0'),
+                        'x' => {
+                            let code = self.parse_hex_escape(2)?;
+                            result.push(char::from(code as u8));
+                        }
+                        'u' => result.push(self.parse_unicode_escape()?),
                         _ => result.push(escaped),
                     }
                 }
@@ -2174,6 +2394,44 @@ impl GqlParser {
         Err(Error::ParseError("Unclosed string".to_string()))
     }
 
+    /// Read exactly `digits` hex digits of an escape sequence
+    fn parse_hex_escape(&mut self, digits: usize) -> Result<u32> {
+        let hex = self
+            .input
+            .get(self.pos..self.pos + digits)
+            .filter(|h| h.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| {
+                Error::ParseError(format!("Escape sequence expects {} hex digits", digits))
+            })?;
+        self.pos += digits;
+        Ok(u32::from_str_radix(hex, 16).expect("validated hex digits"))
+    }
+
+    /// Decode the code point of a `\uXXXX` escape (after the `u`), joining
+    /// a UTF-16 surrogate pair written as two consecutive escapes
+    fn parse_unicode_escape(&mut self) -> Result<char> {
+        let high = self.parse_hex_escape(4)?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.input[self.pos..].starts_with("\\u") {
+                return Err(Error::ParseError(
+                    "Unpaired surrogate in \\u escape".to_string(),
+                ));
+            }
+            self.pos += 2;
+            let low = self.parse_hex_escape(4)?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(Error::ParseError(
+                    "Unpaired surrogate in \\u escape".to_string(),
+                ));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code)
+            .ok_or_else(|| Error::ParseError(format!("Invalid unicode escape \\u{:04X}", code)))
+    }
+
     fn parse_number(&mut self) -> Result<String> {
         self.skip_whitespace();
         let start = self.pos;
@@ -2182,11 +2440,26 @@ impl GqlParser {
             self.pos += 1;
         }
 
-        while let Some(c) = self.peek_char() {
-            if c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' {
-                self.pos += 1;
-            } else {
-                break;
+        if self.peek_str("0x") || self.peek_str("0X") {
+            self.pos += 2;
+            while let Some(c) = self.peek_char() {
+                if c.is_ascii_hexdigit() || c == '_' {
+                    self.pos += 1;
+                } else {
+                    break;
+                }
+            }
+        } else {
+            let mut prev = None;
+            while let Some(c) = self.peek_char() {
+                let exponent_sign =
+                    (c == '+' || c == '-') && matches!(prev, Some('e') | Some('E'));
+                if c.is_ascii_digit() || c == '_' || c == '.' || c == 'e' || c == 'E' || exponent_sign {
+                    self.pos += 1;
+                    prev = Some(c);
+                } else {
+                    break;
+                }
             }
         }
 
@@ -2197,13 +2470,70 @@ impl GqlParser {
         }
     }
 
+    /// Convert numeric literal text from `parse_number` into a value.
+    ///
+    /// Decimal literals may use `_` digit separators (`1_000_000`) and
+    /// become Integer, or Float when they contain a fraction or exponent.
+    /// Hex literals (`0xff`) become Integer when they fit in i64. Hex
+    /// literals of exactly 40 or 64 digits are addresses or hashes; they
+    /// become the same String as the quoted form, so schema-based
+    /// conversion still applies. Any other wider hex value up to 256 bits
+    /// becomes a TokenAmount.
+    fn numeric_literal_value(text: &str) -> Result<PropertyValue> {
+        let invalid = || Error::ParseError(format!("Invalid numeric literal: {}", text));
+        let (negative, body) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        if body.ends_with('_') || body.contains("__") || body.contains("_.") || body.contains("._") {
+            return Err(invalid());
+        }
+
+        if let Some(hex) = body.strip_prefix("0x").or_else(|| body.strip_prefix("0X")) {
+            let digits = hex.replace('_', "");
+            if digits.is_empty() || hex.starts_with('_') {
+                return Err(invalid());
+            }
+            // Addresses and hashes are identified by width alone, so leading
+            // zeros (e.g. the zero address) do not turn them into integers
+            if !negative && matches!(digits.len(), 40 | 64) {
+                return Ok(PropertyValue::String(format!("0x{}", digits)));
+            }
+            // Parse wider than i64 so `-0x8000000000000000` reaches i64::MIN
+            if let Ok(value) = i128::from_str_radix(&digits, 16) {
+                let value = if negative { -value } else { value };
+                if let Ok(value) = i64::try_from(value) {
+                    return Ok(PropertyValue::Integer(value));
+                }
+            }
+            if negative {
+                return Err(invalid());
+            }
+            return match digits.len() {
+                n if n <= 64 => TokenAmount::from_str_radix(&digits, 16)
+                    .map(PropertyValue::TokenAmount)
+                    .map_err(|_| invalid()),
+                _ => Err(invalid()),
+            };
+        }
+
+        let digits = text.replace('_', "");
+        if digits.contains(['.', 'e', 'E']) {
+            digits.parse().map(PropertyValue::Float).map_err(|_| invalid())
+        } else {
+            digits.parse().map(PropertyValue::Integer).map_err(|_| invalid())
+        }
+    }
+
     fn parse_integer(&mut self) -> Result<i64> {
         let num = self.parse_number()?;
-        num.parse()
-            .map_err(|_| Error::ParseError("Invalid integer".to_string()))
+        match Self::numeric_literal_value(&num) {
+            Ok(PropertyValue::Integer(value)) => Ok(value),
+            _ => Err(Error::ParseError("Invalid integer".to_string())),
+        }
     }
 
-    fn parse_vertex_label(s: &str) -> Option<VertexLabel> {
+    pub(crate) fn parse_vertex_label(s: &str) -> Option<VertexLabel> {
         match s.to_uppercase().as_str() {
             "ACCOUNT" => Some(VertexLabel::Account),
             "CONTRACT" => Some(VertexLabel::Contract),
@@ -2482,6 +2812,17 @@ impl GqlParser {
         Ok(GqlStatement::Session(stmt))
     }
 
+    /// Parse EXPLAIN [ANALYZE] <statement>
+    fn parse_explain(&mut self) -> Result<GqlStatement> {
+        self.expect_keyword("EXPLAIN")?;
+        let analyze = self.try_keyword("ANALYZE");
+        let statement = self.parse()?;
+        Ok(GqlStatement::Explain(ExplainStatement {
+            analyze,
+            statement: Box::new(statement),
+        }))
+    }
+
     /// Parse START TRANSACTION
     fn parse_transaction_start(&mut self) -> Result<GqlStatement> {
         self.expect_keyword("START")?;
@@ -2530,6 +2871,25 @@ pub fn parse(query: &str) -> Result<GqlStatement> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_statements() {
+        let script = "INSERT (a:Account {address: '0x;a'});\n MATCH (n:Account) RETURN n;";
+        let statements = GqlParser::new(script).parse_statements().unwrap();
+        assert_eq!(statements.len(), 2);
+        assert!(matches!(statements[0], GqlStatement::Insert(_)));
+        assert!(matches!(statements[1], GqlStatement::Match(_)));
+
+        let single = GqlParser::new("MATCH (n) RETURN n")
+            .parse_statements()
+            .unwrap();
+        assert_eq!(single.len(), 1);
+
+        assert!(GqlParser::new(" ;; ").parse_statements().is_err());
+        assert!(GqlParser::new("MATCH (n) RETURN n )")
+            .parse_statements()
+            .is_err());
+    }
+
     #[test]
     fn test_parse_simple_match() {
         let query = "MATCH (n:Account) RETURN n";
@@ -2588,6 +2948,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_quantifier_range_validation() {
+        let edge_quantifier = |query: &str| match parse(query).unwrap() {
+            GqlStatement::Match(m) => match &m.graph_pattern.paths[0].elements[1] {
+                PathElement::Edge(e) => e.quantifier.clone(),
+                _ => panic!("Expected edge pattern"),
+            },
+            _ => panic!("Expected Match statement"),
+        };
+
+        assert_eq!(
+            edge_quantifier("MATCH (a)-[:Transfer]->{0,0}(b) RETURN a, b"),
+            Some(PatternQuantifier::Range(0, 0))
+        );
+        assert_eq!(
+            edge_quantifier("MATCH (a)-[:Transfer]->{ 3 , 3 }(b) RETURN a, b"),
+            Some(PatternQuantifier::Range(3, 3))
+        );
+
+        let err = parse("MATCH (a)-[:Transfer]->{5,2}(b) RETURN a, b").unwrap_err();
+        assert!(err.to_string().contains("minimum exceeds maximum"), "{}", err);
+
+        for invalid in ["{-1,2}", "{1,-2}", "{1.5}", "{1,2.5}", "{,}", "{}"] {
+            let query = format!("MATCH (a)-[:Transfer]->{}(b) RETURN a, b", invalid);
+            assert!(parse(&query).is_err(), "{}", invalid);
+        }
+        // The legacy quantifier syntax shares the same validation
+        assert!(parse("MATCH (a)-[:Transfer]->(b){3,1} RETURN a, b").is_err());
+    }
+
     #[test]
     fn test_parse_gql_quantifier() {
         // ISO GQL 39075 quantifier syntax: ->{min,max}
@@ -2693,6 +3083,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_insert_return() {
+        let query = "INSERT (n:Account {address: '0x123'}) RETURN n, n.address AS addr";
+        let GqlStatement::Insert(insert) = parse(query).unwrap() else {
+            panic!("Expected Insert statement");
+        };
+        assert_eq!(insert.nodes.len(), 1);
+        assert_eq!(insert.return_clause.len(), 2);
+        assert_eq!(insert.return_clause[1].alias.as_deref(), Some("addr"));
+
+        let GqlStatement::Insert(insert) = parse("INSERT (n:Account)").unwrap() else {
+            panic!("Expected Insert statement");
+        };
+        assert!(insert.return_clause.is_empty());
+    }
+
+    #[test]
+    fn test_parse_unwind_insert() {
+        let query = "UNWIND $rows AS r INSERT (a:Account {address: r.from})-[:Transfer {amount: r.amount, note: 'bulk'}]->(b:Account {address: r.to})";
+        let GqlStatement::Insert(insert) = parse(query).unwrap() else {
+            panic!("Expected Insert statement");
+        };
+        let unwind = insert.unwind.expect("unwind clause");
+        assert_eq!(unwind.variable, "r");
+        assert!(matches!(unwind.expression, Expression::Parameter(ref p) if p == "rows"));
+        assert_eq!(insert.nodes[0].property_exprs.len(), 1);
+        assert!(insert.nodes[0].properties.is_empty());
+        // literal values stay literal, expressions are kept for the executor
+        assert_eq!(insert.edges[0].edge.properties.len(), 1);
+        assert_eq!(insert.edges[0].edge.property_exprs[0].0, "amount");
+
+        assert!(parse("UNWIND [1, 2] AS x MATCH (n) RETURN n").is_err());
+        assert!(parse("MATCH (n:Account {address: r.from}) RETURN n").is_err());
+    }
+
+    #[test]
+    fn test_parse_insert_anonymous_nodes() {
+        let query = "INSERT (b:Account), (:Account)-[:Transfer]->(b), (b)-[:Call]->(:Contract)";
+        let GqlStatement::Insert(insert) = parse(query).unwrap() else {
+            panic!("Expected Insert statement");
+        };
+
+        // b is stored once; each anonymous node gets its own slot
+        assert_eq!(insert.nodes.len(), 3);
+        let ends: Vec<(&str, &str)> = insert
+            .edges
+            .iter()
+            .map(|e| (e.source.as_str(), e.target.as_str()))
+            .collect();
+        assert_eq!(ends, vec![("_anon_1", "b"), ("b", "_anon_2")]);
+    }
+
     #[test]
     fn test_parse_set() {
         let query = "SET n.balance = 1000";
@@ -2852,6 +3294,159 @@ mod tests {
         }
     }
 
+    fn node_property(query: &str, key: &str) -> PropertyValue {
+        match parse(query).unwrap() {
+            GqlStatement::Match(m) => match &m.graph_pattern.paths[0].elements[0] {
+                PathElement::Node(n) => n
+                    .properties
+                    .iter()
+                    .find(|(k, _)| k == key)
+                    .map(|(_, v)| v.clone())
+                    .expect("property not found"),
+                _ => panic!("Expected node pattern"),
+            },
+            _ => panic!("Expected Match statement"),
+        }
+    }
+
+    #[test]
+    fn test_parse_string_unicode_escapes() {
+        let tag = node_property(r"MATCH (n:Account {tag: 'caf\u00e9 \x41\0'}) RETURN n", "tag");
+        assert_eq!(tag, PropertyValue::String("café A\0".to_string()));
+
+        // Surrogate pair escapes combine into a single code point
+        let tag = node_property(r#"MATCH (n:Account {tag: "\uD83D\uDE80"}) RETURN n"#, "tag");
+        assert_eq!(tag, PropertyValue::String("🚀".to_string()));
+
+        // Literal multi-byte UTF-8 passes through unchanged
+        let tag = node_property("MATCH (n:Account {tag: '交易所 🚀'}) RETURN n", "tag");
+        assert_eq!(tag, PropertyValue::String("交易所 🚀".to_string()));
+
+        assert!(parse(r"MATCH (n:Account {tag: '\u12'}) RETURN n").is_err());
+        assert!(parse(r"MATCH (n:Account {tag: '\uD83D'}) RETURN n").is_err());
+    }
+
+    #[test]
+    fn test_parse_negative_property_values() {
+        let value = |literal: &str| {
+            node_property(
+                &format!("MATCH (n:Account {{v: {}}}) RETURN n", literal),
+                "v",
+            )
+        };
+
+        assert_eq!(value("-100"), PropertyValue::Integer(-100));
+        assert_eq!(value("- 100"), PropertyValue::Integer(-100));
+        assert_eq!(
+            value("-9223372036854775808"),
+            PropertyValue::Integer(i64::MIN)
+        );
+        assert_eq!(
+            value("-0x8000000000000000"),
+            PropertyValue::Integer(i64::MIN)
+        );
+        assert_eq!(value("-1.5"), PropertyValue::Float(-1.5));
+        assert_eq!(value("-.5"), PropertyValue::Float(-0.5));
+        assert_eq!(value("-1e-3"), PropertyValue::Float(-0.001));
+
+        // Negative values next to other properties, in INSERT node and edge maps
+        let query =
+            "INSERT (a:Account {a: 1, balance: -100, b: -0.25})-[:Transfer {fee: -5}]->(c:Account)";
+        match parse(query).unwrap() {
+            GqlStatement::Insert(i) => {
+                assert_eq!(
+                    i.nodes[0].properties,
+                    vec![
+                        ("a".to_string(), PropertyValue::Integer(1)),
+                        ("balance".to_string(), PropertyValue::Integer(-100)),
+                        ("b".to_string(), PropertyValue::Float(-0.25)),
+                    ]
+                );
+                assert_eq!(
+                    i.edges[0].edge.properties,
+                    vec![("fee".to_string(), PropertyValue::Integer(-5))]
+                );
+            }
+            _ => panic!("Expected Insert statement"),
+        }
+
+        for invalid in ["-", "- x", "-'1'", "--1", "-0x8000000000000001"] {
+            let query = format!("MATCH (n:Account {{v: {}}}) RETURN n", invalid);
+            assert!(parse(&query).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_parse_hex_and_separated_numbers() {
+        let value = |literal: &str| {
+            node_property(&format!("MATCH (n:Account {{v: {}}}) RETURN n", literal), "v")
+        };
+
+        assert_eq!(value("0xFF"), PropertyValue::Integer(255));
+        assert_eq!(value("-0x10"), PropertyValue::Integer(-16));
+        assert_eq!(value("1_000"), PropertyValue::Integer(1000));
+        assert_eq!(value("1_000.5"), PropertyValue::Float(1000.5));
+        assert_eq!(value("1.5e3"), PropertyValue::Float(1500.0));
+        assert_eq!(value("2E-3"), PropertyValue::Float(0.002));
+        assert_eq!(value("1e5"), PropertyValue::Float(100000.0));
+
+        // 40-digit hex is an address literal, same as the quoted form
+        let address = "0x742d35Cc6634C0532925a3b844Bc9e7595f3fBb0";
+        assert_eq!(value(address), PropertyValue::String(address.to_string()));
+        // Leading zeros do not make an address or hash an integer
+        let zero = format!("0x{}", "0".repeat(40));
+        assert_eq!(value(&zero), PropertyValue::String(zero.clone()));
+        let small = format!("0x{}aa", "0".repeat(38));
+        assert_eq!(value(&small), PropertyValue::String(small.clone()));
+        let hash = format!("0x{}1", "0".repeat(63));
+        assert_eq!(value(&hash), PropertyValue::String(hash.clone()));
+        // Wider hex values become token amounts
+        assert_eq!(
+            value("0xde0b6b3a7640000_0000"),
+            PropertyValue::TokenAmount(TokenAmount::from_dec_str("0xde0b6b3a76400000000").unwrap())
+        );
+
+        // Expressions share the same literal rules
+        match parse("MATCH (n) WHERE n.balance > 0x1_00 RETURN n").unwrap() {
+            GqlStatement::Match(m) => match m.where_clause {
+                Some(Expression::BinaryOp(_, _, rhs)) => {
+                    assert!(matches!(*rhs, Expression::Literal(PropertyValue::Integer(256))))
+                }
+                other => panic!("unexpected WHERE clause: {:?}", other),
+            },
+            _ => panic!("Expected Match statement"),
+        }
+
+        for invalid in ["1__000", "1_", "0x", "0x_1", "1_.5"] {
+            let query = format!("MATCH (n:Account {{v: {}}}) RETURN n", invalid);
+            assert!(parse(&query).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_parse_exact_label_match() {
+        let stmt = parse("MATCH (n:=Account&Token) RETURN n").unwrap();
+        match stmt {
+            GqlStatement::Match(m) => match &m.graph_pattern.paths[0].elements[0] {
+                PathElement::Node(n) => {
+                    assert!(n.exact_labels);
+                    assert!(matches!(n.label_expr, Some(LabelExpression::Conjunction(_))));
+                }
+                _ => panic!("Expected node pattern"),
+            },
+            _ => panic!("Expected Match statement"),
+        }
+
+        let stmt = parse("MATCH (n:Account) RETURN n").unwrap();
+        match stmt {
+            GqlStatement::Match(m) => match &m.graph_pattern.paths[0].elements[0] {
+                PathElement::Node(n) => assert!(!n.exact_labels),
+                _ => panic!("Expected node pattern"),
+            },
+            _ => panic!("Expected Match statement"),
+        }
+    }
+
     #[test]
     fn test_parse_label_wildcard() {
         // Test label wildcard % (ISO GQL 39075)
@@ -3024,12 +3619,9 @@ mod tests {
                     // Should have quantifier
                     assert_eq!(outer.quantifier, Some(PatternQuantifier::Range(1, 5)));
                     // Inner should contain the path pattern
-                    match &outer.path_pattern {
-                        PathPatternExpression::Term(elements) => {
-                            // Should have inner parenthesized path or elements
-                            assert!(!elements.is_empty());
-                        }
-                        _ => {}
+                    if let PathPatternExpression::Term(elements) = &outer.path_pattern {
+                        // Should have inner parenthesized path or elements
+                        assert!(!elements.is_empty());
                     }
                 } else {
                     panic!("Expected outer ParenthesizedPath");
@@ -3038,4 +3630,92 @@ mod tests {
             _ => panic!("Expected Match statement"),
         }
     }
+
+    #[test]
+    fn test_parse_match_set() {
+        let query = "MATCH p = (a:Account)-[:Transfer]->{1,3}(b:Account) WHERE a.address = '0x1' SET p.flagged = true";
+        let stmt = parse(query).unwrap();
+
+        match stmt {
+            GqlStatement::Set(s) => {
+                let m = s.match_clause.expect("Expected preceding MATCH");
+                assert_eq!(m.graph_pattern.paths[0].variable, Some("p".to_string()));
+                assert!(m.where_clause.is_some());
+                assert!(matches!(&s.items[0], SetItem::Property(var, prop, _) if var == "p" && prop == "flagged"));
+            }
+            _ => panic!("Expected Set statement"),
+        }
+    }
+
+    #[test]
+    fn test_parse_match_remove() {
+        let query = "MATCH (n:Account) WHERE n.address = '0x1' REMOVE n.risk, n:Token";
+        let stmt = parse(query).unwrap();
+
+        match stmt {
+            GqlStatement::Remove(s) => {
+                let m = s.match_clause.expect("Expected preceding MATCH");
+                assert!(m.where_clause.is_some());
+                assert!(
+                    matches!(&s.items[0], RemoveItem::Property(var, prop) if var == "n" && prop == "risk")
+                );
+                assert!(
+                    matches!(&s.items[1], RemoveItem::Label(var, VertexLabel::Token) if var == "n")
+                );
+            }
+            _ => panic!("Expected Remove statement"),
+        }
+    }
+
+    #[test]
+    fn test_parse_match_hints() {
+        let query = "MATCH (n:Account)-[:Transfer]->(m) USING INDEX n:Account(address) USING SCAN m:Account WHERE n.address = '0xa' RETURN m";
+        let stmt = parse(query).unwrap();
+
+        match stmt {
+            GqlStatement::Match(m) => {
+                assert_eq!(
+                    m.hints,
+                    vec![
+                        MatchHint::Index {
+                            variable: "n".to_string(),
+                            label: VertexLabel::Account,
+                            property: "address".to_string(),
+                        },
+                        MatchHint::Scan {
+                            variable: "m".to_string(),
+                            label: VertexLabel::Account,
+                        },
+                    ]
+                );
+                assert!(m.where_clause.is_some());
+                assert_eq!(m.hints[0].to_string(), "USING INDEX n:Account(address)");
+            }
+            _ => panic!("Expected Match statement"),
+        }
+
+        // 缺少索引属性或未知关键字时报错
+        assert!(parse("MATCH (n:Account) USING INDEX n:Account RETURN n").is_err());
+        assert!(parse("MATCH (n:Account) USING TABLE n:Account RETURN n").is_err());
+    }
+
+    #[test]
+    fn test_parse_explain() {
+        match parse("EXPLAIN MATCH (a:Account) RETURN a").unwrap() {
+            GqlStatement::Explain(e) => {
+                assert!(!e.analyze);
+                assert!(matches!(*e.statement, GqlStatement::Match(_)));
+            }
+            _ => panic!("Expected Explain statement"),
+        }
+
+        let query = "explain analyze MATCH (a:Account)-[:Transfer]->(b) RETURN b LIMIT 5";
+        match parse(query).unwrap() {
+            GqlStatement::Explain(e) => {
+                assert!(e.analyze);
+                assert!(matches!(*e.statement, GqlStatement::Match(_)));
+            }
+            _ => panic!("Expected Explain statement"),
+        }
+    }
 }
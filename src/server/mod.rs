@@ -2,27 +2,116 @@
 //!
 //! 提供 REST API 和 GQL 查询接口
 
-use crate::algorithm::{EdmondsKarp, PathFinder, TraceDirection};
+use crate::algorithm::{
+    build_thread_pool, CancellationToken, FlowEdge, MaxFlowAlgorithm, PathFinder, PathResult,
+    TraceDirection, DEFAULT_MAX_FLOW_EDGES, MAX_ALL_PATHS,
+};
 use crate::error::{Error, Result};
-use crate::graph::{EdgeId, GraphCatalog, VertexId};
+use crate::graph::{Edge, EdgeId, Graph, GraphCatalog, Vertex, VertexId};
 use crate::metrics;
-use crate::query::{GqlParser, QueryExecutor};
+use crate::query::{
+    GqlParser, GqlStatement, QueryExecutor, QueryResult, ResultCache, ResultValue,
+    DEFAULT_MAX_BINDINGS, DEFAULT_MAX_EXPANSION_STEPS, DEFAULT_MAX_ROWS,
+    DEFAULT_MAX_UNLABELED_SCAN,
+};
+use crate::storage::BufferPoolWatermark;
+use crate::types::{EdgeLabel, PropertyValue, TokenAmount};
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::{IntoResponse, Json, Response},
-    routing::{get, post},
+    extract::{rejection::JsonRejection, DefaultBodyLimit, Path, Query, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json, Response,
+    },
+    routing::{get, post, put},
     Router,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use tokio::net::TcpListener;
+use tokio_stream::wrappers::ReceiverStream;
 
 /// 服务器配置
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    /// `/query` 接受的最大查询文本长度（字节），超出返回 413
+    pub max_query_length: usize,
+    /// 所有接口的请求体大小上限（字节）
+    pub max_body_size: usize,
+    /// 单个 MATCH 在内存中保留的中间绑定行数上限，超出时查询中止
+    pub max_bindings: usize,
+    /// 无标签节点模式（如 `MATCH (n)`）在没有 LIMIT 时允许全量扫描的最大顶点数
+    pub max_unlabeled_scan: usize,
+    /// 最大流结果中列出的流量边数上限（`/algorithm/max-flow` 与 `CALL max_flow`）
+    pub max_flow_edges: usize,
+    /// 没有 LIMIT 的 MATCH / SELECT 最多返回的行数，超出时截断并标记 `truncated`
+    pub max_rows: usize,
+    /// 单个 MATCH 中所有可变长度边段合计的展开步数上限，超出时查询中止
+    pub max_expansion_steps: usize,
+    /// 算法 CALL 结果缓存的条目数，0 表示不缓存
+    pub result_cache_size: usize,
+    /// JSON 响应字段的默认命名风格，请求可通过 `Accept` 参数覆盖
+    pub json_field_case: JsonFieldCase,
+    /// 并行算法使用的线程数，未设置时使用 rayon 全局线程池（占满所有核心）
+    pub threads: Option<usize>,
+    /// `/admin/*` 接口要求的 API key（请求头 `X-API-Key`），未设置时管理接口禁用
+    pub admin_api_key: Option<String>,
+    /// 错误响应是否包含完整信息；关闭时只返回通用提示与错误码，详细信息写入服务端日志
+    pub verbose_errors: bool,
+}
+
+/// JSON 响应字段命名风格
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JsonFieldCase {
+    /// snake_case（默认）
+    #[default]
+    Snake,
+    /// camelCase
+    Camel,
+}
+
+impl JsonFieldCase {
+    /// 名称（与 `FromStr` 对应）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JsonFieldCase::Snake => "snake",
+            JsonFieldCase::Camel => "camel",
+        }
+    }
+
+    /// 从 `Accept: application/json; case=camel` 形式的请求头读取命名风格
+    fn from_accept(accept: &str) -> Option<Self> {
+        accept
+            .split(',')
+            .flat_map(|media| media.split(';').skip(1))
+            .find_map(|param| {
+                let (name, value) = param.split_once('=')?;
+                if name.trim().eq_ignore_ascii_case("case") {
+                    value.trim().trim_matches('"').parse().ok()
+                } else {
+                    None
+                }
+            })
+    }
+}
+
+impl std::str::FromStr for JsonFieldCase {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "snake" | "snake_case" => Ok(JsonFieldCase::Snake),
+            "camel" | "camelcase" => Ok(JsonFieldCase::Camel),
+            other => Err(Error::ParseError(format!(
+                "未知的字段命名风格 '{}'，可选: snake, camel",
+                other
+            ))),
+        }
+    }
 }
 
 impl Default for ServerConfig {
@@ -30,6 +119,18 @@ impl Default for ServerConfig {
         Self {
             host: "127.0.0.1".to_string(),
             port: 8080,
+            max_query_length: 1024 * 1024,
+            max_body_size: 4 * 1024 * 1024,
+            max_bindings: DEFAULT_MAX_BINDINGS,
+            max_unlabeled_scan: DEFAULT_MAX_UNLABELED_SCAN,
+            max_flow_edges: DEFAULT_MAX_FLOW_EDGES,
+            max_rows: DEFAULT_MAX_ROWS,
+            max_expansion_steps: DEFAULT_MAX_EXPANSION_STEPS,
+            result_cache_size: 0,
+            json_field_case: JsonFieldCase::default(),
+            threads: None,
+            admin_api_key: None,
+            verbose_errors: true,
         }
     }
 }
@@ -38,11 +139,36 @@ impl Default for ServerConfig {
 #[derive(Clone)]
 pub struct AppState {
     pub catalog: Arc<GraphCatalog>,
+    pub max_query_length: usize,
+    pub max_bindings: usize,
+    pub max_unlabeled_scan: usize,
+    pub max_flow_edges: usize,
+    pub max_rows: usize,
+    pub max_expansion_steps: usize,
+    /// 算法 CALL 结果缓存，未启用时为 `None`
+    pub result_cache: Option<Arc<ResultCache>>,
+    /// 并行算法使用的线程池，`None` 时使用 rayon 全局线程池
+    pub thread_pool: Option<Arc<rayon::ThreadPool>>,
+    pub admin_api_key: Option<String>,
+    pub verbose_errors: bool,
 }
 
 /// 启动服务器
 pub async fn start_server(config: ServerConfig, catalog: Arc<GraphCatalog>) -> Result<()> {
-    let state = AppState { catalog };
+    let state = AppState {
+        catalog,
+        max_query_length: config.max_query_length,
+        max_bindings: config.max_bindings,
+        max_unlabeled_scan: config.max_unlabeled_scan,
+        max_flow_edges: config.max_flow_edges,
+        max_rows: config.max_rows,
+        max_expansion_steps: config.max_expansion_steps,
+        result_cache: (config.result_cache_size > 0)
+            .then(|| Arc::new(ResultCache::new(config.result_cache_size))),
+        thread_pool: config.threads.map(build_thread_pool).transpose()?,
+        admin_api_key: config.admin_api_key.clone(),
+        verbose_errors: config.verbose_errors,
+    };
 
     let app = Router::new()
         // 健康检查
@@ -50,20 +176,42 @@ pub async fn start_server(config: ServerConfig, catalog: Arc<GraphCatalog>) -> R
         // 指标和统计
         .route("/metrics", get(metrics_handler))
         .route("/stats", get(stats_handler))
+        .route("/transactions/active", get(active_transactions))
+        // 运维管理（需要 API key）
+        .route("/admin/checkpoint", post(admin_checkpoint))
+        .route("/admin/storage", get(admin_storage))
         // GQL 查询
         .route("/query", post(execute_query))
+        .route("/query/batch", post(query_batch))
+        .route("/delete-by-query", post(delete_by_query))
         // 顶点操作
+        .route("/vertices/batch", post(batch_get_vertices))
         .route("/vertices/:id", get(get_vertex))
         .route("/vertices/address/:address", get(get_vertex_by_address))
+        .route(
+            "/vertices/address/:address/tag",
+            put(set_address_tag).delete(remove_address_tag),
+        )
         // 边操作
+        .route("/edges/between", get(get_edges_between))
         .route("/edges/:id", get(get_edge))
         .route("/vertices/:id/outgoing", get(get_outgoing_edges))
         .route("/vertices/:id/incoming", get(get_incoming_edges))
+        .route("/vertices/:id/neighborhood", get(get_neighborhood))
+        // 分析
+        .route("/analytics/volume", get(transfer_volume))
+        .route("/analytics/timeseries", get(transfer_timeseries))
         // 图算法
         .route("/algorithm/shortest-path", post(shortest_path))
         .route("/algorithm/all-paths", post(all_paths))
         .route("/algorithm/max-flow", post(max_flow))
         .route("/algorithm/trace", post(trace_path))
+        .route("/algorithm/trace/stream", get(trace_path_stream))
+        .layer(DefaultBodyLimit::max(config.max_body_size))
+        .layer(middleware::from_fn(move |req, next| {
+            rename_json_fields(config.json_field_case, req, next)
+        }))
+        .layer(middleware::from_fn(track_connections))
         .with_state(state);
 
     let addr = format!("{}:{}", config.host, config.port);
@@ -80,6 +228,87 @@ pub async fn start_server(config: ServerConfig, catalog: Arc<GraphCatalog>) -> R
     Ok(())
 }
 
+/// 统计正在处理的连接数，请求结束（或被客户端中断）时自动回落
+async fn track_connections(req: Request, next: Next) -> Response {
+    let _guard = metrics::global_metrics().record_connection_open();
+    next.run(req).await
+}
+
+/// 按配置或请求的 `Accept: application/json; case=camel` 把 JSON 响应的字段名
+/// 改写为 camelCase；顶点/边的 `properties` 中是用户属性名，保持原样
+async fn rename_json_fields(default_case: JsonFieldCase, req: Request, next: Next) -> Response {
+    let case = req
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .and_then(JsonFieldCase::from_accept)
+        .unwrap_or(default_case);
+    let response = next.run(req).await;
+    if case == JsonFieldCase::Snake {
+        return response;
+    }
+    camel_case_response(response).await
+}
+
+/// 改写 JSON 响应体的字段名为 camelCase，非 JSON 响应原样返回
+async fn camel_case_response(response: Response) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, usize::MAX).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let body = match serde_json::from_slice::<serde_json::Value>(&bytes) {
+        Ok(mut value) => {
+            camel_case_keys(&mut value);
+            serde_json::to_vec(&value).unwrap_or_else(|_| bytes.to_vec())
+        }
+        Err(_) => bytes.to_vec(),
+    };
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, axum::body::Body::from(body))
+}
+
+/// 递归地把对象字段名从 snake_case 改为 camelCase
+fn camel_case_keys(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let fields = std::mem::take(map);
+            for (key, mut field) in fields {
+                if key != "properties" {
+                    camel_case_keys(&mut field);
+                }
+                map.insert(to_camel_case(&key), field);
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(camel_case_keys),
+        _ => {}
+    }
+}
+
+fn to_camel_case(key: &str) -> String {
+    let mut camel = String::with_capacity(key.len());
+    let mut upper = false;
+    for c in key.chars() {
+        if c == '_' && !camel.is_empty() {
+            upper = true;
+        } else if upper {
+            camel.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            camel.push(c);
+        }
+    }
+    camel
+}
+
 // ==================== 处理器 ====================
 
 async fn health_check() -> impl IntoResponse {
@@ -112,6 +341,20 @@ async fn stats_handler(State(state): State<AppState>) -> impl IntoResponse {
     // 获取缓冲池水位信息
     let graph = state.catalog.current_graph();
     let watermark = graph.buffer_pool_watermark();
+
+    // 当前图的标签分布（按名称排序，输出稳定）
+    let vertices_by_label: BTreeMap<String, usize> = graph
+        .vertex_index()
+        .label_counts()
+        .into_iter()
+        .map(|(label, count)| (label.to_string(), count))
+        .collect();
+    let edges_by_type: BTreeMap<String, usize> = graph
+        .edge_index()
+        .label_counts()
+        .into_iter()
+        .map(|(label, count)| (label.to_string(), count))
+        .collect();
     
     Json(serde_json::json!({
         "query": {
@@ -121,6 +364,7 @@ async fn stats_handler(State(state): State<AppState>) -> impl IntoResponse {
             "avg_duration_ms": snapshot.avg_query_duration_ms,
             "slow_queries": snapshot.slow_queries,
             "qps": snapshot.qps,
+            "active": snapshot.active_queries,
         },
         "buffer_pool": {
             "hits": snapshot.buffer_pool_hits,
@@ -130,23 +374,345 @@ async fn stats_handler(State(state): State<AppState>) -> impl IntoResponse {
             "dirty_writes": snapshot.buffer_pool_dirty_writes,
             "watermark": watermark,
         },
+        "result_cache": {
+            "enabled": state.result_cache.is_some(),
+            "entries": state.result_cache.as_ref().map_or(0, |cache| cache.len()),
+            "hits": snapshot.result_cache_hits,
+            "misses": snapshot.result_cache_misses,
+        },
         "graph": {
+            "version": graph.version(),
             "vertices_inserted": snapshot.vertices_inserted,
             "edges_inserted": snapshot.edges_inserted,
             "vertices_queried": snapshot.vertices_queried,
             "edges_queried": snapshot.edges_queried,
+            "vertices_by_label": vertices_by_label,
+            "edges_by_type": edges_by_type,
         },
         "system": {
             "uptime_seconds": snapshot.uptime_seconds,
+            "open_connections": snapshot.open_connections,
             "version": env!("CARGO_PKG_VERSION"),
         }
     }))
 }
 
+/// 活跃事务
+#[derive(Debug, Serialize)]
+pub struct TransactionResponse {
+    pub id: u64,
+    pub access_mode: String,
+    pub age_ms: u64,
+}
+
+/// 列出尚未提交或回滚的事务
+async fn active_transactions(State(state): State<AppState>) -> impl IntoResponse {
+    let transactions: Vec<TransactionResponse> = state
+        .catalog
+        .transactions()
+        .active()
+        .into_iter()
+        .map(|tx| TransactionResponse {
+            id: tx.id,
+            access_mode: tx.mode.as_str().to_string(),
+            age_ms: tx.age().as_millis() as u64,
+        })
+        .collect();
+
+    (StatusCode::OK, Json(ApiResponse::success(transactions)))
+}
+
+/// 管理接口的 API key 请求头
+const ADMIN_API_KEY_HEADER: &str = "x-api-key";
+
+/// 校验管理接口的 API key：服务端未配置密钥时返回 403，密钥缺失或不匹配时返回 401
+fn check_admin_key(state: &AppState, headers: &HeaderMap) -> Option<Response> {
+    let Some(expected) = state.admin_api_key.as_deref() else {
+        return Some(
+            (
+                StatusCode::FORBIDDEN,
+                Json(ApiResponse::<()>::error("管理接口未启用")),
+            )
+                .into_response(),
+        );
+    };
+    let provided = headers
+        .get(ADMIN_API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok());
+    let matches = provided.is_some_and(|provided| keys_equal(provided, expected));
+    if !matches {
+        return Some(
+            (
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::<()>::error("API key 无效")),
+            )
+                .into_response(),
+        );
+    }
+    None
+}
+
+/// 以常量时间比较 API key：耗时只取决于两者长度，不随首个不同字节的位置变化，
+/// 避免逐字节猜测密钥
+fn keys_equal(provided: &str, expected: &str) -> bool {
+    let (provided, expected) = (provided.as_bytes(), expected.as_bytes());
+    let mut diff = (provided.len() != expected.len()) as u8;
+    for i in 0..provided.len().max(expected.len()) {
+        let a = provided.get(i).copied().unwrap_or(0);
+        let b = expected.get(i).copied().unwrap_or(0);
+        diff |= a ^ b;
+    }
+    std::hint::black_box(diff) == 0
+}
+
+/// 由内部错误构造失败响应体。`verbose_errors` 开启时返回完整信息（带 context 前缀）；
+/// 关闭时只返回 context（未给出时为通用提示）与错误码，完整信息写入服务端日志
+fn error_body(state: &AppState, context: Option<&str>, err: &Error) -> Json<ApiResponse<()>> {
+    let detail = match context {
+        Some(context) => format!("{}: {}", context, err),
+        None => err.to_string(),
+    };
+    if state.verbose_errors {
+        return Json(ApiResponse::error_with_code(&detail, err.code()));
+    }
+    eprintln!("[{}] {}", err.code(), detail);
+    let message = context.unwrap_or("服务器内部错误");
+    Json(ApiResponse::error_with_code(message, err.code()))
+}
+
+/// 条件 GET：ETag 由当前图的实例编号与数据版本组成，图有任何写入或重新打开后随之变化。
+/// 请求的 `If-None-Match` 与之相同时直接返回 304，否则执行读取并在成功响应上附带 ETag；
+/// `If-None-Match: *` 只在资源存在（读取成功）时返回 304。
+/// 版本在读取前取得，与读取并发的写入只会让客户端多取一次，不会得到过期数据
+fn conditional_get(
+    graph: &Graph,
+    headers: &HeaderMap,
+    read: impl FnOnce() -> Response,
+) -> Response {
+    let etag = format!("\"{:x}-{}\"", graph.instance_id(), graph.version());
+    let tags: Vec<&str> = headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim())
+        .collect();
+    let not_modified =
+        || (StatusCode::NOT_MODIFIED, [(header::ETAG, etag.clone())]).into_response();
+    if tags.iter().any(|tag| tag.trim_start_matches("W/") == etag) {
+        return not_modified();
+    }
+
+    let mut response = read();
+    if response.status() == StatusCode::OK {
+        if tags.contains(&"*") {
+            return not_modified();
+        }
+        if let Ok(value) = HeaderValue::from_str(&etag) {
+            response.headers_mut().insert(header::ETAG, value);
+        }
+    }
+    response
+}
+
+/// 触发检查点：保存元数据并把当前图的脏页写回磁盘
+async fn admin_checkpoint(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(rejected) = check_admin_key(&state, &headers) {
+        return rejected;
+    }
+    match state.catalog.current_graph().checkpoint() {
+        Ok(stats) => (StatusCode::OK, Json(ApiResponse::success(stats))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            error_body(&state, None, &e),
+        )
+            .into_response(),
+    }
+}
+
+/// 当前图的存储状态
+#[derive(Debug, Serialize)]
+pub struct StorageStatus {
+    /// 数据文件大小（字节）
+    pub file_size: u64,
+    /// 尚未写回磁盘的脏页数
+    pub dirty_pages: usize,
+    pub watermark: BufferPoolWatermark,
+}
+
+/// 报告数据文件大小、脏页数与缓冲池水位
+async fn admin_storage(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(rejected) = check_admin_key(&state, &headers) {
+        return rejected;
+    }
+    let graph = state.catalog.current_graph();
+    let file_size = match graph.buffer_pool().file_size() {
+        Ok(size) => size,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                error_body(&state, None, &e),
+            )
+                .into_response()
+        }
+    };
+    let status = StorageStatus {
+        file_size,
+        dirty_pages: graph.buffer_pool().dirty_pages(),
+        watermark: graph.buffer_pool_watermark(),
+    };
+    (StatusCode::OK, Json(ApiResponse::success(status))).into_response()
+}
+
 /// GQL 查询请求
 #[derive(Debug, Deserialize)]
 pub struct QueryRequest {
     pub query: String,
+    /// 代币精度（如 18）；设置后结果额外附带与 `rows` 对应的 `formatted`，给出换算后的金额
+    #[serde(default)]
+    pub amount_decimals: Option<u32>,
+    /// 结果中浮点数保留的小数位数；不设置时保持完整精度
+    #[serde(default)]
+    pub float_precision: Option<u32>,
+    /// 确认执行破坏性语句（无 WHERE 的 DELETE、DROP GRAPH）
+    #[serde(default)]
+    pub confirm_destructive: bool,
+    /// 查询参数，在查询中以 `$name` 引用
+    #[serde(default)]
+    pub params: HashMap<String, serde_json::Value>,
+}
+
+/// 把 JSON 参数转换为属性值：整数优先取 Integer，对象转为 Map
+fn json_to_property(value: &serde_json::Value) -> PropertyValue {
+    match value {
+        serde_json::Value::Null => PropertyValue::Null,
+        serde_json::Value::Bool(b) => PropertyValue::Boolean(*b),
+        serde_json::Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => PropertyValue::Integer(i),
+            (None, Some(u)) => PropertyValue::UInt(u),
+            _ => PropertyValue::Float(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => PropertyValue::String(s.clone()),
+        serde_json::Value::Array(items) => {
+            PropertyValue::List(items.iter().map(json_to_property).collect())
+        }
+        serde_json::Value::Object(entries) => PropertyValue::Map(
+            entries
+                .iter()
+                .map(|(k, v)| (k.clone(), json_to_property(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// 按服务端配置的执行上限创建执行器
+fn request_executor(state: &AppState, cancel: CancellationToken) -> QueryExecutor {
+    QueryExecutor::new(state.catalog.clone())
+        .with_cancellation(cancel)
+        .with_max_bindings(state.max_bindings)
+        .with_max_unlabeled_scan(state.max_unlabeled_scan)
+        .with_max_flow_edges(state.max_flow_edges)
+        .with_max_rows(state.max_rows)
+        .with_max_expansion_steps(state.max_expansion_steps)
+        .with_result_cache(state.result_cache.clone())
+}
+
+/// 查询请求失败时的 HTTP 状态与响应体
+struct QueryFailure {
+    status: StatusCode,
+    body: ApiResponse<()>,
+}
+
+impl QueryFailure {
+    fn new(status: StatusCode, message: &str) -> Self {
+        Self {
+            status,
+            body: ApiResponse::error(message),
+        }
+    }
+
+    fn from_error(state: &AppState, context: &str, err: &Error) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            body: error_body(state, Some(context), err).0,
+        }
+    }
+}
+
+/// 解析前先拒绝超长查询，避免解析阶段占用大量内存
+fn check_query_length(state: &AppState, query: &str) -> std::result::Result<(), QueryFailure> {
+    if query.len() > state.max_query_length {
+        return Err(QueryFailure::new(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            &format!(
+                "查询长度 {} 超过上限 {}",
+                query.len(),
+                state.max_query_length
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// 在执行器上执行一个 `/query` 请求，`/query` 与 `/query/batch` 共用。
+///
+/// 请求中的 `params` 与 `float_precision` 只作用于本次请求，执行后恢复执行器原有的设置；
+/// 语句中的 `SESSION SET` 照常保留在执行器上。多条语句依次执行，遇到第一个错误即停止，
+/// 之前语句的修改保留
+fn run_query_request(
+    executor: &QueryExecutor,
+    state: &AppState,
+    req: &QueryRequest,
+) -> std::result::Result<serde_json::Value, QueryFailure> {
+    check_query_length(state, &req.query)?;
+    let statements = GqlParser::new(&req.query)
+        .parse_statements()
+        .map_err(|e| QueryFailure::from_error(state, "解析错误", &e))?;
+    if !req.confirm_destructive && statements.iter().any(|stmt| stmt.is_destructive()) {
+        return Err(QueryFailure::new(
+            StatusCode::BAD_REQUEST,
+            "破坏性语句（无 WHERE 的 DELETE 或 DROP GRAPH）需要设置 confirm_destructive: true",
+        ));
+    }
+
+    let saved_params: Vec<(&String, Option<PropertyValue>)> = req
+        .params
+        .keys()
+        .map(|name| (name, executor.parameter(name)))
+        .collect();
+    let saved_precision = executor.float_precision();
+    for (name, value) in &req.params {
+        executor.set_parameter(name.clone(), json_to_property(value));
+    }
+    if req.float_precision.is_some() {
+        executor.set_float_precision(req.float_precision);
+    }
+
+    let single = statements.len() == 1;
+    let results = statements
+        .iter()
+        .enumerate()
+        .map(|(i, stmt)| {
+            executor.execute(stmt).map_err(|e| {
+                if single {
+                    e
+                } else {
+                    Error::QueryError(format!("第 {} 条语句: {}", i + 1, e))
+                }
+            })
+        })
+        .collect::<Result<Vec<_>>>();
+
+    for (name, value) in saved_params {
+        match value {
+            Some(value) => executor.set_parameter(name.clone(), value),
+            None => executor.remove_parameter(name),
+        }
+    }
+    executor.set_float_precision(saved_precision);
+
+    let results = results.map_err(|e| QueryFailure::from_error(state, "执行错误", &e))?;
+    Ok(results_json(&results, single, req.amount_decimals))
 }
 
 /// 执行 GQL 查询
@@ -154,118 +720,878 @@ async fn execute_query(
     State(state): State<AppState>,
     Json(req): Json<QueryRequest>,
 ) -> axum::response::Response {
-    let executor = QueryExecutor::new(state.catalog.clone());
+    let cancel = CancellationToken::new();
+    let executor = request_executor(&state, cancel.clone());
+    let request_state = state.clone();
+    let run = move || Ok(run_query_request(&executor, &request_state, &req));
+    match run_cancellable(cancel, run).await {
+        Ok(Ok(data)) => (StatusCode::OK, Json(ApiResponse::success(data))).into_response(),
+        Ok(Err(failure)) => (failure.status, Json(failure.body)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            error_body(&state, None, &e),
+        )
+            .into_response(),
+    }
+}
+
+/// 批量执行 GQL 查询
+///
+/// 各条查询按顺序在同一执行器中执行，前面的 `SESSION SET` 对后续查询可见，
+/// 各条请求自带的 `params` 与 `float_precision` 只作用于该条；
+/// 长度、破坏性语句和执行上限逐条检查，某条失败不影响后续查询。
+/// 返回的数组与请求按下标一一对应，每项与 `/query` 的响应格式相同，
+/// 并以 `status` 给出单独请求 `/query` 时的 HTTP 状态码
+async fn query_batch(
+    State(state): State<AppState>,
+    Json(requests): Json<Vec<QueryRequest>>,
+) -> axum::response::Response {
+    let cancel = CancellationToken::new();
+    let executor = request_executor(&state, cancel.clone());
 
-    match GqlParser::new(&req.query).parse() {
-        Ok(stmt) => match executor.execute(&stmt) {
-            Ok(result) => (StatusCode::OK, Json(ApiResponse::success(result))).into_response(),
-            Err(e) => (
+    let item_state = state.clone();
+    let run = move || {
+        let items = requests
+            .iter()
+            .map(|req| {
+                let (status, item) = match run_query_request(&executor, &item_state, req) {
+                    Ok(data) => (StatusCode::OK, serde_json::to_value(ApiResponse::success(data))),
+                    Err(failure) => (failure.status, serde_json::to_value(failure.body)),
+                };
+                let mut item = item.unwrap_or_default();
+                if let serde_json::Value::Object(map) = &mut item {
+                    map.insert("status".to_string(), status.as_u16().into());
+                }
+                item
+            })
+            .collect::<Vec<_>>();
+        Ok(items)
+    };
+    match run_cancellable(cancel, run).await {
+        Ok(items) => (StatusCode::OK, Json(ApiResponse::success(items))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            error_body(&state, None, &e),
+        )
+            .into_response(),
+    }
+}
+
+/// 按模式批量删除请求
+#[derive(Debug, Deserialize)]
+pub struct DeleteByQueryRequest {
+    /// MATCH 查询；RETURN 中的变量（无 RETURN 时为全部模式变量）即删除目标
+    pub query: String,
+    /// 仅统计将被删除的数量，不实际删除（默认开启）
+    #[serde(default = "default_preview")]
+    pub preview: bool,
+    /// 实际删除时必须为 true
+    #[serde(default)]
+    pub confirm_destructive: bool,
+}
+
+fn default_preview() -> bool {
+    true
+}
+
+/// 按模式批量删除响应
+#[derive(Debug, Serialize)]
+pub struct DeleteByQueryResponse {
+    pub preview: bool,
+    /// 删除（或将删除）的顶点数
+    pub vertices: usize,
+    /// 删除（或将删除）的边数，含随顶点一并删除的关联边
+    pub edges: usize,
+}
+
+/// 按 MATCH 结果批量 DETACH DELETE，支持预览数量
+async fn delete_by_query(
+    State(state): State<AppState>,
+    Json(req): Json<DeleteByQueryRequest>,
+) -> axum::response::Response {
+    if let Err(failure) = check_query_length(&state, &req.query) {
+        return (failure.status, Json(failure.body)).into_response();
+    }
+    if !req.preview && !req.confirm_destructive {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                "实际删除需要设置 confirm_destructive: true，或使用 preview: true 预览数量",
+            )),
+        )
+            .into_response();
+    }
+
+    let query = match GqlParser::new(&req.query).parse() {
+        Ok(GqlStatement::Match(query)) => query,
+        Ok(_) => {
+            return (
                 StatusCode::BAD_REQUEST,
-                Json(ApiResponse::<()>::error(&format!("执行错误: {}", e))),
+                Json(ApiResponse::<()>::error(
+                    "delete-by-query 只接受 MATCH 查询",
+                )),
             )
-                .into_response(),
-        },
+                .into_response()
+        }
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                error_body(&state, Some("解析错误"), &e),
+            )
+                .into_response()
+        }
+    };
+
+    let cancel = CancellationToken::new();
+    let executor = request_executor(&state, cancel.clone());
+    let preview = req.preview;
+    match run_cancellable(cancel, move || executor.delete_matches(&query, preview)).await {
+        Ok(summary) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(DeleteByQueryResponse {
+                preview,
+                vertices: summary.vertices,
+                edges: summary.edges,
+            })),
+        )
+            .into_response(),
         Err(e) => (
             StatusCode::BAD_REQUEST,
-            Json(ApiResponse::<()>::error(&format!("解析错误: {}", e))),
+            error_body(&state, Some("执行错误"), &e),
         )
             .into_response(),
     }
 }
 
+/// 查询结果的响应 JSON：单条语句为结果对象，多条语句为结果数组。
+/// 指定代币精度时，每个结果附带与 `rows` 逐行逐列对应的 `formatted`，
+/// 金额所在位置为换算后的字符串，其余为 null；`rows` 本身保持不变
+fn results_json(
+    results: &[QueryResult],
+    single: bool,
+    amount_decimals: Option<u32>,
+) -> serde_json::Value {
+    let mut items: Vec<serde_json::Value> = results
+        .iter()
+        .map(|result| {
+            let mut value = serde_json::to_value(result).unwrap_or_default();
+            if let (Some(decimals), serde_json::Value::Object(map)) = (amount_decimals, &mut value)
+            {
+                let formatted = result
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|cell| format_result_amounts(cell, decimals))
+                            .collect()
+                    })
+                    .collect();
+                map.insert("formatted".to_string(), serde_json::Value::Array(formatted));
+            }
+            value
+        })
+        .collect();
+    if single && items.len() == 1 {
+        items.remove(0)
+    } else {
+        serde_json::Value::Array(items)
+    }
+}
+
+/// 单元格中的金额按精度换算：标量金额为字符串，顶点与边为属性名到换算结果的对象，
+/// 路径为 `{"vertices": [...], "edges": [...]}`；不含金额时为 null
+fn format_result_amounts(cell: &ResultValue, decimals: u32) -> serde_json::Value {
+    match cell {
+        ResultValue::Scalar(value) => format_amount_value(value, decimals).unwrap_or_default(),
+        ResultValue::Vertex(vertex) => format_amount_properties(&vertex.properties, decimals),
+        ResultValue::Edge(edge) => format_amount_properties(&edge.properties, decimals),
+        ResultValue::Path(path) => {
+            let vertices: Vec<_> = path
+                .vertices
+                .iter()
+                .map(|v| format_amount_properties(&v.properties, decimals))
+                .collect();
+            let edges: Vec<_> = path
+                .edges
+                .iter()
+                .map(|e| format_amount_properties(&e.properties, decimals))
+                .collect();
+            if vertices.iter().chain(&edges).all(|v| v.is_null()) {
+                serde_json::Value::Null
+            } else {
+                serde_json::json!({ "vertices": vertices, "edges": edges })
+            }
+        }
+        ResultValue::Null => serde_json::Value::Null,
+    }
+}
+
+fn format_amount_properties(
+    properties: &HashMap<String, PropertyValue>,
+    decimals: u32,
+) -> serde_json::Value {
+    let formatted: serde_json::Map<String, serde_json::Value> = properties
+        .iter()
+        .filter_map(|(name, value)| Some((name.clone(), format_amount_value(value, decimals)?)))
+        .collect();
+    if formatted.is_empty() {
+        serde_json::Value::Null
+    } else {
+        serde_json::Value::Object(formatted)
+    }
+}
+
+/// 金额类型的属性值换算为字符串，列表与映射按元素换算；不含金额时为 None
+fn format_amount_value(value: &PropertyValue, decimals: u32) -> Option<serde_json::Value> {
+    match value {
+        PropertyValue::TokenAmount(amount) | PropertyValue::Amount(amount) => {
+            Some(serde_json::Value::String(amount.format_units(decimals)))
+        }
+        PropertyValue::List(items) => {
+            let formatted: Vec<_> = items
+                .iter()
+                .map(|item| format_amount_value(item, decimals))
+                .collect();
+            formatted.iter().any(Option::is_some).then(|| {
+                serde_json::Value::Array(
+                    formatted
+                        .into_iter()
+                        .map(Option::unwrap_or_default)
+                        .collect(),
+                )
+            })
+        }
+        PropertyValue::Map(entries) => match format_amount_properties(entries, decimals) {
+            serde_json::Value::Null => None,
+            formatted => Some(formatted),
+        },
+        _ => None,
+    }
+}
+
 /// 获取顶点
 async fn get_vertex(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Path(id): Path<u64>,
 ) -> axum::response::Response {
     let graph = state.catalog.current_graph();
-    match graph.get_vertex(VertexId::new(id)) {
-        Some(vertex) => (StatusCode::OK, Json(ApiResponse::success(vertex))).into_response(),
-        None => (
-            StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("顶点不存在")),
-        )
-            .into_response(),
-    }
+    conditional_get(&graph, &headers, || {
+        match graph.get_vertex(VertexId::new(id)) {
+            Some(vertex) => (StatusCode::OK, Json(ApiResponse::success(vertex))).into_response(),
+            None => (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::error("顶点不存在")),
+            )
+                .into_response(),
+        }
+    })
 }
 
 /// 通过地址获取顶点
 async fn get_vertex_by_address(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Path(address): Path<String>,
 ) -> axum::response::Response {
     // 地址作为普通字符串处理
     let graph = state.catalog.current_graph();
-    match graph.get_vertex_by_address(&address) {
-        Some(vertex) => (StatusCode::OK, Json(ApiResponse::success(vertex))).into_response(),
-        None => (
+    conditional_get(&graph, &headers, || {
+        match graph.get_vertex_by_address(&address) {
+            Some(vertex) => (StatusCode::OK, Json(ApiResponse::success(vertex))).into_response(),
+            None => (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::error("顶点不存在")),
+            )
+                .into_response(),
+        }
+    })
+}
+
+/// 地址标记请求
+#[derive(Debug, Deserialize)]
+pub struct AddressTagRequest {
+    pub tag: String,
+}
+
+/// 地址标记
+#[derive(Debug, Serialize)]
+pub struct AddressTag {
+    pub address: String,
+    pub tag: Option<String>,
+}
+
+/// 设置地址标记；地址可以尚未出现在图中
+async fn set_address_tag(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Json(req): Json<AddressTagRequest>,
+) -> axum::response::Response {
+    let tag = req.tag.trim();
+    if tag.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error("标记不能为空")),
+        )
+            .into_response();
+    }
+    let graph = state.catalog.current_graph();
+    match graph.set_address_tag(&address, tag) {
+        Ok(()) => {
+            let tagged = AddressTag {
+                address,
+                tag: Some(tag.to_string()),
+            };
+            (StatusCode::OK, Json(ApiResponse::success(tagged))).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            error_body(&state, None, &e),
+        )
+            .into_response(),
+    }
+}
+
+/// 移除地址标记
+async fn remove_address_tag(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> axum::response::Response {
+    let graph = state.catalog.current_graph();
+    match graph.remove_address_tag(&address) {
+        Ok(true) => {
+            let removed = AddressTag { address, tag: None };
+            (StatusCode::OK, Json(ApiResponse::success(removed))).into_response()
+        }
+        Ok(false) => (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("顶点不存在")),
+            Json(ApiResponse::<()>::error("地址没有标记")),
         )
             .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            error_body(&state, None, &e),
+        )
+            .into_response(),
+    }
+}
+
+/// 批量获取顶点的最大数量
+pub const MAX_VERTEX_BATCH_SIZE: usize = 1000;
+
+/// 批量获取顶点请求（ids 与 addresses 可同时提供）
+#[derive(Debug, Deserialize)]
+pub struct BatchVertexRequest {
+    #[serde(default)]
+    pub ids: Vec<u64>,
+    #[serde(default)]
+    pub addresses: Vec<String>,
+}
+
+/// 批量获取顶点响应
+#[derive(Debug, Serialize)]
+pub struct BatchVertexResponse {
+    pub vertices: Vec<Vertex>,
+    pub missing_ids: Vec<u64>,
+    pub missing_addresses: Vec<String>,
+}
+
+/// 批量获取顶点
+async fn batch_get_vertices(
+    State(state): State<AppState>,
+    Json(req): Json<BatchVertexRequest>,
+) -> axum::response::Response {
+    let requested = req.ids.len() + req.addresses.len();
+    if requested > MAX_VERTEX_BATCH_SIZE {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(&format!(
+                "批量请求数量 {} 超过上限 {}",
+                requested, MAX_VERTEX_BATCH_SIZE
+            ))),
+        )
+            .into_response();
+    }
+
+    let graph = state.catalog.current_graph();
+    let mut response = BatchVertexResponse {
+        vertices: Vec::with_capacity(requested),
+        missing_ids: Vec::new(),
+        missing_addresses: Vec::new(),
+    };
+
+    for id in req.ids {
+        match graph.get_vertex(VertexId::new(id)) {
+            Some(vertex) => response.vertices.push(vertex),
+            None => response.missing_ids.push(id),
+        }
+    }
+    for address in req.addresses {
+        match graph.get_vertex_by_address(&address) {
+            Some(vertex) => response.vertices.push(vertex),
+            None => response.missing_addresses.push(address),
+        }
     }
+
+    (StatusCode::OK, Json(ApiResponse::success(response))).into_response()
 }
 
 /// 获取边
-async fn get_edge(State(state): State<AppState>, Path(id): Path<u64>) -> axum::response::Response {
+async fn get_edge(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<u64>,
+) -> axum::response::Response {
     let graph = state.catalog.current_graph();
-    match graph.get_edge(EdgeId::new(id)) {
+    conditional_get(&graph, &headers, || match graph.get_edge(EdgeId::new(id)) {
         Some(edge) => (StatusCode::OK, Json(ApiResponse::success(edge))).into_response(),
         None => (
             StatusCode::NOT_FOUND,
             Json(ApiResponse::<()>::error("边不存在")),
         )
             .into_response(),
-    }
+    })
 }
 
 /// 获取出边
 async fn get_outgoing_edges(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Path(id): Path<u64>,
-) -> impl IntoResponse {
+) -> axum::response::Response {
     let graph = state.catalog.current_graph();
-    let edges = graph.get_outgoing_edges(VertexId::new(id));
-    (StatusCode::OK, Json(ApiResponse::success(edges)))
+    conditional_get(&graph, &headers, || {
+        let edges = graph.get_outgoing_edges(VertexId::new(id));
+        (StatusCode::OK, Json(ApiResponse::success(edges))).into_response()
+    })
 }
 
 /// 获取入边
 async fn get_incoming_edges(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Path(id): Path<u64>,
-) -> impl IntoResponse {
+) -> axum::response::Response {
     let graph = state.catalog.current_graph();
-    let edges = graph.get_incoming_edges(VertexId::new(id));
-    (StatusCode::OK, Json(ApiResponse::success(edges)))
+    conditional_get(&graph, &headers, || {
+        let edges = graph.get_incoming_edges(VertexId::new(id));
+        (StatusCode::OK, Json(ApiResponse::success(edges))).into_response()
+    })
 }
 
-/// 路径请求
+/// 两顶点间边查询参数
 #[derive(Debug, Deserialize)]
-pub struct PathRequest {
-    pub source: u64,
-    pub target: u64,
-    #[serde(default = "default_max_depth")]
-    pub max_depth: usize,
-    #[serde(default = "default_k")]
-    pub k: usize,
+pub struct EdgesBetweenParams {
+    pub src: u64,
+    pub dst: u64,
+    pub from_block: Option<u64>,
+    pub to_block: Option<u64>,
 }
 
-fn default_max_depth() -> usize {
-    10
-}
+/// 获取 src -> dst 的所有边（含属性），可按区块范围过滤
+async fn get_edges_between(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<EdgesBetweenParams>,
+) -> axum::response::Response {
+    let graph = state.catalog.current_graph();
+    conditional_get(&graph, &headers, || {
+        let (src, dst) = (VertexId::new(params.src), VertexId::new(params.dst));
+        if !graph.vertex_exists(src) || !graph.vertex_exists(dst) {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::error("顶点不存在")),
+            )
+                .into_response();
+        }
+
+        let edges: Vec<Edge> = graph
+            .get_edges_between(src, dst)
+            .into_iter()
+            .filter(|e| in_block_window(params.from_block, params.to_block, e.block_number()))
+            .collect();
+        (StatusCode::OK, Json(ApiResponse::success(edges))).into_response()
+    })
+}
+
+/// 邻域查询参数
+#[derive(Debug, Deserialize)]
+pub struct NeighborhoodParams {
+    #[serde(default = "default_neighborhood_depth")]
+    pub depth: usize,
+    #[serde(default = "default_neighborhood_direction")]
+    pub direction: NeighborhoodDirection,
+    /// 返回边数上限
+    #[serde(default = "default_neighborhood_limit")]
+    pub limit: usize,
+}
+
+fn default_neighborhood_depth() -> usize {
+    1
+}
+
+/// 邻域展开方向；`outgoing` / `incoming` 为 `out` / `in` 的别名，
+/// 其他取值在反序列化时被拒绝
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NeighborhoodDirection {
+    #[serde(alias = "outgoing")]
+    Out,
+    #[serde(alias = "incoming")]
+    In,
+    Both,
+}
+
+fn default_neighborhood_direction() -> NeighborhoodDirection {
+    NeighborhoodDirection::Both
+}
+
+fn default_neighborhood_limit() -> usize {
+    100
+}
+
+/// 邻域响应：中心顶点及其邻接边与相邻顶点
+#[derive(Debug, Serialize)]
+pub struct NeighborhoodResponse {
+    pub center: Vertex,
+    pub vertices: Vec<Vertex>,
+    pub edges: Vec<Edge>,
+    /// 是否因 limit 截断
+    pub truncated: bool,
+}
+
+/// 获取顶点邻域（顶点 + 边 + 相邻顶点）
+async fn get_neighborhood(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<u64>,
+    Query(params): Query<NeighborhoodParams>,
+) -> axum::response::Response {
+    let graph = state.catalog.current_graph();
+    conditional_get(&graph, &headers, || {
+        let center = match graph.get_vertex(VertexId::new(id)) {
+            Some(v) => v,
+            None => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(ApiResponse::<()>::error("顶点不存在")),
+                )
+                    .into_response()
+            }
+        };
+
+        let (outgoing, incoming) = match params.direction {
+            NeighborhoodDirection::Out => (true, false),
+            NeighborhoodDirection::In => (false, true),
+            NeighborhoodDirection::Both => (true, true),
+        };
+
+        let mut visited = std::collections::HashSet::from([center.id()]);
+        let mut seen_edges = std::collections::HashSet::new();
+        let mut frontier = vec![center.id()];
+        let mut vertices = Vec::new();
+        let mut edges = Vec::new();
+        let mut truncated = false;
+
+        'outer: for _ in 0..params.depth {
+            let mut next = Vec::new();
+            for vid in frontier {
+                let mut adjacent = Vec::new();
+                if outgoing {
+                    adjacent.extend(graph.get_outgoing_edges(vid));
+                }
+                if incoming {
+                    adjacent.extend(graph.get_incoming_edges(vid));
+                }
+                for edge in adjacent {
+                    if !seen_edges.insert(edge.id()) {
+                        continue;
+                    }
+                    if edges.len() >= params.limit {
+                        truncated = true;
+                        break 'outer;
+                    }
+                    let other = if edge.src() == vid { edge.dst() } else { edge.src() };
+                    edges.push(edge);
+                    if visited.insert(other) {
+                        if let Some(v) = graph.get_vertex(other) {
+                            vertices.push(v);
+                        }
+                        next.push(other);
+                    }
+                }
+            }
+            frontier = next;
+        }
+
+        let response = NeighborhoodResponse {
+            center,
+            vertices,
+            edges,
+            truncated,
+        };
+        (StatusCode::OK, Json(ApiResponse::success(response))).into_response()
+    })
+}
+
+/// 转账量查询参数
+#[derive(Debug, Deserialize)]
+pub struct VolumeParams {
+    pub from: String,
+    pub to: String,
+    pub from_block: Option<u64>,
+    pub to_block: Option<u64>,
+    /// 不统计未记录金额的转账（与金额为 0 的转账区分）
+    #[serde(default)]
+    pub exclude_missing_amount: bool,
+}
+
+/// 转账量统计结果
+#[derive(Debug, Serialize)]
+pub struct VolumeResponse {
+    pub from: String,
+    pub to: String,
+    pub total_volume: TokenAmount,
+    pub transfer_count: usize,
+}
+
+/// 统计 from -> to 在区块窗口（闭区间）内的转账总量
+/// 区块号是否落在 [from_block, to_block] 内；未指定窗口时全部通过，
+/// 指定窗口时缺少区块号的边被排除
+fn in_block_window(from_block: Option<u64>, to_block: Option<u64>, block: Option<u64>) -> bool {
+    match (from_block, to_block, block) {
+        (None, None, _) => true,
+        (_, _, None) => false,
+        (lo, hi, Some(b)) => lo.is_none_or(|lo| b >= lo) && hi.is_none_or(|hi| b <= hi),
+    }
+}
+
+async fn transfer_volume(
+    State(state): State<AppState>,
+    Query(params): Query<VolumeParams>,
+) -> axum::response::Response {
+    let graph = state.catalog.current_graph();
+    let (src, dst) = match (
+        graph.get_vertex_by_address(&params.from),
+        graph.get_vertex_by_address(&params.to),
+    ) {
+        (Some(src), Some(dst)) => (src, dst),
+        _ => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::error("地址不存在")),
+            )
+                .into_response()
+        }
+    };
+
+    let in_window = |block| in_block_window(params.from_block, params.to_block, block);
+
+    let mut total = primitive_types::U256::zero();
+    let mut transfer_count = 0;
+    for edge in graph.get_edges_between(src.id(), dst.id()) {
+        if edge.label() != &EdgeLabel::Transfer || !in_window(edge.block_number()) {
+            continue;
+        }
+        match edge.amount() {
+            Some(amount) => total = total.saturating_add(amount.0),
+            None if params.exclude_missing_amount => continue,
+            None => {}
+        }
+        transfer_count += 1;
+    }
+
+    let response = VolumeResponse {
+        from: params.from,
+        to: params.to,
+        total_volume: TokenAmount(total),
+        transfer_count,
+    };
+    (StatusCode::OK, Json(ApiResponse::success(response))).into_response()
+}
+
+/// 时间序列最多返回的桶数
+pub const MAX_TIMESERIES_BUCKETS: usize = 1000;
+
+/// 时间序列查询参数
+#[derive(Debug, Deserialize)]
+pub struct TimeseriesParams {
+    pub address: String,
+    /// 每个桶覆盖的区块数
+    #[serde(default = "default_bucket_size")]
+    pub bucket: u64,
+}
+
+fn default_bucket_size() -> u64 {
+    10000
+}
+
+/// 单个区块桶的流入/流出统计
+#[derive(Debug, Serialize)]
+pub struct TimeseriesBucket {
+    pub start_block: u64,
+    pub end_block: u64,
+    pub inflow: TokenAmount,
+    pub outflow: TokenAmount,
+    pub inflow_count: usize,
+    pub outflow_count: usize,
+}
+
+/// 时间序列响应
+#[derive(Debug, Serialize)]
+pub struct TimeseriesResponse {
+    pub address: String,
+    pub bucket: u64,
+    pub buckets: Vec<TimeseriesBucket>,
+    /// 桶数超过上限时为 true（只保留最早的桶）
+    pub truncated: bool,
+}
+
+/// 按区块区间统计地址的转入/转出
+async fn transfer_timeseries(
+    State(state): State<AppState>,
+    Query(params): Query<TimeseriesParams>,
+) -> axum::response::Response {
+    if params.bucket == 0 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error("bucket 必须大于 0")),
+        )
+            .into_response();
+    }
+
+    let graph = state.catalog.current_graph();
+    let vertex = match graph.get_vertex_by_address(&params.address) {
+        Some(v) => v,
+        None => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::error("地址不存在")),
+            )
+                .into_response()
+        }
+    };
+
+    let mut buckets: std::collections::BTreeMap<u64, TimeseriesBucket> =
+        std::collections::BTreeMap::new();
+    let flows = graph
+        .get_incoming_edges(vertex.id())
+        .into_iter()
+        .map(|e| (true, e))
+        .chain(graph.get_outgoing_edges(vertex.id()).into_iter().map(|e| (false, e)));
+
+    for (inbound, edge) in flows {
+        if edge.label() != &EdgeLabel::Transfer {
+            continue;
+        }
+        let Some(block) = edge.block_number() else {
+            continue;
+        };
+        let start_block = block - block % params.bucket;
+        let bucket = buckets.entry(start_block).or_insert_with(|| TimeseriesBucket {
+            start_block,
+            end_block: start_block.saturating_add(params.bucket - 1),
+            inflow: TokenAmount::from_u64(0),
+            outflow: TokenAmount::from_u64(0),
+            inflow_count: 0,
+            outflow_count: 0,
+        });
+        let amount = edge.amount().map(|a| a.0).unwrap_or_default();
+        if inbound {
+            bucket.inflow.0 = bucket.inflow.0.saturating_add(amount);
+            bucket.inflow_count += 1;
+        } else {
+            bucket.outflow.0 = bucket.outflow.0.saturating_add(amount);
+            bucket.outflow_count += 1;
+        }
+    }
+
+    let truncated = buckets.len() > MAX_TIMESERIES_BUCKETS;
+    let response = TimeseriesResponse {
+        address: params.address,
+        bucket: params.bucket,
+        buckets: buckets
+            .into_values()
+            .take(MAX_TIMESERIES_BUCKETS)
+            .collect(),
+        truncated,
+    };
+    (StatusCode::OK, Json(ApiResponse::success(response))).into_response()
+}
+
+/// 请求参数解析失败时直接返回给客户端的响应
+type ResolveError = (StatusCode, Json<ApiResponse<()>>);
+
+/// 解析请求中的顶点：数字 ID 与地址二选一，地址经地址索引解析为顶点
+fn resolve_vertex(
+    graph: &Graph,
+    field: &str,
+    id: Option<u64>,
+    address: Option<&str>,
+) -> std::result::Result<VertexId, ResolveError> {
+    let error = |status, message: String| (status, Json(ApiResponse::<()>::error(&message)));
+    match (id, address) {
+        (Some(id), None) => Ok(VertexId::new(id)),
+        (None, Some(address)) => graph
+            .get_vertex_by_address(address)
+            .map(|v| v.id())
+            .ok_or_else(|| error(StatusCode::NOT_FOUND, format!("地址不存在: {}", address))),
+        (Some(_), Some(_)) => Err(error(
+            StatusCode::BAD_REQUEST,
+            format!("{0} 与 {0}_address 只能指定其一", field),
+        )),
+        (None, None) => Err(error(
+            StatusCode::BAD_REQUEST,
+            format!("缺少 {0} 或 {0}_address", field),
+        )),
+    }
+}
+
+/// 路径请求；起点与终点可用顶点 ID 或地址指定
+#[derive(Debug, Deserialize)]
+pub struct PathRequest {
+    pub source: Option<u64>,
+    pub source_address: Option<String>,
+    pub target: Option<u64>,
+    pub target_address: Option<String>,
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize,
+    #[serde(default = "default_k")]
+    pub k: usize,
+    /// 只返回 block_number 单调不减的路径（资金流时间因果）
+    #[serde(default)]
+    pub monotonic_blocks: bool,
+}
+
+fn default_max_depth() -> usize {
+    10
+}
 
 fn default_k() -> usize {
     5
 }
 
+fn resolve_path_endpoints(
+    graph: &Graph,
+    req: &PathRequest,
+) -> std::result::Result<(VertexId, VertexId), ResolveError> {
+    let source = resolve_vertex(graph, "source", req.source, req.source_address.as_deref())?;
+    let target = resolve_vertex(graph, "target", req.target, req.target_address.as_deref())?;
+    Ok((source, target))
+}
+
 /// 最短路径
 async fn shortest_path(
     State(state): State<AppState>,
     Json(req): Json<PathRequest>,
 ) -> axum::response::Response {
     let graph = state.catalog.current_graph();
+    let (source, target) = match resolve_path_endpoints(&graph, &req) {
+        Ok(endpoints) => endpoints,
+        Err(error) => return error.into_response(),
+    };
     let finder = PathFinder::new(graph);
-    let result = finder.shortest_path(VertexId::new(req.source), VertexId::new(req.target));
+    let result = finder.shortest_path(source, target);
 
     match result {
         Some(path) => (StatusCode::OK, Json(ApiResponse::success(path))).into_response(),
@@ -277,68 +1603,224 @@ async fn shortest_path(
 async fn all_paths(
     State(state): State<AppState>,
     Json(req): Json<PathRequest>,
-) -> impl IntoResponse {
+) -> axum::response::Response {
+    let cancel = CancellationToken::new();
     let graph = state.catalog.current_graph();
-    let finder = PathFinder::new(graph);
-    let paths = finder.all_paths(
-        VertexId::new(req.source),
-        VertexId::new(req.target),
-        req.max_depth,
-    );
+    let (source, target) = match resolve_path_endpoints(&graph, &req) {
+        Ok(endpoints) => endpoints,
+        Err(error) => return error.into_response(),
+    };
+    let mut finder = PathFinder::new(graph)
+        .with_monotonic_blocks(req.monotonic_blocks)
+        .with_parallelism(true)
+        .with_max_paths(Some(MAX_ALL_PATHS))
+        .with_cancellation(cancel.clone());
+    if let Some(pool) = state.thread_pool.clone() {
+        finder = finder.with_thread_pool(pool);
+    }
+
+    let result =
+        run_cancellable(cancel, move || finder.all_paths(source, target, req.max_depth)).await;
 
-    (StatusCode::OK, Json(ApiResponse::success(paths)))
+    match result {
+        Ok(paths) => (StatusCode::OK, Json(ApiResponse::success(paths))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            error_body(&state, None, &e),
+        )
+            .into_response(),
+    }
 }
 
-/// 最大流请求
+/// 最大流请求；源点与汇点可用顶点 ID 或地址指定
 #[derive(Debug, Deserialize)]
 pub struct MaxFlowRequest {
-    pub source: u64,
-    pub sink: u64,
+    pub source: Option<u64>,
+    pub source_address: Option<String>,
+    pub sink: Option<u64>,
+    pub sink_address: Option<String>,
+    /// `edmonds_karp`（默认）或 `dinic`
+    #[serde(default)]
+    pub algorithm: MaxFlowAlgorithm,
+}
+
+/// 最大流响应：流量边按流量从大到小排列，超出上限时截断
+#[derive(Debug, Serialize)]
+pub struct MaxFlowResponse {
+    pub value: f64,
+    pub flow: Vec<FlowEdge>,
+    /// 最小割的源侧顶点（升序）
+    pub source_side: Vec<VertexId>,
+    /// 流量边是否因上限被截断
+    pub truncated: bool,
 }
 
 /// 最大流
 async fn max_flow(
     State(state): State<AppState>,
     Json(req): Json<MaxFlowRequest>,
-) -> impl IntoResponse {
+) -> axum::response::Response {
+    let cancel = CancellationToken::new();
     let graph = state.catalog.current_graph();
-    let algo = EdmondsKarp::new(graph);
-    let result = algo.max_flow(VertexId::new(req.source), VertexId::new(req.sink));
+    let source = resolve_vertex(&graph, "source", req.source, req.source_address.as_deref());
+    let sink = resolve_vertex(&graph, "sink", req.sink, req.sink_address.as_deref());
+    let (source, sink) = match (source, sink) {
+        (Ok(source), Ok(sink)) => (source, sink),
+        (Err(error), _) | (_, Err(error)) => return error.into_response(),
+    };
+    let algorithm = req.algorithm;
+    let task_cancel = cancel.clone();
 
-    (StatusCode::OK, Json(ApiResponse::success(result)))
+    let result = run_cancellable(cancel, move || {
+        algorithm.max_flow(graph, task_cancel, source, sink)
+    })
+    .await;
+
+    match result {
+        Ok(result) => {
+            let (flow, truncated) = result.top_flows(state.max_flow_edges);
+            let mut source_side: Vec<VertexId> = result.source_side.into_iter().collect();
+            source_side.sort_by_key(|v| v.as_u64());
+            let response = MaxFlowResponse {
+                value: result.value,
+                flow,
+                source_side,
+                truncated,
+            };
+            (StatusCode::OK, Json(ApiResponse::success(response))).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            error_body(&state, None, &e),
+        )
+            .into_response(),
+    }
+}
+
+/// 在阻塞线程池中执行可取消的计算
+///
+/// 令牌的守卫由当前 future 持有：客户端断开连接导致请求 future 被丢弃时，
+/// 令牌随之取消，后台计算在下一个循环边界返回 `Error::Cancelled`。
+async fn run_cancellable<T, F>(cancel: CancellationToken, f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    let _guard = cancel.drop_guard();
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| Error::InternalError(e.to_string()))?
 }
 
-/// 追踪请求
+/// 追踪请求；起点可用顶点 ID 或地址指定
 #[derive(Debug, Deserialize)]
 pub struct TraceRequest {
-    pub start: u64,
+    pub start: Option<u64>,
+    pub start_address: Option<String>,
+    /// forward / backward / both，其他取值返回 400
     #[serde(default = "default_direction")]
-    pub direction: String,
+    pub direction: TraceDirection,
     #[serde(default = "default_max_depth")]
     pub max_depth: usize,
+    /// 途经顶点的分支数上限，超过时不再展开并将路径标记为 truncated
+    pub max_fanout: Option<usize>,
+    /// 在每条路径上附带途经地址的标记
+    #[serde(default)]
+    pub include_tags: bool,
+}
+
+fn default_direction() -> TraceDirection {
+    TraceDirection::Forward
+}
+
+/// 追踪结果中的一条路径；`tags` 以顶点 ID 为键，仅含带标记的顶点
+#[derive(Debug, Serialize)]
+pub struct TracedPath {
+    #[serde(flatten)]
+    pub path: PathResult,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<u64, String>,
 }
 
-fn default_direction() -> String {
-    "forward".to_string()
+impl TracedPath {
+    fn new(graph: &Graph, path: PathResult, include_tags: bool) -> Self {
+        let tags = if include_tags {
+            path.vertices
+                .iter()
+                .filter_map(|&id| Some((id.as_u64(), graph.vertex_tag(id)?)))
+                .collect()
+        } else {
+            BTreeMap::new()
+        };
+        Self { path, tags }
+    }
 }
 
 /// 路径追踪
 async fn trace_path(
     State(state): State<AppState>,
-    Json(req): Json<TraceRequest>,
-) -> impl IntoResponse {
+    payload: std::result::Result<Json<TraceRequest>, JsonRejection>,
+) -> axum::response::Response {
+    // 请求体无法反序列化（如未知的 direction）时统一返回 400，而不是 axum 默认的 422
+    let Json(req) = match payload {
+        Ok(payload) => payload,
+        Err(rejection) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(&rejection.body_text())),
+            )
+                .into_response()
+        }
+    };
     let graph = state.catalog.current_graph();
-    let finder = PathFinder::new(graph);
+    let start = match resolve_vertex(&graph, "start", req.start, req.start_address.as_deref()) {
+        Ok(start) => start,
+        Err(error) => return error.into_response(),
+    };
+    let finder = PathFinder::new(graph.clone()).with_max_fanout(req.max_fanout);
+    let traces: Vec<TracedPath> = finder
+        .trace(start, req.direction, req.max_depth, None)
+        .into_iter()
+        .map(|path| TracedPath::new(&graph, path, req.include_tags))
+        .collect();
 
-    let direction = match req.direction.as_str() {
-        "backward" => TraceDirection::Backward,
-        "both" => TraceDirection::Both,
-        _ => TraceDirection::Forward,
+    (StatusCode::OK, Json(ApiResponse::success(traces))).into_response()
+}
+
+/// 流式追踪时缓冲的事件数，客户端读取较慢时追踪线程在此处等待
+const TRACE_STREAM_BUFFER: usize = 64;
+
+/// 流式路径追踪（SSE）：每发现一条路径推送一个 `path` 事件，
+/// 结束时推送 `done` 事件；客户端断开后追踪随即停止
+async fn trace_path_stream(
+    State(state): State<AppState>,
+    Query(req): Query<TraceRequest>,
+) -> axum::response::Response {
+    let graph = state.catalog.current_graph();
+    let start = match resolve_vertex(&graph, "start", req.start, req.start_address.as_deref()) {
+        Ok(start) => start,
+        Err(error) => return error.into_response(),
     };
+    let (tx, rx) = tokio::sync::mpsc::channel(TRACE_STREAM_BUFFER);
 
-    let traces = finder.trace(VertexId::new(req.start), direction, req.max_depth, None);
+    tokio::task::spawn_blocking(move || {
+        let finder = PathFinder::new(graph.clone()).with_max_fanout(req.max_fanout);
+        let mut paths = 0;
+        finder.trace_each(start, req.direction, req.max_depth, None, |path| {
+            paths += 1;
+            let path = TracedPath::new(&graph, path.clone(), req.include_tags);
+            let event = Event::default().event("path").json_data(path);
+            tx.blocking_send(event).is_ok()
+        });
+        let done = Event::default()
+            .event("done")
+            .json_data(serde_json::json!({ "paths": paths }));
+        let _ = tx.blocking_send(done);
+    });
 
-    (StatusCode::OK, Json(ApiResponse::success(traces)))
+    Sse::new(ReceiverStream::new(rx))
+        .keep_alive(KeepAlive::default())
+        .into_response()
 }
 
 /// 统计信息
@@ -359,6 +1841,9 @@ pub struct ApiResponse<T> {
     pub data: Option<T>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// 错误码（见 `Error::code`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
 }
 
 impl<T: Serialize> ApiResponse<T> {
@@ -367,6 +1852,7 @@ impl<T: Serialize> ApiResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            code: None,
         }
     }
 
@@ -375,6 +1861,14 @@ impl<T: Serialize> ApiResponse<T> {
             success: false,
             data: None,
             error: Some(msg.to_string()),
+            code: None,
+        }
+    }
+
+    pub fn error_with_code(msg: &str, code: &str) -> Self {
+        Self {
+            code: Some(code.to_string()),
+            ..Self::error(msg)
         }
     }
 }
@@ -387,3 +1881,1179 @@ where
         Json(self).into_response()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::VertexLabel;
+    use tempfile::TempDir;
+
+    fn test_state() -> (AppState, TempDir) {
+        let dir = TempDir::new().unwrap();
+        let catalog = GraphCatalog::open(dir.path(), Some(64)).unwrap();
+        let state = AppState {
+            catalog,
+            max_query_length: ServerConfig::default().max_query_length,
+            max_bindings: ServerConfig::default().max_bindings,
+            max_unlabeled_scan: ServerConfig::default().max_unlabeled_scan,
+            max_flow_edges: ServerConfig::default().max_flow_edges,
+            max_rows: ServerConfig::default().max_rows,
+            max_expansion_steps: ServerConfig::default().max_expansion_steps,
+            result_cache: None,
+            thread_pool: None,
+            admin_api_key: None,
+            verbose_errors: true,
+        };
+        (state, dir)
+    }
+
+    async fn response_json(response: Response) -> (StatusCode, serde_json::Value) {
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_batch_get_vertices() {
+        let (state, _dir) = test_state();
+        let graph = state.catalog.current_graph();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        let b = graph.add_account("0xbbb".to_string()).unwrap();
+
+        let req = BatchVertexRequest {
+            ids: vec![a.as_u64(), 999, b.as_u64()],
+            addresses: vec!["0xbbb".to_string(), "0xccc".to_string()],
+        };
+        let (status, json) = response_json(batch_get_vertices(State(state), Json(req)).await).await;
+
+        assert_eq!(status, StatusCode::OK);
+        let data = &json["data"];
+        assert_eq!(data["vertices"].as_array().unwrap().len(), 3);
+        assert_eq!(data["missing_ids"], serde_json::json!([999]));
+        assert_eq!(data["missing_addresses"], serde_json::json!(["0xccc"]));
+    }
+
+    #[tokio::test]
+    async fn test_get_neighborhood() {
+        let (state, _dir) = test_state();
+        let graph = state.catalog.current_graph();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        let b = graph.add_account("0xbbb".to_string()).unwrap();
+        let c = graph.add_account("0xccc".to_string()).unwrap();
+        let d = graph.add_account("0xddd".to_string()).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(1), 1).unwrap();
+        graph.add_transfer(c, a, TokenAmount::from_u64(2), 2).unwrap();
+        graph.add_transfer(b, d, TokenAmount::from_u64(3), 3).unwrap();
+
+        let params = NeighborhoodParams {
+            depth: 1,
+            direction: NeighborhoodDirection::Both,
+            limit: 100,
+        };
+        let (status, json) = response_json(
+            get_neighborhood(
+                State(state.clone()),
+                HeaderMap::new(),
+                Path(a.as_u64()),
+                Query(params),
+            )
+            .await,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let data = &json["data"];
+        assert_eq!(data["center"]["id"], a.as_u64());
+        let mut neighbors: Vec<u64> = data["vertices"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v["id"].as_u64().unwrap())
+            .collect();
+        neighbors.sort();
+        assert_eq!(neighbors, vec![b.as_u64(), c.as_u64()]);
+        assert_eq!(data["edges"].as_array().unwrap().len(), 2);
+        assert_eq!(data["truncated"], false);
+
+        let params = NeighborhoodParams {
+            depth: 2,
+            direction: NeighborhoodDirection::Out,
+            limit: 1,
+        };
+        let (_, json) = response_json(
+            get_neighborhood(
+                State(state),
+                HeaderMap::new(),
+                Path(a.as_u64()),
+                Query(params),
+            )
+            .await,
+        )
+        .await;
+        assert_eq!(json["data"]["edges"].as_array().unwrap().len(), 1);
+        assert_eq!(json["data"]["truncated"], true);
+    }
+
+    #[tokio::test]
+    async fn test_transfer_volume_respects_block_window() {
+        let (state, _dir) = test_state();
+        let graph = state.catalog.current_graph();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        let b = graph.add_account("0xbbb".to_string()).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(100), 10).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(200), 20).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(400), 30).unwrap();
+        // 反向转账不计入
+        graph.add_transfer(b, a, TokenAmount::from_u64(800), 20).unwrap();
+
+        let params = VolumeParams {
+            from: "0xaaa".to_string(),
+            to: "0xbbb".to_string(),
+            from_block: Some(15),
+            to_block: Some(30),
+            exclude_missing_amount: false,
+        };
+        let (status, json) =
+            response_json(transfer_volume(State(state.clone()), Query(params)).await).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["data"]["total_volume"], "600");
+        assert_eq!(json["data"]["transfer_count"], 2);
+
+        let params = VolumeParams {
+            from: "0xaaa".to_string(),
+            to: "0xbbb".to_string(),
+            from_block: None,
+            to_block: None,
+            exclude_missing_amount: false,
+        };
+        let (_, json) =
+            response_json(transfer_volume(State(state.clone()), Query(params)).await).await;
+        assert_eq!(json["data"]["total_volume"], "700");
+        assert_eq!(json["data"]["transfer_count"], 3);
+
+        let params = VolumeParams {
+            from: "0xaaa".to_string(),
+            to: "0xzzz".to_string(),
+            from_block: None,
+            to_block: None,
+            exclude_missing_amount: false,
+        };
+        let (status, _) = response_json(transfer_volume(State(state), Query(params)).await).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_transfer_volume_excludes_missing_amounts() {
+        let (state, _dir) = test_state();
+        let script = "SESSION SET missing_amount = 'absent'; \
+            INSERT (a:Account {address: '0xaaa'})-[:Transfer {amount: 0}]->(b:Account {address: '0xbbb'}); \
+            INSERT (a:Account {address: '0xaaa'})-[:Transfer]->(b:Account {address: '0xbbb'})";
+        let response =
+            execute_query(State(state.clone()), Json(query_request(script, false))).await;
+        let (status, _) = response_json(response).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let volume = |exclude_missing_amount| {
+            let params = VolumeParams {
+                from: "0xaaa".to_string(),
+                to: "0xbbb".to_string(),
+                from_block: None,
+                to_block: None,
+                exclude_missing_amount,
+            };
+            transfer_volume(State(state.clone()), Query(params))
+        };
+        // 显式的 0 金额计入，未记录金额的转账可以排除
+        let (_, json) = response_json(volume(true).await).await;
+        assert_eq!(json["data"]["total_volume"], "0");
+        assert_eq!(json["data"]["transfer_count"], 1);
+        let (_, json) = response_json(volume(false).await).await;
+        assert_eq!(json["data"]["transfer_count"], 2);
+
+        // 默认设置下缺省金额记为 0，无法排除
+        let script =
+            "INSERT (a:Account {address: '0xaaa'})-[:Transfer]->(b:Account {address: '0xbbb'})";
+        execute_query(State(state.clone()), Json(query_request(script, false))).await;
+        let (_, json) = response_json(volume(true).await).await;
+        assert_eq!(json["data"]["transfer_count"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_transfer_timeseries_buckets() {
+        let (state, _dir) = test_state();
+        let graph = state.catalog.current_graph();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        let b = graph.add_account("0xbbb".to_string()).unwrap();
+        graph.add_transfer(b, a, TokenAmount::from_u64(100), 5).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(30), 9).unwrap();
+        graph.add_transfer(b, a, TokenAmount::from_u64(50), 10).unwrap();
+        graph.add_transfer(b, a, TokenAmount::from_u64(70), 19).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(5), 35).unwrap();
+
+        let params = TimeseriesParams {
+            address: "0xaaa".to_string(),
+            bucket: 10,
+        };
+        let (status, json) =
+            response_json(transfer_timeseries(State(state), Query(params)).await).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let buckets = json["data"]["buckets"].as_array().unwrap();
+        assert_eq!(buckets.len(), 3);
+        assert_eq!(buckets[0]["start_block"], 0);
+        assert_eq!(buckets[0]["end_block"], 9);
+        assert_eq!(buckets[0]["inflow"], "100");
+        assert_eq!(buckets[0]["outflow"], "30");
+        assert_eq!(buckets[1]["start_block"], 10);
+        assert_eq!(buckets[1]["end_block"], 19);
+        assert_eq!(buckets[1]["inflow"], "120");
+        assert_eq!(buckets[1]["inflow_count"], 2);
+        assert_eq!(buckets[1]["outflow_count"], 0);
+        assert_eq!(buckets[2]["start_block"], 30);
+        assert_eq!(buckets[2]["outflow"], "5");
+        assert_eq!(json["data"]["truncated"], false);
+    }
+
+    fn query_request(query: &str, confirm_destructive: bool) -> QueryRequest {
+        QueryRequest {
+            query: query.to_string(),
+            amount_decimals: None,
+            float_precision: None,
+            confirm_destructive,
+            params: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_formats_token_amounts() {
+        let (state, _dir) = test_state();
+        let graph = state.catalog.current_graph();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        let b = graph.add_account("0xbbb".to_string()).unwrap();
+        let amount = TokenAmount::from_dec_str("1500000000000000000").unwrap();
+        graph.add_transfer(a, b, amount, 1).unwrap();
+        let query = "MATCH (x:Account)-[t:Transfer]->(y:Account) RETURN t, t.amount, x";
+
+        let mut req = query_request(query, false);
+        req.amount_decimals = Some(18);
+        let (status, json) = response_json(execute_query(State(state.clone()), Json(req)).await).await;
+        assert_eq!(status, StatusCode::OK);
+        let data = &json["data"];
+        // rows 保持原样，仍可按 QueryResult 反序列化
+        assert_eq!(
+            data["rows"][0][1],
+            serde_json::json!({ "Scalar": { "TokenAmount": "1500000000000000000" } })
+        );
+        assert!(serde_json::from_value::<QueryResult>(data.clone()).is_ok());
+        // formatted 与 rows 逐格对应
+        assert_eq!(data["formatted"][0][0]["amount"], "1.5");
+        assert_eq!(data["formatted"][0][1], "1.5");
+        assert!(data["formatted"][0][2].is_null());
+
+        // 未指定精度时不附带 formatted
+        let req = query_request(query, false);
+        let (_, json) = response_json(execute_query(State(state), Json(req)).await).await;
+        assert!(json["data"].get("formatted").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_query_batch_returns_ordered_results() {
+        let (state, _dir) = test_state();
+        let graph = state.catalog.current_graph();
+        graph.add_account("0xaaa".to_string()).unwrap();
+        graph.add_account("0xbbb".to_string()).unwrap();
+
+        let lookup_query = "MATCH (n:Account) WHERE n.address = $addr RETURN n.address";
+        let mut lookup = query_request(lookup_query, false);
+        let addr = serde_json::json!("0xbbb");
+        lookup.params.insert("addr".into(), addr);
+        let requests = vec![
+            query_request("MATCH (n:Account) RETURN count(n) AS total", false),
+            query_request("MATCH (n:Account RETURN n", false),
+            // 前面的 SESSION SET 对后续查询可见
+            query_request("SESSION SET first = '0xaaa'", false),
+            query_request(&lookup_query.replace("$addr", "$first"), false),
+            query_request("MATCH (n:Account) DETACH DELETE n", false),
+            lookup,
+        ];
+        let (status, json) =
+            response_json(query_batch(State(state.clone()), Json(requests)).await).await;
+        assert_eq!(status, StatusCode::OK);
+        let items = json["data"].as_array().unwrap();
+        assert_eq!(items.len(), 6);
+
+        let rows = |i: usize| items[i]["data"]["rows"].as_array().unwrap().clone();
+        assert_eq!(items[0]["success"], true);
+        assert_eq!(rows(0)[0][0]["Scalar"]["Integer"], 2);
+        // 解析失败只影响该条
+        assert_eq!(items[1]["success"], false);
+        assert_eq!(items[1]["code"], "PARSE_ERROR");
+        assert_eq!(items[2]["success"], true);
+        assert_eq!(rows(3).len(), 1);
+        assert_eq!(items[4]["success"], false);
+        let error = items[4]["error"].as_str().unwrap();
+        assert!(error.contains("confirm_destructive"));
+        assert_eq!(rows(5)[0][0]["Scalar"]["String"], "0xbbb");
+        assert_eq!(graph.vertex_count(), 2);
+        // 每项附带单独请求 /query 时的状态码
+        let statuses: Vec<_> = items.iter().map(|item| item["status"].clone()).collect();
+        assert_eq!(statuses, [200, 400, 200, 200, 400, 200]);
+    }
+
+    #[tokio::test]
+    async fn test_query_batch_scopes_request_settings_to_items() {
+        let (mut state, _dir) = test_state();
+        state.max_query_length = 96;
+        let graph = state.catalog.current_graph();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        let mut vertex = graph.get_vertex(a).unwrap();
+        vertex.set_property("rate".to_string(), PropertyValue::Float(0.123456));
+        graph.update_vertex(vertex).unwrap();
+
+        let lookup = "MATCH (n:Account) WHERE n.address = $addr RETURN n.rate";
+        let mut first = query_request(lookup, false);
+        first.params.insert("addr".into(), serde_json::json!("0xaaa"));
+        first.float_precision = Some(2);
+        let mut second = query_request(lookup, false);
+        second.params.insert("addr".into(), serde_json::json!("0xaaa"));
+        let long_query = format!("MATCH (n) WHERE n.address = '{}' RETURN n", "a".repeat(64));
+        let requests = vec![
+            first,
+            second,
+            query_request(lookup, false),
+            query_request(&long_query, false),
+        ];
+        let (status, json) = response_json(query_batch(State(state), Json(requests)).await).await;
+        assert_eq!(status, StatusCode::OK);
+        let items = json["data"].as_array().unwrap();
+
+        let rows = |i: usize| items[i]["data"]["rows"].as_array().unwrap().clone();
+        assert_eq!(rows(0)[0][0]["Scalar"]["Float"], 0.12);
+        // 上一项的精度与参数不会带到后续各项
+        assert_eq!(rows(1)[0][0]["Scalar"]["Float"], 0.123456);
+        assert!(rows(2).is_empty());
+        // 超长查询与 /query 一样返回 413
+        assert_eq!(items[3]["success"], false);
+        assert_eq!(items[3]["status"], 413);
+    }
+
+    #[tokio::test]
+    async fn test_destructive_query_requires_confirmation() {
+        let (state, _dir) = test_state();
+        state.catalog.create_graph("scratch").unwrap();
+
+        for query in ["MATCH (n:Account) DETACH DELETE n", "DROP GRAPH scratch"] {
+            let req = query_request(query, false);
+            let (status, json) =
+                response_json(execute_query(State(state.clone()), Json(req)).await).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", query);
+            assert!(json["error"].as_str().unwrap().contains("confirm_destructive"));
+        }
+        assert!(state.catalog.list_graphs().contains(&"scratch".to_string()));
+
+        let req = query_request("DROP GRAPH scratch", true);
+        let (status, _) = response_json(execute_query(State(state.clone()), Json(req)).await).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(!state.catalog.list_graphs().contains(&"scratch".to_string()));
+
+        // 带 WHERE 的 DELETE 不需要确认
+        let req = query_request(
+            "MATCH (n:Account) WHERE n.address = '0xaaa' DELETE n",
+            false,
+        );
+        let (status, _) = response_json(execute_query(State(state), Json(req)).await).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_delete_by_query_preview_and_confirm() {
+        let (state, _dir) = test_state();
+        let graph = state.catalog.current_graph();
+        let keep = graph.add_account("0xkeep".to_string()).unwrap();
+        for i in 0..3 {
+            let id = graph.add_account(format!("0xtest{}", i)).unwrap();
+            let mut vertex = graph.get_vertex(id).unwrap();
+            vertex.set_property("tag".to_string(), PropertyValue::String("test".to_string()));
+            graph.update_vertex(vertex).unwrap();
+            graph
+                .add_transfer(keep, id, TokenAmount::from_u64(1), i)
+                .unwrap();
+        }
+        let request = |preview: bool, confirm_destructive: bool| DeleteByQueryRequest {
+            query: "MATCH (n:Account {tag: 'test'}) RETURN n".to_string(),
+            preview,
+            confirm_destructive,
+        };
+
+        // 预览只统计，不删除
+        let (status, json) =
+            response_json(delete_by_query(State(state.clone()), Json(request(true, false))).await)
+                .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["data"]["preview"], true);
+        assert_eq!(json["data"]["vertices"], 3);
+        assert_eq!(json["data"]["edges"], 3);
+        assert_eq!(graph.vertex_count(), 4);
+
+        // 未确认的实际删除被拒绝
+        let (status, _) =
+            response_json(delete_by_query(State(state.clone()), Json(request(false, false))).await)
+                .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(graph.vertex_count(), 4);
+
+        let (status, json) =
+            response_json(delete_by_query(State(state.clone()), Json(request(false, true))).await)
+                .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["data"]["vertices"], 3);
+        assert_eq!(graph.vertex_count(), 1);
+        assert_eq!(graph.edge_count(), 0);
+        assert!(graph.get_vertex(keep).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_batch_get_vertices_rejects_oversized_batch() {
+        let (state, _dir) = test_state();
+        let req = BatchVertexRequest {
+            ids: (0..=MAX_VERTEX_BATCH_SIZE as u64).collect(),
+            addresses: Vec::new(),
+        };
+        let (status, json) = response_json(batch_get_vertices(State(state), Json(req)).await).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(json["success"], false);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_request_cancels_all_paths() {
+        let (state, _dir) = test_state();
+        let graph = state.catalog.current_graph();
+
+        // 完全图 + 不可达终点：不取消的话 DFS 要枚举数亿条简单路径
+        let vertices: Vec<VertexId> = (0..12)
+            .map(|_| graph.add_vertex(VertexLabel::Account).unwrap())
+            .collect();
+        for &u in &vertices {
+            for &v in &vertices {
+                if u != v {
+                    graph.add_transfer(u, v, TokenAmount::from_u64(1), 1).unwrap();
+                }
+            }
+        }
+        let unreachable = graph.add_vertex(VertexLabel::Account).unwrap();
+
+        let cancel = CancellationToken::new();
+        let finder = PathFinder::new(graph).with_cancellation(cancel.clone());
+        let (tx, rx) = std::sync::mpsc::channel();
+        let request = run_cancellable(cancel, move || {
+            let result = finder.all_paths(vertices[0], unreachable, 12);
+            let cancelled = matches!(result, Err(Error::Cancelled));
+            tx.send(cancelled).unwrap();
+            result
+        });
+
+        // 超时会丢弃请求 future，等同于客户端断开连接
+        let timed_out =
+            tokio::time::timeout(std::time::Duration::from_millis(50), request).await;
+        assert!(timed_out.is_err());
+
+        let cancelled = rx
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .expect("计算应在请求被丢弃后停止");
+        assert!(cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_stats_label_breakdown() {
+        let (state, _dir) = test_state();
+        let graph = state.catalog.current_graph();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        let b = graph.add_account("0xbbb".to_string()).unwrap();
+        let token = graph.add_vertex(VertexLabel::Token).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(1), 1).unwrap();
+        graph.add_transfer(b, a, TokenAmount::from_u64(2), 2).unwrap();
+        graph.add_edge(EdgeLabel::Approve, a, token).unwrap();
+
+        let response = stats_handler(State(state)).await.into_response();
+        let (status, body) = response_json(response).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body["graph"]["vertices_by_label"],
+            serde_json::json!({"Account": 2, "Token": 1})
+        );
+        assert_eq!(
+            body["graph"]["edges_by_type"],
+            serde_json::json!({"Approve": 1, "Transfer": 2})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_graph_version_in_results_and_stats() {
+        let (state, _dir) = test_state();
+        let version = |query: &str| {
+            let req = query_request(query, false);
+            let state = state.clone();
+            async move {
+                let (_, json) = response_json(execute_query(State(state), Json(req)).await).await;
+                json["data"]["stats"]["graph_version"].as_u64().unwrap()
+            }
+        };
+
+        let before = version("MATCH (n:Account) RETURN n").await;
+        let after = version("INSERT (a:Account {address: '0xaaa'})").await;
+        assert!(after > before);
+        // 只读查询不改变版本
+        assert_eq!(version("MATCH (n:Account) RETURN n").await, after);
+
+        let response = stats_handler(State(state.clone())).await.into_response();
+        let (_, body) = response_json(response).await;
+        assert_eq!(body["graph"]["version"], after);
+    }
+
+    #[tokio::test]
+    async fn test_conditional_get_etag() {
+        let (state, _dir) = test_state();
+        let graph = state.catalog.current_graph();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        let fetch = |etag: Option<&str>| {
+            let mut headers = HeaderMap::new();
+            if let Some(etag) = etag {
+                headers.insert(header::IF_NONE_MATCH, etag.parse().unwrap());
+            }
+            get_vertex(State(state.clone()), headers, Path(a.as_u64()))
+        };
+
+        let response = fetch(None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[header::ETAG]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        // 图未变化时重复请求返回 304，不带响应体
+        let response = fetch(Some(&etag)).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.is_empty());
+
+        // 写入后同一个 ETag 失效，返回新的响应体与新的 ETag
+        let mut vertex = graph.get_vertex(a).unwrap();
+        vertex.set_property("risk".to_string(), PropertyValue::Integer(9));
+        graph.update_vertex(vertex).unwrap();
+        let response = fetch(Some(&etag)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ne!(response.headers()[header::ETAG], etag.as_str());
+        let (_, json) = response_json(response).await;
+        assert_eq!(json["data"]["properties"]["risk"]["Integer"], 9);
+
+        // 不存在的顶点照常返回 404，不附带 ETag
+        let response = get_vertex(State(state.clone()), HeaderMap::new(), Path(999)).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(response.headers().get(header::ETAG).is_none());
+
+        // `*` 只对存在的资源返回 304
+        assert_eq!(fetch(Some("*")).await.status(), StatusCode::NOT_MODIFIED);
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, "*".parse().unwrap());
+        let response = get_vertex(State(state.clone()), headers, Path(999)).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_etag_changes_after_reopen() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let etag = |graph: &Graph| {
+            let response =
+                conditional_get(graph, &HeaderMap::new(), || StatusCode::OK.into_response());
+            response.headers()[header::ETAG]
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+
+        let before = {
+            let graph = Graph::open(dir.path(), Some(64)).unwrap();
+            graph.add_account("0xaaa".to_string()).unwrap();
+            graph.flush().unwrap();
+            etag(&graph)
+        };
+        // 重启后版本计数从头开始，ETag 仍不能与重启前的相同
+        let graph = Graph::open(dir.path(), Some(64)).unwrap();
+        graph.add_account("0xbbb".to_string()).unwrap();
+        assert_ne!(etag(&graph), before);
+    }
+
+    #[tokio::test]
+    async fn test_active_transactions_listing() {
+        let (state, _dir) = test_state();
+
+        // 长期存在的会话（如 CLI）打开的事务会被列出
+        let session = QueryExecutor::new(state.catalog.clone());
+        let start = GqlParser::new("START TRANSACTION READ WRITE")
+            .parse()
+            .unwrap();
+        session.execute(&start).unwrap();
+
+        let response = active_transactions(State(state.clone())).await.into_response();
+        let (status, body) = response_json(response).await;
+        assert_eq!(status, StatusCode::OK);
+        let listed = body["data"].as_array().unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0]["access_mode"], "READ WRITE");
+
+        // 其他请求不能提交该会话的事务
+        let req = query_request("COMMIT", false);
+        let (status, _) = response_json(execute_query(State(state.clone()), Json(req)).await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // 单次请求中未结束的事务在请求结束时回滚
+        drop(session);
+        let req = query_request("START TRANSACTION READ WRITE", false);
+        let (status, _) = response_json(execute_query(State(state.clone()), Json(req)).await).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let response = active_transactions(State(state)).await.into_response();
+        let (_, body) = response_json(response).await;
+        assert!(body["data"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_admin_checkpoint_flushes_dirty_pages() {
+        let (mut state, _dir) = test_state();
+        let graph = state.catalog.current_graph();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        let b = graph.add_account("0xbbb".to_string()).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(1), 1).unwrap();
+
+        // 未配置密钥时管理接口禁用
+        let response = admin_checkpoint(State(state.clone()), HeaderMap::new()).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        state.admin_api_key = Some("secret".to_string());
+        // 错误密钥、前缀与超长密钥都被拒绝
+        for guess in ["guess", "secre", "secret2", ""] {
+            let mut wrong = HeaderMap::new();
+            wrong.insert("x-api-key", guess.parse().unwrap());
+            let response = admin_checkpoint(State(state.clone()), wrong).await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "secret".parse().unwrap());
+        let storage = |state: AppState, headers: HeaderMap| async move {
+            response_json(admin_storage(State(state), headers).await).await
+        };
+        let (status, before) = storage(state.clone(), headers.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        let dirty = before["data"]["dirty_pages"].as_u64().unwrap();
+        assert!(dirty > 0);
+        assert!(before["data"]["file_size"].as_u64().unwrap() > 0);
+        assert_eq!(before["data"]["watermark"]["status"], "Normal");
+
+        let response = admin_checkpoint(State(state.clone()), headers.clone()).await;
+        let (status, body) = response_json(response).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["data"]["pages_flushed"].as_u64().unwrap() >= dirty);
+
+        let (_, after) = storage(state, headers).await;
+        assert_eq!(after["data"]["dirty_pages"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_over_length_query_rejected() {
+        let (mut state, _dir) = test_state();
+        state.max_query_length = 32;
+
+        let long_query = format!("MATCH (n:Account) WHERE n.address = '{}' RETURN n", "a".repeat(64));
+        let req = query_request(&long_query, false);
+        let (status, json) = response_json(execute_query(State(state.clone()), Json(req)).await).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(json["error"].as_str().unwrap().contains("32"));
+
+        let req = query_request("MATCH (n) RETURN n", false);
+        let (status, _) = response_json(execute_query(State(state), Json(req)).await).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_sanitized_errors_hide_details() {
+        let (mut state, _dir) = test_state();
+        let bad = "MATCH (n:Account) WHERE n.secret_column = RETURN n";
+
+        // 默认返回完整信息与错误码
+        let req = query_request(bad, false);
+        let (status, json) =
+            response_json(execute_query(State(state.clone()), Json(req)).await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(json["error"].as_str().unwrap().len() > "解析错误".len());
+        assert_eq!(json["code"], "PARSE_ERROR");
+
+        // 生产模式只返回通用提示与错误码
+        state.verbose_errors = false;
+        let req = query_request(bad, false);
+        let (status, json) =
+            response_json(execute_query(State(state.clone()), Json(req)).await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(json["error"], "解析错误");
+        assert_eq!(json["code"], "PARSE_ERROR");
+
+        let req = query_request(
+            "MATCH (n:Account) USING INDEX n:Account(address) RETURN n",
+            false,
+        );
+        let (status, json) = response_json(execute_query(State(state), Json(req)).await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(json["error"], "执行错误");
+        assert_eq!(json["code"], "QUERY_ERROR");
+    }
+
+    #[tokio::test]
+    async fn test_algorithms_accept_addresses() {
+        let (state, _dir) = test_state();
+        let graph = state.catalog.current_graph();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        let b = graph.add_account("0xbbb".to_string()).unwrap();
+        let c = graph.add_account("0xccc".to_string()).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(5), 1).unwrap();
+        graph.add_transfer(b, c, TokenAmount::from_u64(3), 2).unwrap();
+
+        let path_request = |source: Option<u64>, source_address: Option<&str>| PathRequest {
+            source,
+            source_address: source_address.map(str::to_string),
+            target: None,
+            target_address: Some("0xccc".to_string()),
+            max_depth: default_max_depth(),
+            k: default_k(),
+            monotonic_blocks: false,
+        };
+        let ids = |json: &serde_json::Value| json["data"]["vertices"].clone();
+        let shortest = |req| shortest_path(State(state.clone()), Json(req));
+
+        let by_address = path_request(None, Some("0xaaa"));
+        let (status, by_address) = response_json(shortest(by_address).await).await;
+        assert_eq!(status, StatusCode::OK);
+        let expected = serde_json::json!([a.as_u64(), b.as_u64(), c.as_u64()]);
+        assert_eq!(ids(&by_address), expected);
+        assert_eq!(by_address["data"]["total_amount"], "8");
+        assert_eq!(by_address["data"]["min_block"], 1);
+        assert_eq!(by_address["data"]["max_block"], 2);
+        // 数字 ID 与地址可以混用
+        let by_id = path_request(Some(a.as_u64()), None);
+        let (_, by_id) = response_json(shortest(by_id).await).await;
+        assert_eq!(ids(&by_id), expected);
+
+        let request = path_request(None, Some("0xaaa"));
+        let (_, json) = response_json(all_paths(State(state.clone()), Json(request)).await).await;
+        assert_eq!(json["data"].as_array().unwrap().len(), 1);
+
+        let request = MaxFlowRequest {
+            source: None,
+            source_address: Some("0xaaa".to_string()),
+            sink: None,
+            sink_address: Some("0xzzz".to_string()),
+            algorithm: MaxFlowAlgorithm::default(),
+        };
+        let response = max_flow(State(state.clone()), Json(request)).await;
+        let (status, json) = response_json(response).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(json["error"].as_str().unwrap().contains("0xzzz"));
+
+        let request = TraceRequest {
+            start: None,
+            start_address: Some("0xbbb".to_string()),
+            direction: TraceDirection::Forward,
+            max_depth: 3,
+            max_fanout: None,
+            include_tags: false,
+        };
+        let (_, json) =
+            response_json(trace_path(State(state.clone()), Ok(Json(request))).await).await;
+        let expected = serde_json::json!([b.as_u64(), c.as_u64()]);
+        assert_eq!(json["data"][0]["vertices"], expected);
+
+        // 无法解析的地址返回 404，ID 与地址同时给出或都缺失返回 400
+        let unknown = path_request(None, Some("0xzzz"));
+        let (status, json) = response_json(shortest(unknown).await).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(json["error"].as_str().unwrap().contains("0xzzz"));
+        let both = path_request(Some(a.as_u64()), Some("0xaaa"));
+        let (status, _) = response_json(shortest(both).await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = response_json(shortest(path_request(None, None)).await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_direction_rejected() {
+        use axum::extract::FromRequest;
+
+        let (state, _dir) = test_state();
+        let graph = state.catalog.current_graph();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        let b = graph.add_account("0xbbb".to_string()).unwrap();
+        graph.add_transfer(b, a, TokenAmount::from_u64(1), 1).unwrap();
+
+        let trace = |direction: &str| {
+            let body = serde_json::json!({ "start": a.as_u64(), "direction": direction });
+            let request = axum::http::Request::builder()
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap();
+            let state = state.clone();
+            async move {
+                let payload = Json::<TraceRequest>::from_request(request, &()).await;
+                response_json(trace_path(State(state), payload).await).await
+            }
+        };
+
+        // 拼写错误不再静默回退为 forward
+        let (status, json) = trace("forwrd").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(json["error"].as_str().unwrap().contains("forwrd"));
+        let (status, json) = trace("backward").await;
+        assert_eq!(status, StatusCode::OK);
+        let expected = serde_json::json!([a.as_u64(), b.as_u64()]);
+        assert_eq!(json["data"][0]["vertices"], expected);
+
+        // 查询参数中的方向同样校验
+        let uri = |query: &str| format!("http://localhost/?{}", query).parse().unwrap();
+        let rejection =
+            Query::<TraceRequest>::try_from_uri(&uri("start=1&direction=up")).unwrap_err();
+        assert_eq!(rejection.status(), StatusCode::BAD_REQUEST);
+        let rejection =
+            Query::<NeighborhoodParams>::try_from_uri(&uri("direction=sideways")).unwrap_err();
+        assert_eq!(rejection.status(), StatusCode::BAD_REQUEST);
+        let Query(params) =
+            Query::<NeighborhoodParams>::try_from_uri(&uri("direction=outgoing")).unwrap();
+        assert_eq!(params.direction, NeighborhoodDirection::Out);
+    }
+
+    #[tokio::test]
+    async fn test_trace_stream_matches_batch() {
+        let (state, _dir) = test_state();
+        let graph = state.catalog.current_graph();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        let b = graph.add_account("0xbbb".to_string()).unwrap();
+        let c = graph.add_account("0xccc".to_string()).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(1), 1).unwrap();
+        graph.add_transfer(b, c, TokenAmount::from_u64(2), 2).unwrap();
+        graph.add_transfer(a, c, TokenAmount::from_u64(3), 3).unwrap();
+
+        let request = || TraceRequest {
+            start: Some(a.as_u64()),
+            start_address: None,
+            direction: TraceDirection::Forward,
+            max_depth: 3,
+            max_fanout: None,
+            include_tags: false,
+        };
+        let batch = trace_path(State(state.clone()), Ok(Json(request())))
+            .await
+            .into_response();
+        let (_, batch) = response_json(batch).await;
+
+        let stream = trace_path_stream(State(state), Query(request()))
+            .await
+            .into_response();
+        assert_eq!(stream.headers()["content-type"], "text/event-stream");
+        let body = axum::body::to_bytes(stream.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        // 每个事件形如 "event: <name>\ndata: <json>"
+        let events: Vec<(String, serde_json::Value)> = body
+            .split("\n\n")
+            .filter(|chunk| !chunk.is_empty())
+            .map(|chunk| {
+                let field = |name: &str| {
+                    chunk
+                        .lines()
+                        .find_map(|line| line.strip_prefix(name))
+                        .unwrap()
+                        .to_string()
+                };
+                let data = serde_json::from_str(&field("data: ")).unwrap();
+                (field("event: "), data)
+            })
+            .collect();
+
+        let (done, paths) = events.split_last().unwrap();
+        assert_eq!(done.0, "done");
+        assert_eq!(done.1["paths"], 3);
+        let streamed: Vec<serde_json::Value> = paths
+            .iter()
+            .map(|(name, data)| {
+                assert_eq!(name, "path");
+                data.clone()
+            })
+            .collect();
+        assert_eq!(serde_json::Value::Array(streamed), batch["data"]);
+    }
+
+    #[tokio::test]
+    async fn test_address_tags_surface_in_results() {
+        let (state, _dir) = test_state();
+        let graph = state.catalog.current_graph();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        let b = graph.add_account("0xbbb".to_string()).unwrap();
+        let c = graph.add_account("0xccc".to_string()).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(1), 1).unwrap();
+        graph.add_transfer(b, c, TokenAmount::from_u64(2), 2).unwrap();
+
+        let tag = |address: &str, tag: &str| {
+            let req = AddressTagRequest {
+                tag: tag.to_string(),
+            };
+            set_address_tag(State(state.clone()), Path(address.to_string()), Json(req))
+        };
+        let (status, json) = response_json(tag("0xbbb", "exchange").await).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["data"]["tag"], "exchange");
+        let (status, _) = response_json(tag("0xccc", "  ").await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // 查询中用 tag(n) 取标记
+        let query = "MATCH (n:Account) WHERE tag(n) = 'exchange' RETURN n.address, tag(n) AS tag";
+        let req = query_request(query, false);
+        let (_, json) = response_json(execute_query(State(state.clone()), Json(req)).await).await;
+        let rows = json["data"]["rows"].as_array().unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][0]["Scalar"]["String"], "0xbbb");
+        assert_eq!(rows[0][1]["Scalar"]["String"], "exchange");
+
+        // 追踪结果按需附带标记
+        let trace = |include_tags: bool| {
+            let request = TraceRequest {
+                start: Some(a.as_u64()),
+                start_address: None,
+                direction: TraceDirection::Forward,
+                max_depth: 3,
+                max_fanout: None,
+                include_tags,
+            };
+            trace_path(State(state.clone()), Ok(Json(request)))
+        };
+        let (_, json) = response_json(trace(true).await).await;
+        let path = &json["data"][0];
+        assert_eq!(path["vertices"], serde_json::json!([a.as_u64(), b.as_u64()]));
+        let expected = serde_json::json!({ b.as_u64().to_string(): "exchange" });
+        assert_eq!(path["tags"], expected);
+        let (_, json) = response_json(trace(false).await).await;
+        assert!(json["data"][0].get("tags").is_none());
+
+        let remove = || remove_address_tag(State(state.clone()), Path("0xbbb".to_string()));
+        let (status, _) = response_json(remove().await).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = response_json(remove().await).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(graph.get_address_tag("0xbbb"), None);
+    }
+
+    #[tokio::test]
+    async fn test_json_field_case() {
+        let (state, _dir) = test_state();
+        let graph = state.catalog.current_graph();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        let mut vertex = graph.get_vertex(a).unwrap();
+        vertex.set_property(
+            "token_symbol".to_string(),
+            PropertyValue::String("USDT".to_string()),
+        );
+        graph.update_vertex(vertex).unwrap();
+
+        let fetch = || get_vertex(State(state.clone()), HeaderMap::new(), Path(a.as_u64()));
+        let (status, json) = response_json(fetch().await).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(json["data"].get("page_id").is_some(), "{}", json);
+
+        // camelCase 只改写字段名，属性名保持原样
+        let (status, json) = response_json(camel_case_response(fetch().await).await).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(json["data"].get("pageId").is_some(), "{}", json);
+        assert!(json["data"].get("page_id").is_none());
+        assert_eq!(json["data"]["properties"]["token_symbol"]["String"], "USDT");
+
+        let from_accept = JsonFieldCase::from_accept;
+        assert_eq!(
+            from_accept("application/json; case=camel"),
+            Some(JsonFieldCase::Camel)
+        );
+        assert_eq!(
+            from_accept("text/html, application/json;case=\"snake\""),
+            Some(JsonFieldCase::Snake)
+        );
+        assert_eq!(from_accept("application/json"), None);
+        assert_eq!(from_accept("application/json; case=kebab"), None);
+        assert_eq!(to_camel_case("total_weight"), "totalWeight");
+        assert_eq!(to_camel_case("_id"), "_id");
+    }
+
+    #[tokio::test]
+    async fn test_query_multiple_statements() {
+        let (state, _dir) = test_state();
+        let query = "INSERT (a:Account {address: '0xaaa'}); MATCH (n:Account) RETURN n;";
+        let req = query_request(query, false);
+        let (status, json) =
+            response_json(execute_query(State(state.clone()), Json(req)).await).await;
+        assert_eq!(status, StatusCode::OK);
+        let results = json["data"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1]["rows"].as_array().unwrap().len(), 1);
+
+        // 遇到错误即停止，错误信息指出出错的语句
+        let query = "INSERT (b:Account {address: '0xbbb'}); USE GRAPH missing; INSERT (c:Account)";
+        let req = query_request(query, false);
+        let (status, json) =
+            response_json(execute_query(State(state.clone()), Json(req)).await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(json["error"].as_str().unwrap().contains("第 2 条语句"));
+        assert_eq!(state.catalog.current_graph().vertex_count(), 2);
+
+        // 任一语句具有破坏性时整批拒绝
+        let req = query_request("MATCH (n) RETURN n; DROP GRAPH default", false);
+        let (status, _) = response_json(execute_query(State(state), Json(req)).await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_max_flow_cap_matches_call() {
+        let (mut state, _dir) = test_state();
+        state.max_flow_edges = 3;
+        let graph = state.catalog.current_graph();
+        let s = graph.add_account("0xaaa".to_string()).unwrap();
+        let t = graph.add_account("0xfff".to_string()).unwrap();
+        // 四条并行路径 s -> m_i -> t，共 8 条有流量的边
+        for i in 1..=4u64 {
+            let m = graph.add_account(format!("0xb{}", i)).unwrap();
+            let amount = TokenAmount::from_u64(i * 10);
+            graph.add_transfer(s, m, amount, i).unwrap();
+            graph.add_transfer(m, t, amount, i).unwrap();
+        }
+
+        let request = MaxFlowRequest {
+            source: None,
+            source_address: Some("0xaaa".to_string()),
+            sink: None,
+            sink_address: Some("0xfff".to_string()),
+            algorithm: MaxFlowAlgorithm::default(),
+        };
+        let response = max_flow(State(state.clone()), Json(request)).await;
+        let (status, json) = response_json(response).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["data"]["value"], 100.0);
+        assert_eq!(json["data"]["truncated"], true);
+        let rest: Vec<String> = json["data"]["flow"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| format!("{} -> {}", e["source"], e["target"]))
+            .collect();
+        assert_eq!(rest.len(), 3);
+
+        // CALL max_flow 使用同一上限，列出相同的边
+        let query = format!("CALL max_flow({}, {})", s.as_u64(), t.as_u64());
+        let req = query_request(&query, false);
+        let (_, json) = response_json(execute_query(State(state), Json(req)).await).await;
+        let rows = json["data"]["rows"].as_array().unwrap();
+        let cell = |row: &serde_json::Value, i: usize| row[i]["Scalar"].clone();
+        let call: Vec<String> = rows[1..]
+            .iter()
+            .map(|row| cell(row, 0)["String"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(call, rest);
+        assert_eq!(json["data"]["stats"]["truncated"], true);
+    }
+
+    #[tokio::test]
+    async fn test_query_float_precision() {
+        let (state, _dir) = test_state();
+        let query = "MATCH (n:Account) RETURN n";
+        let graph = state.catalog.current_graph();
+        let id = graph.add_account("0xaaa".to_string()).unwrap();
+        let mut vertex = graph.get_vertex(id).unwrap();
+        vertex.set_property("rate".to_string(), PropertyValue::Float(0.1 + 0.2));
+        graph.update_vertex(vertex).unwrap();
+
+        let rate = |json: &serde_json::Value| {
+            json["data"]["rows"][0][0]["Vertex"]["properties"]["rate"]["Float"].clone()
+        };
+        let req = query_request(query, false);
+        let (_, json) = response_json(execute_query(State(state.clone()), Json(req)).await).await;
+        assert_eq!(rate(&json), serde_json::json!(0.30000000000000004));
+
+        let mut req = query_request(query, false);
+        req.float_precision = Some(2);
+        let (status, json) = response_json(execute_query(State(state), Json(req)).await).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(rate(&json), serde_json::json!(0.3));
+    }
+
+    #[tokio::test]
+    async fn test_edges_between_with_block_range() {
+        let (state, _dir) = test_state();
+        let graph = state.catalog.current_graph();
+        let a = graph.add_account("0xaaa".to_string()).unwrap();
+        let b = graph.add_account("0xbbb".to_string()).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(100), 10).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(200), 20).unwrap();
+        graph.add_transfer(a, b, TokenAmount::from_u64(400), 30).unwrap();
+        // 反向转账不属于 a -> b
+        graph.add_transfer(b, a, TokenAmount::from_u64(800), 20).unwrap();
+
+        let params = |from_block, to_block| EdgesBetweenParams {
+            src: a.as_u64(),
+            dst: b.as_u64(),
+            from_block,
+            to_block,
+        };
+
+        let (status, json) = response_json(
+            get_edges_between(
+                State(state.clone()),
+                HeaderMap::new(),
+                Query(params(None, None)),
+            )
+            .await,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["data"].as_array().unwrap().len(), 3);
+
+        let (_, json) = response_json(
+            get_edges_between(
+                State(state.clone()),
+                HeaderMap::new(),
+                Query(params(Some(15), Some(30))),
+            )
+            .await,
+        )
+        .await;
+        let edges = json["data"].as_array().unwrap();
+        assert_eq!(edges.len(), 2);
+        let mut amounts: Vec<&str> = edges
+            .iter()
+            .map(|e| e["properties"]["amount"]["TokenAmount"].as_str().unwrap())
+            .collect();
+        amounts.sort();
+        assert_eq!(amounts, vec!["200", "400"]);
+
+        let missing = EdgesBetweenParams {
+            src: a.as_u64(),
+            dst: 9999,
+            from_block: None,
+            to_block: None,
+        };
+        let (status, _) = response_json(
+            get_edges_between(State(state), HeaderMap::new(), Query(missing)).await,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -8,7 +8,8 @@ use crate::storage::disk::DiskStorage;
 use crate::storage::page::{Page, PageType};
 use parking_lot::{Mutex, RwLock};
 use std::collections::{HashMap, VecDeque};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 /// 默认缓冲池大小（页面数）
@@ -107,14 +108,29 @@ pub struct BufferPool {
     free_list: Mutex<VecDeque<usize>>,
     /// 缓冲池大小
     pool_size: usize,
+    /// 页面读取次数（含命中）
+    page_reads: AtomicU64,
 }
 
 impl BufferPool {
     /// 创建缓冲池
     pub fn new<P: AsRef<Path>>(data_dir: P, pool_size: Option<usize>) -> Result<Arc<Self>> {
-        let pool_size = pool_size.unwrap_or(DEFAULT_POOL_SIZE);
-        let disk = DiskStorage::open(data_dir, false)?;
+        Ok(Self::with_disk(
+            DiskStorage::open(data_dir, false)?,
+            pool_size,
+        ))
+    }
+
+    /// 在单个数据文件上创建缓冲池（见 `DiskStorage::open_file`）
+    pub fn open_file<P: AsRef<Path>>(data_path: P, pool_size: Option<usize>) -> Result<Arc<Self>> {
+        Ok(Self::with_disk(
+            DiskStorage::open_file(data_path, false)?,
+            pool_size,
+        ))
+    }
 
+    fn with_disk(disk: Arc<DiskStorage>, pool_size: Option<usize>) -> Arc<Self> {
+        let pool_size = pool_size.unwrap_or(DEFAULT_POOL_SIZE);
         let mut frames = Vec::with_capacity(pool_size);
         let mut free_list = VecDeque::with_capacity(pool_size);
 
@@ -123,14 +139,15 @@ impl BufferPool {
             free_list.push_back(i);
         }
 
-        Ok(Arc::new(Self {
+        Arc::new(Self {
             disk,
             frames,
             page_table: Mutex::new(HashMap::new()),
             replacer: Mutex::new(LRUReplacer::new()),
             free_list: Mutex::new(free_list),
             pool_size,
-        }))
+            page_reads: AtomicU64::new(0),
+        })
     }
 
     /// 创建新页面
@@ -157,6 +174,8 @@ impl BufferPool {
 
     /// 获取页面
     pub fn fetch_page(&self, page_id: u64) -> Result<PageHandle<'_>> {
+        self.page_reads.fetch_add(1, Ordering::Relaxed);
+
         // 检查是否已在缓冲池中
         {
             let page_table = self.page_table.lock();
@@ -278,19 +297,22 @@ impl BufferPool {
         Ok(())
     }
 
-    /// 刷新所有脏页
-    pub fn flush_all(&self) -> Result<()> {
+    /// 刷新所有脏页，返回写回的页面数
+    pub fn flush_all(&self) -> Result<usize> {
         let page_table = self.page_table.lock();
+        let mut flushed = 0;
         for (&_page_id, &frame_id) in page_table.iter() {
             let mut frame = self.frames[frame_id].write();
             if frame.is_dirty {
                 if let Some(ref page) = frame.page {
                     self.disk.write_page(page)?;
                     frame.is_dirty = false;
+                    flushed += 1;
                 }
             }
         }
-        self.disk.sync()
+        self.disk.sync()?;
+        Ok(flushed)
     }
 
     /// 删除页面
@@ -326,6 +348,35 @@ impl BufferPool {
         self.page_table.lock().len()
     }
 
+    /// 尚未写回磁盘的脏页数
+    pub fn dirty_pages(&self) -> usize {
+        let page_table = self.page_table.lock();
+        page_table
+            .values()
+            .filter(|&&frame_id| self.frames[frame_id].read().is_dirty)
+            .count()
+    }
+
+    /// 获取数据文件路径
+    pub fn data_path(&self) -> &Path {
+        self.disk.data_path()
+    }
+
+    /// 与数据文件同目录的附属文件路径（见 `DiskStorage::side_file`）
+    pub fn side_file(&self, name: &str) -> PathBuf {
+        self.disk.side_file(name)
+    }
+
+    /// 获取底层数据文件大小（字节）
+    pub fn file_size(&self) -> Result<u64> {
+        self.disk.file_size()
+    }
+
+    /// 本缓冲池累计的页面读取次数（`fetch_page` 调用数，含命中）
+    pub fn page_reads(&self) -> u64 {
+        self.page_reads.load(Ordering::Relaxed)
+    }
+
     /// 获取水位信息（用于监控）
     pub fn watermark_info(&self) -> BufferPoolWatermark {
         let cached = self.cached_pages();
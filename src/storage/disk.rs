@@ -21,8 +21,22 @@ const DEFAULT_INITIAL_SIZE: u64 = 64 * 1024 * 1024;
 const EXTEND_SIZE: u64 = 16 * 1024 * 1024;
 /// 文件魔数
 const MAGIC_NUMBER: u64 = 0x4348_4149_4E47_5248; // "CHAINGR\0"
-/// 文件版本
-const FILE_VERSION: u32 = 1;
+/// 文件版本，磁盘布局变化时递增，并在 `MIGRATIONS` 中登记升级步骤
+///
+/// - v1：记录只有插入，长度前缀不带标志位
+/// - v2：记录可带删除标记（墓碑），同一 ID 的多条记录以最后一条为准
+const FILE_VERSION: u32 = 2;
+
+/// 把文件从某个版本升级到下一版本的步骤
+type Migration = fn(&mut MmapMut) -> Result<()>;
+
+/// 升级步骤表：`(from, step)` 把版本 `from` 的文件原地改写为 `from + 1` 的布局
+const MIGRATIONS: &[(u32, Migration)] = &[(1, migrate_v1_to_v2)];
+
+/// v1 → v2：v1 文件只含插入记录，按 v2 读取结果相同，只需改写头部版本号
+fn migrate_v1_to_v2(_mmap: &mut MmapMut) -> Result<()> {
+    Ok(())
+}
 
 /// 文件头部（第 0 页）
 #[derive(Debug)]
@@ -49,7 +63,10 @@ impl FileHeader {
         }
         let magic = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
         if magic != MAGIC_NUMBER {
-            return Err(Error::StorageError("无效的数据文件格式".to_string()));
+            return Err(Error::StorageError(format!(
+                "无效的数据文件格式: 魔数 {:#018x}，应为 {:#018x}",
+                magic, MAGIC_NUMBER
+            )));
         }
         Ok(Self {
             magic,
@@ -62,8 +79,10 @@ impl FileHeader {
 
 /// 磁盘存储引擎
 pub struct DiskStorage {
-    /// 数据目录
-    data_dir: PathBuf,
+    /// 数据文件路径
+    data_path: PathBuf,
+    /// 同目录下附属文件（索引定义、地址标记等）的文件名前缀；目录布局下为空
+    side_file_prefix: String,
     /// 数据文件
     data_file: RwLock<File>,
     /// 内存映射
@@ -79,18 +98,49 @@ pub struct DiskStorage {
 }
 
 impl DiskStorage {
-    /// 打开或创建存储
+    /// 打开或创建存储：数据文件为目录下的 `data.cgd`，附属文件与之同目录
     pub fn open<P: AsRef<Path>>(data_dir: P, enable_compression: bool) -> Result<Arc<Self>> {
-        let data_dir = data_dir.as_ref().to_path_buf();
-        std::fs::create_dir_all(&data_dir)?;
+        Self::open_at(
+            Self::data_file_in(data_dir),
+            String::new(),
+            enable_compression,
+        )
+    }
+
+    /// 目录布局下数据文件的路径
+    pub fn data_file_in<P: AsRef<Path>>(data_dir: P) -> PathBuf {
+        data_dir.as_ref().join(format!("data.{}", DATA_FILE_EXT))
+    }
+
+    /// 打开或创建单个数据文件（如 `<data-dir>/<graph>.cg`），
+    /// 附属文件以文件名去掉扩展名后的部分为前缀，如 `<graph>.indexes.json`
+    pub fn open_file<P: AsRef<Path>>(data_path: P, enable_compression: bool) -> Result<Arc<Self>> {
+        let data_path = data_path.as_ref().to_path_buf();
+        let stem = data_path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| {
+                Error::StorageError(format!("无效的数据文件路径: {}", data_path.display()))
+            })?;
+        let prefix = format!("{}.", stem);
+        Self::open_at(data_path, prefix, enable_compression)
+    }
 
-        let data_file_path = data_dir.join(format!("data.{}", DATA_FILE_EXT));
+    fn open_at(
+        data_file_path: PathBuf,
+        side_file_prefix: String,
+        enable_compression: bool,
+    ) -> Result<Arc<Self>> {
+        if let Some(parent) = data_file_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
         let is_new = !data_file_path.exists();
 
         let data_file = OpenOptions::new()
             .read(true)
             .write(true)
             .create(true)
+            .truncate(false)
             .open(&data_file_path)?;
 
         // 初始化或加载文件
@@ -102,6 +152,10 @@ impl DiskStorage {
             // 读取文件头
             let mmap = unsafe { MmapOptions::new().map(&data_file)? };
             let header = FileHeader::from_bytes(&mmap[0..PAGE_SIZE])?;
+            if header.version != FILE_VERSION {
+                let mut mmap = unsafe { MmapOptions::new().map_mut(&data_file)? };
+                Self::migrate(&mut mmap, header.version)?;
+            }
             (header.page_count, header.free_page_head)
         };
 
@@ -109,7 +163,8 @@ impl DiskStorage {
         let mmap = unsafe { MmapOptions::new().map_mut(&data_file)? };
 
         let storage = Arc::new(Self {
-            data_dir,
+            data_path: data_file_path,
+            side_file_prefix,
             data_file: RwLock::new(data_file),
             mmap: RwLock::new(mmap),
             page_count: AtomicU64::new(page_count),
@@ -126,6 +181,32 @@ impl DiskStorage {
         Ok(storage)
     }
 
+    /// 按 `MIGRATIONS` 逐版本把文件升级到 `FILE_VERSION`
+    ///
+    /// 比当前程序新的版本，或缺少某一步升级的旧版本，拒绝打开并报告
+    /// 文件版本与支持的版本。
+    fn migrate(mmap: &mut MmapMut, version: u32) -> Result<()> {
+        let incompatible = || {
+            Error::StorageError(format!(
+                "不兼容的数据文件版本: 文件为 v{}，当前支持 v{}",
+                version, FILE_VERSION
+            ))
+        };
+        if version > FILE_VERSION {
+            return Err(incompatible());
+        }
+        for from in version..FILE_VERSION {
+            let (_, step) = MIGRATIONS
+                .iter()
+                .find(|(v, _)| *v == from)
+                .ok_or_else(incompatible)?;
+            step(mmap)?;
+            mmap[8..12].copy_from_slice(&(from + 1).to_le_bytes());
+        }
+        mmap.flush()?;
+        Ok(())
+    }
+
     /// 写入文件头
     fn write_header(&self) -> Result<()> {
         let header = FileHeader {
@@ -275,9 +356,20 @@ impl DiskStorage {
         self.page_count.load(Ordering::SeqCst)
     }
 
-    /// 获取数据目录
-    pub fn data_dir(&self) -> &Path {
-        &self.data_dir
+    /// 获取数据文件路径
+    pub fn data_path(&self) -> &Path {
+        &self.data_path
+    }
+
+    /// 名为 `name` 的附属文件路径，与数据文件位于同一目录
+    pub fn side_file(&self, name: &str) -> PathBuf {
+        self.data_path
+            .with_file_name(format!("{}{}", self.side_file_prefix, name))
+    }
+
+    /// 获取数据文件大小（字节）
+    pub fn file_size(&self) -> Result<u64> {
+        Ok(self.data_file.read().metadata()?.len())
     }
 
     /// 批量写入页面（优化 SSD 顺序写入）
@@ -346,4 +438,60 @@ mod tests {
         let page3 = storage.allocate_page(PageType::Vertex).unwrap();
         assert_eq!(page3.page_id, 1);
     }
+
+    #[test]
+    fn test_reject_incompatible_version() {
+        let dir = tempdir().unwrap();
+        drop(DiskStorage::open(dir.path(), false).unwrap());
+        let path = dir.path().join(format!("data.{}", DATA_FILE_EXT));
+        let patch = |offset: u64, bytes: &[u8]| {
+            use std::io::{Seek, SeekFrom, Write};
+            let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+            file.seek(SeekFrom::Start(offset)).unwrap();
+            file.write_all(bytes).unwrap();
+        };
+
+        // 更新的版本号：报告文件版本与支持的版本
+        patch(8, &(FILE_VERSION + 1).to_le_bytes());
+        let err = DiskStorage::open(dir.path(), false).err().unwrap();
+        let expected = format!("文件为 v{}，当前支持 v{}", FILE_VERSION + 1, FILE_VERSION);
+        assert!(err.to_string().contains(&expected), "{}", err);
+
+        // 恢复版本号后可以正常打开
+        patch(8, &FILE_VERSION.to_le_bytes());
+        assert!(DiskStorage::open(dir.path(), false).is_ok());
+
+        // 魔数不符
+        patch(0, &[0u8; 8]);
+        let err = DiskStorage::open(dir.path(), false).err().unwrap();
+        assert!(err.to_string().contains("无效的数据文件格式"), "{}", err);
+    }
+
+    #[test]
+    fn test_upgrade_v1_file() {
+        let dir = tempdir().unwrap();
+        let storage = DiskStorage::open(dir.path(), false).unwrap();
+        let mut page = storage.allocate_page(PageType::Vertex).unwrap();
+        page.append_data(b"v1 data").unwrap();
+        storage.write_page(&page).unwrap();
+        drop(storage);
+
+        let path = dir.path().join(format!("data.{}", DATA_FILE_EXT));
+        let version = || {
+            let bytes = std::fs::read(&path).unwrap();
+            u32::from_le_bytes(bytes[8..12].try_into().unwrap())
+        };
+        {
+            use std::io::{Seek, SeekFrom, Write};
+            let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+            file.seek(SeekFrom::Start(8)).unwrap();
+            file.write_all(&1u32.to_le_bytes()).unwrap();
+        }
+        assert_eq!(version(), 1);
+
+        // 打开时升级到当前版本，原有数据不变
+        let storage = DiskStorage::open(dir.path(), false).unwrap();
+        assert_eq!(version(), FILE_VERSION);
+        assert_eq!(&storage.read_page(1).unwrap().data[0..7], b"v1 data");
+    }
 }
@@ -4,6 +4,7 @@ use primitive_types::{H160, H256, U256};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
+use std::str::FromStr;
 
 /// 顶点 ID (64位整数，便于磁盘存储和索引)
 pub type VertexId = u64;
@@ -18,7 +19,9 @@ pub type PageId = u64;
 pub type BlockNumber = u64;
 
 /// 以太坊地址 (20 bytes)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+///
+/// 可读格式中序列化为 `0x` 前缀的小写十六进制字符串
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Address(pub H160);
 
 impl Address {
@@ -51,8 +54,39 @@ impl fmt::Display for Address {
     }
 }
 
+impl FromStr for Address {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Address::from_hex(s)
+    }
+}
+
+impl Serialize for Address {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            Address::from_hex(&s).map_err(serde::de::Error::custom)
+        } else {
+            H160::deserialize(deserializer).map(Address)
+        }
+    }
+}
+
 /// 交易哈希 (32 bytes)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+///
+/// 可读格式中序列化为 `0x` 前缀的小写十六进制字符串
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct TxHash(pub H256);
 
 impl TxHash {
@@ -81,8 +115,40 @@ impl fmt::Display for TxHash {
     }
 }
 
+impl FromStr for TxHash {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TxHash::from_hex(s)
+    }
+}
+
+impl Serialize for TxHash {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TxHash {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            TxHash::from_hex(&s).map_err(serde::de::Error::custom)
+        } else {
+            H256::deserialize(deserializer).map(TxHash)
+        }
+    }
+}
+
 /// 代币数量 (256位大整数)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// JSON 等可读格式中序列化为十进制字符串（以 wei 为单位，避免 JS 数字精度丢失），
+/// bincode 等二进制格式保持 U256 原有编码，磁盘数据不受影响。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct TokenAmount(pub U256);
 
 impl TokenAmount {
@@ -95,6 +161,72 @@ impl TokenAmount {
             .map(TokenAmount)
             .map_err(|e| crate::Error::InternalError(e.to_string()))
     }
+
+    /// 解析十进制字符串，兼容 `0x` 前缀的十六进制
+    pub fn from_dec_str(s: &str) -> Result<Self, crate::Error> {
+        match s.strip_prefix("0x") {
+            Some(hex) => Self::from_str_radix(hex, 16),
+            None => U256::from_dec_str(s)
+                .map(TokenAmount)
+                .map_err(|e| crate::Error::InternalError(format!("{:?}", e))),
+        }
+    }
+
+    /// 按精度（如 ERC20 的 18 位）换算为可读的代币数量，去掉小数部分末尾的 0
+    pub fn format_units(&self, decimals: u32) -> String {
+        let raw = self.0.to_string();
+        if decimals == 0 {
+            return raw;
+        }
+        let decimals = decimals as usize;
+        let padded = if raw.len() <= decimals {
+            format!("{}{}", "0".repeat(decimals - raw.len() + 1), raw)
+        } else {
+            raw
+        };
+        let (int_part, frac_part) = padded.split_at(padded.len() - decimals);
+        let frac_part = frac_part.trim_end_matches('0');
+        if frac_part.is_empty() {
+            int_part.to_string()
+        } else {
+            format!("{}.{}", int_part, frac_part)
+        }
+    }
+}
+
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Serialize for TokenAmount {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TokenAmount {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            #[derive(Deserialize)]
+            #[serde(untagged)]
+            enum Repr {
+                Str(String),
+                Num(u64),
+            }
+            match Repr::deserialize(deserializer)? {
+                Repr::Str(s) => TokenAmount::from_dec_str(&s).map_err(serde::de::Error::custom),
+                Repr::Num(n) => Ok(TokenAmount::from_u64(n)),
+            }
+        } else {
+            U256::deserialize(deserializer).map(TokenAmount)
+        }
+    }
 }
 
 /// 属性值
@@ -147,6 +279,19 @@ impl PropertyValue {
         }
     }
 
+    /// 数值类属性转为 f64（大额 TokenAmount 会损失精度），非数值返回 None
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            PropertyValue::Int(v) | PropertyValue::Integer(v) | PropertyValue::Timestamp(v) => {
+                Some(*v as f64)
+            }
+            PropertyValue::UInt(v) | PropertyValue::BlockNumber(v) => Some(*v as f64),
+            PropertyValue::Float(v) => Some(*v),
+            PropertyValue::Amount(v) | PropertyValue::TokenAmount(v) => v.0.to_string().parse().ok(),
+            _ => None,
+        }
+    }
+
     pub fn as_string(&self) -> Option<&str> {
         match self {
             PropertyValue::String(v) => Some(v),
@@ -202,19 +347,14 @@ impl From<TxHash> for PropertyValue {
 pub type Properties = HashMap<String, PropertyValue>;
 
 /// 遍历方向
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub enum Direction {
     Outgoing,
     Incoming,
+    #[default]
     Both,
 }
 
-impl Default for Direction {
-    fn default() -> Self {
-        Direction::Both
-    }
-}
-
 /// 顶点类型标签
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum VertexLabel {
@@ -300,6 +440,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_token_amount_serializes_as_decimal_string() {
+        let amount = TokenAmount::from_dec_str("1500000000000000000").unwrap();
+        let json = serde_json::to_string(&PropertyValue::TokenAmount(amount)).unwrap();
+        assert_eq!(json, r#"{"TokenAmount":"1500000000000000000"}"#);
+
+        let back: PropertyValue = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, PropertyValue::TokenAmount(amount));
+
+        // 二进制编码不受影响
+        let bytes = bincode::serialize(&amount).unwrap();
+        assert_eq!(bincode::deserialize::<TokenAmount>(&bytes).unwrap(), amount);
+
+        assert_eq!(amount.format_units(18), "1.5");
+        assert_eq!(TokenAmount::from_u64(42).format_units(3), "0.042");
+        assert_eq!(TokenAmount::from_u64(2000).format_units(3), "2");
+    }
+
     #[test]
     fn test_tx_hash_parsing() {
         let hash =
@@ -310,4 +468,33 @@ mod tests {
             "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060"
         );
     }
+
+    #[test]
+    fn test_address_json_hex_roundtrip() {
+        let addr: Address = "0x742d35Cc6634C0532925a3b844Bc9e7595f5bB01".parse().unwrap();
+        let json = serde_json::to_string(&addr).unwrap();
+        assert_eq!(json, "\"0x742d35cc6634c0532925a3b844bc9e7595f5bb01\"");
+        assert_eq!(serde_json::from_str::<Address>(&json).unwrap(), addr);
+
+        let value = serde_json::to_string(&PropertyValue::Address(addr)).unwrap();
+        assert_eq!(
+            value,
+            r#"{"Address":"0x742d35cc6634c0532925a3b844bc9e7595f5bb01"}"#
+        );
+        assert!(serde_json::from_str::<Address>("\"0x1234\"").is_err());
+    }
+
+    #[test]
+    fn test_tx_hash_json_hex_roundtrip() {
+        let hex = "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060";
+        let hash: TxHash = hex.parse().unwrap();
+        assert_eq!(hash.to_string(), hex);
+
+        let json = serde_json::to_string(&hash).unwrap();
+        assert_eq!(json, format!("\"{}\"", hex));
+        assert_eq!(serde_json::from_str::<TxHash>(&json).unwrap(), hash);
+
+        let bytes = bincode::serialize(&hash).unwrap();
+        assert_eq!(bincode::deserialize::<TxHash>(&bytes).unwrap(), hash);
+    }
 }
@@ -0,0 +1,170 @@
+//! End-to-end checks of the offline `chaingraph-cli` subcommands.
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+const IMPORT_BIN: &str = env!("CARGO_BIN_EXE_chaingraph-import");
+const CLI_BIN: &str = env!("CARGO_BIN_EXE_chaingraph-cli");
+
+fn cli(data_dir: &str, args: &[&str]) -> Output {
+    Command::new(CLI_BIN)
+        .args(args)
+        .args(["--data-dir", data_dir])
+        .output()
+        .expect("failed to run chaingraph-cli")
+}
+
+/// 导入两笔转账：3 个账户、2 条 Transfer 边
+fn seed(dir: &Path, data_dir: &str) {
+    import_csv(
+        dir,
+        data_dir,
+        "0x00000000000000000000000000000000000000aa,0x00000000000000000000000000000000000000bb,100,1\n\
+         0x00000000000000000000000000000000000000bb,0x00000000000000000000000000000000000000cc,40,2\n",
+    );
+}
+
+/// 把 `rows`（from,to,amount,block）导入 data_dir
+fn import_csv(dir: &Path, data_dir: &str, rows: &str) {
+    let csv = dir.join("transfers.csv");
+    fs::write(&csv, format!("from,to,amount,block\n{}", rows)).unwrap();
+    let imported = Command::new(IMPORT_BIN)
+        .args(["--data-dir", data_dir, "-i"])
+        .arg(&csv)
+        .output()
+        .unwrap();
+    assert!(imported.status.success(), "{}", String::from_utf8_lossy(&imported.stderr));
+}
+
+#[test]
+fn test_stats_command() {
+    let dir = tempfile::tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let data_dir = data_dir.to_str().unwrap();
+    seed(dir.path(), data_dir);
+
+    let output = cli(data_dir, &["stats"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("顶点数: 3"), "{}", stdout);
+    assert!(stdout.contains("Account: 3"), "{}", stdout);
+    assert!(stdout.contains("边数: 2"), "{}", stdout);
+    assert!(stdout.contains("Transfer: 2"), "{}", stdout);
+    assert!(stdout.contains("缓冲池"), "{}", stdout);
+    assert!(stdout.contains("数据文件大小"), "{}", stdout);
+    // 不进入交互模式
+    assert!(!stdout.contains("数据库已连接"), "{}", stdout);
+
+    let missing = cli(data_dir, &["stats", "--graph", "nope"]);
+    assert!(!missing.status.success());
+}
+
+#[test]
+fn test_bench_command() {
+    let dir = tempfile::tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let data_dir = data_dir.to_str().unwrap();
+    seed(dir.path(), data_dir);
+
+    let queries = dir.path().join("queries.gql");
+    fs::write(
+        &queries,
+        "-- 基准查询\nMATCH (n:Account) RETURN n\n\nMATCH (a)-[t:Transfer]->(b) RETURN a, b\n",
+    )
+    .unwrap();
+    let queries = queries.to_str().unwrap();
+
+    let output = cli(
+        data_dir,
+        &["bench", "--queries", queries, "--iterations", "3", "--warmup", "1"],
+    );
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("2 条查询 × 3 轮"), "{}", stdout);
+    assert!(stdout.contains("执行次数: 6"), "{}", stdout);
+    assert!(stdout.contains("吞吐量"), "{}", stdout);
+    for label in ["p50", "p95", "p99"] {
+        let line = stdout
+            .lines()
+            .find(|line| line.contains(label))
+            .unwrap_or_else(|| panic!("missing {}: {}", label, stdout));
+        assert!(line.trim_end().ends_with(" ms"), "{}", line);
+    }
+
+    // 无法解析的查询直接报错
+    let bad = dir.path().join("bad.gql");
+    fs::write(&bad, "MATCH (n RETURN n\n").unwrap();
+    let output = cli(data_dir, &["bench", "--queries", bad.to_str().unwrap()]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_index_commands() {
+    let dir = tempfile::tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let data_dir = data_dir.to_str().unwrap();
+    seed(dir.path(), data_dir);
+
+    let list = |data_dir: &str| {
+        let output = cli(data_dir, &["index", "list"]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    assert!(list(data_dir).contains("没有属性索引"));
+
+    let create = ["index", "create", "--label", "Account", "--property", "address"];
+    let created = cli(data_dir, &create);
+    assert!(created.status.success(), "{}", String::from_utf8_lossy(&created.stderr));
+    // 索引定义跨进程保留
+    assert!(list(data_dir).contains("Account(address): 3 个顶点"), "{}", list(data_dir));
+    assert!(!cli(data_dir, &create).status.success());
+
+    let drop = ["index", "drop", "--label", "Account", "--property", "address"];
+    let dropped = cli(data_dir, &drop);
+    assert!(dropped.status.success(), "{}", String::from_utf8_lossy(&dropped.stderr));
+    assert!(list(data_dir).contains("没有属性索引"));
+    assert!(!cli(data_dir, &drop).status.success());
+}
+
+#[test]
+fn test_merge_command() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = dir.path().join("a");
+    let second = dir.path().join("b");
+    let out = dir.path().join("c");
+    let (first, second, out) = (
+        first.to_str().unwrap(),
+        second.to_str().unwrap(),
+        out.to_str().unwrap(),
+    );
+    seed(dir.path(), first);
+    // 与第一个目录共享 0xbb、0xcc 两个账户
+    import_csv(
+        dir.path(),
+        second,
+        "0x00000000000000000000000000000000000000bb,0x00000000000000000000000000000000000000cc,40,2\n\
+         0x00000000000000000000000000000000000000cc,0x00000000000000000000000000000000000000dd,5,3\n",
+    );
+
+    let output = Command::new(CLI_BIN)
+        .args(["merge", first, second, "--out", out])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("新增顶点 1，合并重复顶点 2，追加边 2"), "{}", stdout);
+
+    // 输出目录可被重新打开：顶点去重，边取并集
+    let stats = cli(out, &["stats"]);
+    let stdout = String::from_utf8_lossy(&stats.stdout);
+    assert!(stdout.contains("顶点数: 4"), "{}", stdout);
+    assert!(stdout.contains("Transfer: 4"), "{}", stdout);
+
+    let missing = dir.path().join("missing");
+    let output = Command::new(CLI_BIN)
+        .args(["merge", first, missing.to_str().unwrap(), "--out", out])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
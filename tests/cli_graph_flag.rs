@@ -0,0 +1,71 @@
+//! End-to-end check of the `--graph` flag shared by the import and CLI binaries.
+
+use std::fs;
+use std::process::Command;
+
+const IMPORT_BIN: &str = env!("CARGO_BIN_EXE_chaingraph-import");
+const CLI_BIN: &str = env!("CARGO_BIN_EXE_chaingraph-cli");
+
+fn cli(data_dir: &str, graph: Option<&str>, query: &str) -> std::process::Output {
+    let mut cmd = Command::new(CLI_BIN);
+    cmd.args(["--data-dir", data_dir]);
+    if let Some(graph) = graph {
+        cmd.args(["--graph", graph]);
+    }
+    cmd.args(["-e", query]).output().expect("failed to run chaingraph-cli")
+}
+
+#[test]
+fn test_import_and_query_named_graph() {
+    let dir = tempfile::tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let data_dir = data_dir.to_str().unwrap();
+
+    let created = cli(data_dir, None, "CREATE GRAPH analytics");
+    assert!(created.status.success(), "{}", String::from_utf8_lossy(&created.stderr));
+
+    let csv = dir.path().join("transfers.csv");
+    fs::write(
+        &csv,
+        "from,to,amount,block\n\
+         0x00000000000000000000000000000000000000aa,0x00000000000000000000000000000000000000bb,100,1\n",
+    )
+    .unwrap();
+
+    let imported = Command::new(IMPORT_BIN)
+        .args(["--data-dir", data_dir, "--graph", "analytics", "-i"])
+        .arg(&csv)
+        .output()
+        .unwrap();
+    assert!(imported.status.success(), "{}", String::from_utf8_lossy(&imported.stderr));
+
+    let queried = cli(data_dir, Some("analytics"), "MATCH (n:Account) RETURN n");
+    assert!(queried.status.success(), "{}", String::from_utf8_lossy(&queried.stderr));
+    let stdout = String::from_utf8_lossy(&queried.stdout).to_lowercase();
+    assert!(stdout.contains("00000000000000000000000000000000000000aa"), "{}", stdout);
+
+    // 默认图不受影响
+    let default = cli(data_dir, None, "MATCH (n:Account) RETURN n");
+    assert!(default.status.success());
+    let stdout = String::from_utf8_lossy(&default.stdout).to_lowercase();
+    assert!(!stdout.contains("00000000000000000000000000000000000000aa"), "{}", stdout);
+}
+
+#[test]
+fn test_missing_named_graph_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let data_dir = dir.path().to_str().unwrap();
+
+    let queried = cli(data_dir, Some("nope"), "MATCH (n) RETURN n");
+    assert!(!queried.status.success());
+    assert!(String::from_utf8_lossy(&queried.stderr).contains("nope"));
+
+    let csv = dir.path().join("transfers.csv");
+    fs::write(&csv, "from,to,amount,block\n").unwrap();
+    let imported = Command::new(IMPORT_BIN)
+        .args(["--data-dir", data_dir, "--graph", "nope", "-i"])
+        .arg(&csv)
+        .output()
+        .unwrap();
+    assert!(!imported.status.success());
+}